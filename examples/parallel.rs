@@ -1,3 +1,5 @@
+#[cfg(feature = "rayon")]
+use easy_tree::rayon::prelude::*;
 use easy_tree::Tree;
 
 fn main() {
@@ -0,0 +1,79 @@
+//! Times `Tree::traverse` on deep, wide and random trees, and compares it to replaying a
+//! precomputed `TraversalPlan`.
+//!
+//! Run it in release mode, optionally with the number of nodes per tree:
+//!
+//! ```sh
+//! cargo run --release --example traversal_benchmark -- 2000000
+//! ```
+
+use easy_tree::Tree;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Returns the fastest of several runs, to filter out noise from other processes.
+fn fastest(mut run: impl FnMut()) -> Duration {
+    (0..7)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn benchmark(name: &str, tree: &Tree<u64>) {
+    let before = |_: usize, data: &u64, sum: &mut u64| *sum = sum.wrapping_add(*data);
+    let after = |index: usize, _: &u64, sum: &mut u64| *sum ^= index as u64;
+
+    let traverse = fastest(|| {
+        let mut sum = 0;
+        tree.traverse(before, after, &mut sum);
+        black_box(sum);
+    });
+    let plan = tree.compile_traversal();
+    let replay = fastest(|| {
+        let mut sum = 0;
+        plan.replay(tree, before, after, &mut sum);
+        black_box(sum);
+    });
+    println!("{name:>8}: traverse {traverse:>10.2?}, plan replay {replay:>10.2?}");
+}
+
+fn main() {
+    let len: usize = std::env::args()
+        .nth(1)
+        .map_or(1_000_000, |len| len.parse().expect("invalid node count"));
+
+    let mut deep = Tree::new();
+    let mut last = deep.add_node(0);
+    for data in 1..len as u64 {
+        last = deep.add_child(last, data);
+    }
+    benchmark("deep", &deep);
+
+    let mut wide = Tree::new();
+    let root = wide.add_node(0);
+    wide.add_children(root, 1..len as u64);
+    benchmark("wide", &wide);
+
+    let mut bushy = Tree::new();
+    bushy.add_node(0);
+    for index in 1..len {
+        bushy.add_child((index - 1) / 4, index as u64);
+    }
+    benchmark("bushy", &bushy);
+
+    // A random recursive tree, from a xorshift generator so that runs are comparable.
+    let mut random = Tree::new();
+    random.add_node(0);
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    for index in 1..len {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        random.add_child((state % index as u64) as usize, index as u64);
+    }
+    benchmark("random", &random);
+}
@@ -0,0 +1,105 @@
+#ifndef EASY_TREE_H
+#define EASY_TREE_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Returned instead of a node index when there is no such node.
+#define EASY_TREE_NO_INDEX SIZE_MAX
+
+
+
+// An opaque tree of `void *` node data, created with [`easy_tree_new`] and destroyed with
+// [`easy_tree_free`].
+typedef struct EasyTree EasyTree;
+
+// A traversal callback, called with the index of a node, its data and the `user_data` passed
+// to [`easy_tree_traverse`].
+typedef void (*EasyTreeVisitor)(size_t index, void *data, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates an empty tree.
+//
+// # Returns
+// A handle that must be destroyed with [`easy_tree_free`].
+struct EasyTree *easy_tree_new(void);
+
+// Destroys a tree. The node data is not freed, as it belongs to the caller.
+//
+// # Safety
+// `tree` must be null or a handle returned by [`easy_tree_new`] that was not destroyed yet.
+void easy_tree_free(struct EasyTree *tree);
+
+// Returns the number of nodes in a tree.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`].
+size_t easy_tree_len(const struct EasyTree *tree);
+
+// Adds a node without a parent.
+//
+// # Returns
+// The index of the new node.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`].
+size_t easy_tree_add_node(struct EasyTree *tree, void *data);
+
+// Adds a node as the last child of `parent`.
+//
+// # Returns
+// The index of the new node, or [`EASY_TREE_NO_INDEX`] if `parent` does not exist, in which
+// case the tree is left unchanged.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`].
+size_t easy_tree_add_child(struct EasyTree *tree, size_t parent, void *data);
+
+// Returns the data of a node, or null if there is no such node.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`].
+void *easy_tree_get(const struct EasyTree *tree, size_t index);
+
+// Returns the index of the parent of a node, or [`EASY_TREE_NO_INDEX`] if the node has no
+// parent or does not exist.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`].
+size_t easy_tree_parent(const struct EasyTree *tree, size_t index);
+
+// Returns the indices of the children of a node, in order.
+//
+// # Returns
+// A pointer to `*len` indices, valid until the tree is next modified or destroyed. If the node
+// does not exist, `*len` is set to `0` and null is returned.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`], and `len` must be valid for
+// writes.
+const size_t *easy_tree_children(const struct EasyTree *tree, size_t index, size_t *len);
+
+// Traverses the subtree of node `0` depth-first, like [`Tree::traverse`], calling `before`
+// when entering a node and `after` when leaving it. Either callback may be null.
+//
+// # Safety
+// `tree` must be a live handle returned by [`easy_tree_new`], and must not be modified or
+// destroyed by the callbacks. The callbacks must be safe to call with `user_data`.
+void easy_tree_traverse(const struct EasyTree *tree,
+                        EasyTreeVisitor before,
+                        EasyTreeVisitor after,
+                        void *user_data);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* EASY_TREE_H */
@@ -0,0 +1,674 @@
+use crate::{EulerStep, Tree, TreeStorage};
+use std::marker::PhantomData;
+use std::ops::Add;
+
+/// An associative operation with an identity element, used to aggregate values over nodes.
+///
+/// `combine` must be associative, and `identity` must be neutral with respect to it. It does
+/// not have to be commutative: values are always combined in depth-first pre-order.
+pub trait Monoid {
+    /// The type of the aggregated values.
+    type Value: Clone;
+
+    /// Returns the identity element.
+    fn identity(&self) -> Self::Value;
+
+    /// Combines two values.
+    fn combine(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// The [`Monoid`] of addition, with `V::default()` as the identity.
+pub struct Sum<V>(PhantomData<V>);
+
+impl<V> Sum<V> {
+    /// Creates the addition monoid.
+    pub fn new() -> Self {
+        Sum(PhantomData)
+    }
+}
+
+impl<V> Default for Sum<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + Default + Add<Output = V>> Monoid for Sum<V> {
+    type Value = V;
+
+    fn identity(&self) -> V {
+        V::default()
+    }
+
+    fn combine(&self, a: &V, b: &V) -> V {
+        a.clone() + b.clone()
+    }
+}
+
+/// A [`Monoid`] defined by an identity value and a combining closure.
+///
+/// Constructed with [`monoid`].
+pub struct MonoidFn<V, F> {
+    identity: V,
+    combine: F,
+}
+
+/// Creates a [`Monoid`] from an identity value and a combining closure.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::aggregate::{monoid, Monoid};
+///
+/// let max = monoid(i64::MIN, |a: &i64, b: &i64| *a.max(b));
+/// assert_eq!(max.combine(&3, &max.identity()), 3);
+/// ```
+pub fn monoid<V: Clone, F: Fn(&V, &V) -> V>(identity: V, combine: F) -> MonoidFn<V, F> {
+    MonoidFn { identity, combine }
+}
+
+impl<V: Clone, F: Fn(&V, &V) -> V> Monoid for MonoidFn<V, F> {
+    type Value = V;
+
+    fn identity(&self) -> V {
+        self.identity.clone()
+    }
+
+    fn combine(&self, a: &V, b: &V) -> V {
+        (self.combine)(a, b)
+    }
+}
+
+/// A segment tree over a fixed number of values, combining them in order.
+struct SegmentTree<V> {
+    len: usize,
+    segments: Vec<V>,
+}
+
+impl<V: Clone> SegmentTree<V> {
+    /// Builds the segment tree from values listed in order.
+    fn new<M: Monoid<Value = V>>(monoid: &M, values: Vec<V>) -> Self {
+        let len = values.len();
+        let mut segments = vec![monoid.identity(); len];
+        segments.extend(values);
+        for segment in (1..len).rev() {
+            segments[segment] = monoid.combine(&segments[2 * segment], &segments[2 * segment + 1]);
+        }
+        SegmentTree { len, segments }
+    }
+
+    fn get(&self, position: usize) -> &V {
+        &self.segments[self.len + position]
+    }
+
+    fn set<M: Monoid<Value = V>>(&mut self, monoid: &M, position: usize, value: V) {
+        let mut segment = self.len + position;
+        self.segments[segment] = value;
+        while segment > 1 {
+            segment /= 2;
+            self.segments[segment] =
+                monoid.combine(&self.segments[2 * segment], &self.segments[2 * segment + 1]);
+        }
+    }
+
+    /// Aggregates the values at the positions `start..end`, in order.
+    fn query<M: Monoid<Value = V>>(&self, monoid: &M, start: usize, end: usize) -> V {
+        let mut left = monoid.identity();
+        let mut right = monoid.identity();
+        let (mut start, mut end) = (start + self.len, end + self.len);
+        while start < end {
+            if start % 2 == 1 {
+                left = monoid.combine(&left, &self.segments[start]);
+                start += 1;
+            }
+            if end % 2 == 1 {
+                end -= 1;
+                right = monoid.combine(&self.segments[end], &right);
+            }
+            start /= 2;
+            end /= 2;
+        }
+        monoid.combine(&left, &right)
+    }
+}
+
+/// An index answering subtree aggregate queries in `O(log n)` while supporting point updates
+/// in `O(log n)`.
+///
+/// The nodes are laid out in depth-first pre-order, in which every subtree occupies a
+/// contiguous range, and a segment tree is maintained over that layout.
+///
+/// The index holds its own copy of the node values and does not borrow the tree. It reflects
+/// the structure of the tree at the time it was built; nodes added later are not covered.
+///
+/// Constructed with [`Tree::build_subtree_index`].
+pub struct SubtreeIndex<M: Monoid> {
+    monoid: M,
+    /// The position of every node in the pre-order layout.
+    position: Vec<usize>,
+    /// The position one past the last node of every node's subtree.
+    end: Vec<usize>,
+    values: SegmentTree<M::Value>,
+}
+
+impl<M: Monoid> SubtreeIndex<M> {
+    /// Returns the aggregate of the values of all nodes in the subtree of `index`, including
+    /// the node itself.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn aggregate(&self, index: usize) -> M::Value {
+        self.values
+            .query(&self.monoid, self.position[index], self.end[index])
+    }
+
+    /// Returns the value of a single node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &M::Value {
+        self.values.get(self.position[index])
+    }
+
+    /// Replaces the value of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        self.values.set(&self.monoid, self.position[index], value);
+    }
+}
+
+/// An index answering aggregate queries over the path between two nodes in `O(log² n)` while
+/// supporting point updates in `O(log n)`.
+///
+/// The tree is split into heavy paths (heavy-light decomposition), so that any path crosses
+/// `O(log n)` of them, and each heavy path occupies a contiguous range of a segment tree.
+///
+/// Like [`SubtreeIndex`], the index holds its own copy of the node values and reflects the
+/// structure of the tree at the time it was built.
+///
+/// Constructed with [`Tree::build_path_index`].
+pub struct PathIndex<M: Monoid> {
+    monoid: M,
+    parent: Vec<Option<usize>>,
+    depth: Vec<usize>,
+    /// The topmost node of the heavy path every node belongs to.
+    head: Vec<usize>,
+    /// The root of the tree every node belongs to.
+    root: Vec<usize>,
+    position: Vec<usize>,
+    /// Values laid out so that every heavy path is contiguous and ordered top-down.
+    forward: SegmentTree<M::Value>,
+    /// The same values in the opposite order, used to aggregate paths going up.
+    backward: SegmentTree<M::Value>,
+}
+
+impl<M: Monoid> PathIndex<M> {
+    /// Returns the aggregate of the values of all nodes on the path from `a` to `b`, both
+    /// included, combined in order from `a` to `b`.
+    ///
+    /// # Returns
+    /// The aggregate, or `None` if `a` and `b` are not in the same tree.
+    ///
+    /// # Panics
+    /// This method panics if `a` or `b` is out of bounds.
+    pub fn aggregate_on_path(&self, mut a: usize, mut b: usize) -> Option<M::Value> {
+        if self.root[a] != self.root[b] {
+            return None;
+        }
+        let monoid = &self.monoid;
+        let mut up = monoid.identity();
+        let mut down = monoid.identity();
+        while self.head[a] != self.head[b] {
+            if self.depth[self.head[a]] >= self.depth[self.head[b]] {
+                let chain = self.upward(self.head[a], a);
+                up = monoid.combine(&up, &chain);
+                a = self.parent[self.head[a]]?;
+            } else {
+                let chain = self.downward(self.head[b], b);
+                down = monoid.combine(&chain, &down);
+                b = self.parent[self.head[b]]?;
+            }
+        }
+        if self.depth[a] >= self.depth[b] {
+            up = monoid.combine(&up, &self.upward(b, a));
+        } else {
+            down = monoid.combine(&self.downward(a, b), &down);
+        }
+        Some(monoid.combine(&up, &down))
+    }
+
+    /// Returns the value of a single node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &M::Value {
+        self.forward.get(self.position[index])
+    }
+
+    /// Replaces the value of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        let position = self.position[index];
+        let mirrored = self.position.len() - 1 - position;
+        self.backward.set(&self.monoid, mirrored, value.clone());
+        self.forward.set(&self.monoid, position, value);
+    }
+
+    /// Aggregates a heavy path segment from `top` down to `bottom`.
+    fn downward(&self, top: usize, bottom: usize) -> M::Value {
+        self.forward
+            .query(&self.monoid, self.position[top], self.position[bottom] + 1)
+    }
+
+    /// Aggregates a heavy path segment from `bottom` up to `top`.
+    fn upward(&self, top: usize, bottom: usize) -> M::Value {
+        let len = self.position.len();
+        self.backward.query(
+            &self.monoid,
+            len - 1 - self.position[bottom],
+            len - self.position[top],
+        )
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Builds a [`SubtreeIndex`] for fast subtree aggregate queries with point updates.
+    ///
+    /// All nodes are covered, including those that are not connected to the root.
+    ///
+    /// # Parameters
+    /// - `monoid`: The operation used to aggregate values, for example [`Sum`].
+    /// - `value`: Extracts the initial value of a node from its data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::aggregate::Sum;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut org = Tree::new();
+    /// let ceo = org.add_node(("ceo", 300));
+    /// let cto = org.add_child(ceo, ("cto", 200));
+    /// org.add_child(cto, ("engineer", 100));
+    /// org.add_child(ceo, ("cfo", 200));
+    ///
+    /// let mut salaries = org.build_subtree_index(Sum::new(), |(_, salary)| *salary);
+    /// assert_eq!(salaries.aggregate(ceo), 800);
+    /// assert_eq!(salaries.aggregate(cto), 300);
+    ///
+    /// salaries.set(cto, 250);
+    /// assert_eq!(salaries.aggregate(ceo), 850);
+    /// ```
+    pub fn build_subtree_index<M: Monoid>(
+        &self,
+        monoid: M,
+        mut value: impl FnMut(&T) -> M::Value,
+    ) -> SubtreeIndex<M> {
+        let len = self.links.len();
+        let mut position = vec![0; len];
+        let mut end = vec![0; len];
+        let mut order = Vec::with_capacity(len);
+        for step in self.euler_tour() {
+            match step {
+                EulerStep::Enter { index, .. } => {
+                    position[index] = order.len();
+                    order.push(index);
+                }
+                EulerStep::Leave { index, .. } => end[index] = order.len(),
+            }
+        }
+
+        let values = order
+            .into_iter()
+            .map(|index| value(&self.data[index]))
+            .collect();
+        SubtreeIndex {
+            values: SegmentTree::new(&monoid, values),
+            monoid,
+            position,
+            end,
+        }
+    }
+
+    /// Builds a [`PathIndex`] for fast aggregate queries along paths between nodes, with point
+    /// updates.
+    ///
+    /// All nodes are covered, including those that are not connected to the root.
+    ///
+    /// # Parameters
+    /// - `monoid`: The operation used to aggregate values, for example [`Sum`].
+    /// - `value`: Extracts the initial value of a node from its data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::aggregate::Sum;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut roads = Tree::new();
+    /// let hub = roads.add_node(0);
+    /// let north = roads.add_child(hub, 5);
+    /// let far_north = roads.add_child(north, 3);
+    /// let south = roads.add_child(hub, 7);
+    ///
+    /// let distances = roads.build_path_index(Sum::new(), |length| *length);
+    /// assert_eq!(distances.aggregate_on_path(far_north, south), Some(15));
+    /// assert_eq!(distances.aggregate_on_path(hub, far_north), Some(8));
+    /// ```
+    pub fn build_path_index<M: Monoid>(
+        &self,
+        monoid: M,
+        mut value: impl FnMut(&T) -> M::Value,
+    ) -> PathIndex<M> {
+        let len = self.links.len();
+        let parent: Vec<_> = self.links.iter().map(|links| links.parent()).collect();
+        let roots: Vec<_> = (0..len).filter(|&index| parent[index].is_none()).collect();
+
+        let mut size = vec![1; len];
+        let mut depth = vec![0; len];
+        for &root in &roots {
+            let order = self.post_order(root);
+            for &index in order.iter().rev() {
+                if let Some(parent) = parent[index] {
+                    depth[index] = depth[parent] + 1;
+                }
+            }
+            for index in order {
+                if let Some(parent) = parent[index] {
+                    size[parent] += size[index];
+                }
+            }
+        }
+
+        let mut head = vec![0; len];
+        let mut root_of = vec![0; len];
+        let mut position = vec![0; len];
+        let mut order = Vec::with_capacity(len);
+        for &root in &roots {
+            let mut stack = vec![root];
+            head[root] = root;
+            while let Some(index) = stack.pop() {
+                root_of[index] = root;
+                position[index] = order.len();
+                order.push(index);
+                let children = &self.links[index].children;
+                let heavy = children.iter().copied().max_by_key(|&child| size[child]);
+                for &child in children.iter().rev() {
+                    if Some(child) != heavy {
+                        head[child] = child;
+                        stack.push(child);
+                    }
+                }
+                // The heavy child is visited next, right after its parent.
+                if let Some(heavy) = heavy {
+                    head[heavy] = head[index];
+                    stack.push(heavy);
+                }
+            }
+        }
+
+        let values: Vec<_> = order
+            .into_iter()
+            .map(|index| value(&self.data[index]))
+            .collect();
+        let reversed = values.iter().rev().cloned().collect();
+        PathIndex {
+            forward: SegmentTree::new(&monoid, values),
+            backward: SegmentTree::new(&monoid, reversed),
+            monoid,
+            parent,
+            depth,
+            head,
+            root: root_of,
+            position,
+        }
+    }
+
+    /// Computes the aggregate of every subtree in a single post-order pass.
+    ///
+    /// The aggregate of a node is its own value combined with the aggregates of its children,
+    /// from left to right: `combine(combine(value, first), second)` and so on. Unlike
+    /// [`Tree::build_subtree_index`], `combine` needs no identity element and may consume its
+    /// arguments, but the result cannot be updated afterwards.
+    ///
+    /// All nodes are covered, including those that are not connected to the root.
+    ///
+    /// # Parameters
+    /// - `extract`: Extracts the value of a node from its data.
+    /// - `combine`: Combines two values.
+    ///
+    /// # Returns
+    /// The aggregate of the subtree of every node, indexed by node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut dirs = Tree::new();
+    /// let root = dirs.add_node(("/", 0));
+    /// let home = dirs.add_child(root, ("home", 4));
+    /// dirs.add_child(home, ("photos", 120));
+    /// dirs.add_child(root, ("etc", 2));
+    ///
+    /// let sizes = dirs.rollup(|(_, size)| *size, |a, b| a + b);
+    /// assert_eq!(sizes, vec![126, 124, 120, 2]);
+    /// ```
+    pub fn rollup<A: Clone>(
+        &self,
+        extract: impl Fn(&T) -> A,
+        combine: impl Fn(A, A) -> A,
+    ) -> Vec<A> {
+        let mut aggregates: Vec<Option<A>> = vec![None; self.links.len()];
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        for root in roots {
+            for index in self.post_order(root) {
+                let aggregate = self.links[index].children.iter().fold(
+                    extract(&self.data[index]),
+                    |aggregate, &child| {
+                        let child = aggregates[child].clone();
+                        combine(aggregate, child.expect("children are aggregated first"))
+                    },
+                );
+                aggregates[index] = Some(aggregate);
+            }
+        }
+        aggregates
+            .into_iter()
+            .map(|aggregate| aggregate.expect("every node is reachable from a root"))
+            .collect()
+    }
+
+    /// Computes the share of every node in the total of its parent's subtree.
+    ///
+    /// Values are summed over subtrees with [`Tree::rollup`], and the share of a node is the
+    /// total of its subtree divided by the total of its parent's subtree. Nodes without a
+    /// parent have a share of `1.0`, and children of a subtree whose total is `0.0` have a share
+    /// of `0.0`. With non-negative values, the shares of the children of a node add up to at
+    /// most `1.0`, the rest being the node's own value.
+    ///
+    /// # Parameters
+    /// - `extract`: Extracts the value of a node from its data.
+    ///
+    /// # Returns
+    /// The share of every node, indexed by node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut budget = Tree::new();
+    /// let company = budget.add_node(0.0);
+    /// let research = budget.add_child(company, 30.0);
+    /// let sales = budget.add_child(company, 50.0);
+    /// budget.add_child(sales, 20.0);
+    ///
+    /// let shares = budget.parent_shares(|amount| *amount);
+    /// assert_eq!(shares[company], 1.0);
+    /// assert_eq!(shares[research], 0.3);
+    /// assert_eq!(shares[sales], 0.7);
+    /// ```
+    pub fn parent_shares(&self, extract: impl Fn(&T) -> f64) -> Vec<f64> {
+        let totals = self.rollup(extract, |a, b| a + b);
+        self.links
+            .iter()
+            .enumerate()
+            .map(|(index, links)| match links.parent() {
+                None => 1.0,
+                Some(parent) if totals[parent] == 0.0 => 0.0,
+                Some(parent) => totals[index] / totals[parent],
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_shares_of_empty_subtrees() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0.0);
+        let empty = tree.add_child(root, 0.0);
+        let leaf = tree.add_child(empty, 0.0);
+        let full = tree.add_child(root, 4.0);
+
+        let shares = tree.parent_shares(|value| *value);
+        assert_eq!(shares[root], 1.0);
+        assert_eq!(shares[empty], 0.0);
+        assert_eq!(shares[leaf], 0.0);
+        assert_eq!(shares[full], 1.0);
+    }
+
+    #[test]
+    fn test_rollup_combines_children_in_order() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("a");
+        let b = tree.add_child(root, "b");
+        tree.add_child(b, "c");
+        tree.add_child(root, "d");
+        let orphan = tree.add_node("e");
+        tree.add_child(orphan, "f");
+
+        let concatenated = tree.rollup(|data| data.to_string(), |a, b| a + &b);
+        assert_eq!(concatenated, vec!["abcd", "bc", "c", "d", "ef", "f"]);
+    }
+
+    #[test]
+    fn test_subtree_index_matches_naive_sums() {
+        let tree = Tree::unfold(
+            0u64,
+            |&n| {
+                (
+                    n,
+                    if n < 40 {
+                        vec![2 * n + 1, 2 * n + 2]
+                    } else {
+                        vec![]
+                    },
+                )
+            },
+            10,
+        );
+        let mut index = tree.build_subtree_index(Sum::new(), |&n| n);
+        let naive = |tree: &Tree<u64>, root: usize| -> u64 {
+            tree.reachable_from(root).map(|i| tree[i]).sum()
+        };
+        for node in 0..tree.len() {
+            assert_eq!(index.aggregate(node), naive(&tree, node));
+        }
+
+        let mut tree = tree;
+        for node in (0..tree.len()).step_by(3) {
+            tree[node] += 1000;
+            index.set(node, tree[node]);
+        }
+        for node in 0..tree.len() {
+            assert_eq!(index.aggregate(node), naive(&tree, node));
+        }
+    }
+
+    #[test]
+    fn test_subtree_index_non_commutative_forest() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("a");
+        let b = tree.add_child(root, "b");
+        tree.add_child(b, "c");
+        tree.add_child(root, "d");
+        let other = tree.add_node("x");
+        tree.add_child(other, "y");
+
+        let concat = monoid(String::new(), |a: &String, b: &String| format!("{a}{b}"));
+        let mut index = tree.build_subtree_index(concat, |data| data.to_string());
+        assert_eq!(index.aggregate(root), "abcd");
+        assert_eq!(index.aggregate(b), "bc");
+        assert_eq!(index.aggregate(other), "xy");
+        index.set(b, "B".to_string());
+        assert_eq!(index.get(b), "B");
+        assert_eq!(index.aggregate(root), "aBcd");
+    }
+
+    #[test]
+    fn test_path_index_matches_naive_paths() {
+        let tree = Tree::unfold(
+            1u64,
+            |&n| {
+                (
+                    n,
+                    if n < 60 {
+                        vec![3 * n, 3 * n + 1, 3 * n + 2]
+                    } else {
+                        vec![]
+                    },
+                )
+            },
+            10,
+        );
+        let concat = monoid(Vec::new(), |a: &Vec<usize>, b: &Vec<usize>| {
+            [a.as_slice(), b].concat()
+        });
+        let mut index = tree.build_path_index(concat, |_| Vec::new());
+        for node in 0..tree.len() {
+            index.set(node, vec![node]);
+        }
+
+        let ancestors = |mut node: usize| {
+            let mut chain = vec![node];
+            while let Some(parent) = tree.parent_index_unchecked(node) {
+                chain.push(parent);
+                node = parent;
+            }
+            chain
+        };
+        for a in (0..tree.len()).step_by(7) {
+            for b in (0..tree.len()).step_by(5) {
+                let (up, down) = (ancestors(a), ancestors(b));
+                let common = up.iter().find(|node| down.contains(node)).unwrap();
+                let mut expected: Vec<_> = up
+                    .iter()
+                    .take_while(|&node| node != common)
+                    .copied()
+                    .collect();
+                expected.push(*common);
+                let below: Vec<_> = down.iter().take_while(|&node| node != common).collect();
+                expected.extend(below.into_iter().rev());
+                assert_eq!(index.aggregate_on_path(a, b), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_index_across_trees() {
+        let mut tree = Tree::new();
+        let a = tree.add_node(1);
+        let b = tree.add_child(a, 2);
+        let other = tree.add_node(10);
+
+        let mut index = tree.build_path_index(Sum::new(), |&n| n);
+        assert_eq!(index.aggregate_on_path(b, other), None);
+        assert_eq!(index.aggregate_on_path(other, other), Some(10));
+        index.set(a, 100);
+        assert_eq!(index.aggregate_on_path(b, a), Some(102));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{Tree, TreeStorage};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Aligns this tree with another one of the same type, matching the children of matched
+    /// nodes by key rather than by position.
+    ///
+    /// This is [`Tree::align_by_keys`] with the same key function on both sides.
+    ///
+    /// # Parameters
+    /// - `other`: The tree to align with.
+    /// - `key_fn`: Produces the key of a node from its data, such as its name.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut old = Tree::new();
+    /// let root = old.add_node(("server", 1));
+    /// old.add_child(root, ("port", 80));
+    /// old.add_child(root, ("host", 2));
+    ///
+    /// let mut new = Tree::new();
+    /// let root = new.add_node(("server", 1));
+    /// new.add_child(root, ("tls", 3));
+    /// new.add_child(root, ("port", 443));
+    ///
+    /// let aligned = old.align_by_key(&new, |(name, _)| *name);
+    /// let changes: Vec<_> = aligned
+    ///     .iter()
+    ///     .filter(|(_, (old, new))| old != new)
+    ///     .map(|(_, pair)| *pair)
+    ///     .collect();
+    /// assert_eq!(
+    ///     changes,
+    ///     [
+    ///         (Some(&("port", 80)), Some(&("port", 443))),
+    ///         (Some(&("host", 2)), None),
+    ///         (None, Some(&("tls", 3))),
+    ///     ]
+    /// );
+    /// ```
+    pub fn align_by_key<'a, 'b, K: Eq + Hash, E: TreeStorage<T>>(
+        &'a self,
+        other: &'b Tree<T, E>,
+        key_fn: impl Fn(&T) -> K,
+    ) -> Tree<(Option<&'a T>, Option<&'b T>)> {
+        self.align_by_keys(other, &key_fn, &key_fn)
+    }
+
+    /// Aligns this tree with another one, matching nodes by key rather than by position, like
+    /// a zip-longest of the two trees.
+    ///
+    /// The nodes without a parent of both trees are matched by key, then the children of every
+    /// pair of matched nodes are, recursively. A node without a match is paired with `None`,
+    /// and so are all its descendants. When siblings share a key, they are matched in order:
+    /// the first with the first, and so on.
+    ///
+    /// The result has one node per pair, in depth-first order. The children of a pair list the
+    /// children of the node of this tree in order, with their matches, followed by the
+    /// unmatched children of the node of `other`, in order.
+    ///
+    /// # Parameters
+    /// - `other`: The tree to align with.
+    /// - `key_fn`: Produces the key of a node of this tree from its data.
+    /// - `other_key_fn`: Produces the key of a node of `other` from its data.
+    ///
+    /// # Returns
+    /// A tree of `(node of this tree, node of other)` pairs, where at least one side is set.
+    pub fn align_by_keys<'a, 'b, U, K: Eq + Hash, E: TreeStorage<U>>(
+        &'a self,
+        other: &'b Tree<U, E>,
+        mut key_fn: impl FnMut(&'a T) -> K,
+        mut other_key_fn: impl FnMut(&'b U) -> K,
+    ) -> Tree<(Option<&'a T>, Option<&'b U>)> {
+        // Pairs the nodes of `left` with the nodes of `right` sharing their key.
+        let mut pair = |left: &[usize], right: &[usize]| {
+            let mut unmatched: HashMap<K, VecDeque<usize>> = HashMap::new();
+            for (position, &index) in right.iter().enumerate() {
+                let key = other_key_fn(&other.data[index]);
+                unmatched.entry(key).or_default().push_back(position);
+            }
+            let mut matched = vec![false; right.len()];
+            let mut pairs = Vec::with_capacity(left.len().max(right.len()));
+            for &index in left {
+                let position = unmatched
+                    .get_mut(&key_fn(&self.data[index]))
+                    .and_then(VecDeque::pop_front);
+                if let Some(position) = position {
+                    matched[position] = true;
+                }
+                pairs.push((Some(index), position.map(|position| right[position])));
+            }
+            let rest = right.iter().zip(&matched).filter(|(_, &matched)| !matched);
+            pairs.extend(rest.map(|(&index, _)| (None, Some(index))));
+            pairs
+        };
+
+        let left_roots: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+        let right_roots: Vec<usize> = (0..other.links.len())
+            .filter(|&index| other.links[index].parent().is_none())
+            .collect();
+
+        let mut aligned = Tree::new();
+        let mut stack: Vec<_> = pair(&left_roots, &right_roots)
+            .into_iter()
+            .rev()
+            .map(|pair| (pair, None))
+            .collect();
+        while let Some(((left, right), parent)) = stack.pop() {
+            let data = (
+                left.map(|index| &self.data[index]),
+                right.map(|index| &other.data[index]),
+            );
+            let index = match parent {
+                Some(parent) => aligned.add_child(parent, data),
+                None => aligned.add_node(data),
+            };
+            let left_children = left.map_or(&[][..], |left| &self.links[left].children);
+            let right_children = right.map_or(&[][..], |right| &other.links[right].children);
+            let pairs = pair(left_children, right_children);
+            stack.extend(pairs.into_iter().rev().map(|pair| (pair, Some(index))));
+        }
+        aligned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_by_key_matches_reordered_and_duplicate_children() {
+        let mut left = Tree::new();
+        let root = left.add_node("root");
+        let a = left.add_child(root, "a");
+        left.add_child(a, "x");
+        left.add_child(root, "b");
+        left.add_child(root, "b");
+
+        let mut right = Tree::new();
+        let root = right.add_node("root");
+        right.add_child(root, "b");
+        let a = right.add_child(root, "a");
+        right.add_child(a, "y");
+        right.add_child(root, "c");
+
+        let aligned = left.align_by_key(&right, |name| *name);
+        let pairs: Vec<_> = aligned
+            .iter()
+            .map(|(_, (l, r))| (l.copied(), r.copied()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                (Some("root"), Some("root")),
+                (Some("a"), Some("a")),
+                (Some("x"), None),
+                (None, Some("y")),
+                (Some("b"), Some("b")),
+                (Some("b"), None),
+                (None, Some("c")),
+            ]
+        );
+        assert_eq!(aligned.children(0), &[1, 4, 5, 6]);
+        assert_eq!(aligned.children(1), &[2, 3]);
+    }
+
+    #[test]
+    fn test_align_by_keys_with_different_types_and_forests() {
+        let mut left = Tree::new();
+        let one = left.add_node((1, "one"));
+        left.add_child(one, (10, "ten"));
+        left.add_node((2, "two"));
+
+        let mut right = Tree::new();
+        right.add_node(2u64);
+        right.add_node(3);
+
+        let aligned = left.align_by_keys(&right, |(key, _)| *key as u64, |key| *key);
+        let pairs: Vec<_> = aligned.iter().map(|(_, pair)| *pair).collect();
+        assert_eq!(
+            pairs,
+            [
+                (Some(&(1, "one")), None),
+                (Some(&(10, "ten")), None),
+                (Some(&(2, "two")), Some(&2)),
+                (None, Some(&3)),
+            ]
+        );
+        assert_eq!(aligned.parent_index_unchecked(1), Some(0));
+        assert_eq!(aligned.parent_index_unchecked(3), None);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{Tree, TreeStorage};
+
+/// An index answering ancestor queries in `O(log n)` using binary lifting.
+///
+/// For every node, the index stores its ancestors at distances 1, 2, 4, 8, and so on, so any
+/// ancestor can be reached in a logarithmic number of jumps instead of walking parent by
+/// parent.
+///
+/// The index does not borrow the tree and reflects its structure at the time it was built;
+/// nodes added or moved later are not covered.
+///
+/// Constructed with [`Tree::build_ancestor_index`].
+pub struct AncestorIndex {
+    depth: Vec<usize>,
+    /// `jumps[level][index]` is the ancestor of `index` at distance `2^level`.
+    jumps: Vec<Vec<Option<usize>>>,
+}
+
+impl AncestorIndex {
+    /// Returns the depth of a node: `0` for nodes without a parent.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn depth(&self, index: usize) -> usize {
+        self.depth[index]
+    }
+
+    /// Returns the ancestor `k` levels above a node.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    /// - `k`: The distance to the ancestor. `0` returns the node itself, `1` its parent.
+    ///
+    /// # Returns
+    /// The index of the ancestor, or `None` if the node is less than `k` levels deep.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn kth_ancestor(&self, index: usize, k: usize) -> Option<usize> {
+        if k > self.depth[index] {
+            return None;
+        }
+        let mut current = index;
+        for (level, jumps) in self.jumps.iter().enumerate() {
+            if k & (1 << level) != 0 {
+                current = jumps[current]?;
+            }
+        }
+        Some(current)
+    }
+
+    /// Returns the lowest common ancestor of two nodes: the deepest node that has both of them
+    /// in its subtree. A node counts as its own ancestor.
+    ///
+    /// # Returns
+    /// The index of the lowest common ancestor, or `None` if the nodes are not in the same
+    /// tree.
+    ///
+    /// # Panics
+    /// This method panics if `a` or `b` is out of bounds.
+    pub fn lca(&self, a: usize, b: usize) -> Option<usize> {
+        let (deeper, shallower) = if self.depth[a] >= self.depth[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let mut a = self.kth_ancestor(deeper, self.depth[deeper] - self.depth[shallower])?;
+        let mut b = shallower;
+        if a == b {
+            return Some(a);
+        }
+        for jumps in self.jumps.iter().rev() {
+            if jumps[a] != jumps[b] {
+                a = jumps[a]?;
+                b = jumps[b]?;
+            }
+        }
+        // Nodes in different trees never meet: their roots have no parents.
+        let (a, b) = (self.jumps[0][a]?, self.jumps[0][b]?);
+        (a == b).then_some(a)
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Builds an [`AncestorIndex`] for `O(log n)` ancestor and lowest common ancestor queries.
+    ///
+    /// Building takes `O(n log n)` time and memory.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let main = tree.add_node("main");
+    /// let parse = tree.add_child(main, "parse");
+    /// let lex = tree.add_child(parse, "lex");
+    /// let run = tree.add_child(main, "run");
+    ///
+    /// let ancestors = tree.build_ancestor_index();
+    /// assert_eq!(ancestors.kth_ancestor(lex, 2), Some(main));
+    /// assert_eq!(ancestors.kth_ancestor(lex, 3), None);
+    /// assert_eq!(ancestors.lca(lex, run), Some(main));
+    /// ```
+    pub fn build_ancestor_index(&self) -> AncestorIndex {
+        let len = self.links.len();
+        let mut depth = vec![0; len];
+        for root in (0..len).filter(|&index| self.links[index].parent().is_none()) {
+            for index in self.reachable_from(root) {
+                for &child in &self.links[index].children {
+                    depth[child] = depth[index] + 1;
+                }
+            }
+        }
+
+        let mut jumps = vec![self
+            .links
+            .iter()
+            .map(|links| links.parent())
+            .collect::<Vec<_>>()];
+        let max_depth = depth.iter().copied().max().unwrap_or(0);
+        while 1 << jumps.len() <= max_depth {
+            let previous = jumps.last().unwrap();
+            let next = previous
+                .iter()
+                .map(|&ancestor| ancestor.and_then(|ancestor| previous[ancestor]))
+                .collect();
+            jumps.push(next);
+        }
+
+        AncestorIndex { depth, jumps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ancestor_index_matches_parent_walk() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..300 {
+            let parent = if index % 3 == 0 { index / 2 } else { index - 1 };
+            tree.add_child(parent, index);
+        }
+        let ancestors = tree.build_ancestor_index();
+        let walk = |mut index: usize| {
+            let mut chain = vec![index];
+            while let Some(parent) = tree.parent_index_unchecked(index) {
+                chain.push(parent);
+                index = parent;
+            }
+            chain
+        };
+        for a in (0..tree.len()).step_by(11) {
+            let chain = walk(a);
+            assert_eq!(ancestors.depth(a), chain.len() - 1);
+            for k in 0..chain.len() + 2 {
+                assert_eq!(ancestors.kth_ancestor(a, k), chain.get(k).copied());
+            }
+            for b in (0..tree.len()).step_by(13) {
+                let other = walk(b);
+                let expected = chain.iter().find(|node| other.contains(node)).copied();
+                assert_eq!(ancestors.lca(a, b), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ancestor_index_forest() {
+        let mut tree = Tree::new();
+        let a = tree.add_node(());
+        let a1 = tree.add_child(a, ());
+        let b = tree.add_node(());
+        let b1 = tree.add_child(b, ());
+
+        let ancestors = tree.build_ancestor_index();
+        assert_eq!(ancestors.lca(a1, b1), None);
+        assert_eq!(ancestors.lca(a, b), None);
+        assert_eq!(ancestors.lca(a1, a1), Some(a1));
+        assert_eq!(ancestors.lca(a, a1), Some(a));
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{Tree, TreeStorage};
+use std::cmp::Ordering;
+
+impl<T> Tree<T> {
+    /// Builds a height-balanced binary tree from items sorted in ascending order.
+    ///
+    /// The middle item becomes the root, the items before it form its first child's subtree
+    /// and the items after it its second child's subtree, recursively. A node with a single
+    /// child therefore always has a smaller child. Nodes are numbered in depth-first
+    /// pre-order, with the root at index `0`.
+    ///
+    /// # Parameters
+    /// - `items`: The items in ascending order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let tree = Tree::balanced_from_sorted(1..=7);
+    /// assert_eq!(tree.get(0), Some(&4));
+    /// let children: Vec<_> = tree.children(0).iter().map(|&c| tree[c]).collect();
+    /// assert_eq!(children, vec![2, 6]);
+    /// assert_eq!(tree.stats().height, 3);
+    /// ```
+    pub fn balanced_from_sorted(items: impl IntoIterator<Item = T>) -> Self {
+        let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+        let mut tree = Tree::new();
+        tree.data.reserve(items.len());
+        tree.links.reserve(items.len());
+
+        let mut stack = vec![(0, items.len(), None)];
+        while let Some((start, end, parent)) = stack.pop() {
+            if start == end {
+                continue;
+            }
+            let middle = start + (end - start) / 2;
+            let data = items[middle].take().expect("every item is used once");
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, data),
+                None => tree.add_node(data),
+            };
+            stack.push((middle + 1, end, Some(index)));
+            stack.push((start, middle, Some(index)));
+        }
+        tree
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Rebalances the tree reachable from the root into a height-balanced binary tree, ordered
+    /// by `compare`.
+    ///
+    /// The nodes are sorted with a stable sort using `compare` and relinked as if built by
+    /// [`Tree::balanced_from_sorted`]: the children of every node are its smaller child, if
+    /// any, followed by its greater or equal child, if any. Nodes keep their data and their
+    /// indices, except that the new root takes the index of the old root by exchanging indices
+    /// with it, so that it stays the [root](Tree::root).
+    ///
+    /// # Parameters
+    /// - `compare`: The ordering of the node data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// // A degenerate chain built from sorted input.
+    /// let mut tree = Tree::new();
+    /// let mut last = tree.add_node(1);
+    /// for value in 2..=7 {
+    ///     last = tree.add_child(last, value);
+    /// }
+    /// assert_eq!(tree.stats().height, 7);
+    ///
+    /// tree.rebalance_by(|a, b| a.cmp(b));
+    /// assert_eq!(tree.stats().height, 3);
+    /// assert_eq!(tree.get(0), Some(&4));
+    /// ```
+    pub fn rebalance_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        let Some(old_root) = self.root() else {
+            return;
+        };
+
+        let mut order: Vec<usize> = self.reachable_from(old_root).collect();
+        order.sort_by(|&a, &b| compare(&self.data[a], &self.data[b]));
+        for &index in &order {
+            self.links[index].children.clear();
+            self.links[index].replace_parent(None);
+        }
+
+        let root = order[order.len() / 2];
+        let mut stack: Vec<(usize, usize, Option<usize>)> = vec![(0, order.len(), None)];
+        while let Some((start, end, parent)) = stack.pop() {
+            if start == end {
+                continue;
+            }
+            let middle = start + (end - start) / 2;
+            let index = order[middle];
+            if let Some(parent) = parent {
+                self.link(parent, index);
+            }
+            stack.push((middle + 1, end, Some(index)));
+            stack.push((start, middle, Some(index)));
+        }
+        self.swap_slots(root, old_root);
+    }
+
+    /// Rotates the subtree rooted at `index` to the left, treating the tree as a binary tree
+    /// ordered by `compare`.
+    ///
+    /// The children of a node that compare less than it form its left side, all others its
+    /// right side; a node must have at most one child on each side. The right child of the
+    /// node takes its place, and the node becomes the left child of its former right child,
+    /// adopting that child's left subtree as its new right child. Children are kept in
+    /// left-to-right order.
+    ///
+    /// Indices of all nodes are preserved, except when rotating a node without a parent, such
+    /// as the root: the new top node then takes its index by exchanging indices with it, as in
+    /// [`Tree::rebalance_by`], so that traversals from the [root](Tree::root) still see the
+    /// whole tree.
+    ///
+    /// # Returns
+    /// The index of the node that took the place of `index`, which is `index` itself when
+    /// rotating a node without a parent, or `None` if the node has no right child.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds, or if one of the involved nodes has more
+    /// than one child on one side.
+    pub fn rotate_left_by(
+        &mut self,
+        index: usize,
+        mut compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Option<usize> {
+        let (left, right) = self.binary_children(index, &mut compare);
+        let pivot = right?;
+        let (pivot_left, pivot_right) = self.binary_children(pivot, &mut compare);
+        self.replace_in_parent(index, pivot);
+        self.set_binary_children(index, left, pivot_left);
+        self.set_binary_children(pivot, Some(index), pivot_right);
+        Some(self.keep_root_first(index, pivot))
+    }
+
+    /// Rotates the subtree rooted at `index` to the right, treating the tree as a binary tree
+    /// ordered by `compare`. This is the inverse of [`Tree::rotate_left_by`], and keeps the
+    /// index of a rotated node without a parent in the same way.
+    ///
+    /// # Returns
+    /// The index of the node that took the place of `index`, which is `index` itself when
+    /// rotating a node without a parent, or `None` if the node has no left child.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds, or if one of the involved nodes has more
+    /// than one child on one side.
+    pub fn rotate_right_by(
+        &mut self,
+        index: usize,
+        mut compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Option<usize> {
+        let (left, right) = self.binary_children(index, &mut compare);
+        let pivot = left?;
+        let (pivot_left, pivot_right) = self.binary_children(pivot, &mut compare);
+        self.replace_in_parent(index, pivot);
+        self.set_binary_children(index, pivot_right, right);
+        self.set_binary_children(pivot, pivot_left, Some(index));
+        Some(self.keep_root_first(index, pivot))
+    }
+
+    /// Moves `pivot` to `index` if the rotation at `index` took the place of a node without a
+    /// parent, returning the index of `pivot` afterwards.
+    fn keep_root_first(&mut self, index: usize, pivot: usize) -> usize {
+        if self.links[pivot].parent().is_none() {
+            self.swap_slots(pivot, index);
+            index
+        } else {
+            pivot
+        }
+    }
+
+    /// Splits the children of a node into its left (smaller) and right child.
+    fn binary_children(
+        &self,
+        index: usize,
+        compare: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (Option<usize>, Option<usize>) {
+        let (mut left, mut right) = (None, None);
+        for &child in &self.links[index].children {
+            let side = if compare(&self.data[child], &self.data[index]).is_lt() {
+                &mut left
+            } else {
+                &mut right
+            };
+            assert!(
+                side.replace(child).is_none(),
+                "node {index} has more than one child on the same side"
+            );
+        }
+        (left, right)
+    }
+
+    /// Replaces the children of a node. A rotation resets the children of every node whose
+    /// children it moves, so a moved child is only released from its former parent here.
+    fn set_binary_children(&mut self, index: usize, left: Option<usize>, right: Option<usize>) {
+        self.links[index].children.clear();
+        for child in left.into_iter().chain(right) {
+            self.links[child].replace_parent(None);
+            self.link(index, child);
+        }
+    }
+
+    /// Puts `replacement` in the place of `index` among the children of its parent.
+    fn replace_in_parent(&mut self, index: usize, replacement: usize) {
+        let parent = self.links[index].parent();
+        if let Some(parent) = parent {
+            for child in &mut self.links[parent].children {
+                if *child == index {
+                    *child = replacement;
+                }
+            }
+        }
+        self.links[replacement].replace_parent(parent);
+    }
+}
+
+impl<T: Ord, D: TreeStorage<T>> Tree<T, D> {
+    /// Rebalances the tree reachable from the root into a height-balanced binary tree, ordered
+    /// by the natural order of the data. See [`Tree::rebalance_by`].
+    pub fn rebalance(&mut self) {
+        self.rebalance_by(T::cmp);
+    }
+
+    /// Rotates the subtree rooted at `index` to the left, using the natural order of the data.
+    /// See [`Tree::rotate_left_by`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// //   2            4
+    /// //  / \          / \
+    /// // 1   4   ->   2   5
+    /// //    / \      / \
+    /// //   3   5    1   3
+    /// let mut tree = Tree::new();
+    /// let two = tree.add_node(2);
+    /// let one = tree.add_child(two, 1);
+    /// let four = tree.add_child(two, 4);
+    /// let three = tree.add_child(four, 3);
+    /// let five = tree.add_child(four, 5);
+    ///
+    /// // The root keeps its index: 4 and 2 exchange their indices.
+    /// assert_eq!(tree.rotate_left(two), Some(0));
+    /// assert_eq!(tree[0], 4);
+    /// assert_eq!(tree[four], 2);
+    /// assert_eq!(tree.children(0), &[four, five]);
+    /// assert_eq!(tree.children(four), &[one, three]);
+    ///
+    /// assert_eq!(tree.rotate_right(0), Some(0));
+    /// assert_eq!(tree[0], 2);
+    /// assert_eq!(tree.children(0), &[one, four]);
+    /// ```
+    pub fn rotate_left(&mut self, index: usize) -> Option<usize> {
+        self.rotate_left_by(index, T::cmp)
+    }
+
+    /// Rotates the subtree rooted at `index` to the right, using the natural order of the data.
+    /// See [`Tree::rotate_right_by`].
+    pub fn rotate_right(&mut self, index: usize) -> Option<usize> {
+        self.rotate_right_by(index, T::cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_order(tree: &Tree<i32>, index: usize, out: &mut Vec<i32>) {
+        let data = tree[index];
+        let (smaller, greater): (Vec<usize>, Vec<usize>) = tree
+            .children(index)
+            .iter()
+            .partition(|&&child| tree[child] < data);
+        for child in smaller {
+            in_order(tree, child, out);
+        }
+        out.push(data);
+        for child in greater {
+            in_order(tree, child, out);
+        }
+    }
+
+    #[test]
+    fn test_balanced_from_sorted() {
+        assert!(Tree::<i32>::balanced_from_sorted(vec![]).is_empty());
+        for len in 1..40 {
+            let tree = Tree::balanced_from_sorted(0..len);
+            let mut values = vec![];
+            in_order(&tree, 0, &mut values);
+            assert_eq!(values, (0..len).collect::<Vec<_>>());
+            let expected_height = (usize::BITS - (len as usize).leading_zeros()) as usize;
+            assert_eq!(tree.stats().height, expected_height);
+        }
+    }
+
+    #[test]
+    fn test_rebalance_keeps_indices_and_detached_nodes() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(5);
+        let mut last = root;
+        for value in [1, 9, 3, 7] {
+            last = tree.add_child(last, value);
+        }
+        let detached = tree.add_node(100);
+
+        tree.rebalance();
+        assert_eq!(tree.get(0), Some(&5));
+        assert_eq!(tree.get(detached), Some(&100));
+        assert_eq!(tree.parent_index_unchecked(detached), None);
+        let mut values = vec![];
+        in_order(&tree, 0, &mut values);
+        assert_eq!(values, vec![1, 3, 5, 7, 9]);
+        assert_eq!(tree.stats().width_per_level, vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn test_rotations_on_inner_node() {
+        // 10 -> 5 -> (3, 7)
+        let mut tree = Tree::new();
+        let ten = tree.add_node(10);
+        let five = tree.add_child(ten, 5);
+        let three = tree.add_child(five, 3);
+        let seven = tree.add_child(five, 7);
+
+        assert_eq!(tree.rotate_right(five), Some(three));
+        assert_eq!(tree.children(ten), &[three]);
+        assert_eq!(tree.children(three), &[five]);
+        assert_eq!(tree.children(five), &[seven]);
+        assert_eq!(tree.rotate_right(five), None);
+        assert_eq!(tree.rotate_left(three), Some(five));
+        assert_eq!(tree.children(five), &[three, seven]);
+        assert_eq!(tree.parent_index_unchecked(five), Some(ten));
+    }
+
+    #[test]
+    fn test_rotating_the_root_keeps_it_at_index_zero() {
+        let mut tree = Tree::new();
+        let two = tree.add_node(2);
+        tree.add_child(two, 1);
+        let four = tree.add_child(two, 4);
+        tree.add_child(four, 3);
+        tree.add_child(four, 5);
+
+        let traversed = |tree: &Tree<i32>| {
+            let mut values = vec![];
+            tree.traverse(
+                |_, value, values| values.push(*value),
+                |_, _, _| {},
+                &mut values,
+            );
+            values
+        };
+        assert_eq!(tree.rotate_left(0), Some(0));
+        assert_eq!(traversed(&tree), [4, 2, 1, 3, 5]);
+        assert_eq!(tree.parent_index_unchecked(0), None);
+        assert_eq!(tree.rotate_right(0), Some(0));
+        assert_eq!(traversed(&tree), [2, 1, 4, 3, 5]);
+    }
+}
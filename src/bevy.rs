@@ -0,0 +1,170 @@
+use crate::checkpoint::History;
+use crate::ext::Extensions;
+use crate::node_id::TreeNonce;
+use crate::{EulerStep, Tree, TreeStorage};
+use bevy_ecs::bundle::Bundle;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::hierarchy::{ChildOf, Children};
+use bevy_ecs::world::{EntityRef, World};
+use std::marker::PhantomData;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Spawns every node as an entity of a Bevy `World`, linked by `ChildOf` components, so
+    /// that the entity hierarchy mirrors the tree.
+    ///
+    /// Children are spawned in order, so the `Children` of every entity are ordered as in the
+    /// tree. Nodes without a parent become root entities. Nodes are spawned in depth-first
+    /// order, parents before their children.
+    ///
+    /// # Parameters
+    /// - `world`: The world to spawn the entities in.
+    /// - `bundle_fn`: Produces the components of a node from its index and data.
+    ///
+    /// # Returns
+    /// A tree with the same structure holding the entity of every node, so that an index into
+    /// this tree addresses the entity of the corresponding node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use easy_tree::Tree;
+    ///
+    /// #[derive(Component)]
+    /// struct Name(&'static str);
+    ///
+    /// let mut scene = Tree::new();
+    /// let root = scene.add_node("player");
+    /// let weapon = scene.add_child(root, "sword");
+    ///
+    /// let mut world = World::new();
+    /// let entities = scene.spawn_hierarchy(&mut world, |_, name| Name(name));
+    /// assert_eq!(world.get::<ChildOf>(entities[weapon]), Some(&ChildOf(entities[root])));
+    /// assert_eq!(world.get::<Name>(entities[weapon]).unwrap().0, "sword");
+    /// ```
+    pub fn spawn_hierarchy<B: Bundle>(
+        &self,
+        world: &mut World,
+        mut bundle_fn: impl FnMut(usize, &T) -> B,
+    ) -> Tree<Entity> {
+        let mut entities = vec![Entity::PLACEHOLDER; self.links.len()];
+        for step in self.euler_tour() {
+            let EulerStep::Enter { index, .. } = step else {
+                continue;
+            };
+            let bundle = bundle_fn(index, &self.data[index]);
+            entities[index] = match self.links[index].parent() {
+                Some(parent) => world.spawn((bundle, ChildOf(entities[parent]))).id(),
+                None => world.spawn(bundle).id(),
+            };
+        }
+        Tree {
+            data: entities,
+            marker: PhantomData,
+            links: self.links.clone(),
+            ext: Extensions::default(),
+            nonce: TreeNonce::new(),
+            history: History::default(),
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    /// Captures the hierarchy of a Bevy entity and its descendants, following their `Children`
+    /// components, into a tree.
+    ///
+    /// `root` becomes node `0` and its descendants follow in depth-first order, with children
+    /// ordered as in their parent's `Children`. Use `|entity| entity.id()` as `data_fn` to get a
+    /// `Tree<Entity>`.
+    ///
+    /// # Parameters
+    /// - `world`: The world holding the entities.
+    /// - `root`: The entity at the root of the captured hierarchy.
+    /// - `data_fn`: Produces the data of a node from its entity.
+    ///
+    /// # Panics
+    /// Panics if `root` or one of its descendants does not exist in `world`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut world = World::new();
+    /// let root = world.spawn_empty().id();
+    /// let child = world.spawn(ChildOf(root)).id();
+    /// let grandchild = world.spawn(ChildOf(child)).id();
+    ///
+    /// let tree = Tree::from_entity_hierarchy(&world, root, |entity| entity.id());
+    /// assert_eq!(tree.data(), &[root, child, grandchild]);
+    /// assert_eq!(tree.parent_index_unchecked(2), Some(1));
+    /// ```
+    pub fn from_entity_hierarchy(
+        world: &World,
+        root: Entity,
+        mut data_fn: impl FnMut(EntityRef<'_>) -> T,
+    ) -> Self {
+        let mut tree = Tree::new();
+        let mut stack = vec![(root, None)];
+        while let Some((entity, parent)) = stack.pop() {
+            let entity = world.entity(entity);
+            let data = data_fn(entity);
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, data),
+                None => tree.add_node(data),
+            };
+            if let Some(children) = entity.get::<Children>() {
+                stack.extend(children.iter().rev().map(|&child| (child, Some(index))));
+            }
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::component::Component;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Label(&'static str);
+
+    #[test]
+    fn test_spawn_and_capture_round_trip() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        tree.add_child(a, "a1");
+        tree.add_child(root, "b");
+        // A parent with a higher index than its child is still spawned first.
+        let c = tree.add_node("c");
+        tree.set_parent(c, a).unwrap();
+
+        let mut world = World::new();
+        let entities = tree.spawn_hierarchy(&mut world, |_, label| Label(label));
+        assert_eq!(
+            &**world.get::<Children>(entities[a]).unwrap(),
+            &[entities[2], entities[c]]
+        );
+
+        let captured = Tree::from_entity_hierarchy(&world, entities[root], |entity| {
+            entity.get::<Label>().unwrap().0
+        });
+        assert_eq!(captured.data(), &["root", "a", "a1", "c", "b"]);
+        assert_eq!(captured.children(1), &[2, 3]);
+    }
+
+    #[test]
+    fn test_spawn_forest_creates_several_roots() {
+        let mut tree = Tree::new();
+        tree.add_node("first");
+        tree.add_node("second");
+
+        let mut world = World::new();
+        let entities = tree.spawn_hierarchy(&mut world, |index, _| Label(["x", "y"][index]));
+        assert!(entities
+            .data()
+            .iter()
+            .all(|&entity| world.get::<ChildOf>(entity).is_none()));
+        assert_eq!(world.get::<Label>(entities[1]), Some(&Label("y")));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::node_id::TreeNonce;
+use crate::{Tree, TreeError, TreeStorage};
+
+/// The position of a traversal started with [`Tree::traverse_budgeted`], to be continued
+/// with [`Tree::resume_traversal`].
+///
+/// The handle does not borrow the tree, but remembers which tree it was taken from, like a
+/// [`Checkpoint`](crate::Checkpoint), and [`Tree::resume_traversal`] rejects it on any other
+/// tree, including clones. If the structure of the tree changes between two steps, the
+/// traversal continues from the same indices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraversalResume {
+    /// The pending visits, the next one last, with `true` for nodes whose children were
+    /// visited and only `after_processing_the_subtree` remains.
+    stack: Vec<(usize, bool)>,
+    /// The tree the traversal runs on.
+    tree: TreeNonce,
+}
+
+impl TraversalResume {
+    /// Returns `true` if every node was visited and every callback was called.
+    pub fn is_finished(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Starts a depth-first traversal like [`Tree::traverse`], but stops after visiting
+    /// `budget_nodes` nodes, so that a large traversal can be spread over several frames of a
+    /// game or a UI without blocking one of them.
+    ///
+    /// A node counts towards the budget when `before_processing_children` is called for it.
+    /// The `after_processing_the_subtree` calls that are due before the next node are made in
+    /// the same step, so a step never stops between the last child of a node and the node.
+    ///
+    /// # Parameters
+    /// - `budget_nodes`: The maximum number of nodes to visit in this step.
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Returns
+    /// A handle to pass to [`Tree::resume_traversal`] to continue with the next step.
+    ///
+    /// # Panics
+    /// This method panics if `budget_nodes` is `0`, since the traversal would never advance.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// for value in 2..=10 {
+    ///     tree.add_child(root, value);
+    /// }
+    ///
+    /// // Sum the values over several frames, visiting at most 4 nodes per frame.
+    /// let add = |_, value: &i32, sum: &mut i32| *sum += value;
+    /// let mut sum = 0;
+    /// let mut frames = 1;
+    /// let mut resume = tree.traverse_budgeted(4, add, |_, _, _| {}, &mut sum);
+    /// while !resume.is_finished() {
+    ///     frames += 1;
+    ///     resume = tree
+    ///         .resume_traversal(resume, 4, add, |_, _, _| {}, &mut sum)
+    ///         .unwrap();
+    /// }
+    /// assert_eq!(sum, 55);
+    /// assert_eq!(frames, 3);
+    /// ```
+    pub fn traverse_budgeted<S>(
+        &self,
+        budget_nodes: usize,
+        before_processing_children: impl FnMut(usize, &T, &mut S),
+        after_processing_the_subtree: impl FnMut(usize, &T, &mut S),
+        s: &mut S,
+    ) -> TraversalResume {
+        let stack = self.root().map(|root| (root, false)).into_iter().collect();
+        self.traverse_steps(
+            TraversalResume {
+                stack,
+                tree: self.nonce,
+            },
+            budget_nodes,
+            before_processing_children,
+            after_processing_the_subtree,
+            s,
+        )
+    }
+
+    /// Continues a traversal started with [`Tree::traverse_budgeted`] for another
+    /// `budget_nodes` nodes. A finished traversal is returned as is.
+    ///
+    /// # Parameters
+    /// - `resume`: The handle returned by the previous step.
+    /// - `budget_nodes`: The maximum number of nodes to visit in this step.
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Errors
+    /// - [`TreeError::ForeignNode`] with the next pending node if the handle was taken from
+    ///   another tree.
+    /// - [`TreeError::InvalidIndex`] if the handle refers to a node that this tree no longer
+    ///   has.
+    ///
+    /// No callback is called when an error is returned.
+    ///
+    /// # Panics
+    /// This method panics if `budget_nodes` is `0`, since the traversal would never advance.
+    pub fn resume_traversal<S>(
+        &self,
+        resume: TraversalResume,
+        budget_nodes: usize,
+        before_processing_children: impl FnMut(usize, &T, &mut S),
+        after_processing_the_subtree: impl FnMut(usize, &T, &mut S),
+        s: &mut S,
+    ) -> Result<TraversalResume, TreeError> {
+        if resume.tree != self.nonce {
+            let next = resume.stack.last().map_or(0, |&(index, _)| index);
+            return Err(TreeError::ForeignNode(next));
+        }
+        if let Some(&(index, _)) = resume.stack.iter().find(|&&(index, _)| index >= self.len()) {
+            return Err(TreeError::InvalidIndex(index));
+        }
+        Ok(self.traverse_steps(
+            resume,
+            budget_nodes,
+            before_processing_children,
+            after_processing_the_subtree,
+            s,
+        ))
+    }
+
+    /// Visits up to `budget_nodes` nodes from a handle whose indices are in bounds.
+    fn traverse_steps<S>(
+        &self,
+        mut resume: TraversalResume,
+        budget_nodes: usize,
+        mut before_processing_children: impl FnMut(usize, &T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &T, &mut S),
+        s: &mut S,
+    ) -> TraversalResume {
+        assert!(budget_nodes > 0, "the node budget must be at least 1");
+        let mut visited = 0;
+        while let Some(&(index, children_visited)) = resume.stack.last() {
+            if children_visited {
+                resume.stack.pop();
+                after_processing_the_subtree(index, &self.data[index], s);
+            } else if visited < budget_nodes {
+                visited += 1;
+                resume.stack.pop();
+                before_processing_children(index, &self.data[index], s);
+                resume.stack.push((index, true));
+                let children = self.links[index].children.iter().rev();
+                resume.stack.extend(children.map(|&child| (child, false)));
+            } else {
+                break;
+            }
+        }
+        resume
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budgeted_steps_match_traverse() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..40 {
+            tree.add_child((index - 1) / 3, index);
+        }
+        let mut expected = vec![];
+        tree.traverse(
+            |index, _, log: &mut Vec<(bool, usize)>| log.push((true, index)),
+            |index, _, log| log.push((false, index)),
+            &mut expected,
+        );
+
+        let before = |index, _: &usize, log: &mut Vec<(bool, usize)>| log.push((true, index));
+        let after = |index, _: &usize, log: &mut Vec<(bool, usize)>| log.push((false, index));
+        let entered = |log: &[(bool, usize)]| log.iter().filter(|(entered, _)| *entered).count();
+        for budget in [1, 5, 40] {
+            let mut log = vec![];
+            let mut resume = tree.traverse_budgeted(budget, before, after, &mut log);
+            let mut steps = 1;
+            while !resume.is_finished() {
+                let previously_entered = entered(&log);
+                resume = tree
+                    .resume_traversal(resume, budget, before, after, &mut log)
+                    .unwrap();
+                assert!(entered(&log) - previously_entered <= budget);
+                steps += 1;
+            }
+            assert_eq!(log, expected);
+            assert_eq!(steps, 40_usize.div_ceil(budget));
+        }
+
+        let mut count = 0;
+        let resume =
+            Tree::<u8>::new().traverse_budgeted(3, |_, _, n| *n += 1, |_, _, _| {}, &mut count);
+        assert!(resume.is_finished());
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_resume_traversal_rejects_a_handle_of_another_tree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        for value in 1..10 {
+            tree.add_child(root, value);
+        }
+        let count = |_: usize, _: &i32, calls: &mut usize| *calls += 1;
+        let resume = tree.traverse_budgeted(2, |_, _, _| {}, |_, _, _| {}, &mut ());
+
+        // A clone has the same nodes, but is another tree.
+        let copy = tree.clone();
+        let mut calls = 0;
+        let result = copy.resume_traversal(resume.clone(), 2, count, |_, _, _| {}, &mut calls);
+        assert_eq!(result, Err(TreeError::ForeignNode(2)));
+
+        tree.clear();
+        tree.add_node(0);
+        let result = tree.resume_traversal(resume, 2, count, |_, _, _| {}, &mut calls);
+        assert!(matches!(result, Err(TreeError::InvalidIndex(_))));
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the node budget must be at least 1")]
+    fn test_zero_budget_panics() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        tree.traverse_budgeted(0, |_, _, _| {}, |_, _, _| {}, &mut ());
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{Links, Tree, TreeStorage};
+use std::collections::VecDeque;
+use std::mem;
+
+/// A node together with its descendants, written as a nested value, for inserting whole
+/// subtrees at once with [`Tree::add_subtree`].
+///
+/// # Example
+/// ```rust
+/// use easy_tree::NestedNode;
+///
+/// let menu = NestedNode::new(
+///     "file",
+///     vec![
+///         "new".into(),
+///         NestedNode::new("recent", vec!["a.txt".into(), "b.txt".into()]),
+///     ],
+/// );
+/// assert_eq!(menu.children.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NestedNode<T> {
+    /// The data of the node.
+    pub data: T,
+    /// The children of the node, in order.
+    pub children: Vec<NestedNode<T>>,
+}
+
+impl<T> NestedNode<T> {
+    /// Creates a node with the given children.
+    pub fn new(data: T, children: Vec<NestedNode<T>>) -> Self {
+        NestedNode { data, children }
+    }
+
+    /// Creates a node without children.
+    pub fn leaf(data: T) -> Self {
+        Self::new(data, Vec::new())
+    }
+}
+
+impl<T> From<T> for NestedNode<T> {
+    fn from(data: T) -> Self {
+        Self::leaf(data)
+    }
+}
+
+impl<T> Tree<T> {
+    /// Builds a tree by repeatedly expanding seeds, starting from a single root seed.
+    ///
+    /// `expand` turns a seed into the data of its node and the seeds of the node's children.
+    /// Nodes are created in breadth-first order, so the children of a node always have
+    /// consecutive indices and the root is at index `0`.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the root node.
+    /// - `expand`: Produces the data of a node and the seeds of its children.
+    /// - `max_depth`: The depth of the deepest nodes to create, where the root is at depth `0`.
+    ///   Seeds produced by nodes at this depth are dropped.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// // A binary tree of ranges, halving the range at each level.
+    /// let tree = Tree::unfold(
+    ///     (0, 8),
+    ///     |&(start, end)| {
+    ///         let middle = (start + end) / 2;
+    ///         let children = if end - start > 1 {
+    ///             vec![(start, middle), (middle, end)]
+    ///         } else {
+    ///             vec![]
+    ///         };
+    ///         ((start, end), children)
+    ///     },
+    ///     2,
+    /// );
+    ///
+    /// assert_eq!(tree.len(), 7);
+    /// assert_eq!(tree.get(0), Some(&(0, 8)));
+    /// assert_eq!(tree.get(1), Some(&(0, 4)));
+    /// assert_eq!(tree.children(1), &[3, 4]);
+    /// ```
+    pub fn unfold<S>(seed: S, mut expand: impl FnMut(&S) -> (T, Vec<S>), max_depth: usize) -> Self {
+        let mut tree = Tree::new();
+        let mut queue = VecDeque::from([(None, seed, 0)]);
+        while let Some((parent, seed, depth)) = queue.pop_front() {
+            let (data, children) = expand(&seed);
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, data),
+                None => tree.add_node(data),
+            };
+            if depth < max_depth {
+                queue.extend(
+                    children
+                        .into_iter()
+                        .map(|child| (Some(index), child, depth + 1)),
+                );
+            }
+        }
+        tree
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Adds several children to a node at once, after its existing children.
+    ///
+    /// Storage for the new nodes and for the parent's child list is reserved once up front,
+    /// based on the size hint of `data`.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the parent node.
+    /// - `data`: The data of the new children, in order.
+    ///
+    /// # Returns
+    /// The indices of the new children, in order. They are consecutive.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0);
+    /// let children = tree.add_children(root, 1..=3);
+    /// assert_eq!(tree.children(root), children.as_slice());
+    /// ```
+    pub fn add_children(&mut self, parent: usize, data: impl IntoIterator<Item = T>) -> Vec<usize> {
+        let data = data.into_iter();
+        let (additional, _) = data.size_hint();
+        self.links[parent].children.reserve(additional);
+        self.data.reserve(additional);
+        self.links.reserve(additional);
+
+        let start = self.links.len();
+        for data in data {
+            self.data.push(data);
+            self.links.push(Links::with_parent(Some(parent)));
+        }
+        let indices: Vec<_> = (start..self.links.len()).collect();
+        self.links[parent].children.extend_from_slice(&indices);
+        indices
+    }
+
+    /// Adds a whole subtree under a node, after its existing children.
+    ///
+    /// Nodes are created in breadth-first order, so the children of every node have
+    /// consecutive indices.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the node to attach the subtree to.
+    /// - `subtree`: The subtree to add.
+    ///
+    /// # Returns
+    /// The index of the root of the added subtree.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{NestedNode, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("menu");
+    /// let file = tree.add_subtree(
+    ///     root,
+    ///     NestedNode::new("file", vec!["new".into(), "open".into()]),
+    /// );
+    /// assert_eq!(tree.path_string(tree.children(file)[1], "/"), "menu/file/open");
+    /// ```
+    pub fn add_subtree(&mut self, parent: usize, subtree: NestedNode<T>) -> usize {
+        let root = self.add_child(parent, subtree.data);
+        let mut queue = VecDeque::from([(root, subtree.children)]);
+        while let Some((parent, children)) = queue.pop_front() {
+            let mut grandchildren = Vec::with_capacity(children.len());
+            let indices = self.add_children(
+                parent,
+                children.into_iter().map(|child| {
+                    grandchildren.push(child.children);
+                    child.data
+                }),
+            );
+            queue.extend(indices.into_iter().zip(grandchildren));
+        }
+        root
+    }
+
+    /// Moves the children of another tree's root, with their subtrees, under a node of this
+    /// tree, after its existing children. The root of `other` is discarded, as are any nodes of
+    /// `other` not connected to its root.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the node to attach the children to.
+    /// - `other`: The tree whose root's children are moved.
+    ///
+    /// # Returns
+    /// The new indices of the moved children of `other`'s root, in order.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut results = Tree::new();
+    /// let all = results.add_node("all");
+    ///
+    /// let mut partial = Tree::new();
+    /// let batch = partial.add_node("batch");
+    /// let first = partial.add_child(batch, "first");
+    /// partial.add_child(first, "detail");
+    /// partial.add_child(batch, "second");
+    ///
+    /// let grafted = results.merge_children(all, partial);
+    /// assert_eq!(grafted.len(), 2);
+    /// assert_eq!(results.path_string(grafted[1], "/"), "all/second");
+    /// assert_eq!(results.len(), 4);
+    /// ```
+    pub fn merge_children(&mut self, parent: usize, other: Tree<T>) -> Vec<usize> {
+        assert!(parent < self.links.len(), "parent index out of bounds");
+        let Some(root) = other.root() else {
+            return Vec::new();
+        };
+        let mut data: Vec<_> = other.data.into_iter().map(Some).collect();
+        let mut links = other.links;
+        let top = mem::take(&mut links[root].children);
+        let mut grafted = Vec::with_capacity(top.len());
+        let mut queue: VecDeque<_> = top.into_iter().map(|child| (child, None)).collect();
+        while let Some((old, new_parent)) = queue.pop_front() {
+            let node_data = data[old].take().unwrap();
+            let index = self.add_child(new_parent.unwrap_or(parent), node_data);
+            if new_parent.is_none() {
+                grafted.push(index);
+            }
+            let children = mem::take(&mut links[old].children);
+            queue.extend(children.into_iter().map(|child| (child, Some(index))));
+        }
+        grafted
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Moves the subtree rooted at `index` out of this tree and into `dest`, transforming the
+    /// data of every node with `f`. The data is moved, not cloned, so `T` need not be `Clone`.
+    ///
+    /// The moved nodes are added to `dest` in depth-first order, keeping the order of their
+    /// children. In this tree, the remaining nodes keep their relative order but are renumbered
+    /// to close the gaps, as with [`Tree::garbage_collect`]: a node moves down by the number of
+    /// moved nodes that had a smaller index. The extension values of the moved nodes are
+    /// dropped.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the root of the subtree to move.
+    /// - `dest`: The tree to move the subtree into.
+    /// - `dest_parent`: The node of `dest` to attach the subtree to, or `None` to add it as a
+    ///   node without a parent.
+    /// - `f`: Transforms the data of every moved node.
+    ///
+    /// # Returns
+    /// The index of the moved root in `dest`.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds for this tree or `dest_parent` is out of
+    /// bounds for `dest`. Neither tree is changed in that case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// // A payload that cannot be cloned.
+    /// struct Connection(&'static str);
+    ///
+    /// let mut pool = Tree::new();
+    /// let root = pool.add_node(Connection("pool"));
+    /// let shard = pool.add_child(root, Connection("shard"));
+    /// pool.add_child(shard, Connection("replica"));
+    /// pool.add_child(root, Connection("spare"));
+    ///
+    /// let mut names = Tree::new();
+    /// let moved = names.add_node("moved");
+    /// let shard = pool.transplant_into(shard, &mut names, Some(moved), |c| c.0);
+    ///
+    /// assert_eq!(names.path_string(names.children(shard)[0], "/"), "moved/shard/replica");
+    /// assert_eq!(pool.len(), 2);
+    /// assert_eq!(pool[pool.children(0)[0]].0, "spare");
+    /// ```
+    pub fn transplant_into<U, E: TreeStorage<U>>(
+        &mut self,
+        index: usize,
+        dest: &mut Tree<U, E>,
+        dest_parent: Option<usize>,
+        mut f: impl FnMut(T) -> U,
+    ) -> usize {
+        assert!(index < self.links.len(), "node index out of bounds");
+        if let Some(parent) = dest_parent {
+            assert!(parent < dest.links.len(), "parent index out of bounds");
+        }
+        self.detach(index);
+
+        let moved: Vec<usize> = self.reachable_from(index).collect();
+        let kept = self.move_to_end(&moved);
+
+        let mut data = Vec::with_capacity(moved.len());
+        while self.data.len() > kept {
+            data.push(self.data.pop().unwrap());
+        }
+        let links = self.links.split_off(kept);
+        self.ext.truncate(kept);
+        self.history.changed_from(kept);
+
+        let mut added = Vec::with_capacity(links.len());
+        for (node_data, node_links) in data.into_iter().rev().zip(&links) {
+            let parent = match node_links.parent() {
+                Some(parent) => Some(added[parent - kept]),
+                None => dest_parent,
+            };
+            let node_data = f(node_data);
+            added.push(match parent {
+                Some(parent) => dest.add_child(parent, node_data),
+                None => dest.add_node(node_data),
+            });
+        }
+        added[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfold() {
+        // Each number n expands into 0..n children.
+        let tree = Tree::unfold(3, |&n| (n, (0..n).collect()), usize::MAX);
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.children(0), &[1, 2, 3]);
+        let data: Vec<_> = tree.iter().map(|(_, data)| *data).collect();
+        assert_eq!(data, vec![3, 0, 1, 2, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_unfold_max_depth() {
+        let tree = Tree::unfold(0u32, |&depth| (depth, vec![depth + 1; 2]), 0);
+        assert_eq!(tree.len(), 1);
+
+        let tree = Tree::unfold(0u32, |&depth| (depth, vec![depth + 1; 2]), 3);
+        assert_eq!(tree.len(), 15);
+        assert_eq!(tree.stats().height, 4);
+    }
+
+    #[test]
+    fn test_add_children_and_subtree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let first = tree.add_child(root, 1);
+        let rest = tree.add_children(root, vec![2, 3]);
+        assert_eq!(tree.children(root), &[first, rest[0], rest[1]]);
+        assert!(tree.add_children(first, []).is_empty());
+
+        let subtree = NestedNode::new(10, vec![NestedNode::new(11, vec![12.into()]), 13.into()]);
+        let added = tree.add_subtree(first, subtree);
+        assert_eq!(tree.parent_index_unchecked(added), Some(first));
+        let data: Vec<_> = tree
+            .reachable_from(added)
+            .map(|index| tree[index])
+            .collect();
+        assert_eq!(data, vec![10, 11, 12, 13]);
+        for index in tree.reachable_from(root).skip(1) {
+            let parent = tree.parent_index_unchecked(index).unwrap();
+            assert!(tree.children(parent).contains(&index));
+        }
+    }
+
+    #[test]
+    fn test_merge_children() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let existing = tree.add_child(root, "existing");
+
+        let mut other = Tree::new();
+        let other_root = other.add_node("discarded");
+        let a = other.add_child(other_root, "a");
+        other.add_child(a, "a1");
+        other.add_child(other_root, "b");
+        other.add_node("unreachable");
+
+        let grafted = tree.merge_children(root, other);
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.children(root), &[existing, grafted[0], grafted[1]]);
+        let paths: Vec<_> = tree
+            .iter_dfs()
+            .map(|(index, _)| tree.path_string(index, "/"))
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["root", "root/existing", "root/a", "root/a/a1", "root/b"]
+        );
+
+        assert!(tree.merge_children(root, Tree::new()).is_empty());
+    }
+
+    #[test]
+    fn test_transplant_into_moves_subtree_and_renumbers_source() {
+        let mut source = Tree::new();
+        let root = source.add_node(String::from("root"));
+        let a = source.add_child(root, String::from("a"));
+        let b = source.add_child(root, String::from("b"));
+        source.add_child(a, String::from("a1"));
+        source.add_child(b, String::from("b1"));
+        source.add_child(a, String::from("a2"));
+        source.insert_ext(b, 'b');
+
+        let mut dest: Tree<usize> = Tree::new();
+        let moved = source.transplant_into(a, &mut dest, None, |name| name.len());
+        assert_eq!(moved, 0);
+        assert_eq!(dest.data(), &[1, 2, 2]);
+        assert_eq!(dest.children(0), &[1, 2]);
+
+        let names: Vec<_> = source.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, ["root", "b", "b1"]);
+        assert_eq!(source.children(0), &[1]);
+        assert_eq!(source.children(1), &[2]);
+        assert_eq!(source.get_ext::<char>(1), Some(&'b'));
+
+        let root_moved = source.transplant_into(0, &mut dest, Some(0), |name| name.len() * 10);
+        assert!(source.is_empty());
+        assert_eq!(dest.children(0), &[1, 2, root_moved]);
+        assert_eq!(dest.children(root_moved), &[root_moved + 1]);
+    }
+}
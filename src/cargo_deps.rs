@@ -0,0 +1,230 @@
+use crate::Tree;
+use cargo_metadata::semver::Version;
+use cargo_metadata::{DependencyKind, Metadata, MetadataCommand, Node, Package, PackageId};
+use std::collections::{HashMap, HashSet};
+use std::iter;
+use std::path::Path;
+
+/// How [`Tree::from_cargo_metadata`] treats a package that is reached more than once, since
+/// the dependency graph of a workspace is usually not a tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicatePolicy {
+    /// Repeats the package with all its dependencies every time it is reached, like
+    /// `cargo tree --no-dedupe`.
+    Expand,
+    /// Repeats the package every time it is reached, but only its first occurrence lists its
+    /// dependencies; the others are marked with [`PackageInfo::is_duplicate`], like `cargo tree`.
+    #[default]
+    Mark,
+    /// Keeps only the first occurrence of the package, so that every package appears once.
+    Skip,
+}
+
+/// The data of a node produced by [`Tree::from_cargo_metadata`], describing a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInfo {
+    /// The opaque id of the package, unique in the workspace.
+    pub id: PackageId,
+    /// The name of the package.
+    pub name: String,
+    /// The version of the package.
+    pub version: Version,
+    /// The depth of the node: `0` for workspace members at the root.
+    pub depth: usize,
+    /// The kinds of the dependency of the parent on this package, such as normal and build.
+    /// Empty for workspace members at the root.
+    pub kinds: Vec<DependencyKind>,
+    /// Whether the dependencies of the package are omitted because it occurs elsewhere in the
+    /// tree, or because it depends on itself through this path.
+    pub is_duplicate: bool,
+}
+
+impl Tree<PackageInfo> {
+    /// Builds the dependency tree of a workspace from the output of `cargo metadata`.
+    ///
+    /// Every workspace member is a root, in the order of the workspace, and is followed by its
+    /// dependencies in depth-first order. A package that depends on itself through a cycle,
+    /// as workspace members can through dev-dependencies, is marked as a duplicate instead of
+    /// being expanded again, whatever the policy.
+    ///
+    /// If the metadata was read without resolving dependencies, the tree only holds the
+    /// workspace members.
+    ///
+    /// # Parameters
+    /// - `metadata`: The metadata of the workspace, with its resolved dependency graph.
+    /// - `policy`: How to treat packages reached more than once. With
+    ///   [`DuplicatePolicy::Skip`], workspace members reached as dependencies are not repeated
+    ///   as roots.
+    pub fn from_cargo_metadata(metadata: &Metadata, policy: DuplicatePolicy) -> Self {
+        let packages: HashMap<&PackageId, &Package> = metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package))
+            .collect();
+        let nodes: HashMap<&PackageId, &Node> = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .map(|node| (&node.id, node))
+            .collect();
+
+        let mut tree: Tree<PackageInfo> = Tree::new();
+        let mut expanded = HashSet::new();
+        let mut stack: Vec<_> = metadata
+            .workspace_members
+            .iter()
+            .rev()
+            .map(|id| (id, Vec::new(), None))
+            .collect();
+        while let Some((id, kinds, parent)) = stack.pop() {
+            let first = !expanded.contains(id);
+            if !first && policy == DuplicatePolicy::Skip {
+                continue;
+            }
+            let in_cycle =
+                iter::successors(parent, |&ancestor| tree.parent_index_unchecked(ancestor))
+                    .any(|ancestor| tree[ancestor].id == *id);
+            let is_duplicate = in_cycle || (!first && policy == DuplicatePolicy::Mark);
+            let Some(package) = packages.get(id) else {
+                continue;
+            };
+            let info = PackageInfo {
+                id: id.clone(),
+                name: package.name.to_string(),
+                version: package.version.clone(),
+                depth: parent.map_or(0, |parent| tree[parent].depth + 1),
+                kinds,
+                is_duplicate,
+            };
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, info),
+                None => tree.add_node(info),
+            };
+            if is_duplicate {
+                continue;
+            }
+            expanded.insert(id);
+            if let Some(node) = nodes.get(id) {
+                stack.extend(node.deps.iter().rev().map(|dep| {
+                    let mut kinds: Vec<_> = dep.dep_kinds.iter().map(|info| info.kind).collect();
+                    kinds.dedup();
+                    (&dep.pkg, kinds, Some(index))
+                }));
+            }
+        }
+        tree
+    }
+
+    /// Runs `cargo metadata` on a manifest and builds the dependency tree of its workspace with
+    /// [`Tree::from_cargo_metadata`].
+    ///
+    /// # Parameters
+    /// - `manifest_path`: The path of a `Cargo.toml` in the workspace.
+    /// - `policy`: How to treat packages reached more than once.
+    ///
+    /// # Errors
+    /// The error of `cargo metadata`, for example if Cargo cannot be run or the manifest is
+    /// invalid.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use easy_tree::{DuplicatePolicy, Tree};
+    ///
+    /// let dependencies = Tree::from_cargo_manifest("Cargo.toml", DuplicatePolicy::Mark).unwrap();
+    /// dependencies.traverse_forest(
+    ///     |_, package, _| {
+    ///         let indent = "  ".repeat(package.depth);
+    ///         let mark = if package.is_duplicate { " (*)" } else { "" };
+    ///         println!("{indent}{} v{}{mark}", package.name, package.version);
+    ///     },
+    ///     |_, _, _| {},
+    ///     &mut (),
+    /// );
+    /// ```
+    pub fn from_cargo_manifest(
+        manifest_path: impl AsRef<Path>,
+        policy: DuplicatePolicy,
+    ) -> Result<Self, cargo_metadata::Error> {
+        let metadata = MetadataCommand::new()
+            .manifest_path(manifest_path.as_ref())
+            .exec()?;
+        Ok(Tree::from_cargo_metadata(&metadata, policy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the metadata of a workspace whose members are `app` and `util`, where `app`
+    /// depends on `util` and `log`, `util` depends on `log` and dev-depends on `app`.
+    fn metadata() -> Metadata {
+        let package = |name: &str| {
+            format!(
+                r#"{{"name": "{name}", "version": "1.0.0", "id": "{name}", "dependencies": [],
+                    "targets": [], "features": {{}}, "manifest_path": "/{name}/Cargo.toml"}}"#
+            )
+        };
+        let dep = |name: &str, kind: &str| {
+            format!(r#"{{"name": "{name}", "pkg": "{name}", "dep_kinds": [{{"kind": {kind}}}]}}"#)
+        };
+        let json = format!(
+            r#"{{"packages": [{}, {}, {}], "workspace_members": ["app", "util"],
+                "resolve": {{"root": null, "nodes": [
+                    {{"id": "app", "deps": [{}, {}], "dependencies": []}},
+                    {{"id": "util", "deps": [{}, {}], "dependencies": []}},
+                    {{"id": "log", "deps": [], "dependencies": []}}
+                ]}},
+                "workspace_root": "/", "target_directory": "/target", "version": 1}}"#,
+            package("app"),
+            package("util"),
+            package("log"),
+            dep("util", "null"),
+            dep("log", "null"),
+            dep("app", r#""dev""#),
+            dep("log", r#""build""#),
+        );
+        MetadataCommand::parse(json).unwrap()
+    }
+
+    fn outline(tree: &Tree<PackageInfo>) -> Vec<String> {
+        tree.iter()
+            .map(|(_, package)| {
+                let mark = if package.is_duplicate { "*" } else { "" };
+                format!("{}{}{mark}", "  ".repeat(package.depth), package.name)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_from_cargo_metadata_policies() {
+        let metadata = metadata();
+        let tree = Tree::from_cargo_metadata(&metadata, DuplicatePolicy::Expand);
+        assert_eq!(
+            outline(&tree),
+            [
+                "app",
+                "  util",
+                "    app*",
+                "    log",
+                "  log",
+                "util",
+                "  app",
+                "    util*",
+                "    log",
+                "  log"
+            ]
+        );
+        let tree = Tree::from_cargo_metadata(&metadata, DuplicatePolicy::Mark);
+        assert_eq!(
+            outline(&tree),
+            ["app", "  util", "    app*", "    log", "  log*", "util*"]
+        );
+        let tree = Tree::from_cargo_metadata(&metadata, DuplicatePolicy::Skip);
+        assert_eq!(outline(&tree), ["app", "  util", "    log"]);
+        assert_eq!(tree[1].kinds, [DependencyKind::Normal]);
+        assert_eq!(tree[2].kinds, [DependencyKind::Build]);
+        assert_eq!(tree[0].version, Version::new(1, 0, 0));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::node_id::TreeNonce;
+use crate::{Tree, TreeStorage};
+use std::mem;
+
+/// A point in the history of a [`Tree`] that it can be rolled back to.
+///
+/// Obtained with [`Tree::checkpoint`] and used with [`Tree::rollback_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    len: usize,
+    /// The generation of the [`History`] of the tree when the checkpoint was taken.
+    generation: u64,
+    tree: TreeNonce,
+}
+
+/// The removals and renumberings a tree went through, so that [`Tree::rollback_to`] can
+/// reject the checkpoints they made stale.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct History {
+    /// The number of removals and renumberings so far.
+    generation: u64,
+    /// The generation of a removal or renumbering and the number of leading nodes it left
+    /// untouched. Entries that a later one touched more of are dropped, so both are
+    /// increasing.
+    changes: Vec<(u64, usize)>,
+}
+
+impl History {
+    /// Records that the nodes from index `untouched` on were removed or renumbered.
+    pub(crate) fn changed_from(&mut self, untouched: usize) {
+        self.generation += 1;
+        while self
+            .changes
+            .last()
+            .is_some_and(|&(_, previous)| previous >= untouched)
+        {
+            self.changes.pop();
+        }
+        self.changes.push((self.generation, untouched));
+    }
+
+    /// Returns the number of leading nodes that no removal or renumbering touched since
+    /// `generation`, or `usize::MAX` if there was none.
+    fn untouched_since(&self, generation: u64) -> usize {
+        let first_later = self.changes.partition_point(|&(g, _)| g <= generation);
+        self.changes
+            .get(first_later)
+            .map_or(usize::MAX, |&(_, untouched)| untouched)
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Records the current state of the tree, so that nodes added afterwards can be removed
+    /// again with [`Tree::rollback_to`].
+    ///
+    /// Taking a checkpoint is free: since nodes are only ever appended, the checkpoint just
+    /// remembers how many there are.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.links.len(),
+            generation: self.history.generation,
+            tree: self.nonce,
+        }
+    }
+
+    /// Removes every node added since `checkpoint` was taken, together with all links to
+    /// them.
+    ///
+    /// Rolling back only undoes additions. Nodes that existed at the checkpoint keep their
+    /// indices and data, but changes to their links are kept: a node that was attached to a
+    /// removed node becomes a node without a parent instead of getting its former parent
+    /// back, and changes such as [`Tree::detach`], [`Tree::set_parent`] or
+    /// [`Tree::remove_child`] between nodes that existed at the checkpoint are not undone.
+    ///
+    /// Operations that remove or renumber existing nodes, such as [`Tree::garbage_collect`],
+    /// [`Tree::drain_subtree`], [`Tree::transplant_into`] or [`Tree::clear`], cannot be
+    /// rolled back. They make the checkpoints taken before them stale, as does rolling back to
+    /// an earlier checkpoint for the checkpoints taken since.
+    ///
+    /// This takes time proportional to the number of removed nodes, their children and the
+    /// children of their parents, not to the size of the tree.
+    ///
+    /// # Panics
+    /// This method panics if the checkpoint belongs to another tree, including a clone of
+    /// this one, or if it is stale.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let expr = tree.add_node("expr");
+    ///
+    /// // Try to parse a call, and backtrack when it turns out not to be one.
+    /// let checkpoint = tree.checkpoint();
+    /// let call = tree.add_child(expr, "call");
+    /// tree.add_child(call, "callee");
+    /// tree.rollback_to(checkpoint);
+    ///
+    /// let ident = tree.add_child(expr, "ident");
+    /// assert_eq!(tree.len(), 2);
+    /// assert_eq!(tree.children(expr), &[ident]);
+    /// ```
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) {
+        let len = checkpoint.len;
+        assert!(
+            checkpoint.tree == self.nonce,
+            "checkpoint belongs to another tree"
+        );
+        assert!(
+            len <= self.links.len() && len <= self.history.untouched_since(checkpoint.generation),
+            "checkpoint is stale: nodes it covers were removed or renumbered since it was taken"
+        );
+        if len == self.links.len() {
+            return;
+        }
+
+        let mut parents = Vec::new();
+        for index in len..self.links.len() {
+            parents.extend(self.links[index].parent().filter(|&parent| parent < len));
+            // The links of removed nodes are dropped below, so their children can be taken.
+            for child in mem::take(&mut self.links[index].children) {
+                if child < len {
+                    self.links[child].replace_parent(None);
+                }
+            }
+        }
+        parents.sort_unstable();
+        parents.dedup();
+        for parent in parents {
+            self.links[parent].children.retain(|&child| child < len);
+        }
+        self.links.truncate(len);
+        self.data.truncate(len);
+        self.ext.truncate(len);
+        self.history.changed_from(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let kept = tree.add_child(root, 1);
+
+        let outer = tree.checkpoint();
+        let a = tree.add_child(root, 2);
+        let inner = tree.checkpoint();
+        tree.add_child(a, 3);
+        tree.add_child(kept, 4);
+        tree.rollback_to(inner);
+        assert_eq!(tree.len(), 3);
+        assert!(tree.children(a).is_empty());
+        assert!(tree.children(kept).is_empty());
+
+        tree.rollback_to(outer);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.children(root), &[kept]);
+        // Rolling back again changes nothing.
+        tree.rollback_to(outer);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_rollback_releases_reattached_nodes() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let orphan = tree.add_node("orphan");
+
+        let checkpoint = tree.checkpoint();
+        let wrapper = tree.add_child(root, "wrapper");
+        tree.set_parent(orphan, wrapper).unwrap();
+        tree.rollback_to(checkpoint);
+
+        assert_eq!(tree.parent_index_unchecked(orphan), None);
+        assert!(tree.children(root).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint is stale")]
+    fn test_rollback_to_future_checkpoint() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        let checkpoint = tree.checkpoint();
+        tree.clear();
+        tree.rollback_to(checkpoint);
+    }
+
+    #[test]
+    fn test_stale_checkpoints_are_rejected() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let build = || {
+            let mut tree = Tree::new();
+            let root = tree.add_node(0);
+            let a = tree.add_child(root, 1);
+            tree.add_child(a, 2);
+            tree.add_child(root, 3);
+            tree
+        };
+        let rejects = |tree: &mut Tree<i32>, checkpoint| {
+            catch_unwind(AssertUnwindSafe(|| tree.rollback_to(checkpoint))).is_err()
+        };
+
+        // Draining renumbers the nodes after the drained subtree.
+        let mut tree = build();
+        let checkpoint = tree.checkpoint();
+        tree.drain_subtree(1).for_each(drop);
+        tree.add_node(4);
+        tree.add_node(5);
+        assert!(rejects(&mut tree, checkpoint));
+
+        // Nodes removed by a rollback are not the nodes a later checkpoint covered.
+        let mut tree = build();
+        let outer = tree.checkpoint();
+        tree.add_node(4);
+        let inner = tree.checkpoint();
+        tree.rollback_to(outer);
+        tree.add_node(5);
+        assert!(rejects(&mut tree, inner));
+        tree.rollback_to(outer);
+        assert_eq!(tree.len(), 4);
+
+        // A checkpoint of a smaller prefix survives changes to later nodes.
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let early = tree.checkpoint();
+        tree.add_child(root, 1);
+        tree.add_node(2);
+        tree.garbage_collect([root]);
+        tree.rollback_to(early);
+        assert_eq!(tree.len(), 1);
+
+        let clone = build();
+        assert!(rejects(&mut clone.clone(), clone.checkpoint()));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{Links, Tree, TreeStorage};
+use std::collections::HashMap;
+
+/// A copy-on-write fork of a [`Tree`], sharing all untouched nodes with the original.
+///
+/// Reading a node that was not changed in the fork reads it from the original tree. The data
+/// or links of a node are copied the first time they are changed, so the cost of a fork is
+/// proportional to the number of nodes it touches, not to the size of the tree. The original
+/// tree is borrowed immutably and never changes.
+///
+/// A fork that turned out to be worth keeping can be turned into an owned tree with
+/// [`CowTree::into_tree`], or its changes can be moved into the original tree with
+/// [`CowTree::into_changes`] and [`Tree::apply_changes`] without copying anything else.
+///
+/// Constructed with [`Tree::fork`].
+pub struct CowTree<'a, T, D = Vec<T>> {
+    base: &'a Tree<T, D>,
+    /// The data of nodes of the original tree that were changed in the fork.
+    data: HashMap<usize, T>,
+    /// The data of nodes added in the fork, which follow the nodes of the original tree.
+    added: Vec<T>,
+    /// The links of nodes that were changed or added in the fork.
+    links: HashMap<usize, Links>,
+}
+
+/// The changes made in a [`CowTree`], detached from the tree it was forked from.
+///
+/// Obtained with [`CowTree::into_changes`] and applied with [`Tree::apply_changes`].
+pub struct TreeChanges<T> {
+    base_len: usize,
+    data: HashMap<usize, T>,
+    added: Vec<T>,
+    links: HashMap<usize, Links>,
+}
+
+impl<'a, T, D: TreeStorage<T>> CowTree<'a, T, D> {
+    /// Returns the tree this fork was created from.
+    pub fn base(&self) -> &'a Tree<T, D> {
+        self.base
+    }
+
+    /// Returns the number of nodes in the fork, including the nodes added to it.
+    pub fn len(&self) -> usize {
+        self.base.len() + self.added.len()
+    }
+
+    /// Returns `true` if the fork contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the data or the links of a node were changed in the fork, or if the
+    /// node was added to it.
+    pub fn is_modified(&self, index: usize) -> bool {
+        index >= self.base.len()
+            || self.data.contains_key(&index)
+            || self.links.contains_key(&index)
+    }
+
+    /// Returns a reference to the data stored in a node, or `None` if the index is out of
+    /// bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match index.checked_sub(self.base.len()) {
+            Some(added) => self.added.get(added),
+            None => self.data.get(&index).or_else(|| self.base.get(index)),
+        }
+    }
+
+    /// Returns the indices of the children of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn children(&self, index: usize) -> &[usize] {
+        &self.links(index).children
+    }
+
+    /// Returns the index of the parent of a node, or `None` if it has no parent.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn parent_index_unchecked(&self, index: usize) -> Option<usize> {
+        self.links(index).parent()
+    }
+
+    /// Returns the index of the root of the tree: the first node without a parent.
+    pub fn root(&self) -> Option<usize> {
+        (0..self.len()).find(|&index| self.links(index).parent().is_none())
+    }
+
+    /// Returns an iterator over the indices and data of the nodes reachable from the root, in
+    /// depth-first pre-order. See [`Tree::iter_dfs`].
+    pub fn iter_dfs(&self) -> impl Iterator<Item = (usize, &T)> {
+        let mut stack: Vec<usize> = self.root().into_iter().collect();
+        std::iter::from_fn(move || {
+            let index = stack.pop()?;
+            stack.extend(self.children(index).iter().rev());
+            Some((index, &self[index]))
+        })
+    }
+
+    /// Adds a new node without a parent to the fork.
+    ///
+    /// # Returns
+    /// The index of the new node, which follows the indices of the original tree.
+    pub fn add_node(&mut self, data: T) -> usize {
+        let index = self.len();
+        self.added.push(data);
+        self.links.insert(index, Links::default());
+        index
+    }
+
+    /// Adds a child node to an existing node in the fork, after its existing children.
+    ///
+    /// # Returns
+    /// The index of the new child.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    pub fn add_child(&mut self, parent: usize, data: T) -> usize {
+        assert!(parent < self.len(), "node index {parent} is out of bounds");
+        let index = self.add_node(data);
+        self.links_mut(parent).add_child(index);
+        self.links_mut(index).set_parent(parent);
+        index
+    }
+
+    /// Unlinks a node from its parent in the fork. See [`Tree::detach`].
+    ///
+    /// # Returns
+    /// The index of the former parent, or `None` if the node had no parent.
+    ///
+    /// # Panics
+    /// This method panics if `child` is out of bounds.
+    pub fn detach(&mut self, child: usize) -> Option<usize> {
+        let parent = self.parent_index_unchecked(child)?;
+        self.links_mut(child).replace_parent(None);
+        self.links_mut(parent)
+            .children
+            .retain(|&index| index != child);
+        Some(parent)
+    }
+
+    /// Extracts the changes made in the fork, ending the borrow of the original tree.
+    pub fn into_changes(self) -> TreeChanges<T> {
+        TreeChanges {
+            base_len: self.base.len(),
+            data: self.data,
+            added: self.added,
+            links: self.links,
+        }
+    }
+
+    /// Creates an owned tree with the contents of the fork, cloning the data of the nodes
+    /// that were not changed.
+    pub fn into_tree(self) -> Tree<T>
+    where
+        T: Clone,
+    {
+        let mut tree = Tree::new();
+        tree.data.reserve(self.base.len());
+        tree.data.extend(self.base.data.iter().cloned());
+        tree.links = self.base.links.clone();
+        tree.apply_changes(self.into_changes());
+        tree
+    }
+
+    fn links(&self, index: usize) -> &Links {
+        self.links
+            .get(&index)
+            .unwrap_or_else(|| &self.base.links[index])
+    }
+
+    fn links_mut(&mut self, index: usize) -> &mut Links {
+        let base = self.base;
+        self.links
+            .entry(index)
+            .or_insert_with(|| base.links[index].clone())
+    }
+}
+
+impl<T: Clone, D: TreeStorage<T>> CowTree<'_, T, D> {
+    /// Returns a mutable reference to the data stored in a node, copying it from the original
+    /// tree the first time, or `None` if the index is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match index.checked_sub(self.base.len()) {
+            Some(added) => self.added.get_mut(added),
+            None => {
+                let base = self.base;
+                Some(
+                    self.data
+                        .entry(index)
+                        .or_insert_with(|| base[index].clone()),
+                )
+            }
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> std::ops::Index<usize> for CowTree<'_, T, D> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("node index out of bounds")
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Creates a copy-on-write fork of the tree, which can be changed without affecting the
+    /// tree and without copying the nodes it does not touch.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// let child = tree.add_child(root, 2);
+    ///
+    /// let mut fork = tree.fork();
+    /// *fork.get_mut(child).unwrap() = 20;
+    /// let added = fork.add_child(root, 3);
+    /// assert_eq!(fork.children(root), &[child, added]);
+    ///
+    /// // Keep the speculative changes.
+    /// let changes = fork.into_changes();
+    /// assert_eq!(tree[child], 2);
+    /// tree.apply_changes(changes);
+    /// assert_eq!(tree[child], 20);
+    /// assert_eq!(tree.children(root), &[child, added]);
+    /// ```
+    pub fn fork(&self) -> CowTree<'_, T, D> {
+        CowTree {
+            base: self,
+            data: HashMap::new(),
+            added: Vec::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    /// Applies the changes made in a fork of this tree, as obtained with
+    /// [`CowTree::into_changes`].
+    ///
+    /// # Panics
+    /// This method panics if the tree does not have as many nodes as when it was forked. The
+    /// changes must be applied to the tree they were forked from, which must not have been
+    /// changed in between.
+    pub fn apply_changes(&mut self, changes: TreeChanges<T>) {
+        assert_eq!(
+            self.links.len(),
+            changes.base_len,
+            "changes were made to a fork of a different tree"
+        );
+        for (index, data) in changes.data {
+            self.data[index] = data;
+        }
+        for data in changes.added {
+            self.data.push(data);
+            self.links.push(Links::default());
+        }
+        for (index, links) in changes.links {
+            self.links[index] = links;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_shares_untouched_nodes() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root".to_string());
+        let a = tree.add_child(root, "a".to_string());
+        let b = tree.add_child(root, "b".to_string());
+        let a1 = tree.add_child(a, "a1".to_string());
+
+        let mut fork = tree.fork();
+        fork.get_mut(a1).unwrap().push('!');
+        assert_eq!(fork.detach(b), Some(root));
+        let c = fork.add_child(a1, "c".to_string());
+
+        assert!(!fork.is_modified(a));
+        assert!(fork.is_modified(a1) && fork.is_modified(b) && fork.is_modified(c));
+        assert!(std::ptr::eq(fork.get(a).unwrap(), tree.get(a).unwrap()));
+        let data: Vec<_> = fork.iter_dfs().map(|(_, data)| data.as_str()).collect();
+        assert_eq!(data, vec!["root", "a", "a1!", "c"]);
+
+        // The original tree is unchanged.
+        let data: Vec<_> = tree.iter_dfs().map(|(_, data)| data.as_str()).collect();
+        assert_eq!(data, vec!["root", "a", "a1", "b"]);
+
+        let owned = fork.into_tree();
+        assert_eq!(owned.len(), 5);
+        assert_eq!(owned.parent_index_unchecked(c), Some(a1));
+        assert_eq!(owned.parent_index_unchecked(b), None);
+        assert_eq!(owned[a1], "a1!");
+    }
+
+    #[test]
+    #[should_panic(expected = "changes were made to a fork of a different tree")]
+    fn test_apply_changes_to_changed_tree() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        let mut fork = tree.fork();
+        fork.add_node(1);
+        let changes = fork.into_changes();
+        tree.add_node(2);
+        tree.apply_changes(changes);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{NodeMask, Tree};
+use csv::StringRecord;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+/// An error produced when importing a tree with [`Tree::from_csv`].
+///
+/// Line numbers start at `1` and count the header line.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CsvError {
+    /// The input is not valid CSV, or could not be read.
+    Csv(csv::Error),
+    /// The header has no column with the given name.
+    MissingColumn(String),
+    /// Two rows have the same id.
+    DuplicateId {
+        /// The duplicated id.
+        id: String,
+        /// The line of the second row with that id.
+        line: u64,
+    },
+    /// A row refers to a parent id that no row has.
+    UnknownParent {
+        /// The unknown parent id.
+        parent: String,
+        /// The line of the row.
+        line: u64,
+    },
+    /// A row is its own ancestor through its parent column.
+    Cycle {
+        /// The id of the row.
+        id: String,
+        /// The line of the row.
+        line: u64,
+    },
+    /// The payload function rejected a row.
+    InvalidRow {
+        /// The line of the row.
+        line: u64,
+        /// The reason given by the payload function.
+        message: String,
+    },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Csv(error) => write!(f, "invalid CSV: {error}"),
+            CsvError::MissingColumn(name) => write!(f, "missing column {name:?}"),
+            CsvError::DuplicateId { id, line } => write!(f, "line {line}: duplicate id {id:?}"),
+            CsvError::UnknownParent { parent, line } => {
+                write!(f, "line {line}: unknown parent id {parent:?}")
+            }
+            CsvError::Cycle { id, line } => write!(f, "line {line}: id {id:?} is its own ancestor"),
+            CsvError::InvalidRow { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsvError::Csv(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        CsvError::Csv(error)
+    }
+}
+
+/// A data row passed to the payload function of [`Tree::from_csv`].
+pub struct CsvRow<'r> {
+    headers: &'r StringRecord,
+    record: &'r StringRecord,
+    line: u64,
+}
+
+impl<'r> CsvRow<'r> {
+    /// Returns the value of the column with the given header, or `None` if there is no such
+    /// column.
+    pub fn get(&self, column: &str) -> Option<&'r str> {
+        let position = self.headers.iter().position(|header| header == column)?;
+        self.record.get(position)
+    }
+
+    /// Returns the raw record.
+    pub fn record(&self) -> &'r StringRecord {
+        self.record
+    }
+
+    /// Returns the line of the row, starting at `1` for the header.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+}
+
+impl<T> Tree<T> {
+    /// Imports a tree from CSV rows with an id column and a parent id column, as org charts and
+    /// category trees are usually exported.
+    ///
+    /// The first row must be a header naming the columns. Every following row becomes a node,
+    /// in order, so the node of the `n`-th data row has index `n - 1`. A row whose parent
+    /// column is empty has no parent; otherwise the parent column must hold the id of another
+    /// row, which may come before or after it. Children are ordered as their rows.
+    ///
+    /// # Parameters
+    /// - `reader`: The CSV input.
+    /// - `id_col`: The header of the column holding the id of each row.
+    /// - `parent_col`: The header of the column holding the id of the parent of each row.
+    /// - `payload_fn`: Produces the data of a node from its row, or an error message.
+    ///
+    /// # Errors
+    /// A [`CsvError`] describing the first problem found, with its line number. No tree is
+    /// returned if the input has any problem.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let csv = "id,parent_id,title\n2,1,CTO\n1,,CEO\n3,2,Engineer\n";
+    /// let org = Tree::from_csv(csv.as_bytes(), "id", "parent_id", |row| {
+    ///     Ok(row.get("title").unwrap().to_string())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(org.path_string(2, " > "), "CEO > CTO > Engineer");
+    /// // The CEO is listed second, but is still the root that traversals start from.
+    /// let names: Vec<_> = org.iter_dfs().map(|(_, title)| title.as_str()).collect();
+    /// assert_eq!(names, ["CEO", "CTO", "Engineer"]);
+    ///
+    /// let error = Tree::from_csv("id,parent_id\n1,\n2,7\n".as_bytes(), "id", "parent_id", |_| {
+    ///     Ok(())
+    /// })
+    /// .err()
+    /// .unwrap();
+    /// assert_eq!(error.to_string(), "line 3: unknown parent id \"7\"");
+    /// ```
+    pub fn from_csv<R: io::Read>(
+        reader: R,
+        id_col: &str,
+        parent_col: &str,
+        mut payload_fn: impl FnMut(&CsvRow<'_>) -> Result<T, String>,
+    ) -> Result<Self, CsvError> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| CsvError::MissingColumn(name.to_string()))
+        };
+        let (id_col, parent_col) = (column(id_col)?, column(parent_col)?);
+
+        let mut tree = Tree::new();
+        let mut indices = HashMap::new();
+        // The parent id and line of every row, linked once all ids are known.
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            let line = record.position().map_or(0, |position| position.line());
+            let row = CsvRow {
+                headers: &headers,
+                record: &record,
+                line,
+            };
+            let data =
+                payload_fn(&row).map_err(|message| CsvError::InvalidRow { line, message })?;
+            let id = record.get(id_col).unwrap_or_default().to_string();
+            let parent = record.get(parent_col).unwrap_or_default().to_string();
+            let index = tree.add_node(data);
+            if indices.insert(id.clone(), index).is_some() {
+                return Err(CsvError::DuplicateId { id, line });
+            }
+            rows.push((id, parent, line));
+        }
+
+        // Rows after an unknown parent are left unlinked, since the error is reported at the
+        // first row that cannot be linked.
+        let mut parents = vec![None; rows.len()];
+        let mut unknown = None;
+        for (index, (_, parent, line)) in rows.iter().enumerate() {
+            if parent.is_empty() {
+                continue;
+            }
+            match indices.get(parent) {
+                Some(&parent_index) => parents[index] = Some(parent_index),
+                None => {
+                    unknown = Some(CsvError::UnknownParent {
+                        parent: parent.clone(),
+                        line: *line,
+                    });
+                    break;
+                }
+            }
+        }
+
+        if let Some(index) = first_row_closing_a_cycle(&parents) {
+            let (id, _, line) = rows.swap_remove(index);
+            return Err(CsvError::Cycle { id, line });
+        }
+        if let Some(error) = unknown {
+            return Err(error);
+        }
+        for (child, parent) in parents.into_iter().enumerate() {
+            if let Some(parent) = parent {
+                tree.link(parent, child);
+            }
+        }
+        Ok(tree)
+    }
+}
+
+/// Returns the first row that, linking rows in order, would make a node its own ancestor: the
+/// smallest among the last rows of every cycle of `parents`.
+///
+/// Every row is visited once, following parents until a row that was already visited. A row
+/// reached again on the current path closes a cycle.
+fn first_row_closing_a_cycle(parents: &[Option<usize>]) -> Option<usize> {
+    let mut visited = NodeMask::new(parents.len());
+    let mut on_path = NodeMask::new(parents.len());
+    let mut path = Vec::new();
+    let mut first = None;
+    for start in 0..parents.len() {
+        let mut current = Some(start);
+        while let Some(index) = current.filter(|&index| !visited.contains(index)) {
+            visited.set(index);
+            on_path.set(index);
+            path.push(index);
+            current = parents[index];
+        }
+        if let Some(index) = current.filter(|&index| on_path.contains(index)) {
+            let cycle_start = path.iter().position(|&node| node == index).unwrap();
+            let last = *path[cycle_start..].iter().max().unwrap();
+            first = Some(first.map_or(last, |first: usize| first.min(last)));
+        }
+        for index in path.drain(..) {
+            on_path.clear(index);
+        }
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(csv: &str) -> Result<Tree<String>, CsvError> {
+        Tree::from_csv(csv.as_bytes(), "id", "parent", |row| {
+            match row.get("name") {
+                Some("") => Err("empty name".to_string()),
+                name => Ok(name.unwrap_or_default().to_string()),
+            }
+        })
+    }
+
+    #[test]
+    fn test_from_csv_errors_report_lines() {
+        let error = |csv| import(csv).err().unwrap().to_string();
+        assert_eq!(error("id,name\n"), "missing column \"parent\"");
+        assert_eq!(
+            error("id,parent,name\n1,,a\n1,,b\n"),
+            "line 3: duplicate id \"1\""
+        );
+        assert_eq!(
+            error("id,parent,name\n1,,a\n2,3,b\n3,2,c\n"),
+            "line 4: id \"3\" is its own ancestor"
+        );
+        assert_eq!(
+            error("id,parent,name\n1,1,a\n2,9,b\n"),
+            "line 2: id \"1\" is its own ancestor"
+        );
+        assert_eq!(
+            error("id,parent,name\n1,,a\n2,9,b\n3,4,c\n4,3,d\n"),
+            "line 3: unknown parent id \"9\""
+        );
+        assert_eq!(error("id,parent,name\n1,,a\n2,1,\n"), "line 3: empty name");
+        assert!(error("id,parent,name\n1,,a,extra\n").starts_with("invalid CSV"));
+    }
+
+    #[test]
+    fn test_from_csv_traverses_rows_listed_before_their_parent() {
+        let tree = import("id,parent,name\n2,1,CTO\n1,,CEO\n3,2,Engineer\n").unwrap();
+        assert_eq!(tree.root(), Some(1));
+        let mut names = vec![];
+        tree.traverse(
+            |_, name, names| names.push(name.as_str()),
+            |_, _, _| {},
+            &mut names,
+        );
+        assert_eq!(names, ["CEO", "CTO", "Engineer"]);
+        assert_eq!(tree.orphans().count(), 0);
+    }
+
+    #[test]
+    fn test_from_csv_builds_forest() {
+        let tree = import("id,parent,name\nb,a,B\na,,A\nc,a,C\nd,,D\n").unwrap();
+        assert_eq!(tree.children(1), &[0, 2]);
+        assert_eq!(tree.parent_index_unchecked(3), None);
+        assert_eq!(tree[1], "A");
+    }
+}
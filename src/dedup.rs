@@ -0,0 +1,192 @@
+use crate::trace::Operation;
+use crate::{Tree, TreeStorage};
+use std::collections::hash_map::{DefaultHasher, HashMap};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+impl<T: Hash + Eq, D: TreeStorage<T>> Tree<T, D> {
+    /// Removes children of `parent` whose subtrees are structurally identical to an earlier
+    /// sibling's subtree.
+    ///
+    /// Two subtrees are identical if their roots hold equal data and their children are
+    /// pairwise identical, in the same order. The first occurrence is kept.
+    ///
+    /// Removed subtrees are unlinked from `parent` and become detached roots. Their nodes stay
+    /// in the tree, so all indices remain valid.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the node whose children should be deduplicated.
+    ///
+    /// # Returns
+    /// The indices of the roots of the removed duplicate subtrees.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// tree.add_child(a, "leaf");
+    /// let b = tree.add_child(root, "b");
+    /// let a2 = tree.add_child(root, "a");
+    /// tree.add_child(a2, "leaf");
+    ///
+    /// assert_eq!(tree.dedup_children(root), vec![a2]);
+    /// assert_eq!(tree.children(root), &[a, b]);
+    /// assert_eq!(tree.parent_index_unchecked(a2), None);
+    /// ```
+    pub fn dedup_children(&mut self, parent: usize) -> Vec<usize> {
+        let mut hashes = HashMap::new();
+        for child in self.links[parent].children.clone() {
+            for index in self.post_order(child) {
+                let hash = self.subtree_hash(index, &hashes);
+                hashes.insert(index, hash);
+            }
+        }
+        self.dedup_children_with_hashes(parent, &hashes)
+    }
+
+    /// Removes structurally identical sibling subtrees everywhere in the tree.
+    ///
+    /// This applies [`Tree::dedup_children`] to every node, bottom-up, so subtrees that only
+    /// become identical once their own duplicates are removed get merged as well.
+    ///
+    /// # Returns
+    /// The indices of the roots of the removed duplicate subtrees.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// tree.add_child(a, "leaf");
+    /// tree.add_child(a, "leaf");
+    /// let a2 = tree.add_child(root, "a");
+    /// tree.add_child(a2, "leaf");
+    ///
+    /// assert_eq!(tree.dedup_subtrees().len(), 2);
+    /// assert_eq!(tree.children(root), &[a]);
+    /// assert_eq!(tree.children(a).len(), 1);
+    /// ```
+    pub fn dedup_subtrees(&mut self) -> Vec<usize> {
+        let roots: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+
+        let operation = Operation::start("dedup_subtrees", self.links.len());
+        let mut hashes = HashMap::new();
+        let mut removed = Vec::new();
+        for root in roots {
+            for index in self.post_order(root) {
+                removed.extend(self.dedup_children_with_hashes(index, &hashes));
+                let hash = self.subtree_hash(index, &hashes);
+                hashes.insert(index, hash);
+            }
+        }
+        operation.finish(hashes.len());
+        removed
+    }
+
+    fn dedup_children_with_hashes(
+        &mut self,
+        parent: usize,
+        hashes: &HashMap<usize, u64>,
+    ) -> Vec<usize> {
+        let mut kept: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut removed = Vec::new();
+        for &child in &self.links[parent].children {
+            let candidates = kept.entry(hashes[&child]).or_default();
+            if candidates
+                .iter()
+                .any(|&other| self.subtree_eq(other, child))
+            {
+                removed.push(child);
+            } else {
+                candidates.push(child);
+            }
+        }
+
+        if !removed.is_empty() {
+            let removed_set: HashSet<usize> = removed.iter().copied().collect();
+            self.links[parent]
+                .children
+                .retain(|child| !removed_set.contains(child));
+            for &child in &removed {
+                self.links[child].replace_parent(None);
+            }
+        }
+        removed
+    }
+
+    /// Hashes a node's data together with the hashes of its children, which must already be
+    /// present in `hashes`.
+    fn subtree_hash(&self, index: usize, hashes: &HashMap<usize, u64>) -> u64 {
+        let children = &self.links[index].children;
+        let mut hasher = DefaultHasher::new();
+        self.data[index].hash(&mut hasher);
+        children.len().hash(&mut hasher);
+        for child in children {
+            hashes[child].hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn subtree_eq(&self, a: usize, b: usize) -> bool {
+        let mut stack = vec![(a, b)];
+        while let Some((a, b)) = stack.pop() {
+            let (a_children, b_children) = (&self.links[a].children, &self.links[b].children);
+            if self.data[a] != self.data[b] || a_children.len() != b_children.len() {
+                return false;
+            }
+            stack.extend(a_children.iter().copied().zip(b_children.iter().copied()));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_children_keeps_distinct_subtrees() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        tree.add_child(a, 2);
+        let b = tree.add_child(root, 1);
+        tree.add_child(b, 3);
+        let c = tree.add_child(root, 1);
+        tree.add_child(c, 2);
+
+        assert_eq!(tree.dedup_children(root), vec![c]);
+        assert_eq!(tree.children(root), &[a, b]);
+        assert_eq!(tree.parent_index_unchecked(c), None);
+        assert_eq!(tree.len(), 7);
+    }
+
+    #[test]
+    fn test_dedup_subtrees_bottom_up() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let x = tree.add_child(root, "x");
+        let y1 = tree.add_child(x, "y");
+        let y2 = tree.add_child(x, "y");
+        let x2 = tree.add_child(root, "x");
+        let y3 = tree.add_child(x2, "y");
+        let z = tree.add_child(root, "z");
+
+        let mut removed = tree.dedup_subtrees();
+        removed.sort();
+        assert_eq!(removed, vec![y2, x2]);
+        assert_eq!(tree.children(root), &[x, z]);
+        assert_eq!(tree.children(x), &[y1]);
+        assert_eq!(tree.children(x2), &[y3]);
+    }
+}
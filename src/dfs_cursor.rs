@@ -0,0 +1,158 @@
+use crate::{Tree, TreeError, TreeStorage};
+
+/// The position of a [`ResumableDfs`] iterator, which can be stored and later handed to
+/// [`Tree::resume_dfs`] to continue the traversal where it stopped.
+///
+/// The cursor holds the nodes that remain to be visited, so its size grows with the depth of
+/// the tree and the number of children of the nodes on the current path, not with the number
+/// of nodes. With the `serde` feature, it can be serialized, so that a long batch job can
+/// checkpoint its progress and pick it up in another process.
+///
+/// A cursor only makes sense for the tree it was taken from. If nodes are added, removed or
+/// moved in between, the traversal resumes from the same indices, which may now be different
+/// nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DfsCursor {
+    /// The nodes that remain to be visited, the next one last.
+    pending: Vec<usize>,
+}
+
+impl DfsCursor {
+    /// Returns `true` if the traversal has visited every node.
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// A depth-first pre-order iterator over a tree whose position can be saved with
+/// [`ResumableDfs::cursor_state`], created with [`Tree::iter_dfs_resumable`] or
+/// [`Tree::resume_dfs`].
+pub struct ResumableDfs<'a, T, D = Vec<T>> {
+    tree: &'a Tree<T, D>,
+    cursor: DfsCursor,
+}
+
+impl<T, D: TreeStorage<T>> ResumableDfs<'_, T, D> {
+    /// Returns the position of the iterator, from which [`Tree::resume_dfs`] continues with the
+    /// node that `next` would return.
+    pub fn cursor_state(&self) -> DfsCursor {
+        self.cursor.clone()
+    }
+}
+
+impl<'a, T, D: TreeStorage<T>> Iterator for ResumableDfs<'a, T, D> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        let index = self.cursor.pending.pop()?;
+        let children = tree.links[index].children.iter().rev();
+        self.cursor.pending.extend(children);
+        Some((index, &tree.data[index]))
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns a depth-first pre-order iterator over the subtree of the [root](Tree::root), like
+    /// [`Tree::iter_dfs`], whose position can be saved and resumed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// tree.add_child(a, "a1");
+    /// tree.add_child(root, "b");
+    ///
+    /// // Process the first two nodes, then stop and keep the position.
+    /// let mut dfs = tree.iter_dfs_resumable();
+    /// let first: Vec<_> = dfs.by_ref().take(2).map(|(_, name)| *name).collect();
+    /// let cursor = dfs.cursor_state();
+    /// assert_eq!(first, ["root", "a"]);
+    ///
+    /// // Later, continue from the saved position.
+    /// let rest: Vec<_> = tree.resume_dfs(cursor).unwrap().map(|(_, name)| *name).collect();
+    /// assert_eq!(rest, ["a1", "b"]);
+    /// ```
+    pub fn iter_dfs_resumable(&self) -> ResumableDfs<'_, T, D> {
+        let pending = self.root().into_iter().collect();
+        ResumableDfs {
+            tree: self,
+            cursor: DfsCursor { pending },
+        }
+    }
+
+    /// Resumes a depth-first traversal from a position saved with
+    /// [`ResumableDfs::cursor_state`].
+    ///
+    /// # Parameters
+    /// - `cursor`: The saved position.
+    ///
+    /// # Errors
+    /// [`TreeError::InvalidIndex`] if the cursor refers to a node that this tree does not have,
+    /// such as a cursor taken from a larger tree.
+    pub fn resume_dfs(&self, cursor: DfsCursor) -> Result<ResumableDfs<'_, T, D>, TreeError> {
+        if let Some(&index) = cursor.pending.iter().find(|&&index| index >= self.len()) {
+            return Err(TreeError::InvalidIndex(index));
+        }
+        Ok(ResumableDfs { tree: self, cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tree<usize> {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..50 {
+            tree.add_child((index - 1) / 3, index);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_resumed_dfs_matches_iter_dfs() {
+        let tree = sample();
+        let expected: Vec<_> = tree.iter_dfs().map(|(index, _)| index).collect();
+
+        // Stop after every few nodes, as a batch job checkpointing its progress would.
+        let mut visited = vec![];
+        let mut cursor = tree.iter_dfs_resumable().cursor_state();
+        while !cursor.is_finished() {
+            let mut dfs = tree.resume_dfs(cursor).unwrap();
+            visited.extend(dfs.by_ref().take(7).map(|(index, _)| index));
+            cursor = dfs.cursor_state();
+        }
+        assert_eq!(visited, expected);
+        assert!(Tree::<u8>::new()
+            .iter_dfs_resumable()
+            .cursor_state()
+            .is_finished());
+
+        let mut dfs = tree.iter_dfs_resumable();
+        dfs.nth(10);
+        let mut small = Tree::new();
+        small.add_node(1);
+        assert!(matches!(
+            small.resume_dfs(dfs.cursor_state()),
+            Err(TreeError::InvalidIndex(_))
+        ));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_cursor_survives_serialization() {
+        let tree = sample();
+        let mut dfs = tree.iter_dfs_resumable();
+        dfs.nth(20);
+        let bytes = bincode::serialize(&dfs.cursor_state()).unwrap();
+        let cursor: DfsCursor = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(cursor, dfs.cursor_state());
+        assert!(tree.resume_dfs(cursor).unwrap().eq(dfs));
+    }
+}
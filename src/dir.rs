@@ -0,0 +1,321 @@
+use crate::Tree;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::ThreadPool;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The data of a node produced by [`Tree::from_dir`], describing a file, directory or symbolic
+/// link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntryInfo {
+    /// The path of the entry, starting with the path given to [`Tree::from_dir`].
+    pub path: PathBuf,
+    /// The last component of the path.
+    pub file_name: OsString,
+    /// The depth of the entry: `0` for the scanned directory itself.
+    pub depth: usize,
+    /// Whether the entry is a directory, or a link to one when following symbolic links.
+    pub is_dir: bool,
+    /// Whether the entry is a symbolic link.
+    pub is_symlink: bool,
+    /// The size of the entry in bytes, as reported by its metadata.
+    pub len: u64,
+    /// The last modification time, if the platform reports it.
+    pub modified: Option<SystemTime>,
+}
+
+impl DirEntryInfo {
+    fn read(path: PathBuf, depth: usize, follow_symlinks: bool) -> io::Result<Self> {
+        let link_metadata = fs::symlink_metadata(&path)?;
+        let is_symlink = link_metadata.file_type().is_symlink();
+        // A broken link is reported as the link itself.
+        let metadata = if follow_symlinks && is_symlink {
+            fs::metadata(&path).unwrap_or(link_metadata)
+        } else {
+            link_metadata
+        };
+        Ok(DirEntryInfo {
+            file_name: path.file_name().unwrap_or(path.as_os_str()).to_os_string(),
+            path,
+            depth,
+            is_dir: metadata.is_dir(),
+            is_symlink,
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// A predicate deciding which entries [`Tree::from_dir`] keeps.
+type DirFilter = Box<dyn Fn(&DirEntryInfo) -> bool + Send + Sync>;
+
+/// Controls how [`Tree::from_dir`] scans a directory.
+///
+/// The options are built with [`DirOptions::new`] and the `with_*` methods. The struct is
+/// non-exhaustive because some fields only exist with the `rayon` feature, which would
+/// otherwise break struct literals as soon as another crate of the build enables it.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DirOptions {
+    /// Whether to follow symbolic links to directories. Links that lead back to a directory
+    /// being scanned are not followed again.
+    pub follow_symlinks: bool,
+    /// The maximum depth of the entries, where the scanned directory has depth `0`. `None`
+    /// scans the whole hierarchy.
+    pub max_depth: Option<usize>,
+    /// Keeps only the entries for which it returns `true`. A rejected directory is skipped
+    /// along with its contents. The scanned directory itself is always kept.
+    pub filter: Option<DirFilter>,
+    /// Whether to read sibling directories in parallel with rayon.
+    #[cfg(feature = "rayon")]
+    pub parallel: bool,
+    /// The thread pool to read directories on when scanning in parallel. `None` uses the
+    /// global pool.
+    #[cfg(feature = "rayon")]
+    pub pool: Option<Arc<ThreadPool>>,
+}
+
+impl DirOptions {
+    /// Scans the whole hierarchy without following symbolic links or filtering entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follows symbolic links to directories.
+    pub fn with_follow_symlinks(mut self) -> Self {
+        self.follow_symlinks = true;
+        self
+    }
+
+    /// Stops at entries of depth `max_depth`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Keeps only the entries for which `filter` returns `true`, along with their contents.
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&DirEntryInfo) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Reads sibling directories in parallel with rayon, which speeds up scans of large
+    /// hierarchies, especially on network file systems.
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    /// Reads sibling directories in parallel on the given thread pool rather than the global
+    /// one. The pool is shared through an [`Arc`], like the pool of
+    /// [`Tree::with_pool`](crate::Tree::with_pool).
+    #[cfg(feature = "rayon")]
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.parallel = true;
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Runs `op` on the thread pool of the options, if they scan in parallel on one.
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = self.pool.as_ref().filter(|_| self.parallel) {
+            return pool.install(op);
+        }
+        op()
+    }
+}
+
+/// A scanned entry with its kept children, before they are numbered.
+struct Scanned {
+    info: DirEntryInfo,
+    children: Vec<Scanned>,
+}
+
+impl Tree<DirEntryInfo> {
+    /// Scans a directory and its contents into a tree.
+    ///
+    /// `path` becomes node `0` and its contents follow in depth-first order. The children of a
+    /// directory are sorted by file name, so scans of the same hierarchy give the same tree.
+    ///
+    /// # Parameters
+    /// - `path`: The directory to scan. A file gives a tree with a single node.
+    /// - `options`: How to treat symbolic links, how deep to scan and which entries to keep.
+    ///
+    /// # Errors
+    /// The first I/O error met, such as a directory that cannot be read.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{DirOptions, Tree};
+    ///
+    /// let options = DirOptions::new()
+    ///     .with_max_depth(1)
+    ///     .with_filter(|entry| entry.is_dir || entry.path.extension() == Some("rs".as_ref()));
+    /// let tree = Tree::from_dir("src", &options).unwrap();
+    /// assert!(tree
+    ///     .children(0)
+    ///     .iter()
+    ///     .any(|&child| tree[child].file_name == "lib.rs"));
+    /// ```
+    pub fn from_dir(path: impl AsRef<Path>, options: &DirOptions) -> io::Result<Self> {
+        let info = DirEntryInfo::read(path.as_ref().to_path_buf(), 0, options.follow_symlinks)?;
+        let children = if info.is_dir {
+            options.install(|| scan(&info.path, 0, options, &[]))?
+        } else {
+            Vec::new()
+        };
+
+        let mut tree = Tree::new();
+        let mut stack = vec![(Scanned { info, children }, None)];
+        while let Some((scanned, parent)) = stack.pop() {
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, scanned.info),
+                None => tree.add_node(scanned.info),
+            };
+            stack.extend(
+                scanned
+                    .children
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, Some(index))),
+            );
+        }
+        Ok(tree)
+    }
+}
+
+/// Scans the kept contents of the directory `dir` of depth `depth`, whose ancestors have the
+/// canonical paths `ancestors` when following symbolic links.
+fn scan(
+    dir: &Path,
+    depth: usize,
+    options: &DirOptions,
+    ancestors: &[PathBuf],
+) -> io::Result<Vec<Scanned>> {
+    if options
+        .max_depth
+        .is_some_and(|max_depth| depth >= max_depth)
+    {
+        return Ok(Vec::new());
+    }
+    let mut ancestors = ancestors.to_vec();
+    if options.follow_symlinks {
+        let canonical = fs::canonicalize(dir)?;
+        if ancestors.contains(&canonical) {
+            return Ok(Vec::new());
+        }
+        ancestors.push(canonical);
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let info = DirEntryInfo::read(entry?.path(), depth + 1, options.follow_symlinks)?;
+        if options.filter.as_ref().map_or(true, |filter| filter(&info)) {
+            entries.push(info);
+        }
+    }
+    entries.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    let descend = |info: DirEntryInfo| {
+        let children = if info.is_dir {
+            scan(&info.path, depth + 1, options, &ancestors)?
+        } else {
+            Vec::new()
+        };
+        Ok(Scanned { info, children })
+    };
+    #[cfg(feature = "rayon")]
+    if options.parallel {
+        return entries.into_par_iter().map(descend).collect();
+    }
+    entries.into_iter().map(descend).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh directory holding `files`, given as relative paths.
+    fn fixture(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("easy-tree-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        root
+    }
+
+    /// Lists the entries below the root, indented by depth.
+    fn names(tree: &Tree<DirEntryInfo>) -> Vec<String> {
+        tree.iter()
+            .skip(1)
+            .map(|(_, info)| {
+                let indent = "  ".repeat(info.depth - 1);
+                format!("{indent}{}", info.file_name.to_string_lossy())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_from_dir_sorts_filters_and_limits_depth() {
+        let root = fixture("scan", &["b/x.txt", "b/deep/y.txt", "a.txt", "c.log"]);
+
+        let tree = Tree::from_dir(&root, &DirOptions::new()).unwrap();
+        assert_eq!(
+            names(&tree),
+            ["a.txt", "b", "  deep", "    y.txt", "  x.txt", "c.log"]
+        );
+        assert!(tree[0].is_dir && !tree[1].is_dir);
+        assert_eq!(tree[1].len, "a.txt".len() as u64);
+
+        let options = DirOptions::new()
+            .with_max_depth(2)
+            .with_filter(|info| info.file_name != "c.log");
+        let tree = Tree::from_dir(&root, &options).unwrap();
+        assert_eq!(names(&tree), ["a.txt", "b", "  deep", "  x.txt"]);
+
+        #[cfg(feature = "rayon")]
+        {
+            let parallel = Tree::from_dir(&root, &DirOptions::new().with_parallel()).unwrap();
+            assert_eq!(names(&parallel).len(), 6);
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .build()
+                .unwrap();
+            let options = DirOptions::new().with_pool(Arc::new(pool));
+            assert_eq!(names(&Tree::from_dir(&root, &options).unwrap()).len(), 6);
+        }
+        assert!(Tree::from_dir(root.join("missing"), &DirOptions::new()).is_err());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_dir_follows_symlinks_without_looping() {
+        let root = fixture("links", &["dir/file"]);
+        std::os::unix::fs::symlink(root.join("dir"), root.join("dir/back")).unwrap();
+
+        let tree = Tree::from_dir(&root, &DirOptions::new()).unwrap();
+        assert_eq!(names(&tree), ["dir", "  back", "  file"]);
+        assert!(tree[2].is_symlink && !tree[2].is_dir);
+
+        let tree = Tree::from_dir(&root, &DirOptions::new().with_follow_symlinks()).unwrap();
+        assert_eq!(names(&tree), ["dir", "  back", "  file"]);
+        assert!(tree[2].is_symlink && tree[2].is_dir);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
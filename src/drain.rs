@@ -0,0 +1,260 @@
+use crate::{Tree, TreeStorage};
+use std::vec;
+
+impl<T> Tree<T> {
+    /// Consumes the tree, returning an iterator over the indices and owned data of its nodes in
+    /// depth-first pre-order.
+    ///
+    /// Unlike [`Tree::iter_dfs`], every tree of the forest is visited, one after the other in
+    /// the order of their roots, so no data is lost when draining a tree into another
+    /// structure.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// // A payload that cannot be cloned.
+    /// struct Task(&'static str);
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(Task("build"));
+    /// let test = tree.add_child(root, Task("test"));
+    /// tree.add_child(test, Task("lint"));
+    /// tree.add_child(root, Task("deploy"));
+    ///
+    /// let tasks: Vec<_> = tree.into_iter_dfs().map(|(_, task)| task.0).collect();
+    /// assert_eq!(tasks, ["build", "test", "lint", "deploy"]);
+    /// ```
+    pub fn into_iter_dfs(self) -> impl Iterator<Item = (usize, T)> {
+        let mut order = Vec::with_capacity(self.links.len());
+        for root in (0..self.links.len()).filter(|&index| self.links[index].parent().is_none()) {
+            order.extend(self.reachable_from(root));
+        }
+        let mut data: Vec<Option<T>> = self.data.into_iter().map(Some).collect();
+        order.into_iter().map(move |index| {
+            let node = data[index].take().expect("node visited twice");
+            (index, node)
+        })
+    }
+
+    /// Consumes the tree, traversing it like [`Tree::traverse_forest`] and handing the data of
+    /// every node over to `after_processing_the_subtree`.
+    ///
+    /// A node is given away only after its whole subtree, so the callbacks can build a nested
+    /// structure bottom-up, with the parts built from the children kept in the state.
+    ///
+    /// # Parameters
+    /// - `before_processing_children`: A function to apply before visiting children, which
+    ///   borrows the data of the node.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children, which
+    ///   takes the data of the node.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(String::from("root"));
+    /// let a = tree.add_child(root, String::from("a"));
+    /// tree.add_child(a, String::from("a1"));
+    /// tree.add_child(root, String::from("b"));
+    ///
+    /// // A stack of the rendered children of the nodes being visited.
+    /// let mut stack: Vec<Vec<String>> = vec![vec![]];
+    /// tree.into_traverse(
+    ///     |_, _, stack| stack.push(vec![]),
+    ///     |_, name, stack| {
+    ///         let children = stack.pop().unwrap();
+    ///         let rendered = if children.is_empty() {
+    ///             name
+    ///         } else {
+    ///             format!("{name}({})", children.join(" "))
+    ///         };
+    ///         stack.last_mut().unwrap().push(rendered);
+    ///     },
+    ///     &mut stack,
+    /// );
+    /// assert_eq!(stack, [["root(a(a1) b)"]]);
+    /// ```
+    pub fn into_traverse<S>(
+        self,
+        mut before_processing_children: impl FnMut(usize, &T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, T, &mut S),
+        s: &mut S,
+    ) {
+        let mut data: Vec<Option<T>> = self.data.into_iter().map(Some).collect();
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        let mut stack: Vec<(usize, bool)> = Vec::new();
+        for root in roots {
+            stack.push((root, false));
+            while let Some((index, children_visited)) = stack.pop() {
+                if children_visited {
+                    let node = data[index].take().expect("node visited twice");
+                    after_processing_the_subtree(index, node, s);
+                } else {
+                    let node = data[index].as_ref().expect("node visited twice");
+                    before_processing_children(index, node, s);
+                    stack.push((index, true));
+                    let children = self.links[index].children.iter().rev();
+                    stack.extend(children.map(|&child| (child, false)));
+                }
+            }
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Removes the subtree rooted at `index` from the tree, returning an iterator over the
+    /// indices and owned data of its nodes in depth-first pre-order.
+    ///
+    /// The subtree is detached from its parent and moved to the end of the tree right away,
+    /// then every call to `next` takes one node off the tree, so a branch can be streamed into
+    /// a writer while its memory is handed over node by node. The yielded indices are those
+    /// the nodes had before draining. If the iterator is dropped early, the remaining nodes of
+    /// the subtree are removed and dropped.
+    ///
+    /// The other nodes keep their relative order but are renumbered to close the gaps, as with
+    /// [`Tree::transplant_into`].
+    ///
+    /// # Parameters
+    /// - `index`: The index of the root of the subtree to drain.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let logs = tree.add_child(root, "logs");
+    /// tree.add_child(logs, "monday");
+    /// tree.add_child(logs, "tuesday");
+    /// tree.add_child(root, "config");
+    ///
+    /// let mut archive = String::new();
+    /// for (_, name) in tree.drain_subtree(logs) {
+    ///     archive.push_str(name);
+    ///     archive.push('\n');
+    /// }
+    /// assert_eq!(archive, "logs\nmonday\ntuesday\n");
+    /// assert_eq!(tree.data(), &["root", "config"]);
+    /// assert_eq!(tree.children(root), &[1]);
+    /// ```
+    pub fn drain_subtree(
+        &mut self,
+        index: usize,
+    ) -> impl ExactSizeIterator<Item = (usize, T)> + '_ {
+        assert!(index < self.links.len(), "node index out of bounds");
+        self.detach(index);
+        let mut drained: Vec<usize> = self.reachable_from(index).collect();
+        // Nodes are taken from the end, so the last one to be yielded goes first.
+        drained.reverse();
+        self.move_to_end(&drained);
+        drained.reverse();
+        DrainSubtree {
+            tree: self,
+            indices: drained.into_iter(),
+        }
+    }
+}
+
+/// The iterator of [`Tree::drain_subtree`], which holds the drained nodes at the end of the
+/// tree, the next one last.
+struct DrainSubtree<'a, T, D: TreeStorage<T>> {
+    tree: &'a mut Tree<T, D>,
+    indices: vec::IntoIter<usize>,
+}
+
+impl<T, D: TreeStorage<T>> Iterator for DrainSubtree<'_, T, D> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.indices.next()?;
+        let data = self.tree.data.pop().expect("drained node is missing");
+        self.tree.links.pop();
+        self.tree.ext.truncate(self.tree.links.len());
+        self.tree.history.changed_from(self.tree.links.len());
+        Some((index, data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<T, D: TreeStorage<T>> ExactSizeIterator for DrainSubtree<'_, T, D> {}
+
+impl<T, D: TreeStorage<T>> Drop for DrainSubtree<'_, T, D> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_iter_dfs_and_into_traverse_visit_the_forest() {
+        let build = || {
+            let mut tree = Tree::new();
+            let root = tree.add_node(Box::new(0));
+            let orphan = tree.add_node(Box::new(1));
+            let child = tree.add_child(root, Box::new(2));
+            tree.add_child(orphan, Box::new(3));
+            tree.add_child(child, Box::new(4));
+            tree
+        };
+
+        let drained: Vec<_> = build()
+            .into_iter_dfs()
+            .map(|(index, value)| (index, *value))
+            .collect();
+        assert_eq!(drained, [(0, 0), (2, 2), (4, 4), (1, 1), (3, 3)]);
+
+        let mut log = vec![];
+        build().into_traverse(
+            |index, _, log: &mut Vec<(bool, usize)>| log.push((true, index)),
+            |_, value, log| log.push((false, *value)),
+            &mut log,
+        );
+        let mut expected = vec![];
+        build().traverse_forest(
+            |index, _, log: &mut Vec<(bool, usize)>| log.push((true, index)),
+            |_, value, log| log.push((false, **value)),
+            &mut expected,
+        );
+        assert_eq!(log, expected);
+        assert_eq!(Tree::<u8>::new().into_iter_dfs().count(), 0);
+    }
+
+    #[test]
+    fn test_drain_subtree_removes_nodes_lazily() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(String::from("root"));
+        let a = tree.add_child(root, String::from("a"));
+        let b = tree.add_child(root, String::from("b"));
+        tree.add_child(a, String::from("a1"));
+        tree.add_child(b, String::from("b1"));
+        tree.add_child(a, String::from("a2"));
+        tree.insert_ext(b, 'b');
+
+        let mut drain = tree.drain_subtree(a);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some((1, String::from("a"))));
+        drop(drain);
+
+        let names: Vec<_> = tree.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, ["root", "b", "b1"]);
+        assert_eq!(tree.children(0), &[1]);
+        assert_eq!(tree.children(1), &[2]);
+        assert_eq!(tree.get_ext::<char>(1), Some(&'b'));
+
+        let all: Vec<_> = tree.drain_subtree(0).map(|(_, name)| name).collect();
+        assert_eq!(all, ["root", "b", "b1"]);
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Tree, TreeStorage};
+use std::any::Any;
+use std::ops::{Deref, DerefMut};
+
+/// Gives access to the concrete type behind a trait object, so that the nodes of a tree of
+/// trait objects, such as `Tree<Box<dyn Behavior>>`, can be downcast with [`Tree::get_dyn`].
+///
+/// Every `'static` type implements `AsAny`. To make a trait object downcastable, add `AsAny`
+/// as a supertrait of the trait, as in `trait Behavior: AsAny`. `dyn Any` also implements it,
+/// with and without `Send` and `Sync`.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{AsAny, Tree};
+///
+/// trait Behavior: AsAny {
+///     fn update(&mut self);
+/// }
+///
+/// struct Counter(u32);
+/// impl Behavior for Counter {
+///     fn update(&mut self) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// struct Label(&'static str);
+/// impl Behavior for Label {
+///     fn update(&mut self) {}
+/// }
+///
+/// let mut scene: Tree<Box<dyn Behavior>> = Tree::new();
+/// let root = scene.add_node(Box::new(Label("root")));
+/// let counter = scene.add_child(root, Box::new(Counter(0)));
+///
+/// for (_, node) in scene.iter_mut() {
+///     node.update();
+/// }
+/// assert_eq!(scene.get_dyn::<Counter>(counter).map(|c| c.0), Some(1));
+/// assert!(scene.get_dyn::<Counter>(root).is_none());
+/// assert_eq!(scene.get_dyn::<Label>(root).map(|label| label.0), Some("root"));
+/// ```
+pub trait AsAny: Any {
+    /// Returns the value as `&dyn Any`, which can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the value as `&mut dyn Any`, which can be downcast to its concrete type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+macro_rules! impl_as_any_for_dyn_any {
+    ($($any:ty),*) => {
+        $(
+            impl AsAny for $any {
+                fn as_any(&self) -> &dyn Any {
+                    self
+                }
+
+                fn as_any_mut(&mut self) -> &mut dyn Any {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_as_any_for_dyn_any!(dyn Any, dyn Any + Send, dyn Any + Send + Sync);
+
+impl<T, D, X> Tree<T, D>
+where
+    T: Deref<Target = X>,
+    D: TreeStorage<T>,
+    X: AsAny + ?Sized,
+{
+    /// Returns the data of a node downcast to the concrete type `U`, for trees of boxed or
+    /// shared trait objects whose trait has [`AsAny`] as a supertrait.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    ///
+    /// # Returns
+    /// `None` if the index is out of bounds or the node does not hold a `U`.
+    pub fn get_dyn<U: Any>(&self, index: usize) -> Option<&U> {
+        let node: &X = self.get(index)?;
+        node.as_any().downcast_ref()
+    }
+
+    /// Returns an iterator over the nodes holding a value of the concrete type `U`, as
+    /// `(index, value)` pairs in increasing index order.
+    pub fn iter_dyn<U: Any>(&self) -> impl Iterator<Item = (usize, &U)> {
+        self.iter().filter_map(|(index, node)| {
+            let node: &X = node;
+            Some((index, node.as_any().downcast_ref()?))
+        })
+    }
+}
+
+impl<T, D, X> Tree<T, D>
+where
+    T: DerefMut<Target = X>,
+    D: TreeStorage<T>,
+    X: AsAny + ?Sized,
+{
+    /// Returns the data of a node mutably downcast to the concrete type `U`. See
+    /// [`Tree::get_dyn`].
+    ///
+    /// # Returns
+    /// `None` if the index is out of bounds or the node does not hold a `U`.
+    pub fn get_dyn_mut<U: Any>(&mut self, index: usize) -> Option<&mut U> {
+        let node: &mut X = self.get_mut(index)?;
+        node.as_any_mut().downcast_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    trait Shape: AsAny {
+        fn area(&self) -> f64;
+    }
+
+    struct Square(f64);
+    impl Shape for Square {
+        fn area(&self) -> f64 {
+            self.0 * self.0
+        }
+    }
+
+    struct Group;
+    impl Shape for Group {
+        fn area(&self) -> f64 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_trait_object_trees_traverse_map_and_downcast() {
+        let mut scene: Tree<Box<dyn Shape>> = Tree::new();
+        let root = scene.add_node(Box::new(Group));
+        let small = scene.add_child(root, Box::new(Square(1.0)));
+        scene.add_child(root, Box::new(Square(3.0)));
+
+        let mut total = 0.0;
+        scene.traverse(
+            |_, shape, total| *total += shape.area(),
+            |_, _, _| {},
+            &mut total,
+        );
+        assert_eq!(total, 10.0);
+        let areas = scene.map_ref(|shape| shape.area());
+        assert_eq!(areas.data(), &[0.0, 1.0, 9.0]);
+
+        scene.get_dyn_mut::<Square>(small).unwrap().0 = 2.0;
+        let squares: Vec<_> = scene.iter_dyn::<Square>().map(|(i, s)| (i, s.0)).collect();
+        assert_eq!(squares, [(1, 2.0), (2, 3.0)]);
+        assert!(scene.get_dyn::<Square>(root).is_none());
+        assert!(scene.get_dyn::<Square>(10).is_none());
+
+        let mut shared: Tree<Arc<dyn Any + Send + Sync>> = Tree::new();
+        shared.add_node(Arc::new(7u8));
+        assert_eq!(shared.get_dyn::<u8>(0), Some(&7));
+        assert_eq!(shared.get_dyn::<u16>(0), None);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{Tree, TreeSelection, TreeStorage};
+use egui::collapsing_header::CollapsingState;
+use egui::{Id, Response, Ui};
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Renders the tree into an `egui` UI as nested collapsing headers.
+    ///
+    /// Nodes with children get a toggle button, and their expansion state is read from and
+    /// written back to `selection`. Clicking a row selects it alone; clicking with the command
+    /// modifier held toggles its selection instead. Every node without a parent is rendered as
+    /// a top-level row, in index order.
+    ///
+    /// # Parameters
+    /// - `ui`: The UI to render into.
+    /// - `selection`: The selection and expansion state, kept across frames.
+    /// - `row`: Renders the row of a node, for example as a label, and returns its response.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreeSelection};
+    ///
+    /// fn show_files(ui: &mut egui::Ui, files: &Tree<String>, selection: &mut TreeSelection) {
+    ///     let selected = selection.clone();
+    ///     files.show_egui(ui, selection, |ui, index, name| {
+    ///         ui.selectable_label(selected.is_selected(index), name)
+    ///     });
+    /// }
+    /// ```
+    pub fn show_egui(
+        &self,
+        ui: &mut Ui,
+        selection: &mut TreeSelection,
+        mut row: impl FnMut(&mut Ui, usize, &T) -> Response,
+    ) {
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        for root in roots {
+            self.show_egui_node(ui, selection, &mut row, root);
+        }
+    }
+
+    fn show_egui_node(
+        &self,
+        ui: &mut Ui,
+        selection: &mut TreeSelection,
+        row: &mut impl FnMut(&mut Ui, usize, &T) -> Response,
+        index: usize,
+    ) {
+        let node = &self.links[index];
+        let response = if node.children.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add_space(ui.spacing().indent);
+                row(ui, index, &self.data[index])
+            })
+            .inner
+        } else {
+            let id = Id::new(("easy_tree_node", index)).with(ui.id());
+            let expanded = selection.is_expanded(index);
+            let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, expanded);
+            state.set_open(expanded);
+            let header = state.show_header(ui, |ui| row(ui, index, &self.data[index]));
+            if header.is_open() != expanded {
+                selection.toggle_expanded(index);
+            }
+            let (_, header, _) = header.body(|ui| {
+                for &child in &node.children {
+                    self.show_egui_node(ui, selection, row, child);
+                }
+            });
+            header.inner
+        };
+
+        if response.clicked() {
+            if ui.input(|input| input.modifiers.command) {
+                selection.toggle(index);
+            } else {
+                selection.clear_selection();
+                selection.select(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(tree: &Tree<&str>, selection: &mut TreeSelection) -> Vec<usize> {
+        let mut rendered = vec![];
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                tree.show_egui(ui, selection, |ui, index, data| {
+                    rendered.push(index);
+                    ui.label(*data)
+                });
+            });
+        });
+        rendered
+    }
+
+    #[test]
+    fn test_show_egui_renders_expanded_rows() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let folder = tree.add_child(root, "folder");
+        tree.add_child(folder, "file");
+        let leaf = tree.add_child(root, "leaf");
+        let other = tree.add_node("other");
+
+        let mut selection = TreeSelection::new(&tree);
+        selection.collapse(folder);
+        assert_eq!(
+            render(&tree, &mut selection),
+            vec![root, folder, leaf, other]
+        );
+        assert!(!selection.is_expanded(folder));
+        assert!(selection.is_expanded(root));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Tree, TreeStorage};
+
+/// A view into a child slot of a node, which may either be occupied by a matching child or
+/// vacant.
+///
+/// Constructed with [`Tree::child_entry`].
+pub struct ChildEntry<'a, T, D = Vec<T>> {
+    tree: &'a mut Tree<T, D>,
+    parent: usize,
+    child: Option<usize>,
+}
+
+impl<'a, T, D: TreeStorage<T>> ChildEntry<'a, T, D> {
+    /// Returns the index of the matching child, or `None` if the entry is vacant.
+    pub fn index(&self) -> Option<usize> {
+        self.child
+    }
+
+    /// Returns the index of the parent node this entry belongs to.
+    pub fn parent(&self) -> usize {
+        self.parent
+    }
+
+    /// Returns the index of the matching child, inserting a new child with `data` if there is
+    /// none.
+    pub fn or_insert(self, data: T) -> usize {
+        self.or_insert_with(|| data)
+    }
+
+    /// Returns the index of the matching child, inserting a new child with the data produced by
+    /// `default` if there is none. `default` is only called when a child is inserted.
+    pub fn or_insert_with(self, default: impl FnOnce() -> T) -> usize {
+        match self.child {
+            Some(child) => child,
+            None => self.tree.add_child(self.parent, default()),
+        }
+    }
+
+    /// Calls `f` with the data of the matching child if there is one.
+    pub fn and_modify(self, f: impl FnOnce(&mut T)) -> Self {
+        if let Some(child) = self.child {
+            f(&mut self.tree.data[child]);
+        }
+        self
+    }
+}
+
+impl<'a, T: Default, D: TreeStorage<T>> ChildEntry<'a, T, D> {
+    /// Returns the index of the matching child, inserting a new child with `T::default()` if
+    /// there is none.
+    pub fn or_default(self) -> usize {
+        self.or_insert_with(T::default)
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Finds the first child of `parent` matching `predicate`, returning an entry that can be
+    /// used to insert a child if none matches.
+    ///
+    /// This is useful for building merged trees, such as paths or taxonomies, where each level
+    /// should only be created once.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the parent node.
+    /// - `predicate`: Called with the data of each child in order until it returns `true`.
+    ///
+    /// # Returns
+    /// A [`ChildEntry`] for the matching child, or a vacant one if no child matched.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("/");
+    ///
+    /// for path in ["usr/bin", "usr/lib", "etc"] {
+    ///     let mut parent = root;
+    ///     for segment in path.split('/') {
+    ///         parent = tree
+    ///             .child_entry(parent, |name| *name == segment)
+    ///             .or_insert(segment);
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(tree.len(), 5);
+    /// assert_eq!(tree.children(root).len(), 2);
+    /// ```
+    pub fn child_entry(
+        &mut self,
+        parent: usize,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> ChildEntry<'_, T, D> {
+        let child = self.links[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|&child| predicate(&self.data[child]));
+        ChildEntry {
+            tree: self,
+            parent,
+            child,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_entry() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(("root", 0));
+        let a = tree.child_entry(root, |d| d.0 == "a").or_insert(("a", 0));
+        assert_eq!(tree.children(root), &[a]);
+
+        let entry = tree.child_entry(root, |d| d.0 == "a");
+        assert_eq!(entry.index(), Some(a));
+        let again = entry
+            .and_modify(|d| d.1 += 1)
+            .or_insert_with(|| unreachable!());
+        assert_eq!(again, a);
+        assert_eq!(tree.get(a), Some(&("a", 1)));
+
+        let b = tree.child_entry(root, |d| d.0 == "b").or_insert(("b", 0));
+        assert_ne!(a, b);
+        assert_eq!(tree.children(root), &[a, b]);
+        assert_eq!(tree.parent_index_unchecked(b), Some(root));
+    }
+
+    #[test]
+    fn test_child_entry_or_default() {
+        let mut tree: Tree<i32> = Tree::new();
+        let root = tree.add_node(1);
+        let child = tree.child_entry(root, |d| *d == 0).or_default();
+        assert_eq!(tree.get(child), Some(&0));
+        assert_eq!(tree.child_entry(root, |d| *d == 0).or_default(), child);
+    }
+}
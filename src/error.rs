@@ -0,0 +1,63 @@
+use std::fmt;
+
+/// An error produced by fallible operations on a [`Tree`](crate::Tree).
+///
+/// Every variant carries the indices of the nodes involved, which are also included in its
+/// message. New variants may be added as the fallible API grows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TreeError {
+    /// A node index is out of bounds.
+    InvalidIndex(usize),
+    /// A node already has a parent and cannot be given another one.
+    AlreadyHasParent {
+        /// The index of the node.
+        child: usize,
+        /// The index of its current parent.
+        parent: usize,
+    },
+    /// Making a node the parent of another would create a cycle, because the would-be parent is
+    /// the node itself or one of its descendants.
+    WouldCreateCycle {
+        /// The index of the node to attach.
+        child: usize,
+        /// The index of the would-be parent.
+        parent: usize,
+    },
+    /// A node other than the root has no parent, so it is not part of the tree.
+    Orphan(usize),
+    /// A node is not a child of the node it was expected to be a child of.
+    NotAChild {
+        /// The index of the expected parent.
+        parent: usize,
+        /// The index of the node.
+        child: usize,
+    },
+    /// A [`NodeId`](crate::NodeId) was used on a tree other than the one that produced it. The
+    /// index is the one the id holds.
+    ForeignNode(usize),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TreeError::InvalidIndex(index) => write!(f, "node index {index} is out of bounds"),
+            TreeError::AlreadyHasParent { child, parent } => {
+                write!(f, "node {child} already has parent {parent}")
+            }
+            TreeError::WouldCreateCycle { child, parent } => write!(
+                f,
+                "making node {parent} the parent of node {child} would create a cycle"
+            ),
+            TreeError::Orphan(index) => write!(f, "node {index} is an orphan"),
+            TreeError::NotAChild { parent, child } => {
+                write!(f, "node {child} is not a child of node {parent}")
+            }
+            TreeError::ForeignNode(index) => {
+                write!(f, "node id {index} belongs to another tree")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
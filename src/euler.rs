@@ -0,0 +1,128 @@
+use crate::{Tree, TreeStorage};
+
+/// A step of an Euler tour, as returned by [`Tree::euler_tour`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EulerStep {
+    /// The tour enters a node, before visiting its children.
+    Enter {
+        /// The index of the node.
+        index: usize,
+        /// The depth of the node: `0` for nodes without a parent.
+        depth: usize,
+    },
+    /// The tour leaves a node, after visiting its children.
+    Leave {
+        /// The index of the node.
+        index: usize,
+        /// The depth of the node: `0` for nodes without a parent.
+        depth: usize,
+    },
+}
+
+impl EulerStep {
+    /// Returns the index of the node entered or left.
+    pub fn index(&self) -> usize {
+        match *self {
+            EulerStep::Enter { index, .. } | EulerStep::Leave { index, .. } => index,
+        }
+    }
+
+    /// Returns the depth of the node entered or left.
+    pub fn depth(&self) -> usize {
+        match *self {
+            EulerStep::Enter { depth, .. } | EulerStep::Leave { depth, .. } => depth,
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns the Euler tour of the tree: every node is entered, then its children are toured
+    /// in order, then the node is left.
+    ///
+    /// Every node without a parent starts a tour of its own tree, in index order, so all nodes
+    /// are covered and the result always holds exactly `2 * len()` steps. The subtree of a node
+    /// spans the steps between its `Enter` and its `Leave`, which makes the tour the basis for
+    /// subtree range and ancestor queries such as [`Tree::build_subtree_index`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{EulerStep, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    ///
+    /// assert_eq!(
+    ///     tree.euler_tour(),
+    ///     vec![
+    ///         EulerStep::Enter { index: root, depth: 0 },
+    ///         EulerStep::Enter { index: child, depth: 1 },
+    ///         EulerStep::Leave { index: child, depth: 1 },
+    ///         EulerStep::Leave { index: root, depth: 0 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn euler_tour(&self) -> Vec<EulerStep> {
+        let mut tour = Vec::with_capacity(2 * self.links.len());
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        for root in roots {
+            let mut stack = vec![EulerStep::Enter {
+                index: root,
+                depth: 0,
+            }];
+            while let Some(step) = stack.pop() {
+                tour.push(step);
+                if let EulerStep::Enter { index, depth } = step {
+                    stack.push(EulerStep::Leave { index, depth });
+                    for &child in self.links[index].children.iter().rev() {
+                        stack.push(EulerStep::Enter {
+                            index: child,
+                            depth: depth + 1,
+                        });
+                    }
+                }
+            }
+        }
+        tour
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_euler_tour_forest() {
+        let mut tree = Tree::new();
+        let a = tree.add_node('a');
+        let b = tree.add_child(a, 'b');
+        let c = tree.add_child(b, 'c');
+        let d = tree.add_child(a, 'd');
+        let e = tree.add_node('e');
+
+        let tour = tree.euler_tour();
+        assert_eq!(tour.len(), 2 * tree.len());
+        let events: Vec<_> = tour
+            .iter()
+            .map(|step| match step {
+                EulerStep::Enter { index, .. } => (true, *index, step.depth()),
+                EulerStep::Leave { index, .. } => (false, *index, step.depth()),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (true, a, 0),
+                (true, b, 1),
+                (true, c, 2),
+                (false, c, 2),
+                (false, b, 1),
+                (true, d, 1),
+                (false, d, 1),
+                (false, a, 0),
+                (true, e, 0),
+                (false, e, 0),
+            ]
+        );
+    }
+}
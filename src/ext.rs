@@ -0,0 +1,252 @@
+use crate::{Tree, TreeStorage};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+/// A column of extension values of one type, by node index.
+///
+/// The unwind safety bounds keep `Tree` usable with `std::panic::catch_unwind` whenever its
+/// data is.
+trait Column: Any + Send + Sync + UnwindSafe + RefUnwindSafe {
+    fn clone_box(&self) -> Box<dyn Column>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn swap(&mut self, a: usize, b: usize);
+    fn truncate(&mut self, len: usize);
+}
+
+impl<E: Clone + Send + Sync + UnwindSafe + RefUnwindSafe + 'static> Column for Vec<Option<E>> {
+    fn clone_box(&self) -> Box<dyn Column> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        // The column only grows as far as the last node with a value.
+        let len = a.max(b) + 1;
+        if self.len() < len {
+            self.resize_with(len, || None);
+        }
+        <[Option<E>]>::swap(self, a, b);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+}
+
+/// The extension columns of a tree, by type.
+#[derive(Default)]
+pub(crate) struct Extensions {
+    columns: HashMap<TypeId, Box<dyn Column>>,
+}
+
+impl Clone for Extensions {
+    fn clone(&self) -> Self {
+        Extensions {
+            columns: self
+                .columns
+                .iter()
+                .map(|(&type_id, column)| (type_id, column.clone_box()))
+                .collect(),
+        }
+    }
+}
+
+impl Extensions {
+    fn column<E: 'static>(&self) -> Option<&Vec<Option<E>>> {
+        let column = self.columns.get(&TypeId::of::<E>())?;
+        column.as_any().downcast_ref()
+    }
+
+    fn column_mut<E: 'static>(&mut self) -> Option<&mut Vec<Option<E>>> {
+        let column = self.columns.get_mut(&TypeId::of::<E>())?;
+        column.as_any_mut().downcast_mut()
+    }
+
+    /// Exchanges the values of the nodes `a` and `b` in every column, following a swap of
+    /// their data.
+    pub(crate) fn swap(&mut self, a: usize, b: usize) {
+        if a != b {
+            for column in self.columns.values_mut() {
+                column.swap(a, b);
+            }
+        }
+    }
+
+    /// Drops the values of the nodes from `len` on in every column, following a truncation of
+    /// the data.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        for column in self.columns.values_mut() {
+            column.truncate(len);
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Attaches a value of type `E` to a node, replacing the previous value of that type.
+    ///
+    /// Extensions let separate passes attach their own data to nodes, such as layout,
+    /// diagnostics or styles, without making them part of `T`. Each type is stored in its own
+    /// column, indexed by node, and a node has at most one value of each type.
+    ///
+    /// Extensions follow their node when it is moved to another index, as by
+    /// [`Tree::canonicalize`] or [`Tree::garbage_collect`], and are dropped with it. They are cloned with the
+    /// tree, but not serialized, and trees derived with [`Tree::map_ref`] start without any.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    /// - `value`: The value to attach.
+    ///
+    /// # Returns
+    /// The value of type `E` previously attached to the node, if any.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Layout {
+    ///     x: f32,
+    ///     width: f32,
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// struct Diagnostic(&'static str);
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    ///
+    /// tree.insert_ext(root, Layout { x: 0.0, width: 100.0 });
+    /// tree.insert_ext(child, Diagnostic("unused"));
+    ///
+    /// assert_eq!(tree.get_ext::<Layout>(root).map(|layout| layout.width), Some(100.0));
+    /// assert!(tree.get_ext::<Layout>(child).is_none());
+    /// assert_eq!(tree.get_ext::<Diagnostic>(child).map(|d| d.0), Some("unused"));
+    /// ```
+    pub fn insert_ext<E: Clone + Send + Sync + UnwindSafe + RefUnwindSafe + 'static>(
+        &mut self,
+        index: usize,
+        value: E,
+    ) -> Option<E> {
+        assert!(
+            index < self.links.len(),
+            "index {index} is out of bounds for a tree of {} nodes",
+            self.links.len()
+        );
+        let column = match self.ext.column_mut::<E>() {
+            Some(column) => column,
+            None => {
+                let column: Vec<Option<E>> = Vec::new();
+                self.ext.columns.insert(TypeId::of::<E>(), Box::new(column));
+                self.ext.column_mut().unwrap()
+            }
+        };
+        if column.len() <= index {
+            column.resize_with(index + 1, || None);
+        }
+        column[index].replace(value)
+    }
+
+    /// Returns the value of type `E` attached to a node, or `None` if it has none or the index
+    /// is out of bounds.
+    pub fn get_ext<E: 'static>(&self, index: usize) -> Option<&E> {
+        self.ext.column::<E>()?.get(index)?.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of type `E` attached to a node, or `None` if it
+    /// has none or the index is out of bounds.
+    pub fn get_ext_mut<E: 'static>(&mut self, index: usize) -> Option<&mut E> {
+        self.ext.column_mut::<E>()?.get_mut(index)?.as_mut()
+    }
+
+    /// Detaches the value of type `E` from a node.
+    ///
+    /// # Returns
+    /// The detached value, or `None` if the node had none or the index is out of bounds.
+    pub fn remove_ext<E: 'static>(&mut self, index: usize) -> Option<E> {
+        self.ext.column_mut::<E>()?.get_mut(index)?.take()
+    }
+
+    /// Returns an iterator over the nodes with a value of type `E`, as `(index, value)` pairs
+    /// in increasing index order.
+    pub fn iter_ext<E: 'static>(&self) -> impl Iterator<Item = (usize, &E)> + '_ {
+        self.ext
+            .column::<E>()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, value)| Some((index, value.as_ref()?)))
+    }
+
+    /// Detaches the values of type `E` from every node, such as before rerunning the pass that
+    /// computes them.
+    pub fn clear_ext<E: 'static>(&mut self) {
+        self.ext.columns.remove(&TypeId::of::<E>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extensions_are_typed_per_column() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let child = tree.add_child(root, "child");
+
+        assert_eq!(tree.insert_ext(child, 1u32), None);
+        assert_eq!(tree.insert_ext(child, 2u32), Some(1));
+        tree.insert_ext(root, "style");
+        *tree.get_ext_mut::<u32>(child).unwrap() += 1;
+
+        assert_eq!(tree.get_ext::<u32>(child), Some(&3));
+        assert_eq!(tree.get_ext::<u32>(root), None);
+        assert_eq!(tree.get_ext::<u64>(child), None);
+        assert_eq!(tree.get_ext::<u32>(10), None);
+        assert_eq!(tree.iter_ext::<&str>().collect::<Vec<_>>(), [(0, &"style")]);
+
+        let copy = tree.clone();
+        assert_eq!(tree.remove_ext::<u32>(child), Some(3));
+        assert_eq!(tree.get_ext::<u32>(child), None);
+        assert_eq!(copy.get_ext::<u32>(child), Some(&3));
+
+        tree.clear_ext::<&str>();
+        assert_eq!(tree.iter_ext::<&str>().count(), 0);
+    }
+
+    #[test]
+    fn test_extensions_follow_renumbered_nodes() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(3);
+        tree.add_child(root, 2);
+        let one = tree.add_child(root, 1);
+        tree.insert_ext(one, "one");
+
+        let remap = tree.canonicalize();
+        assert_eq!(tree.get_ext::<&str>(remap[one]), Some(&"one"));
+        assert_eq!(tree[remap[one]], 1);
+
+        let checkpoint = tree.checkpoint();
+        let extra = tree.add_child(root, 0);
+        tree.insert_ext(extra, "extra");
+        tree.rollback_to(checkpoint);
+        tree.add_node(4);
+        assert_eq!(tree.get_ext::<&str>(extra), None);
+
+        tree.clear();
+        assert_eq!(tree.iter_ext::<&str>().count(), 0);
+    }
+}
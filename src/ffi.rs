@@ -0,0 +1,241 @@
+//! A C interface to [`Tree`], enabled with the `ffi` feature.
+//!
+//! The tree is exposed as an opaque [`EasyTree`] handle holding one `void *` per node. The tree
+//! never reads, copies or frees these pointers: the caller owns the data they point to.
+//!
+//! The header `include/easy_tree.h` declares these functions. It is generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen) from `cbindgen.toml` at the root of the
+//! crate, and must be regenerated whenever this module changes:
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/easy_tree.h src/ffi.rs
+//! ```
+//!
+//! To link the tree into a C or C++ program, build a static or dynamic library:
+//!
+//! ```sh
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! ```c
+//! #include <stdio.h>
+//! #include "easy_tree.h"
+//!
+//! static void print_name(size_t index, void *data, void *user_data) {
+//!     printf("%zu: %s\n", index, (const char *)data);
+//! }
+//!
+//! int main(void) {
+//!     EasyTree *tree = easy_tree_new();
+//!     size_t root = easy_tree_add_node(tree, "root");
+//!     easy_tree_add_child(tree, root, "child");
+//!     easy_tree_traverse(tree, print_name, NULL, NULL);
+//!     easy_tree_free(tree);
+//!     return 0;
+//! }
+//! ```
+
+use crate::Tree;
+use std::ffi::c_void;
+use std::ptr;
+
+/// Returned instead of a node index when there is no such node.
+pub const EASY_TREE_NO_INDEX: usize = usize::MAX;
+
+/// An opaque tree of `void *` node data, created with [`easy_tree_new`] and destroyed with
+/// [`easy_tree_free`].
+pub struct EasyTree {
+    tree: Tree<*mut c_void>,
+}
+
+/// A traversal callback, called with the index of a node, its data and the `user_data` passed
+/// to [`easy_tree_traverse`].
+pub type EasyTreeVisitor =
+    Option<unsafe extern "C" fn(index: usize, data: *mut c_void, user_data: *mut c_void)>;
+
+/// Creates an empty tree.
+///
+/// # Returns
+/// A handle that must be destroyed with [`easy_tree_free`].
+#[no_mangle]
+pub extern "C" fn easy_tree_new() -> *mut EasyTree {
+    Box::into_raw(Box::new(EasyTree { tree: Tree::new() }))
+}
+
+/// Destroys a tree. The node data is not freed, as it belongs to the caller.
+///
+/// # Safety
+/// `tree` must be null or a handle returned by [`easy_tree_new`] that was not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_free(tree: *mut EasyTree) {
+    if !tree.is_null() {
+        drop(Box::from_raw(tree));
+    }
+}
+
+/// Returns the number of nodes in a tree.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`].
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_len(tree: *const EasyTree) -> usize {
+    (*tree).tree.len()
+}
+
+/// Adds a node without a parent.
+///
+/// # Returns
+/// The index of the new node.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`].
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_add_node(tree: *mut EasyTree, data: *mut c_void) -> usize {
+    (*tree).tree.add_node(data)
+}
+
+/// Adds a node as the last child of `parent`.
+///
+/// # Returns
+/// The index of the new node, or [`EASY_TREE_NO_INDEX`] if `parent` does not exist, in which
+/// case the tree is left unchanged.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`].
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_add_child(
+    tree: *mut EasyTree,
+    parent: usize,
+    data: *mut c_void,
+) -> usize {
+    (*tree)
+        .tree
+        .try_add_child(parent, data)
+        .unwrap_or(EASY_TREE_NO_INDEX)
+}
+
+/// Returns the data of a node, or null if there is no such node.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`].
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_get(tree: *const EasyTree, index: usize) -> *mut c_void {
+    (*tree).tree.get(index).copied().unwrap_or(ptr::null_mut())
+}
+
+/// Returns the index of the parent of a node, or [`EASY_TREE_NO_INDEX`] if the node has no
+/// parent or does not exist.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`].
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_parent(tree: *const EasyTree, index: usize) -> usize {
+    let tree = &(*tree).tree;
+    if index >= tree.len() {
+        return EASY_TREE_NO_INDEX;
+    }
+    tree.parent_index_unchecked(index)
+        .unwrap_or(EASY_TREE_NO_INDEX)
+}
+
+/// Returns the indices of the children of a node, in order.
+///
+/// # Returns
+/// A pointer to `*len` indices, valid until the tree is next modified or destroyed. If the node
+/// does not exist, `*len` is set to `0` and null is returned.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`], and `len` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_children(
+    tree: *const EasyTree,
+    index: usize,
+    len: *mut usize,
+) -> *const usize {
+    let tree = &(*tree).tree;
+    if index >= tree.len() {
+        *len = 0;
+        return ptr::null();
+    }
+    let children = tree.children(index);
+    *len = children.len();
+    children.as_ptr()
+}
+
+/// Traverses the subtree of the root depth-first, like [`Tree::traverse`], calling `before`
+/// when entering a node and `after` when leaving it. Either callback may be null.
+///
+/// # Safety
+/// `tree` must be a live handle returned by [`easy_tree_new`], and must not be modified or
+/// destroyed by the callbacks. The callbacks must be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn easy_tree_traverse(
+    tree: *const EasyTree,
+    before: EasyTreeVisitor,
+    after: EasyTreeVisitor,
+    user_data: *mut c_void,
+) {
+    let visit = |callback: EasyTreeVisitor, index: usize, data: &*mut c_void| {
+        if let Some(callback) = callback {
+            callback(index, *data, user_data);
+        }
+    };
+    (*tree).tree.traverse(
+        |index, data, _| visit(before, index, data),
+        |index, data, _| visit(after, index, data),
+        &mut (),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" fn record(index: usize, data: *mut c_void, user_data: *mut c_void) {
+        let events = &mut *(user_data as *mut Vec<(usize, usize)>);
+        events.push((index, data as usize));
+    }
+
+    #[test]
+    fn test_ffi_builds_and_traverses() {
+        unsafe {
+            let tree = easy_tree_new();
+            let root = easy_tree_add_node(tree, 10 as *mut c_void);
+            let child = easy_tree_add_child(tree, root, 11 as *mut c_void);
+            easy_tree_add_child(tree, child, 12 as *mut c_void);
+            easy_tree_add_child(tree, root, 13 as *mut c_void);
+
+            let mut events: Vec<(usize, usize)> = Vec::new();
+            let user_data = &mut events as *mut _ as *mut c_void;
+            easy_tree_traverse(tree, Some(record), None, user_data);
+            assert_eq!(events, [(0, 10), (1, 11), (2, 12), (3, 13)]);
+
+            let mut len = 0;
+            let children = easy_tree_children(tree, root, &mut len);
+            assert_eq!(std::slice::from_raw_parts(children, len), &[1, 3]);
+            assert_eq!(easy_tree_parent(tree, 2), child);
+            assert_eq!(easy_tree_get(tree, 3) as usize, 13);
+            easy_tree_free(tree);
+        }
+    }
+
+    #[test]
+    fn test_ffi_reports_missing_nodes() {
+        unsafe {
+            let tree = easy_tree_new();
+            assert_eq!(
+                easy_tree_add_child(tree, 0, ptr::null_mut()),
+                EASY_TREE_NO_INDEX
+            );
+            assert_eq!(easy_tree_len(tree), 0);
+            assert!(easy_tree_get(tree, 0).is_null());
+            assert_eq!(easy_tree_parent(tree, 0), EASY_TREE_NO_INDEX);
+            let mut len = 1;
+            assert!(easy_tree_children(tree, 0, &mut len).is_null());
+            assert_eq!(len, 0);
+            easy_tree_traverse(tree, Some(record), Some(record), ptr::null_mut());
+            easy_tree_free(tree);
+        }
+    }
+}
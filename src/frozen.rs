@@ -0,0 +1,150 @@
+use crate::{EulerStep, Tree, TreeStorage};
+use std::ops::Deref;
+
+/// An immutable tree, with precomputed depths and Euler tour.
+///
+/// A `FrozenTree` owns a [`Tree`] and dereferences to it, so the whole read-only API is
+/// available, but no method can change it. APIs that need a tree to stay the same, for
+/// example because they keep indices or derived data around, can take a `FrozenTree` to
+/// enforce that in the type system.
+///
+/// Because the structure can no longer change, freezing computes the depth of every node and
+/// the [Euler tour](Tree::euler_tour) once, which makes depth, ancestry and subtree size
+/// queries `O(1)`.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{FrozenTree, Tree};
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// let child = tree.add_child(root, "child");
+/// let grandchild = tree.add_child(child, "grandchild");
+///
+/// let frozen = tree.freeze();
+/// assert_eq!(frozen.depth(grandchild), 2);
+/// assert!(frozen.is_ancestor(root, grandchild));
+/// assert_eq!(frozen.subtree_size(child), 2);
+/// // Read-only methods of `Tree` are still available.
+/// assert_eq!(frozen.children(root), &[child]);
+///
+/// fn needs_frozen(tree: &FrozenTree<&str>) -> usize {
+///     tree.len()
+/// }
+/// assert_eq!(needs_frozen(&frozen), 3);
+/// ```
+#[derive(Clone)]
+pub struct FrozenTree<T, D = Vec<T>> {
+    tree: Tree<T, D>,
+    tour: Vec<EulerStep>,
+    /// The positions of the `Enter` and `Leave` steps of every node in `tour`.
+    enter: Vec<usize>,
+    leave: Vec<usize>,
+}
+
+impl<T, D: TreeStorage<T>> FrozenTree<T, D> {
+    /// Freezes a tree, precomputing its depths and Euler tour in `O(n)`.
+    pub fn new(tree: Tree<T, D>) -> Self {
+        let tour = tree.euler_tour();
+        let mut enter = vec![0; tree.len()];
+        let mut leave = vec![0; tree.len()];
+        for (position, step) in tour.iter().enumerate() {
+            match *step {
+                EulerStep::Enter { index, .. } => enter[index] = position,
+                EulerStep::Leave { index, .. } => leave[index] = position,
+            }
+        }
+        FrozenTree {
+            tree,
+            tour,
+            enter,
+            leave,
+        }
+    }
+
+    /// Returns the depth of a node: `0` for nodes without a parent.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn depth(&self, index: usize) -> usize {
+        self.tour[self.enter[index]].depth()
+    }
+
+    /// Returns the Euler tour of the tree, as computed by [`Tree::euler_tour`], without
+    /// recomputing it.
+    pub fn euler_tour(&self) -> &[EulerStep] {
+        &self.tour
+    }
+
+    /// Returns `true` if `ancestor` is `descendant` or one of its ancestors.
+    ///
+    /// # Panics
+    /// This method panics if either index is out of bounds.
+    pub fn is_ancestor(&self, ancestor: usize, descendant: usize) -> bool {
+        self.enter[ancestor] <= self.enter[descendant]
+            && self.leave[descendant] <= self.leave[ancestor]
+    }
+
+    /// Returns the number of nodes in the subtree of a node, including the node itself.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn subtree_size(&self, index: usize) -> usize {
+        (self.leave[index] - self.enter[index]).div_ceil(2)
+    }
+
+    /// Returns the frozen tree, so that it can be changed again. The precomputed data is
+    /// dropped.
+    pub fn into_inner(self) -> Tree<T, D> {
+        self.tree
+    }
+}
+
+impl<T, D> Deref for FrozenTree<T, D> {
+    type Target = Tree<T, D>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+impl<T, D: TreeStorage<T>> From<Tree<T, D>> for FrozenTree<T, D> {
+    fn from(tree: Tree<T, D>) -> Self {
+        FrozenTree::new(tree)
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Turns the tree into an immutable [`FrozenTree`]. See [`FrozenTree::new`].
+    pub fn freeze(self) -> FrozenTree<T, D> {
+        FrozenTree::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_queries_match_tree() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..60 {
+            tree.add_child((index - 1) / (1 + index % 3), index);
+        }
+        let detached = tree.add_node(60);
+        tree.add_child(detached, 61);
+        let frozen = FrozenTree::from(tree.clone());
+        let ancestors = tree.build_ancestor_index();
+
+        for a in 0..tree.len() {
+            assert_eq!(frozen.depth(a), ancestors.depth(a));
+            assert_eq!(frozen.subtree_size(a), tree.reachable_from(a).count());
+            for b in 0..tree.len() {
+                assert_eq!(frozen.is_ancestor(a, b), ancestors.lca(a, b) == Some(a));
+            }
+        }
+        assert_eq!(frozen.euler_tour(), tree.euler_tour().as_slice());
+        assert_eq!(frozen.into_inner().len(), 62);
+    }
+}
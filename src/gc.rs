@@ -0,0 +1,175 @@
+use crate::trace::Operation;
+use crate::{Tree, TreeStorage};
+use std::mem;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns an iterator over the indices of all nodes reachable from `root` by following
+    /// child links, including `root` itself, in depth-first pre-order.
+    ///
+    /// # Panics
+    /// The iterator panics if `root` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    /// let grandchild = tree.add_child(child, "grandchild");
+    /// let _detached = tree.add_node("detached");
+    ///
+    /// let reachable: Vec<usize> = tree.reachable_from(root).collect();
+    /// assert_eq!(reachable, vec![root, child, grandchild]);
+    /// ```
+    pub fn reachable_from(&self, root: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![root];
+        let mut visited = 0;
+        std::iter::from_fn(move || {
+            let index = stack.pop()?;
+            if cfg!(feature = "checked") {
+                visited += 1;
+                self.check_visit_count(visited);
+            }
+            stack.extend(self.links[index].children.iter().rev());
+            Some(index)
+        })
+    }
+
+    /// Removes every node that is not reachable from one of `roots`, compacting the tree.
+    ///
+    /// The relative order of the remaining nodes is preserved, but their indices change. The
+    /// returned vector maps each old index to its new index, or to `None` if the node was
+    /// removed. A kept root whose parent was removed becomes a root itself.
+    ///
+    /// # Parameters
+    /// - `roots`: The indices of the nodes whose subtrees should be kept.
+    ///
+    /// # Returns
+    /// A mapping from old node indices to new node indices.
+    ///
+    /// # Panics
+    /// This method panics if any of the `roots` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let garbage = tree.add_node("garbage");
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    ///
+    /// let remap = tree.garbage_collect([root]);
+    /// assert_eq!(remap, vec![None, Some(0), Some(1)]);
+    /// assert_eq!(tree.len(), 2);
+    /// assert_eq!(tree.get(0), Some(&"root"));
+    /// assert_eq!(tree.children(0), &[1]);
+    /// ```
+    pub fn garbage_collect(
+        &mut self,
+        roots: impl IntoIterator<Item = usize>,
+    ) -> Vec<Option<usize>> {
+        let mut keep = vec![false; self.links.len()];
+        for root in roots {
+            if keep[root] {
+                continue;
+            }
+            let reachable: Vec<usize> = self.reachable_from(root).collect();
+            for index in reachable {
+                keep[index] = true;
+            }
+        }
+        self.compact(&keep)
+    }
+
+    /// Removes all nodes whose `keep` flag is `false`, preserving the order of the remaining
+    /// ones and rewriting links to the new indices. Links to removed nodes are dropped.
+    ///
+    /// Returns a mapping from old indices to new ones.
+    pub(crate) fn compact(&mut self, keep: &[bool]) -> Vec<Option<usize>> {
+        let operation = Operation::start("compact", self.links.len());
+        if let Some(first_removed) = keep.iter().position(|&kept| !kept) {
+            self.history.changed_from(first_removed);
+        }
+        let mut remap = Vec::with_capacity(keep.len());
+        let mut next = 0;
+        for &kept in keep {
+            if kept {
+                remap.push(Some(next));
+                next += 1;
+            } else {
+                remap.push(None);
+            }
+        }
+
+        // Move the kept data to the front in place, since the storage cannot be rebuilt.
+        for (index, new_index) in remap.iter().enumerate() {
+            if let Some(new_index) = *new_index {
+                self.data.swap(new_index, index);
+                self.ext.swap(new_index, index);
+            }
+        }
+        self.data.truncate(next);
+        self.ext.truncate(next);
+
+        let old_links = mem::replace(&mut self.links, Vec::with_capacity(next));
+        for (mut links, &kept) in old_links.into_iter().zip(keep) {
+            if !kept {
+                continue;
+            }
+            links.replace_parent(links.parent().and_then(|parent| remap[parent]));
+            links.children.retain_mut(|child| match remap[*child] {
+                Some(new_index) => {
+                    *child = new_index;
+                    true
+                }
+                None => false,
+            });
+            self.links.push(links);
+        }
+
+        operation.finish(next);
+        remap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_garbage_collect_after_dedup() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        tree.add_child(a, 2);
+        let b = tree.add_child(root, 1);
+        tree.add_child(b, 2);
+        let c = tree.add_child(root, 3);
+
+        tree.dedup_children(root);
+        let remap = tree.garbage_collect([root]);
+
+        assert_eq!(remap, vec![Some(0), Some(1), Some(2), None, None, Some(3)]);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.children(0), &[1, 3]);
+        assert_eq!(tree.children(1), &[2]);
+        assert_eq!(tree.parent_index_unchecked(3), Some(0));
+        assert_eq!(tree.get(remap[c].unwrap()), Some(&3));
+    }
+
+    #[test]
+    fn test_garbage_collect_inner_root() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let child = tree.add_child(root, "child");
+        tree.add_child(child, "grandchild");
+
+        let remap = tree.garbage_collect([child]);
+        assert_eq!(remap, vec![None, Some(0), Some(1)]);
+        assert_eq!(tree.parent_index_unchecked(0), None);
+        assert_eq!(tree.parent_index_unchecked(1), Some(0));
+        assert_eq!(tree.reachable_from(0).collect::<Vec<_>>(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Tree, TreeStorage};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Groups the indices of all nodes by a key, in a single pass.
+    ///
+    /// # Parameters
+    /// - `key_fn`: Returns the key of a node, given its index and data.
+    ///
+    /// # Returns
+    /// The indices of the nodes with each key, in increasing order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("src");
+    /// let lib = tree.add_child(root, "lib.rs");
+    /// let docs = tree.add_child(root, "docs");
+    /// let readme = tree.add_child(docs, "README.md");
+    ///
+    /// let groups = tree.group_by(|_, name| name.rsplit_once('.').map(|(_, ext)| ext));
+    /// assert_eq!(groups[&Some("rs")], vec![lib]);
+    /// assert_eq!(groups[&Some("md")], vec![readme]);
+    /// assert_eq!(groups[&None], vec![root, docs]);
+    /// ```
+    pub fn group_by<K: Hash + Eq>(
+        &self,
+        mut key_fn: impl FnMut(usize, &T) -> K,
+    ) -> HashMap<K, Vec<usize>> {
+        let mut groups: HashMap<K, Vec<usize>> = HashMap::new();
+        for (index, data) in self.data.iter().enumerate() {
+            groups.entry(key_fn(index, data)).or_default().push(index);
+        }
+        groups
+    }
+
+    /// Groups the nodes by a key like [`Tree::group_by`], extracting the nodes of each group
+    /// into a forest that preserves their relative structure.
+    ///
+    /// In the forest of a key, the parent of a node is its nearest ancestor with the same key;
+    /// a node without such an ancestor has no parent. The nodes keep their relative index
+    /// order, so the `i`-th node of a forest is the `i`-th index returned by
+    /// [`Tree::group_by`] for that key, and the children of a node are ordered as they are met
+    /// in a depth-first traversal of this tree.
+    ///
+    /// This takes a single traversal of the tree, whatever the number of keys.
+    ///
+    /// # Parameters
+    /// - `key_fn`: Returns the key of a node, given its index and data.
+    ///
+    /// # Returns
+    /// A forest for each key, borrowing the data of this tree.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(("body", "layout"));
+    /// let nav = tree.add_child(root, ("nav", "menu"));
+    /// let main = tree.add_child(root, ("main", "layout"));
+    /// tree.add_child(nav, ("link", "menu"));
+    /// tree.add_child(main, ("aside", "layout"));
+    ///
+    /// let forests = tree.group_forests(|_, (_, facet)| *facet);
+    /// let layout = &forests["layout"];
+    /// assert_eq!(layout.len(), 3);
+    /// assert_eq!(layout.children(0), &[1]);
+    /// assert_eq!(layout.children(1), &[2]);
+    /// let menu = &forests["menu"];
+    /// assert_eq!(menu.get(1), Some(&&("link", "menu")));
+    /// assert_eq!(menu.parent_index_unchecked(1), Some(0));
+    /// ```
+    pub fn group_forests<K: Hash + Eq>(
+        &self,
+        mut key_fn: impl FnMut(usize, &T) -> K,
+    ) -> HashMap<K, Tree<&T>> {
+        let mut groups = HashMap::new();
+        let mut forests: Vec<Tree<&T>> = Vec::new();
+        // The group of every node and its index in the forest of that group.
+        let mut remap = Vec::with_capacity(self.links.len());
+        for (index, data) in self.data.iter().enumerate() {
+            let group = *groups.entry(key_fn(index, data)).or_insert_with(|| {
+                forests.push(Tree::new());
+                forests.len() - 1
+            });
+            remap.push((group, forests[group].add_node(data)));
+        }
+
+        // The nearest ancestor of the current node in each group, restored when leaving it.
+        let mut nearest: Vec<Option<usize>> = vec![None; forests.len()];
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        let mut stack = Vec::new();
+        for root in roots {
+            stack.push((root, None));
+            while let Some((index, restore)) = stack.pop() {
+                let (group, node) = remap[index];
+                if let Some(previous) = restore {
+                    nearest[group] = previous;
+                    continue;
+                }
+                if let Some(parent) = nearest[group] {
+                    forests[group].link(parent, node);
+                }
+                stack.push((index, Some(nearest[group])));
+                nearest[group] = Some(node);
+                stack.extend(
+                    self.links[index]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, None)),
+                );
+            }
+        }
+
+        let mut forests: Vec<Option<Tree<&T>>> = forests.into_iter().map(Some).collect();
+        groups
+            .into_iter()
+            .map(|(key, group)| (key, forests[group].take().unwrap()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::assert_links_consistent;
+
+    #[test]
+    fn test_group_forests_match_group_by() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..30 {
+            tree.add_child((index - 1) / 2, index);
+        }
+        tree.add_node(30);
+
+        let groups = tree.group_by(|_, value| value % 3);
+        let forests = tree.group_forests(|_, value| value % 3);
+        assert_eq!(forests.len(), 3);
+        for (key, forest) in &forests {
+            assert_links_consistent(forest);
+            let values: Vec<usize> = forest.iter().map(|(_, value)| **value).collect();
+            assert_eq!(&values, &groups[key]);
+            for (index, value) in forest.iter() {
+                // The parent in the forest is the nearest ancestor with the same key.
+                let mut ancestor = tree.parent_index_unchecked(**value);
+                while ancestor.is_some_and(|ancestor| ancestor % 3 != *key) {
+                    ancestor = tree.parent_index_unchecked(ancestor.unwrap());
+                }
+                let parent = forest.parent_index_unchecked(index);
+                assert_eq!(parent.map(|parent| *forest[parent]), ancestor);
+            }
+        }
+    }
+}
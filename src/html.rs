@@ -0,0 +1,188 @@
+use crate::Tree;
+use scraper::{Html, Node};
+
+/// The data of a node imported from an HTML document with [`Tree::from_html`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DomNode {
+    /// The root of a document or fragment.
+    Document,
+    /// A `<!DOCTYPE>` declaration.
+    Doctype {
+        /// The name of the doctype, such as `"html"`.
+        name: String,
+    },
+    /// An element.
+    Element {
+        /// The local name of the element, in lowercase, such as `"div"`.
+        name: String,
+        /// The attributes of the element, as `(name, value)` pairs sorted by name.
+        attributes: Vec<(String, String)>,
+    },
+    /// A text node, with entities decoded and whitespace kept.
+    Text(String),
+    /// A comment.
+    Comment(String),
+}
+
+impl DomNode {
+    /// Returns the name of the element, or `None` if the node is not an element.
+    pub fn element_name(&self) -> Option<&str> {
+        match self {
+            DomNode::Element { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an attribute of the element, or `None` if the node is not an
+    /// element or has no such attribute.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        match self {
+            DomNode::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(attribute, _)| attribute == name)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of a text node, or `None` for other nodes.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            DomNode::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl Tree<DomNode> {
+    /// Parses an HTML document with html5ever, as browsers do, and imports its DOM.
+    ///
+    /// Parsing never fails: invalid markup is recovered from, and missing `<html>`, `<head>`
+    /// and `<body>` elements are added.
+    ///
+    /// # Parameters
+    /// - `html`: The HTML document.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{DomNode, Tree};
+    ///
+    /// let dom = Tree::from_html(r#"<ul><li><a href="/a">A</a><li><a href="/b">B</a></ul>"#);
+    /// let links: Vec<_> = dom
+    ///     .iter()
+    ///     .filter_map(|(_, node)| node.attribute("href"))
+    ///     .collect();
+    /// assert_eq!(links, ["/a", "/b"]);
+    /// assert_eq!(dom[0], DomNode::Document);
+    /// ```
+    pub fn from_html(html: &str) -> Self {
+        Tree::from_scraper(&Html::parse_document(html))
+    }
+
+    /// Parses an HTML fragment, such as the inner HTML of an element, and imports its DOM.
+    ///
+    /// As with html5ever, the nodes of the fragment are wrapped in an `<html>` element below the
+    /// root.
+    ///
+    /// # Parameters
+    /// - `html`: The HTML fragment.
+    pub fn from_html_fragment(html: &str) -> Self {
+        Tree::from_scraper(&Html::parse_fragment(html))
+    }
+
+    /// Imports the DOM of a document already parsed with [`scraper`].
+    ///
+    /// The root of the document becomes node `0` and its descendants follow in depth-first
+    /// order, with children in document order. Processing instructions are skipped.
+    ///
+    /// # Parameters
+    /// - `html`: The parsed document or fragment.
+    pub fn from_scraper(html: &Html) -> Self {
+        let mut tree = Tree::new();
+        let mut stack = vec![(html.tree.root(), None)];
+        while let Some((node, parent)) = stack.pop() {
+            let data = match node.value() {
+                Node::Document | Node::Fragment => DomNode::Document,
+                Node::Doctype(doctype) => DomNode::Doctype {
+                    name: doctype.name().to_string(),
+                },
+                Node::Element(element) => DomNode::Element {
+                    name: element.name().to_string(),
+                    attributes: element
+                        .attrs()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                },
+                Node::Text(text) => DomNode::Text(text.to_string()),
+                Node::Comment(comment) => DomNode::Comment(comment.to_string()),
+                Node::ProcessingInstruction(_) => continue,
+            };
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, data),
+                None => tree.add_node(data),
+            };
+            stack.extend(node.children().rev().map(|child| (child, Some(index))));
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_html_builds_document_tree() {
+        let dom = Tree::from_html(
+            "<!DOCTYPE html><title>T</title><p class=x id=main>Hi &amp; <!-- note --><b>bye</b>",
+        );
+        let outline: Vec<String> = dom
+            .iter()
+            .map(|(_, node)| match node {
+                DomNode::Element { name, .. } => name.clone(),
+                DomNode::Text(text) => format!("{text:?}"),
+                other => format!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            [
+                "Document",
+                "Doctype { name: \"html\" }",
+                "html",
+                "head",
+                "title",
+                "\"T\"",
+                "body",
+                "p",
+                "\"Hi & \"",
+                "Comment(\" note \")",
+                "b",
+                "\"bye\"",
+            ]
+        );
+        assert_eq!(
+            dom[7],
+            DomNode::Element {
+                name: "p".to_string(),
+                attributes: vec![
+                    ("class".to_string(), "x".to_string()),
+                    ("id".to_string(), "main".to_string()),
+                ],
+            }
+        );
+        assert_eq!(dom.children(7), &[8, 9, 10]);
+        assert_eq!(dom[11].text(), Some("bye"));
+    }
+
+    #[test]
+    fn test_from_html_fragment_wraps_in_html_element() {
+        let dom = Tree::from_html_fragment("<td>1</td>text");
+        assert_eq!(dom[0], DomNode::Document);
+        assert_eq!(dom[1].element_name(), Some("html"));
+        assert_eq!(dom.children(1).len(), 1);
+        assert_eq!(dom[2].text(), Some("1text"));
+        assert_eq!(dom[2].attribute("id"), None);
+    }
+}
@@ -0,0 +1,53 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+/// How the data of a node is presented by exporters and pretty printers, such as
+/// [`Tree::path_string`](crate::Tree::path_string) and
+/// [`testing::snapshot`](crate::testing::snapshot).
+///
+/// Every type implementing [`Display`] implements `NodeLabel` with its displayed text as the
+/// label and no attributes. Other types can implement it to control both.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{NodeLabel, Tree};
+/// use std::borrow::Cow;
+///
+/// struct File {
+///     name: &'static str,
+///     size: u64,
+/// }
+///
+/// impl NodeLabel for File {
+///     fn label(&self) -> Cow<'_, str> {
+///         Cow::Borrowed(self.name)
+///     }
+///
+///     fn attrs(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+///         vec![("size".into(), self.size.to_string().into())]
+///     }
+/// }
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node(File { name: "src", size: 0 });
+/// let lib = tree.add_child(root, File { name: "lib.rs", size: 1024 });
+/// assert_eq!(tree.path_string(lib, "/"), "src/lib.rs");
+/// ```
+pub trait NodeLabel {
+    /// Returns the text that represents the node.
+    fn label(&self) -> Cow<'_, str>;
+
+    /// Returns additional named values describing the node, in a stable order. Exporters that
+    /// support attributes, such as the styles of a graph, include them next to the label.
+    ///
+    /// Returns no attributes by default.
+    fn attrs(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        Vec::new()
+    }
+}
+
+impl<T: Display + ?Sized> NodeLabel for T {
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Owned(self.to_string())
+    }
+}
@@ -0,0 +1,171 @@
+use crate::Tree;
+use std::ops::Deref;
+
+/// A tree whose children are produced on demand.
+///
+/// The children of a node are created by a user-provided closure the first time they are
+/// requested through [`LazyTree::children`] or [`LazyTree::expand`], and are then stored in an
+/// ordinary [`Tree`]. This allows working with trees that are too large, or even infinite, to
+/// be built up front, such as game trees or remote hierarchies.
+///
+/// `LazyTree` dereferences to the materialized [`Tree`], so the whole read-only API, including
+/// traversal and queries, can be used on the nodes that have been created so far.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::LazyTree;
+///
+/// // Every number n has the children 2n and 2n + 1.
+/// let mut tree = LazyTree::new(1u64, |&n| vec![2 * n, 2 * n + 1]);
+/// assert_eq!(tree.len(), 1);
+///
+/// let children = tree.children(0).to_vec();
+/// assert_eq!(children.iter().map(|&c| tree[c]).collect::<Vec<_>>(), vec![2, 3]);
+///
+/// tree.expand_to_depth(3);
+/// assert_eq!(tree.len(), 15);
+/// assert_eq!(tree.iter_dfs().last(), Some((14, &15)));
+/// ```
+pub struct LazyTree<T, F = Box<dyn FnMut(&T) -> Vec<T>>> {
+    tree: Tree<T>,
+    expanded: Vec<bool>,
+    expand: F,
+}
+
+impl<T, F: FnMut(&T) -> Vec<T>> LazyTree<T, F> {
+    /// Creates a lazy tree with a single root node.
+    ///
+    /// # Parameters
+    /// - `root`: The data of the root node.
+    /// - `expand`: Produces the data of the children of a node from the node's data.
+    pub fn new(root: T, expand: F) -> Self {
+        let mut tree = Tree::new();
+        tree.add_node(root);
+        Self {
+            tree,
+            expanded: vec![false],
+            expand,
+        }
+    }
+
+    /// Returns `true` if the children of the node have already been produced.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn is_expanded(&self, index: usize) -> bool {
+        self.expanded[index]
+    }
+
+    /// Produces the children of a node, if that has not happened yet.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn expand(&mut self, index: usize) {
+        if self.expanded[index] {
+            return;
+        }
+        self.expanded[index] = true;
+        let children = (self.expand)(self.tree.get_unchecked(index));
+        for child in children {
+            self.tree.add_child(index, child);
+            self.expanded.push(false);
+        }
+    }
+
+    /// Returns a slice of the indices of the children of a node, producing them first if
+    /// needed.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn children(&mut self, index: usize) -> &[usize] {
+        self.expand(index);
+        self.tree.children(index)
+    }
+
+    /// Expands all nodes of the subtree of `index` up to `depth` levels below it.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn expand_subtree(&mut self, index: usize, depth: usize) {
+        let mut stack = vec![(index, 0)];
+        while let Some((node, level)) = stack.pop() {
+            if level == depth {
+                continue;
+            }
+            self.expand(node);
+            stack.extend(
+                self.tree
+                    .children(node)
+                    .iter()
+                    .rev()
+                    .map(|&c| (c, level + 1)),
+            );
+        }
+    }
+
+    /// Expands all nodes from the root down to `depth`, so that every node at a depth of at
+    /// most `depth` exists, the root being at depth `0`.
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        self.expand_subtree(0, depth);
+    }
+
+    /// Retrieves a mutable reference to the data stored in a node.
+    ///
+    /// Changing the data of a node does not affect children that were already produced.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.tree.get_mut(index)
+    }
+
+    /// Returns the tree of the nodes that have been produced so far, dropping the closure.
+    pub fn into_tree(self) -> Tree<T> {
+        self.tree
+    }
+}
+
+impl<T, F> Deref for LazyTree<T, F> {
+    type Target = Tree<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_tree_expands_once() {
+        let mut calls = 0;
+        let mut tree = LazyTree::new(String::from("a"), |data: &String| {
+            calls += 1;
+            if data.len() < 3 {
+                vec![format!("{data}a"), format!("{data}b")]
+            } else {
+                vec![]
+            }
+        });
+
+        assert!(!tree.is_expanded(0));
+        assert_eq!(tree.children(0), &[1, 2]);
+        assert_eq!(tree.children(0), &[1, 2]);
+        assert!(tree.is_expanded(0));
+        assert!(!tree.is_expanded(1));
+
+        tree.expand_to_depth(10);
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.query("/a/ab/abb").unwrap(), vec![6]);
+        drop(tree);
+        assert_eq!(calls, 7);
+    }
+
+    #[test]
+    fn test_lazy_tree_boxed() {
+        let mut tree: LazyTree<u32> = LazyTree::new(0, Box::new(|n: &u32| vec![n + 1]));
+        tree.expand_subtree(0, 5);
+        *tree.get_mut(5).unwrap() = 50;
+        let tree = tree.into_tree();
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.get(5), Some(&50));
+    }
+}
@@ -10,6 +10,22 @@
 //! - **Depth-first traversal**: Recursively traverse the tree with callbacks before and after processing subtrees.
 //! - **Flexible node access**: Access parent-child relationships and modify node data.
 //! - **Optional parallel iteration**: Speed up iteration with [rayon](https://docs.rs/rayon) when enabled.
+//! - **Optional egui widget**: Render trees as collapsing headers with the `egui` feature.
+//! - **Optional ratatui widget**: Browse trees in the terminal with the `ratatui` feature.
+//! - **Optional Bevy hierarchies**: Spawn trees as entity hierarchies and capture them back with the `bevy` feature.
+//! - **Optional pretty printing**: Print trees with [ptree](https://docs.rs/ptree) with the `ptree` feature.
+//! - **Optional link checks**: Catch duplicate children, self-parenting and cycles early with the `checked` feature.
+//! - **Optional memory-mapped trees**: Open huge trees instantly from a mapped file with the `zerocopy` feature.
+//! - **Optional serialization**: Serialize trees with [serde](https://docs.rs/serde) with the `serde` feature, and to versioned bincode, CBOR or MessagePack buffers with the `bincode`, `cbor` and `msgpack` features.
+//! - **Optional C interface**: Use trees from C and C++ through an opaque handle and a generated header with the `ffi` feature.
+//! - **Optional Python bindings**: Expose trees to Python with [PyO3](https://pyo3.rs), including a traversal generator, with the `python` feature.
+//! - **WebAssembly support**: Builds for `wasm32-unknown-unknown` without the `rayon` feature, and exposes a `JsTree` to JavaScript with the `wasm-bindgen` feature.
+//! - **Optional tree-sitter import**: Convert [tree-sitter](https://docs.rs/tree-sitter) syntax trees with the `tree-sitter` feature.
+//! - **Optional HTML import**: Parse HTML into a DOM tree with [scraper](https://docs.rs/scraper) and html5ever with the `html` feature.
+//! - **Optional directory scanning**: Model the file system as a tree, optionally in parallel with `rayon`, with the `fs` feature.
+//! - **Optional Cargo dependency trees**: Build the dependency tree of a workspace from `cargo metadata` with the `cargo` feature.
+//! - **Optional CSV import**: Build trees from id/parent id tables with the `csv` feature.
+//! - **Optional instrumentation**: Report node counts and timings of traversals, compaction, deduplication and parallel passes to [tracing](https://docs.rs/tracing) with the `tracing` feature.
 //!
 //! ## Use Cases
 //!
@@ -112,14 +128,96 @@
 //! fn main() {}
 //! ```
 //!
+//! ## Thread Safety
+//!
+//! `Tree<T>` is `Send` and `Sync` whenever `T` is, and so are `FrozenTree`, `SubtreeRef`,
+//! `CowTree`, `TreeStructure`, `NodeMask`, `AncestorIndex` and `TraversalPlan`. `Tree<T>` and
+//! `FrozenTree<T>` are also `UnwindSafe` and `RefUnwindSafe` whenever `T` is. These guarantees
+//! are checked at compile time. Every read-only method takes `&self` and no type of the crate
+//! uses interior mutability, so any number of threads can read the same tree at once, through
+//! scoped threads borrowing `&Tree` or an `Arc<FrozenTree>`, without locking. Changing a tree
+//! takes `&mut self`, so the borrow checker rules out writes during reads.
+//! `TreeZipper` shares nodes with `Rc` and stays on one thread.
+//!
+//! ```rust
+//! use easy_tree::Tree;
+//! use std::thread;
+//!
+//! let mut tree = Tree::new();
+//! let root = tree.add_node(1);
+//! tree.add_child(root, 2);
+//! tree.add_child(root, 3);
+//!
+//! let tree = &tree;
+//! let sums: Vec<i32> = thread::scope(|scope| {
+//!     let readers: Vec<_> = (0..4)
+//!         .map(|_| scope.spawn(|| tree.iter().map(|(_, value)| value).sum()))
+//!         .collect();
+//!     readers.into_iter().map(|reader| reader.join().unwrap()).collect()
+//! });
+//! assert_eq!(sums, [6, 6, 6, 6]);
+//! ```
+//!
 //! ## API Overview
 //!
 //! - `Tree<T>`: Represents the tree structure containing nodes of type `T`.
-//! - `Node<T>`: Represents a single node in the tree.
+//! - `Node<T>`: Represents a single, standalone node.
 //! - `Tree::add_node(data: T) -> usize`: Adds a new root node.
 //! - `Tree::add_child(parent: usize, data: T) -> usize`: Adds a child node to a parent.
+//! - `Tree::root` / `Tree::root_data`: Returns the first node without a parent.
+//! - `Tree::children_data` / `Tree::children_data_mut`: Iterates over the children of a node along with their data.
+//! - `Tree::parent` / `Tree::parent_mut`: Returns the parent of a node along with its data.
+//! - `Tree::sibling_pairs` / `Tree::children_windows`: Iterates over neighboring children of a node.
+//! - `Tree::traverse_budgeted` / `Tree::resume_traversal`: Spreads a traversal over several steps, visiting up to a budget of nodes in each.
 //! - `Tree::traverse`: Walks the tree recursively with customizable callbacks.
+//! - `Tree::compile_traversal`: Records the traversal order once to replay it cheaply.
+//! - `Tree::into_iter_dfs` / `Tree::into_traverse`: Consumes the tree, yielding the owned data of its nodes in structural order.
+//! - `Tree::traverse_forest`: Walks every root's subtree, so that no node is skipped.
+//! - `Tree::find_with_path`: Finds the first matching node along with its path from the root.
+//! - `Tree::iter_dfs_resumable` / `Tree::resume_dfs`: Iterates depth-first with a position, `DfsCursor`, that can be saved and resumed.
 //! - `Tree::iter` / `Tree::iter_mut`: Provides immutable and mutable iterators over the nodes.
+//! - `Tree::split`: Borrows the structure and the data of the tree independently.
+//! - `Tree::partition`: Splits the tree into the nodes that match a predicate and the rest.
+//! - `Tree::group_by` / `Tree::group_forests`: Groups nodes by a key, optionally into per-key forests.
+//! - `Tree::drain_subtree`: Removes a subtree, yielding the owned data of its nodes one at a time.
+//! - `Tree::transplant_into`: Moves a subtree into another tree without cloning, transforming its data.
+//! - `Tree::node_id` / `Tree::resolve`: Tags node indices with their tree, so that using them on another tree, including a clone, is an error. `tree[id]` panics on a foreign id.
+//! - `Tree::induced_subtree`: Extracts nodes with the minimal set of ancestors connecting them, such as search results in context.
+//! - `Tree::with_ancestors`: Expands a set of nodes to include all their ancestors, as a `NodeMask`.
+//! - `Tree::summarize`: Keeps the most important nodes and collapses the rest into placeholders.
+//! - `Tree::get_dyn` / `Tree::iter_dyn`: Downcasts the nodes of a tree of trait objects whose trait extends `AsAny`.
+//! - `Tree::insert_ext` / `Tree::get_ext`: Attaches typed extension values to nodes, one column per type, for data computed by separate passes.
+//! - `Tree::par_iter_subtree` / `Tree::par_leaves`: Iterates over a branch or the leaves in parallel, with the `rayon` feature.
+//! - `Tree::par_traverse_ordered`: Traverses in parallel, combining child results left to right for output that is identical across thread counts.
+//! - `Tree::with_pool` / `Tree::with_pool_mut`: Runs the parallel operations on a given rayon thread pool instead of the global one.
+//! - `Tree::data` / `Tree::data_mut`: Exposes the data of all nodes as one contiguous slice.
+//! - `Tree::checkpoint` / `Tree::rollback_to`: Cheaply undoes the nodes added since a checkpoint.
+//! - `Tree::freeze`: Makes the tree immutable, with precomputed depths and Euler tour.
+//! - `Tree::fork`: Creates a copy-on-write fork for speculative changes.
+//! - `Tree::with_storage`: Keeps the data of the nodes in a custom `TreeStorage`, such as an arena.
+//! - `Tree::canonicalize`: Sorts children recursively and renumbers nodes, so equivalent trees become identical.
+//! - `Tree::align_by_key`: Pairs up the nodes of two trees by key rather than position, to reconcile trees whose children are ordered differently.
+//! - `Tree::stats`: Reports height, leaf count, branching factors and width per level.
+//! - `Tree::rollup`: Aggregates every subtree in one post-order pass.
+//! - `Tree::parent_shares`: Computes the share of every subtree in its parent's total, as needed for treemaps.
+//! - `Tree::treemap`: Lays out nested rectangles for a squarified treemap.
+//! - `Tree::to_markdown_list` / `Tree::to_markdown_list_with` / `Tree::from_markdown_list`: Exports and imports nested Markdown lists.
+//! - `Tree::to_materialized_paths` / `Tree::to_nested_set` / `Tree::to_closure_table`: Encode the tree for relational databases; paths and nested sets can be imported back.
+//! - `Tree::to_bytes_cbor` / `Tree::from_bytes_cbor`: Serialize to a documented, versioned schema, also in bincode and MessagePack.
+//! - `Tree::write_snapshot` / `Tree::deserialize_with_migration`: Keep old snapshots loading as node data evolves, through migration hooks, and detect corruption with optional checksums.
+//! - `SnapshotLog`: Saves append-mostly trees incrementally, appending only new nodes, with compaction.
+//! - `JsTree`: A tree of JavaScript values with callback traversal, for browser front ends (`wasm-bindgen` feature).
+//! - `Tree::spawn_hierarchy` / `Tree::from_entity_hierarchy`: Convert between trees and Bevy `ChildOf`/`Children` hierarchies (`bevy` feature).
+//! - `PyTree`: A tree of Python objects with list children and a traversal generator, exported as `easy_tree.Tree` (`python` feature).
+//! - `ffi::easy_tree_new` and friends: A C interface with callback traversal, declared in `include/easy_tree.h` (`ffi` feature).
+//! - `Tree::from_syntax_tree`: Imports a tree-sitter syntax tree as `SyntaxInfo` nodes with kinds, byte ranges and leaf text (`tree-sitter` feature).
+//! - `Tree::from_html` / `Tree::from_scraper`: Imports an HTML document as `DomNode` elements, text and comments (`html` feature).
+//! - `Tree::from_dir`: Scans a directory into `DirEntryInfo` nodes, with symbolic link, depth and filter options (`fs` feature).
+//! - `Tree::from_cargo_metadata` / `Tree::from_cargo_manifest`: Builds the dependency tree of a Cargo workspace, expanding, marking or skipping repeated packages (`cargo` feature).
+//! - `Tree::from_csv`: Imports a tree from CSV rows with id and parent id columns (`csv` feature).
+//! - `NodeLabel`: How exporters and pretty printers present node data, implemented for every `Display` type.
+//! - `testing::snapshot` / `assert_tree_eq!`: Renders trees into a canonical text form for snapshot tests.
+//! - `testing::check_tree_invariants` / `testing::check_model_invariants`: Applies random operations and checks a tree, or a `TreeModel` such as a wrapper, against a reference model.
 //!
 //! ## Contributing
 //! Contributions are welcome! For more details, see the [GitHub repository](https://github.com/antouhou/easy-tree).
@@ -127,10 +225,185 @@
 //! ## License
 //! This project is licensed under the MIT License. See [LICENSE](https://github.com/antouhou/easy-tree/blob/main/LICENSE) for details.
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub mod aggregate;
+mod align;
+mod ancestors;
+mod balance;
+#[cfg(feature = "bevy")]
+mod bevy;
+mod budgeted;
+mod build;
+#[cfg(feature = "cargo")]
+mod cargo_deps;
+mod checkpoint;
+mod cow;
+#[cfg(feature = "csv")]
+mod csv_import;
+mod dedup;
+mod dfs_cursor;
+#[cfg(feature = "fs")]
+mod dir;
+mod drain;
+mod dyn_node;
+#[cfg(feature = "egui")]
+mod egui_widget;
+mod entry;
+mod error;
+mod euler;
+mod ext;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod frozen;
+mod gc;
+mod group;
+#[cfg(feature = "html")]
+mod html;
+mod label;
+mod lazy;
+mod link;
+mod map;
+mod mask;
+pub mod mcts;
+#[cfg(feature = "zerocopy")]
+mod mmap;
+mod node_id;
+mod outline;
+#[cfg(feature = "rayon")]
+mod par;
+mod partition;
+mod path;
+mod pattern;
+mod plan;
+#[cfg(feature = "rayon")]
+mod pool;
+#[cfg(feature = "ptree")]
+mod ptree_item;
+#[cfg(feature = "python")]
+mod python;
+pub mod query;
+#[cfg(feature = "ratatui")]
+mod ratatui_widget;
+mod relational;
+mod rewrite;
+mod search;
+mod select;
+mod selection;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "msgpack"))]
+mod snapshot;
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "msgpack"))]
+mod snapshot_log;
+mod sorted;
+mod split;
+mod stats;
+mod storage;
+mod subtree;
+mod summary;
+#[cfg(feature = "tree-sitter")]
+mod syntax;
+pub mod testing;
+mod thread_safety;
+mod trace;
+mod treemap;
+mod visible;
+#[cfg(feature = "wasm-bindgen")]
+mod wasm;
+mod zipper;
+
+pub use ancestors::AncestorIndex;
+pub use budgeted::TraversalResume;
+pub use build::NestedNode;
+#[cfg(feature = "cargo")]
+pub use cargo_deps::{DuplicatePolicy, PackageInfo};
+pub use checkpoint::Checkpoint;
+pub use cow::{CowTree, TreeChanges};
+#[cfg(feature = "csv")]
+pub use csv_import::{CsvError, CsvRow};
+pub use dfs_cursor::{DfsCursor, ResumableDfs};
+#[cfg(feature = "fs")]
+pub use dir::{DirEntryInfo, DirOptions};
+pub use dyn_node::AsAny;
+pub use entry::ChildEntry;
+pub use error::TreeError;
+pub use euler::EulerStep;
+pub use frozen::FrozenTree;
+#[cfg(feature = "html")]
+pub use html::DomNode;
+pub use label::NodeLabel;
+pub use lazy::LazyTree;
+pub use mask::NodeMask;
+#[cfg(feature = "zerocopy")]
+pub use mmap::{MmapError, MmapTree};
+pub use node_id::NodeId;
+pub use outline::OutlineError;
+pub use pattern::{Bindings, Pattern};
+pub use plan::TraversalPlan;
+#[cfg(feature = "rayon")]
+pub use pool::{PooledTree, PooledTreeMut};
+#[cfg(feature = "ptree")]
+pub use ptree_item::PtreeItem;
+#[cfg(feature = "python")]
+pub use python::{PyTree, Traversal};
+#[cfg(feature = "ratatui")]
+pub use ratatui_widget::{TreeWidget, TreeWidgetState};
+pub use relational::RelationalError;
+pub use rewrite::{Replacement, RewriteOrder, RewriteReport, RewriteRules, RewriteStrategy};
+pub use select::Selector;
+pub use selection::TreeSelection;
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "msgpack"))]
+pub use snapshot::{
+    Format, Migrations, SnapshotError, SnapshotOptions, CHECKSUM_FLAG, FORMAT_VERSION,
+};
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "msgpack"))]
+pub use snapshot_log::SnapshotLog;
+pub use split::TreeStructure;
+pub use stats::{MemoryReport, TreeStats};
+pub use storage::TreeStorage;
+pub use subtree::SubtreeRef;
+pub use summary::Summary;
+#[cfg(feature = "tree-sitter")]
+pub use syntax::SyntaxInfo;
+pub use treemap::TreemapRect;
+pub use visible::VisibleRows;
+#[cfg(feature = "wasm-bindgen")]
+pub use wasm::JsTree;
+pub use zipper::TreeZipper;
+
+#[cfg(feature = "bevy")]
+pub use bevy_ecs;
+#[cfg(feature = "cargo")]
+pub use cargo_metadata;
+use checkpoint::History;
+#[cfg(feature = "csv")]
+pub use csv;
+#[cfg(feature = "egui")]
+pub use egui;
+use ext::Extensions;
+use node_id::TreeNonce;
+#[cfg(feature = "ptree")]
+pub use ptree;
+#[cfg(feature = "ratatui")]
+pub use ratatui;
 #[cfg(feature = "rayon")]
 pub use rayon;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
+#[cfg(feature = "html")]
+pub use scraper;
+#[cfg(feature = "serde")]
+pub use serde;
+use std::marker::PhantomData;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::ops::{Index, IndexMut};
+use trace::Operation;
+#[cfg(feature = "tree-sitter")]
+pub use tree_sitter;
+#[cfg(feature = "zerocopy")]
+pub use zerocopy;
 
 /// Represents a single node in a tree structure.
 ///
@@ -142,6 +415,9 @@ use rayon::prelude::*;
 /// Normally, you should use the `Tree::add_node` and
 /// `Tree::add_child` methods to create nodes and add them to the tree. There's no need to
 /// address `Node` directly in most cases.
+///
+/// `Tree` itself stores the data and the links of its nodes in separate columns, so that they
+/// can be borrowed independently (see [`Tree::split`]), and does not use this type.
 #[derive(Clone)]
 pub struct Node<T> {
     data: T,
@@ -174,6 +450,68 @@ impl<T> Node<T> {
         }
     }
 
+    /// Returns the data of this node.
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    /// Returns the indices of the children of this node.
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Returns the index of the parent of this node, if it has one.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// The structural part of a node: the indices of its parent and children.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Links {
+    pub(crate) children: Vec<usize>,
+    parent: Option<ParentIndex>,
+}
+
+/// The index of a parent node, stored as its bitwise complement in a `NonZeroUsize`.
+///
+/// This gives `Option<ParentIndex>` a niche, so that it is no larger than a `usize`. The only
+/// index that cannot be stored is `usize::MAX`, which no node can have, since a tree cannot
+/// hold that many links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ParentIndex(NonZeroUsize);
+
+impl ParentIndex {
+    fn new(index: usize) -> Self {
+        ParentIndex(NonZeroUsize::new(!index).expect("node index out of range"))
+    }
+
+    fn get(self) -> usize {
+        !self.0.get()
+    }
+}
+
+impl Links {
+    /// Creates the links of a node with the given parent and no children.
+    pub(crate) fn with_parent(parent: Option<usize>) -> Self {
+        Links {
+            children: Vec::new(),
+            parent: parent.map(ParentIndex::new),
+        }
+    }
+
+    /// Returns the index of the parent of this node, if it has one.
+    pub(crate) fn parent(&self) -> Option<usize> {
+        self.parent.map(ParentIndex::get)
+    }
+
+    /// Sets or clears the parent of this node, returning the previous one.
+    pub(crate) fn replace_parent(&mut self, parent: Option<usize>) -> Option<usize> {
+        let previous = self.parent();
+        self.parent = parent.map(ParentIndex::new);
+        previous
+    }
+
     /// Adds a child to this node.
     ///
     /// # Parameters
@@ -193,7 +531,7 @@ impl<T> Node<T> {
     /// # Internal Use
     /// This method is used internally by the `Tree` struct.
     pub(crate) fn set_parent(&mut self, parent: usize) {
-        self.parent = Some(parent);
+        self.parent = Some(ParentIndex::new(parent));
     }
 }
 
@@ -210,14 +548,82 @@ impl<T> Node<T> {
 /// let root = tree.add_node("root");
 /// let child = tree.add_child(root, "child");
 /// ```
-#[derive(Clone)]
-pub struct Tree<T> {
-    nodes: Vec<Node<T>>,
+///
+/// The data of the nodes is kept in a [`TreeStorage`], which is a `Vec<T>` unless another
+/// storage is passed to [`Tree::with_storage`].
+pub struct Tree<T, D = Vec<T>> {
+    /// The data of every node, by index.
+    data: D,
+    marker: PhantomData<T>,
+    /// The links of every node, by index.
+    links: Vec<Links>,
+    /// The extension values attached to nodes, by type.
+    ext: Extensions,
+    /// Identifies the tree in the ids of its nodes.
+    nonce: TreeNonce,
+    /// The removals and renumberings of nodes, to reject stale checkpoints.
+    history: History,
 }
 
-impl<T> Default for Tree<T> {
+/// A clone is a new tree: it gets its own nonce, so that the [`NodeId`]s of the original are
+/// rejected by the clone, and the other way around.
+impl<T: Clone, D: Clone> Clone for Tree<T, D> {
+    fn clone(&self) -> Self {
+        Tree {
+            data: self.data.clone(),
+            marker: PhantomData,
+            links: self.links.clone(),
+            ext: self.ext.clone(),
+            nonce: TreeNonce::new(),
+            history: self.history.clone(),
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T> + Default> Default for Tree<T, D> {
     fn default() -> Self {
-        Self::new()
+        Self::with_storage(D::default())
+    }
+}
+
+/// Returns a reference to the data stored in a node.
+///
+/// # Panics
+/// Panics if the index is out of bounds. Use [`Tree::get`] for a non-panicking alternative.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::Tree;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// assert_eq!(tree[root], "root");
+/// ```
+impl<T, S: TreeStorage<T>> Index<usize> for Tree<T, S> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+/// Returns a mutable reference to the data stored in a node.
+///
+/// # Panics
+/// Panics if the index is out of bounds. Use [`Tree::get_mut`] for a non-panicking alternative.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::Tree;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node(42);
+/// tree[root] += 1;
+/// assert_eq!(tree[root], 43);
+/// ```
+impl<T, S: TreeStorage<T>> IndexMut<usize> for Tree<T, S> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
     }
 }
 
@@ -234,7 +640,45 @@ impl<T> Tree<T> {
     /// let tree: Tree<i32> = Tree::new();
     /// ```
     pub fn new() -> Self {
-        Self { nodes: Vec::new() }
+        Self::with_storage(Vec::new())
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Creates a new, empty tree that keeps the data of its nodes in the given storage.
+    ///
+    /// # Parameters
+    /// - `storage`: The storage for the data of the nodes. It must be empty.
+    ///
+    /// # Returns
+    /// A `Tree` instance with no nodes.
+    ///
+    /// # Panics
+    /// This method panics if `storage` is not empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::with_storage(Vec::with_capacity(16));
+    /// tree.add_node("root");
+    /// assert_eq!(tree.storage().capacity(), 16);
+    /// ```
+    pub fn with_storage(storage: D) -> Self {
+        assert!(storage.is_empty(), "tree storage must be empty");
+        Self {
+            data: storage,
+            marker: PhantomData,
+            links: Vec::new(),
+            ext: Extensions::default(),
+            nonce: TreeNonce::new(),
+            history: History::default(),
+        }
+    }
+
+    /// Returns the storage holding the data of the nodes.
+    pub fn storage(&self) -> &D {
+        &self.data
     }
 
     /// Adds a new node to the tree.
@@ -255,9 +699,9 @@ impl<T> Tree<T> {
     /// let root = tree.add_node("root");
     /// ```
     pub fn add_node(&mut self, data: T) -> usize {
-        let node = Node::new(data);
-        let index = self.nodes.len();
-        self.nodes.push(node);
+        let index = self.links.len();
+        self.data.push(data);
+        self.links.push(Links::default());
         index
     }
 
@@ -280,29 +724,75 @@ impl<T> Tree<T> {
     /// ```
     pub fn add_child(&mut self, parent: usize, data: T) -> usize {
         let index = self.add_node(data);
-        self.nodes[parent].add_child(index);
-        self.nodes[index].set_parent(parent);
+        self.link(parent, index);
         index
     }
 
-    /// Adds a child node to the tree root.
+    /// Adds a child node to the tree root, as returned by [`Tree::root`].
+    ///
+    /// If the tree is empty, the new node becomes the root instead.
     ///
     /// # Parameters
     /// - `data`: The data to associate with the new child node.
     ///
     /// # Returns
-    /// The index of the newly added child node.
+    /// The index of the newly added node.
     ///
     /// # Example
     /// ```rust
     /// use easy_tree::Tree;
     ///
     /// let mut tree = Tree::new();
-    /// let root = tree.add_node("root");
+    /// let root = tree.add_child_to_root("root");
     /// let child = tree.add_child_to_root("child");
+    /// assert_eq!(tree.children(root), &[child]);
     /// ```
     pub fn add_child_to_root(&mut self, data: T) -> usize {
-        self.add_child(0, data)
+        match self.root() {
+            Some(root) => self.add_child(root, data),
+            None => self.add_node(data),
+        }
+    }
+
+    /// Returns the index of the root of the tree: the first node without a parent.
+    ///
+    /// Traversals such as [`Tree::traverse`] and [`Tree::iter_dfs`] start from this node, which
+    /// is not necessarily node `0`: nodes can be added before their parent, or linked under
+    /// another node with [`Tree::set_parent`].
+    ///
+    /// # Returns
+    /// The index of the root, or `None` if the tree is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// assert_eq!(tree.root(), None);
+    /// let root = tree.add_node("root");
+    /// tree.add_child(root, "child");
+    /// assert_eq!(tree.root(), Some(root));
+    /// ```
+    pub fn root(&self) -> Option<usize> {
+        self.links.iter().position(|links| links.parent().is_none())
+    }
+
+    /// Returns a reference to the data stored in the root of the tree, as returned by
+    /// [`Tree::root`].
+    ///
+    /// # Returns
+    /// `Some(&T)` with the data of the root, or `None` if the tree is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// tree.add_node("root");
+    /// assert_eq!(tree.root_data(), Some(&"root"));
+    /// ```
+    pub fn root_data(&self) -> Option<&T> {
+        self.root().map(|root| &self.data[root])
     }
 
     /// Retrieves a reference to the data stored in a node.
@@ -322,7 +812,7 @@ impl<T> Tree<T> {
     /// assert_eq!(tree.get(root), Some(&42));
     /// ```
     pub fn get(&self, index: usize) -> Option<&T> {
-        self.nodes.get(index).map(|node| &node.data)
+        self.data.get(index)
     }
 
     /// Retrieves a reference to the data stored in a node without bounds checking.
@@ -356,7 +846,7 @@ impl<T> Tree<T> {
     /// ```
     #[inline(always)]
     pub fn get_unchecked(&self, index: usize) -> &T {
-        &self.nodes[index].data
+        &self.data[index]
     }
 
     /// Retrieves a mutable reference to the data stored in a node.
@@ -377,7 +867,7 @@ impl<T> Tree<T> {
     /// assert_eq!(tree.get(root), Some(&43));
     /// ```
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        self.nodes.get_mut(index).map(|node| &mut node.data)
+        self.data.get_mut(index)
     }
 
     /// Retrieves a mutable reference to the data stored in a node without bounds checking.
@@ -413,7 +903,7 @@ impl<T> Tree<T> {
     /// ```
     #[inline(always)]
     pub fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
-        &mut self.nodes[index].data
+        &mut self.data[index]
     }
 
     /// Returns the parent index of a node, if it has a parent.
@@ -437,7 +927,55 @@ impl<T> Tree<T> {
     /// assert_eq!(tree.parent_index_unchecked(child), Some(root));
     /// ```
     pub fn parent_index_unchecked(&self, index: usize) -> Option<usize> {
-        self.nodes[index].parent
+        self.links[index].parent()
+    }
+
+    /// Returns the index and data of the parent of a node, if it has a parent.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    ///
+    /// # Returns
+    /// `Some((parent_index, parent_data))` if the node has a parent, or `None` otherwise.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    /// assert_eq!(tree.parent(child), Some((root, &"root")));
+    /// assert_eq!(tree.parent(root), None);
+    /// ```
+    pub fn parent(&self, index: usize) -> Option<(usize, &T)> {
+        let parent = self.links[index].parent()?;
+        Some((parent, &self.data[parent]))
+    }
+
+    /// Returns the index and mutable data of the parent of a node, if it has a parent.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0);
+    /// let child = tree.add_child(root, 5);
+    /// if let Some((_, total)) = tree.parent_mut(child) {
+    ///     *total += 5;
+    /// }
+    /// assert_eq!(tree[root], 5);
+    /// ```
+    pub fn parent_mut(&mut self, index: usize) -> Option<(usize, &mut T)> {
+        let parent = self.links[index].parent()?;
+        Some((parent, &mut self.data[parent]))
     }
 
     /// Returns a slice of the indices of the children of a node.
@@ -461,10 +999,142 @@ impl<T> Tree<T> {
     /// assert_eq!(tree.children(root), &[child]);
     /// ```
     pub fn children(&self, index: usize) -> &[usize] {
-        &self.nodes[index].children
+        &self.links[index].children
+    }
+
+    /// Returns an iterator over the indices and data of the children of a node, in order.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// let b = tree.add_child(root, "b");
+    /// let children: Vec<_> = tree.children_data(root).collect();
+    /// assert_eq!(children, vec![(a, &"a"), (b, &"b")]);
+    /// ```
+    pub fn children_data(&self, index: usize) -> impl Iterator<Item = (usize, &T)> {
+        self.links[index]
+            .children
+            .iter()
+            .map(move |&child| (child, &self.data[child]))
+    }
+
+    /// Returns an iterator over the indices and mutable data of the children of a node, in
+    /// order.
+    ///
+    /// The children of a node are distinct nodes, so their data can be borrowed mutably at
+    /// once.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds, or if a child is listed twice, which
+    /// only happens if the links are corrupted.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0);
+    /// tree.add_child(root, 1);
+    /// tree.add_child(root, 2);
+    /// for (_, value) in tree.children_data_mut(root) {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!(tree.data(), &[0, 10, 20]);
+    /// ```
+    pub fn children_data_mut(&mut self, index: usize) -> impl Iterator<Item = (usize, &mut T)> {
+        let children = &self.links[index].children;
+        // Split the data in increasing index order, then hand the children out in their order.
+        let mut order: Vec<usize> = (0..children.len()).collect();
+        order.sort_unstable_by_key(|&position| children[position]);
+        let mut slots: Vec<Option<(usize, &mut T)>> = children.iter().map(|_| None).collect();
+        let mut rest: &mut [T] = &mut self.data;
+        let mut offset = 0;
+        for position in order {
+            let child = children[position];
+            assert!(
+                child >= offset,
+                "node {child} is listed twice among the children of node {index}"
+            );
+            let (node, tail) = mem::take(&mut rest)[child - offset..]
+                .split_first_mut()
+                .expect("child index out of bounds");
+            slots[position] = Some((child, node));
+            rest = tail;
+            offset = child + 1;
+        }
+        slots.into_iter().flatten()
+    }
+
+    /// Returns an iterator over the adjacent pairs of children of a node, as `(left, right)`
+    /// indices in child order.
+    ///
+    /// A node with fewer than two children yields no pairs.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0.0);
+    /// let a = tree.add_child(root, 10.0);
+    /// let b = tree.add_child(root, 25.0);
+    /// let c = tree.add_child(root, 30.0);
+    ///
+    /// // The gaps between neighboring siblings.
+    /// let gaps: Vec<f64> = tree
+    ///     .sibling_pairs(root)
+    ///     .map(|(left, right)| tree[right] - tree[left])
+    ///     .collect();
+    /// assert_eq!(gaps, vec![15.0, 5.0]);
+    /// assert_eq!(tree.sibling_pairs(root).next(), Some((a, b)));
+    /// assert_eq!(tree.sibling_pairs(c).count(), 0);
+    /// ```
+    pub fn sibling_pairs(&self, index: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.children_windows(index, 2)
+            .map(|pair| (pair[0], pair[1]))
+    }
+
+    /// Returns an iterator over the windows of `n` consecutive children of a node, in child
+    /// order, like [`slice::windows`].
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    /// - `n`: The number of children in each window.
+    ///
+    /// # Returns
+    /// An iterator over slices of child indices. A node with fewer than `n` children yields
+    /// no windows.
+    ///
+    /// # Panics
+    /// This method panics if the index is out of bounds or if `n` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// for name in ["a", "b", "c", "d"] {
+    ///     tree.add_child(root, name);
+    /// }
+    /// let windows: Vec<_> = tree.children_windows(root, 3).collect();
+    /// assert_eq!(windows, vec![&[1, 2, 3], &[2, 3, 4]]);
+    /// ```
+    pub fn children_windows(&self, index: usize, n: usize) -> impl Iterator<Item = &[usize]> {
+        self.links[index].children.windows(n)
     }
 
-    /// Traverses the tree in a depth-first manner.
+    /// Traverses the tree in a depth-first manner, starting from the [root](Tree::root).
     ///
     /// The traversal applies two callbacks:
     /// - `before_processing_children`: Called before processing the children of a node.
@@ -491,88 +1161,361 @@ impl<T> Tree<T> {
     /// );
     /// ```
     pub fn traverse<'a, S>(
+        &'a self,
+        before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) {
+        let Some(root) = self.root() else {
+            return;
+        };
+
+        let operation = Operation::start("traverse", self.links.len());
+        let visited = self.traverse_from(
+            root,
+            before_processing_children,
+            after_processing_the_subtree,
+            s,
+        );
+        operation.finish(visited);
+    }
+
+    /// Traverses every tree of the forest in a depth-first manner, so that no node is skipped.
+    ///
+    /// Unlike [`Tree::traverse`], which only visits the subtree of the [root](Tree::root), this
+    /// visits the subtree of every node without a parent, in index order. That includes orphans
+    /// created with [`Tree::add_node`] or [`Tree::detach`]. Every node is visited exactly once.
+    ///
+    /// # Parameters
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// tree.add_child(root, "child");
+    /// tree.add_node("orphan");
+    ///
+    /// let mut visited = vec![];
+    /// tree.traverse_forest(|_, data, visited| visited.push(*data), |_, _, _| {}, &mut visited);
+    /// assert_eq!(visited, vec!["root", "child", "orphan"]);
+    /// ```
+    pub fn traverse_forest<'a, S>(
         &'a self,
         mut before_processing_children: impl FnMut(usize, &'a T, &mut S),
         mut after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
         s: &mut S,
     ) {
-        if self.is_empty() {
+        let operation = Operation::start("traverse_forest", self.links.len());
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        let mut visited = 0;
+        for root in roots {
+            visited += self.traverse_from(
+                root,
+                &mut before_processing_children,
+                &mut after_processing_the_subtree,
+                s,
+            );
+        }
+        operation.finish(visited);
+    }
+
+    /// Traverses the tree in a depth-first manner like [`Tree::traverse`], passing the index and
+    /// data of each node's parent to the callbacks alongside the node itself.
+    ///
+    /// # Parameters
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// Both callbacks receive the index and data of the node, its parent's index and data, or
+    /// `None` for the root, and the state.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("html");
+    /// let body = tree.add_child(root, "body");
+    /// tree.add_child(body, "p");
+    ///
+    /// let mut edges = vec![];
+    /// tree.traverse_with_parent(
+    ///     |_, data, parent, edges| {
+    ///         if let Some((_, parent)) = parent {
+    ///             edges.push(format!("{parent} > {data}"));
+    ///         }
+    ///     },
+    ///     |_, _, _, _| {},
+    ///     &mut edges,
+    /// );
+    /// assert_eq!(edges, vec!["html > body", "body > p"]);
+    /// ```
+    pub fn traverse_with_parent<'a, S>(
+        &'a self,
+        mut before_processing_children: impl FnMut(usize, &'a T, Option<(usize, &'a T)>, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, Option<(usize, &'a T)>, &mut S),
+        s: &mut S,
+    ) {
+        let Some(root) = self.root() else {
             return;
+        };
+
+        let parent_of = |index: usize| {
+            let parent = self.links[index].parent()?;
+            Some((parent, &self.data[parent]))
+        };
+        let operation = Operation::start("traverse_with_parent", self.links.len());
+        let visited = self.traverse_from(
+            root,
+            |index, data, s| before_processing_children(index, data, parent_of(index), s),
+            |index, data, s| after_processing_the_subtree(index, data, parent_of(index), s),
+            s,
+        );
+        operation.finish(visited);
+    }
+
+    /// Makes `child` the last child of `parent`.
+    ///
+    /// With the `checked` feature, this panics if `child` would become its own parent, be
+    /// listed twice among the children of `parent`, or still be listed among the children of
+    /// another parent, which would otherwise make traversals loop or visit nodes twice.
+    pub(crate) fn link(&mut self, parent: usize, child: usize) {
+        if cfg!(feature = "checked") {
+            assert_ne!(parent, child, "node {child} cannot be its own parent");
+            assert!(
+                !self.links[parent].children.contains(&child),
+                "node {child} is already a child of node {parent}"
+            );
+            if let Some(previous) = self.links[child].parent() {
+                assert_eq!(
+                    previous, parent,
+                    "node {child} is already a child of node {previous}"
+                );
+            }
         }
+        self.links[parent].add_child(child);
+        self.links[child].set_parent(parent);
+    }
+
+    /// Panics if a traversal has visited more nodes than the tree has, which means that the
+    /// links contain a cycle or a duplicate child.
+    fn check_visit_count(&self, visited: usize) {
+        assert!(
+            visited <= self.links.len(),
+            "traversal visited more nodes than the tree has: the links contain a cycle or a \
+             duplicate child"
+        );
+    }
 
-        let mut stack = vec![(0, false)];
+    /// Traverses the subtree rooted at `root` in a depth-first manner. See [`Tree::traverse`].
+    ///
+    /// Returns the number of visited nodes.
+    fn traverse_from<'a, S>(
+        &'a self,
+        root: usize,
+        mut before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) -> usize {
+        let mut stack = vec![(root, false)];
+        let mut visited = 0;
 
         while let Some((index, children_visited)) = stack.pop() {
             if children_visited {
                 // All children are processed, call f2
-                let node = &self.nodes[index];
-                after_processing_the_subtree(index, &node.data, s);
+                after_processing_the_subtree(index, &self.data[index], s);
             } else {
+                visited += 1;
+                if cfg!(feature = "checked") {
+                    self.check_visit_count(visited);
+                }
+
                 // Call f and mark this node's children for processing
-                let node = &self.nodes[index];
-                before_processing_children(index, &node.data, s);
+                before_processing_children(index, &self.data[index], s);
 
                 // Re-push the current node with children_visited set to true
                 stack.push((index, true));
 
                 // Push all children onto the stack
-                for &child in node.children.iter().rev() {
+                for &child in self.links[index].children.iter().rev() {
+                    stack.push((child, false));
+                }
+            }
+        }
+        visited
+    }
+
+    /// Returns the indices of the subtree rooted at `root` in post-order.
+    fn post_order(&self, root: usize) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut stack = vec![(root, false)];
+        while let Some((index, children_visited)) = stack.pop() {
+            if children_visited {
+                order.push(index);
+            } else {
+                stack.push((index, true));
+                for &child in self.links[index].children.iter().rev() {
                     stack.push((child, false));
                 }
             }
         }
+        order
+    }
+
+    /// Exchanges the nodes stored at indices `a` and `b`, rewriting all links so that the tree
+    /// structure is unchanged: only the indices of the two nodes are swapped.
+    fn swap_slots(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        let swapped = |index: usize| {
+            if index == a {
+                b
+            } else if index == b {
+                a
+            } else {
+                index
+            }
+        };
+
+        self.data.swap(a, b);
+        self.links.swap(a, b);
+        self.ext.swap(a, b);
+        self.history.changed_from(a.min(b));
+        let mut neighbours = Vec::new();
+        for slot in [a, b] {
+            let node = &mut self.links[slot];
+            node.replace_parent(node.parent().map(swapped));
+            for child in &mut node.children {
+                *child = swapped(*child);
+            }
+            neighbours.extend(node.parent());
+            neighbours.extend(node.children.iter().copied());
+        }
+        neighbours.retain(|&index| index != a && index != b);
+        neighbours.sort_unstable();
+        neighbours.dedup();
+
+        for index in neighbours {
+            let node = &mut self.links[index];
+            node.replace_parent(node.parent().map(swapped));
+            for child in &mut node.children {
+                *child = swapped(*child);
+            }
+        }
+    }
+
+    /// Returns an iterator over the indices and data of the nodes reachable from the root, in
+    /// depth-first pre-order.
+    ///
+    /// This visits the nodes in the same order as the `before_processing_children` callback of
+    /// [`Tree::traverse`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child1 = tree.add_child(root, "child1");
+    /// let child2 = tree.add_child(root, "child2");
+    /// let grandchild = tree.add_child(child1, "grandchild");
+    ///
+    /// let order: Vec<usize> = tree.iter_dfs().map(|(idx, _)| idx).collect();
+    /// assert_eq!(order, vec![root, child1, grandchild, child2]);
+    /// ```
+    pub fn iter_dfs(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.root()
+            .into_iter()
+            .flat_map(move |root| self.reachable_from(root))
+            .map(move |index| (index, &self.data[index]))
     }
 
     /// Returns an iterator over the indices and data of the nodes in the tree.
     pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
-        self.nodes
-            .iter()
-            .enumerate()
-            .map(|(index, node)| (index, &node.data))
+        self.data.iter().enumerate()
     }
 
     /// Returns a mutable iterator over the indices and data of the nodes in the tree.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
-        self.nodes
-            .iter_mut()
-            .enumerate()
-            .map(|(index, node)| (index, &mut node.data))
+        self.data.iter_mut().enumerate()
+    }
+
+    /// Returns the data of all nodes as a contiguous slice, indexed by node.
+    ///
+    /// The data is stored apart from the links between nodes, so bulk passes over numeric
+    /// payloads run over a dense array that the compiler can vectorize.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1.0f32);
+    /// tree.add_children(root, [2.0, 3.0]);
+    /// assert_eq!(tree.data().iter().sum::<f32>(), 6.0);
+    /// ```
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the data of all nodes as a contiguous mutable slice, indexed by node.
+    ///
+    /// See [`Tree::data`]. Use [`Tree::split`] to also query the structure at the same time.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1.0f32);
+    /// tree.add_children(root, [2.0, 3.0]);
+    /// for value in tree.data_mut() {
+    ///     *value *= 0.5;
+    /// }
+    /// assert_eq!(tree.data(), &[0.5, 1.0, 1.5]);
+    /// ```
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
     }
 
     /// Returns `true` if the tree contains no nodes.
     pub fn is_empty(&self) -> bool {
-        self.nodes.is_empty()
+        self.links.is_empty()
     }
 
     /// Returns the number of nodes in the tree.
     pub fn len(&self) -> usize {
-        self.nodes.len()
+        self.links.len()
     }
 
     /// Removes all nodes from the tree.
     pub fn clear(&mut self) {
-        self.nodes.clear();
+        self.data.truncate(0);
+        self.links.clear();
+        self.ext.truncate(0);
+        self.history.changed_from(0);
     }
 }
 
 #[cfg(feature = "rayon")]
-impl<T: Send + Sync> Tree<T> {
+impl<T: Send + Sync, D: TreeStorage<T> + Send + Sync> Tree<T, D> {
     #[cfg(feature = "rayon")]
     /// Returns a parallel iterator over the indices and data of the nodes in the tree.
-    pub fn par_iter(&self) -> impl ParallelIterator<Item = (usize, &T)> {
-        self.nodes
-            .par_iter()
-            .enumerate()
-            .map(|(index, node)| (index, &node.data))
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = (usize, &T)> {
+        self.data.par_iter().enumerate()
     }
 
     #[cfg(feature = "rayon")]
     /// Returns a mutable parallel iterator over the indices and data of the nodes in the tree.
-    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (usize, &mut T)> {
-        self.nodes
-            .par_iter_mut()
-            .enumerate()
-            .map(|(index, node)| (index, &mut node.data))
+    pub fn par_iter_mut(&mut self) -> impl IndexedParallelIterator<Item = (usize, &mut T)> {
+        self.data.par_iter_mut().enumerate()
     }
 }
 
@@ -599,8 +1542,151 @@ mod tests {
 
         assert_eq!(tree.children(root), &[child1, child2]);
         assert_eq!(tree.children(child1), &[child3]);
-        assert_eq!(tree.children(child2), &[]);
-        assert_eq!(tree.children(child3), &[]);
+        assert_eq!(tree.children(child2), &[] as &[usize]);
+        assert_eq!(tree.children(child3), &[] as &[usize]);
+    }
+
+    #[test]
+    fn test_root_and_add_child_to_root() {
+        let mut tree = Tree::new();
+        assert_eq!(tree.root(), None);
+        assert_eq!(tree.root_data(), None);
+
+        let root = tree.add_child_to_root(0);
+        let child = tree.add_child_to_root(1);
+        assert_eq!(tree.root(), Some(root));
+        assert_eq!(tree.root_data(), Some(&0));
+        assert_eq!(tree.parent_index_unchecked(child), Some(root));
+        assert_eq!(tree.children(root), &[child]);
+    }
+
+    #[test]
+    fn test_traversals_start_at_a_root_after_index_0() {
+        let mut tree = Tree::new();
+        let a = tree.add_node("a");
+        tree.add_child(a, "a1");
+        let root = tree.add_node("root");
+        tree.add_child(root, "b");
+        tree.set_parent(a, root).unwrap();
+        assert_eq!(tree.root(), Some(root));
+        assert_eq!(tree.orphans().count(), 0);
+
+        let expected = ["root", "b", "a", "a1"];
+        let mut visited = vec![];
+        tree.traverse(
+            |_, data, visited| visited.push(*data),
+            |_, _, _| {},
+            &mut visited,
+        );
+        assert_eq!(visited, expected);
+        let dfs: Vec<_> = tree.iter_dfs().map(|(_, data)| *data).collect();
+        assert_eq!(dfs, expected);
+        let mut parents = vec![];
+        tree.traverse_with_parent(
+            |_, _, parent, parents| parents.push(parent.map(|(_, data)| *data)),
+            |_, _, _, _| {},
+            &mut parents,
+        );
+        assert_eq!(parents, [None, Some("root"), Some("root"), Some("a")]);
+
+        let resumable: Vec<_> = tree.iter_dfs_resumable().map(|(_, data)| *data).collect();
+        assert_eq!(resumable, expected);
+        let mut budgeted = vec![];
+        let resume =
+            tree.traverse_budgeted(10, |_, data, v| v.push(*data), |_, _, _| {}, &mut budgeted);
+        assert!(resume.is_finished());
+        assert_eq!(budgeted, expected);
+        assert_eq!(tree.as_subtree().iter_dfs().count(), 4);
+        assert_eq!(tree.summarize(10, |_, _| 0).len(), 4);
+        assert_eq!(
+            tree.find_with_path(|_, data| *data == "a1").unwrap().1,
+            [root, a, 1]
+        );
+        let plan = tree.compile_traversal();
+        let mut planned = vec![];
+        plan.replay(
+            &tree,
+            |_, data, v| v.push(*data),
+            |_, _, _| {},
+            &mut planned,
+        );
+        assert_eq!(planned, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "node 1 is already a child of node 0")]
+    fn test_checked_duplicate_child() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+        tree.link(root, child);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "node 2 is already a child of node 1")]
+    fn test_checked_child_of_another_parent() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        let a1 = tree.add_child(a, 2);
+        tree.link(root, a1);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "node 0 cannot be its own parent")]
+    fn test_checked_self_parent() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        tree.link(root, root);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "the links contain a cycle or a duplicate child")]
+    fn test_checked_traversal_stops_on_cycle() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+        tree.links[child].add_child(root);
+        tree.traverse(|_, _, _| {}, |_, _, _| {}, &mut ());
+    }
+
+    #[test]
+    fn test_links_parent_is_niche_optimized() {
+        use std::mem::size_of;
+        assert_eq!(size_of::<Option<ParentIndex>>(), size_of::<usize>());
+        assert_eq!(
+            size_of::<Links>(),
+            size_of::<Vec<usize>>() + size_of::<usize>()
+        );
+
+        let mut links = Links::with_parent(Some(0));
+        assert_eq!(links.parent(), Some(0));
+        assert_eq!(links.replace_parent(Some(usize::MAX - 1)), Some(0));
+        assert_eq!(links.replace_parent(None), Some(usize::MAX - 1));
+        assert_eq!(links.parent(), None);
+    }
+
+    #[test]
+    fn test_tree_index() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+
+        assert_eq!(tree[root], 0);
+        tree[child] = 10;
+        assert_eq!(tree[child], 10);
+        assert_eq!(tree.get(child), Some(&10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tree_index_out_of_bounds() {
+        let tree: Tree<i32> = Tree::new();
+        let _ = tree[0];
     }
 
     #[test]
@@ -672,4 +1758,122 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_tree_traverse_forest() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+        tree.add_child(child, 2);
+        let orphan = tree.add_node(3);
+        tree.add_child(orphan, 4);
+        tree.detach(child);
+
+        let mut entered = vec![];
+        let mut left = vec![];
+        tree.traverse_forest(
+            |_, data, entered: &mut Vec<i32>| entered.push(*data),
+            |_, data, _| left.push(*data),
+            &mut entered,
+        );
+        assert_eq!(entered, vec![0, 1, 2, 3, 4]);
+        assert_eq!(left, vec![0, 2, 1, 4, 3]);
+
+        Tree::<i32>::new().traverse_forest(|_, _, _| unreachable!(), |_, _, _| {}, &mut ());
+    }
+
+    #[test]
+    fn test_tree_traverse_with_parent() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(1);
+        let child = tree.add_child(root, 10);
+        tree.add_child(child, 100);
+        tree.add_child(root, 20);
+
+        // Every node's value accumulates its ancestors' values.
+        let mut totals = vec![0; tree.len()];
+        tree.traverse_with_parent(
+            |index, data, parent, totals: &mut Vec<i32>| {
+                totals[index] = data + parent.map_or(0, |(parent, _)| totals[parent]);
+            },
+            |_, _, _, _| {},
+            &mut totals,
+        );
+        assert_eq!(totals, vec![1, 11, 111, 21]);
+
+        let mut post = vec![];
+        tree.traverse_with_parent(
+            |_, _, _, _| {},
+            |_, data, parent, post: &mut Vec<(i32, Option<i32>)>| {
+                post.push((*data, parent.map(|(_, data)| *data)))
+            },
+            &mut post,
+        );
+        assert_eq!(
+            post,
+            vec![(100, Some(10)), (10, Some(1)), (20, Some(1)), (1, None)]
+        );
+    }
+
+    #[test]
+    fn test_children_data_follows_child_order() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 3);
+        let b = tree.add_child(root, 1);
+        let c = tree.add_child(root, 2);
+        tree.add_child(b, 4);
+        tree.sort_children_by_key(root, |&value| value);
+        assert_eq!(tree.children(root), &[b, c, a]);
+
+        for (position, (_, value)) in tree.children_data_mut(root).enumerate() {
+            *value += 10 * position;
+        }
+        let children: Vec<_> = tree.children_data(root).collect();
+        assert_eq!(children, vec![(b, &1), (c, &12), (a, &23)]);
+        assert_eq!(tree.children_data_mut(a).count(), 0);
+    }
+
+    #[test]
+    fn test_parent_accumulates_up_the_tree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(1);
+        let child = tree.add_child(root, 2);
+        let grandchild = tree.add_child(child, 3);
+
+        // Walk up from the deepest node, adding every node to its parent.
+        let mut current = grandchild;
+        while let Some(value) = tree.get(current).copied() {
+            match tree.parent_mut(current) {
+                Some((parent, total)) => {
+                    *total += value;
+                    current = parent;
+                }
+                None => break,
+            }
+        }
+        assert_eq!(tree.data(), &[6, 5, 3]);
+        assert_eq!(tree.parent(grandchild), Some((child, &5)));
+        assert_eq!(tree.parent(root), None);
+    }
+
+    #[test]
+    fn test_sibling_pairs_and_children_windows() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let only = tree.add_child(root, 1);
+        let b = tree.add_child(root, 2);
+        let c = tree.add_child(root, 3);
+        tree.add_child(only, 4);
+
+        let pairs: Vec<_> = tree.sibling_pairs(root).collect();
+        assert_eq!(pairs, [(only, b), (b, c)]);
+        assert_eq!(tree.sibling_pairs(only).count(), 0);
+        assert_eq!(
+            tree.children_windows(root, 3).collect::<Vec<_>>(),
+            [&[1, 2, 3]]
+        );
+        assert_eq!(tree.children_windows(root, 4).count(), 0);
+        assert_eq!(tree.children_windows(root, 1).count(), 3);
+    }
 }
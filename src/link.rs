@@ -0,0 +1,335 @@
+use crate::{Tree, TreeError, TreeStorage};
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Makes an existing node without a parent a child of another node, after its existing
+    /// children.
+    ///
+    /// This is the way to wire in nodes created with [`Tree::add_node`], or detached with
+    /// [`Tree::detach`].
+    ///
+    /// # Parameters
+    /// - `child`: The index of the node to attach.
+    /// - `parent`: The index of its new parent.
+    ///
+    /// # Errors
+    /// - [`TreeError::InvalidIndex`] if either index is out of bounds.
+    /// - [`TreeError::AlreadyHasParent`] if `child` already has a parent.
+    /// - [`TreeError::WouldCreateCycle`] if `parent` is `child` or one of its descendants.
+    ///
+    /// The tree is left unchanged when an error is returned.
+    ///
+    /// Node `0` can be attached like any other node. The [root](Tree::root) that traversals
+    /// start from is then the first node that is still without a parent.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreeError};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let orphan = tree.add_node("orphan");
+    ///
+    /// tree.set_parent(orphan, root).unwrap();
+    /// assert_eq!(tree.children(root), &[orphan]);
+    /// assert_eq!(
+    ///     tree.set_parent(root, orphan),
+    ///     Err(TreeError::WouldCreateCycle { child: root, parent: orphan })
+    /// );
+    /// ```
+    pub fn set_parent(&mut self, child: usize, parent: usize) -> Result<(), TreeError> {
+        self.check_index(child)?;
+        self.check_index(parent)?;
+        if let Some(current) = self.links[child].parent() {
+            return Err(TreeError::AlreadyHasParent {
+                child,
+                parent: current,
+            });
+        }
+        let mut ancestor = Some(parent);
+        while let Some(index) = ancestor {
+            if index == child {
+                return Err(TreeError::WouldCreateCycle { child, parent });
+            }
+            ancestor = self.links[index].parent();
+        }
+
+        self.link(parent, child);
+        Ok(())
+    }
+
+    /// Adds a child node to an existing node, like [`Tree::add_child`], but returns an error
+    /// instead of panicking if the parent does not exist.
+    ///
+    /// # Errors
+    /// [`TreeError::InvalidIndex`] if `parent` is out of bounds. The tree is left unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreeError};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// assert_eq!(tree.try_add_child(root, "child"), Ok(1));
+    /// assert_eq!(tree.try_add_child(7, "lost"), Err(TreeError::InvalidIndex(7)));
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn try_add_child(&mut self, parent: usize, data: T) -> Result<usize, TreeError> {
+        self.check_index(parent)?;
+        Ok(self.add_child(parent, data))
+    }
+
+    /// Returns the position of a node among the children of its parent.
+    ///
+    /// # Errors
+    /// - [`TreeError::InvalidIndex`] if either index is out of bounds.
+    /// - [`TreeError::NotAChild`] if `child` is not a child of `parent`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreeError};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let first = tree.add_child(root, "first");
+    /// let second = tree.add_child(root, "second");
+    ///
+    /// assert_eq!(tree.child_position(root, second), Ok(1));
+    /// assert_eq!(
+    ///     tree.child_position(first, second),
+    ///     Err(TreeError::NotAChild { parent: first, child: second })
+    /// );
+    /// ```
+    pub fn child_position(&self, parent: usize, child: usize) -> Result<usize, TreeError> {
+        self.check_index(parent)?;
+        self.check_index(child)?;
+        self.links[parent]
+            .children
+            .iter()
+            .position(|&index| index == child)
+            .ok_or(TreeError::NotAChild { parent, child })
+    }
+
+    /// Unlinks a child from a given parent, like [`Tree::detach`], but checks that the two
+    /// nodes are related first.
+    ///
+    /// # Returns
+    /// The position the child had among the children of `parent`.
+    ///
+    /// # Errors
+    /// - [`TreeError::InvalidIndex`] if either index is out of bounds.
+    /// - [`TreeError::NotAChild`] if `child` is not a child of `parent`.
+    ///
+    /// The tree is left unchanged when an error is returned.
+    pub fn remove_child(&mut self, parent: usize, child: usize) -> Result<usize, TreeError> {
+        let position = self.child_position(parent, child)?;
+        self.links[parent].children.remove(position);
+        self.links[child].replace_parent(None);
+        Ok(position)
+    }
+
+    /// Unlinks a node from its parent, making it a root of its own subtree. Its descendants
+    /// stay attached to it.
+    ///
+    /// # Parameters
+    /// - `child`: The index of the node to detach.
+    ///
+    /// # Returns
+    /// The index of the former parent, or `None` if the node had no parent.
+    ///
+    /// # Panics
+    /// This method panics if `child` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    ///
+    /// assert_eq!(tree.detach(child), Some(root));
+    /// assert!(tree.children(root).is_empty());
+    /// assert_eq!(tree.parent_index_unchecked(child), None);
+    /// ```
+    pub fn detach(&mut self, child: usize) -> Option<usize> {
+        let parent = self.links[child].replace_parent(None)?;
+        self.links[parent].children.retain(|&index| index != child);
+        Some(parent)
+    }
+
+    /// Returns an iterator over the orphans of the tree: the nodes without a parent other than
+    /// the root, as returned by [`Tree::root`].
+    ///
+    /// Orphans are created by [`Tree::add_node`] and [`Tree::detach`], and are not visited by
+    /// [`Tree::traverse`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// tree.add_child(root, "child");
+    /// let forgotten = tree.add_node("forgotten");
+    ///
+    /// assert_eq!(tree.orphans().collect::<Vec<_>>(), vec![forgotten]);
+    /// ```
+    pub fn orphans(&self) -> impl Iterator<Item = usize> + '_ {
+        let root = self.root();
+        (0..self.links.len())
+            .filter(move |&index| self.links[index].parent().is_none() && Some(index) != root)
+    }
+
+    /// Checks that the tree has no orphans, as listed by [`Tree::orphans`].
+    ///
+    /// This is meant for trees that are expected to be fully connected, to catch nodes that
+    /// were created but never attached, for example in tests or debug assertions.
+    ///
+    /// # Errors
+    /// [`TreeError::Orphan`] with the first orphan found.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreeError};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_node("child");
+    /// assert_eq!(tree.ensure_no_orphans(), Err(TreeError::Orphan(child)));
+    ///
+    /// tree.set_parent(child, root).unwrap();
+    /// assert_eq!(tree.ensure_no_orphans(), Ok(()));
+    /// ```
+    pub fn ensure_no_orphans(&self) -> Result<(), TreeError> {
+        match self.orphans().next() {
+            Some(orphan) => Err(TreeError::Orphan(orphan)),
+            None => Ok(()),
+        }
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), TreeError> {
+        if index < self.links.len() {
+            Ok(())
+        } else {
+            Err(TreeError::InvalidIndex(index))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_parent_errors_leave_tree_unchanged() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        let b = tree.add_child(a, 2);
+        let free = tree.add_node(3);
+
+        assert_eq!(tree.set_parent(free, 10), Err(TreeError::InvalidIndex(10)));
+        assert_eq!(
+            tree.set_parent(b, free),
+            Err(TreeError::AlreadyHasParent {
+                child: b,
+                parent: a
+            })
+        );
+        assert_eq!(
+            tree.set_parent(free, free),
+            Err(TreeError::WouldCreateCycle {
+                child: free,
+                parent: free
+            })
+        );
+        assert!(tree.children(free).is_empty());
+
+        assert_eq!(tree.detach(a), Some(root));
+        assert_eq!(tree.detach(a), None);
+        assert_eq!(
+            tree.set_parent(a, b),
+            Err(TreeError::WouldCreateCycle {
+                child: a,
+                parent: b
+            })
+        );
+        tree.set_parent(a, free).unwrap();
+        assert_eq!(tree.path_string(b, "/"), "3/1/2");
+        assert!(tree.children(root).is_empty());
+    }
+
+    #[test]
+    fn test_set_parent_of_node_0_keeps_the_new_root_visible() {
+        let mut tree = Tree::new();
+        let a = tree.add_node("a");
+        tree.add_child(a, "a1");
+        let root = tree.add_node("root");
+        tree.set_parent(a, root).unwrap();
+
+        let mut visited = vec![];
+        tree.traverse(
+            |_, data, visited| visited.push(*data),
+            |_, _, _| {},
+            &mut visited,
+        );
+        assert_eq!(visited, ["root", "a", "a1"]);
+        let dfs: Vec<_> = tree.iter_dfs().map(|(_, data)| *data).collect();
+        assert_eq!(dfs, ["root", "a", "a1"]);
+        assert!(tree.ensure_no_orphans().is_ok());
+    }
+
+    #[test]
+    fn test_orphans() {
+        let mut tree: Tree<()> = Tree::new();
+        assert_eq!(tree.orphans().count(), 0);
+        let root = tree.add_node(());
+        let child = tree.add_child(root, ());
+        let orphan = tree.add_node(());
+        assert_eq!(tree.orphans().collect::<Vec<_>>(), vec![orphan]);
+
+        tree.detach(child);
+        assert_eq!(tree.orphans().collect::<Vec<_>>(), vec![child, orphan]);
+        assert_eq!(tree.ensure_no_orphans(), Err(TreeError::Orphan(child)));
+    }
+
+    #[test]
+    fn test_remove_child() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        let b = tree.add_child(root, 2);
+        let c = tree.add_child(a, 3);
+
+        assert_eq!(
+            tree.remove_child(root, c),
+            Err(TreeError::NotAChild {
+                parent: root,
+                child: c
+            })
+        );
+        assert_eq!(tree.remove_child(root, 9), Err(TreeError::InvalidIndex(9)));
+        assert_eq!(tree.parent_index_unchecked(c), Some(a));
+
+        assert_eq!(tree.remove_child(root, a), Ok(0));
+        assert_eq!(tree.children(root), &[b]);
+        assert_eq!(tree.parent_index_unchecked(a), None);
+        assert_eq!(
+            TreeError::NotAChild {
+                parent: 4,
+                child: 2
+            }
+            .to_string(),
+            "node 2 is not a child of node 4"
+        );
+    }
+
+    #[test]
+    fn test_tree_error_display() {
+        let error = TreeError::AlreadyHasParent {
+            child: 4,
+            parent: 2,
+        };
+        assert_eq!(error.to_string(), "node 4 already has parent 2");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::checkpoint::History;
+use crate::ext::Extensions;
+use crate::node_id::TreeNonce;
+use crate::{Tree, TreeStorage};
+use std::marker::PhantomData;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Creates a new tree with the same structure, where the data of every node is produced by
+    /// applying `f` to a reference to the data of the corresponding node in this tree.
+    ///
+    /// Node indices are preserved, so an index into this tree addresses the corresponding node
+    /// in the new tree.
+    ///
+    /// # Parameters
+    /// - `f`: Produces the data of a node in the new tree.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    ///
+    /// let lengths = tree.map_ref(|data| data.len());
+    /// assert_eq!(lengths.get(child), Some(&5));
+    /// assert_eq!(lengths.children(root), &[child]);
+    /// ```
+    pub fn map_ref<'a, U>(&'a self, mut f: impl FnMut(&'a T) -> U) -> Tree<U> {
+        Tree {
+            data: self.data.iter().map(&mut f).collect(),
+            marker: PhantomData,
+            links: self.links.clone(),
+            ext: Extensions::default(),
+            nonce: TreeNonce::new(),
+            history: History::default(),
+        }
+    }
+
+    /// Creates a new tree with the same structure, borrowing the data of this tree.
+    ///
+    /// This allows passing the tree to APIs that expect an owned `Tree<U>` without cloning the
+    /// payloads.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(String::from("root"));
+    ///
+    /// let borrowed: Tree<&String> = tree.as_ref_tree();
+    /// assert_eq!(borrowed.get(root), Some(&&String::from("root")));
+    /// ```
+    pub fn as_ref_tree(&self) -> Tree<&T> {
+        self.map_ref(|data| data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_ref_preserves_structure() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(1);
+        let child1 = tree.add_child(root, 2);
+        let child2 = tree.add_child(root, 3);
+        let grandchild = tree.add_child(child1, 4);
+        let detached = tree.add_node(5);
+
+        let mapped = tree.map_ref(|data| data * 10);
+        assert_eq!(mapped.len(), tree.len());
+        assert_eq!(mapped.children(root), &[child1, child2]);
+        assert_eq!(mapped.parent_index_unchecked(grandchild), Some(child1));
+        assert_eq!(mapped.parent_index_unchecked(detached), None);
+        let data: Vec<_> = mapped.iter().map(|(_, data)| *data).collect();
+        assert_eq!(data, vec![10, 20, 30, 40, 50]);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{Tree, TreeStorage};
+
+const BITS: usize = u64::BITS as usize;
+
+/// A compact set of node indices, stored as one bit per node.
+///
+/// A mask is sized to a tree when it is created; indices at or past its length are never in
+/// the mask. It is a cheaper replacement for a `HashSet<usize>` or `HashMap<usize, bool>` when
+/// tracking visited nodes, selections or collapsed state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodeMask {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl NodeMask {
+    /// Creates an empty mask covering the indices `0..len`.
+    pub fn new(len: usize) -> Self {
+        NodeMask {
+            words: vec![0; len.div_ceil(BITS)],
+            len,
+        }
+    }
+
+    /// Creates an empty mask covering every node of `tree`.
+    pub fn for_tree<T, D: TreeStorage<T>>(tree: &Tree<T, D>) -> Self {
+        Self::new(tree.len())
+    }
+
+    /// Returns the number of indices the mask covers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the mask covers no indices.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Extends the mask to cover the indices `0..len`, leaving the new indices unset. Does
+    /// nothing if the mask already covers them.
+    pub fn grow(&mut self, len: usize) {
+        if len > self.len {
+            self.words.resize(len.div_ceil(BITS), 0);
+            self.len = len;
+        }
+    }
+
+    /// Adds a node to the mask.
+    ///
+    /// # Returns
+    /// `true` if the node was not in the mask before.
+    ///
+    /// # Panics
+    /// This method panics if `index` is not covered by the mask.
+    pub fn set(&mut self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        let was_set = self.words[word] & bit != 0;
+        self.words[word] |= bit;
+        !was_set
+    }
+
+    /// Removes a node from the mask.
+    ///
+    /// # Returns
+    /// `true` if the node was in the mask before.
+    ///
+    /// # Panics
+    /// This method panics if `index` is not covered by the mask.
+    pub fn clear(&mut self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        let was_set = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        was_set
+    }
+
+    /// Adds a node to the mask if it is not in it, or removes it otherwise.
+    ///
+    /// # Returns
+    /// `true` if the node is in the mask afterwards.
+    ///
+    /// # Panics
+    /// This method panics if `index` is not covered by the mask.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        let (word, bit) = self.locate(index);
+        self.words[word] ^= bit;
+        self.words[word] & bit != 0
+    }
+
+    /// Returns `true` if the node is in the mask. Indices not covered by the mask are never in
+    /// it.
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && self.words[index / BITS] & (1 << (index % BITS)) != 0
+    }
+
+    /// Removes every node from the mask.
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Returns the number of nodes in the mask.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns an iterator over the indices in the mask, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(position, &word)| {
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(position * BITS + bit)
+            })
+        })
+    }
+
+    fn locate(&self, index: usize) -> (usize, u64) {
+        assert!(
+            index < self.len,
+            "index {index} is out of bounds for a mask of length {}",
+            self.len
+        );
+        (index / BITS, 1 << (index % BITS))
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Traverses the tree in a depth-first manner like [`Tree::traverse`], skipping every node in
+    /// `mask` together with its whole subtree.
+    ///
+    /// # Parameters
+    /// - `mask`: The nodes to skip.
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{NodeMask, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let hidden = tree.add_child(root, "hidden");
+    /// tree.add_child(hidden, "hidden child");
+    /// tree.add_child(root, "shown");
+    ///
+    /// let mut mask = NodeMask::for_tree(&tree);
+    /// mask.set(hidden);
+    ///
+    /// let mut visited = vec![];
+    /// tree.traverse_masked(&mask, |_, data, visited| visited.push(*data), |_, _, _| {}, &mut visited);
+    /// assert_eq!(visited, vec!["root", "shown"]);
+    /// ```
+    pub fn traverse_masked<'a, S>(
+        &'a self,
+        mask: &NodeMask,
+        mut before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) {
+        let Some(root) = self.root().filter(|&root| !mask.contains(root)) else {
+            return;
+        };
+        let mut stack = vec![(root, false)];
+        while let Some((index, children_visited)) = stack.pop() {
+            let node = &self.links[index];
+            if children_visited {
+                after_processing_the_subtree(index, &self.data[index], s);
+            } else {
+                before_processing_children(index, &self.data[index], s);
+                stack.push((index, true));
+                for &child in node.children.iter().rev() {
+                    if !mask.contains(child) {
+                        stack.push((child, false));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the mask of the given nodes and all their ancestors, such as the matches of a
+    /// filter along with the folders enclosing them.
+    ///
+    /// Each walk towards the root stops at the first node already in the mask, so every node is
+    /// visited at most once however many nodes share ancestors.
+    ///
+    /// # Parameters
+    /// - `nodes`: The indices of the nodes to include, in any order. Duplicates are ignored.
+    ///
+    /// # Panics
+    /// This method panics if any of `nodes` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("src");
+    /// let tests = tree.add_child(root, "tests");
+    /// let matched = tree.add_child(tests, "mask_test.rs");
+    /// tree.add_child(root, "lib.rs");
+    ///
+    /// let shown = tree.with_ancestors([matched]);
+    /// assert_eq!(shown.iter().collect::<Vec<_>>(), vec![root, tests, matched]);
+    /// ```
+    pub fn with_ancestors(&self, nodes: impl IntoIterator<Item = usize>) -> NodeMask {
+        let mut mask = NodeMask::for_tree(self);
+        for node in nodes {
+            let mut ancestor = Some(node);
+            while let Some(index) = ancestor {
+                if !mask.set(index) {
+                    break;
+                }
+                ancestor = self.links[index].parent();
+            }
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_mask_operations() {
+        let mut mask = NodeMask::new(130);
+        assert!(mask.set(0));
+        assert!(!mask.set(0));
+        assert!(mask.set(64));
+        assert!(mask.set(129));
+        assert!(mask.toggle(5));
+        assert!(!mask.toggle(5));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![0, 64, 129]);
+        assert_eq!(mask.count(), 3);
+        assert!(mask.clear(64));
+        assert!(!mask.clear(64));
+        assert!(!mask.contains(64));
+        assert!(!mask.contains(1000));
+
+        mask.grow(200);
+        assert_eq!(mask.len(), 200);
+        mask.set(199);
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![0, 129, 199]);
+        mask.clear_all();
+        assert_eq!(mask.count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_node_mask_set_out_of_bounds() {
+        NodeMask::new(3).set(3);
+    }
+
+    #[test]
+    fn test_with_ancestors_shares_walks_in_a_forest() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        let a1 = tree.add_child(a, 2);
+        let a2 = tree.add_child(a, 3);
+        tree.add_child(root, 4);
+        let other = tree.add_node(5);
+        let o1 = tree.add_child(other, 6);
+
+        let mask = tree.with_ancestors([a2, a1, o1, a1]);
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 5, 6]);
+        assert_eq!(mask.len(), tree.len());
+        assert_eq!(tree.with_ancestors([]).count(), 0);
+    }
+}
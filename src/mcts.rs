@@ -0,0 +1,298 @@
+//! Scaffolding for Monte Carlo tree search.
+//!
+//! [`Mcts`] grows a [`Tree`] of [`MctsNode`]s, each holding a user-defined state together with
+//! its visit statistics. Like a [`LazyTree`](crate::LazyTree), children are only created when a
+//! node is first expanded. The game-specific parts of the search are provided by implementing
+//! [`MctsPolicy`]:
+//!
+//! 1. **Selection** descends from the root to a node that has not been expanded yet, choosing
+//!    the child with the highest [`MctsPolicy::score`] at every step (UCB1 by default).
+//! 2. **Expansion** creates the children of that node with [`MctsPolicy::expand`] and picks the
+//!    first one.
+//! 3. **Simulation** estimates the reward of the picked node with [`MctsPolicy::simulate`],
+//!    typically by playing random moves until the game ends.
+//! 4. **Backpropagation** adds the reward to every node on the path back to the root, passing
+//!    it through [`MctsPolicy::propagate`] at each level.
+//!
+//! # Example
+//! ```rust
+//! use easy_tree::mcts::{Mcts, MctsPolicy};
+//!
+//! // Pick digits one by one; the reward is the value of the resulting two-digit number.
+//! struct Digits;
+//!
+//! impl MctsPolicy<Vec<u8>> for Digits {
+//!     fn expand(&mut self, state: &Vec<u8>) -> Vec<Vec<u8>> {
+//!         if state.len() == 2 {
+//!             return vec![];
+//!         }
+//!         (0..10)
+//!             .map(|digit| {
+//!                 let mut next = state.clone();
+//!                 next.push(digit);
+//!                 next
+//!             })
+//!             .collect()
+//!     }
+//!
+//!     fn simulate(&mut self, state: &Vec<u8>) -> f64 {
+//!         // Assume the remaining digits are average.
+//!         let mut value = 0.0;
+//!         for position in 0..2 {
+//!             value = value * 10.0 + state.get(position).map_or(4.5, |&d| d as f64);
+//!         }
+//!         value / 99.0
+//!     }
+//! }
+//!
+//! let mut search = Mcts::new(vec![], Digits);
+//! search.run(2_000);
+//! let best = search.best_child(0).unwrap();
+//! assert_eq!(search.tree()[best].state, vec![9]);
+//! ```
+
+use crate::Tree;
+
+/// The data stored in every node of the search tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MctsNode<S> {
+    /// The user-defined state of the node.
+    pub state: S,
+    /// How many times the node was part of a search iteration.
+    pub visits: u64,
+    /// The sum of the rewards backpropagated through the node.
+    pub total_reward: f64,
+}
+
+impl<S> MctsNode<S> {
+    /// Creates an unvisited node.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            visits: 0,
+            total_reward: 0.0,
+        }
+    }
+
+    /// Returns the average reward of the node, or `0.0` if it has not been visited.
+    pub fn mean_reward(&self) -> f64 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.total_reward / self.visits as f64
+        }
+    }
+}
+
+/// Computes the UCB1 score of a child: its mean reward plus an exploration bonus that shrinks as
+/// the child gets visited more often. Unvisited children get an infinite score.
+///
+/// # Parameters
+/// - `parent_visits`: The number of visits of the parent.
+/// - `child`: The child to score.
+/// - `exploration`: The weight of the exploration bonus, commonly `sqrt(2)`.
+pub fn ucb1<S>(parent_visits: u64, child: &MctsNode<S>, exploration: f64) -> f64 {
+    if child.visits == 0 {
+        return f64::INFINITY;
+    }
+    let bonus = ((parent_visits.max(1) as f64).ln() / child.visits as f64).sqrt();
+    child.mean_reward() + exploration * bonus
+}
+
+/// The problem-specific parts of a Monte Carlo tree search.
+pub trait MctsPolicy<S> {
+    /// Returns the states reachable from `state` in one step. An empty vector marks a terminal
+    /// state.
+    fn expand(&mut self, state: &S) -> Vec<S>;
+
+    /// Estimates the reward of `state`, typically by a random playout.
+    fn simulate(&mut self, state: &S) -> f64;
+
+    /// Scores a child during selection; the child with the highest score is descended into.
+    /// Defaults to [`ucb1`] with an exploration weight of `sqrt(2)`.
+    fn score(&self, parent: &MctsNode<S>, child: &MctsNode<S>) -> f64 {
+        ucb1(parent.visits, child, std::f64::consts::SQRT_2)
+    }
+
+    /// Converts a reward backpropagated from a child into the reward for its parent. Defaults
+    /// to the identity; two-player games usually negate the reward here.
+    fn propagate(&self, reward: f64) -> f64 {
+        reward
+    }
+}
+
+/// A Monte Carlo tree search over states of type `S`, driven by the policy `P`.
+///
+/// See the [module documentation](self) for an overview.
+pub struct Mcts<S, P> {
+    tree: Tree<MctsNode<S>>,
+    expanded: Vec<bool>,
+    policy: P,
+}
+
+impl<S, P: MctsPolicy<S>> Mcts<S, P> {
+    /// Creates a search with a single root node holding `root`.
+    pub fn new(root: S, policy: P) -> Self {
+        let mut tree = Tree::new();
+        tree.add_node(MctsNode::new(root));
+        Self {
+            tree,
+            expanded: vec![false],
+            policy,
+        }
+    }
+
+    /// Runs a single iteration of selection, expansion, simulation and backpropagation.
+    ///
+    /// # Returns
+    /// The index of the node whose state was simulated.
+    pub fn iterate(&mut self) -> usize {
+        let leaf = self.select();
+        let node = self.expand(leaf);
+        let reward = self.policy.simulate(&self.tree[node].state);
+        self.backpropagate(node, reward);
+        node
+    }
+
+    /// Runs `iterations` search iterations.
+    pub fn run(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterate();
+        }
+    }
+
+    /// Returns the most visited child of a node, which is the usual choice for the next move.
+    ///
+    /// # Returns
+    /// `None` if the node has no children yet.
+    pub fn best_child(&self, index: usize) -> Option<usize> {
+        self.tree
+            .children(index)
+            .iter()
+            .copied()
+            .max_by_key(|&child| self.tree[child].visits)
+    }
+
+    /// Returns the search tree.
+    pub fn tree(&self) -> &Tree<MctsNode<S>> {
+        &self.tree
+    }
+
+    /// Returns the policy.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Returns the search tree, dropping the policy.
+    pub fn into_tree(self) -> Tree<MctsNode<S>> {
+        self.tree
+    }
+
+    fn select(&self) -> usize {
+        let mut node = 0;
+        while self.expanded[node] {
+            let parent = &self.tree[node];
+            let best = self.tree.children(node).iter().copied().max_by(|&a, &b| {
+                let a = self.policy.score(parent, &self.tree[a]);
+                let b = self.policy.score(parent, &self.tree[b]);
+                a.total_cmp(&b)
+            });
+            match best {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        node
+    }
+
+    fn expand(&mut self, node: usize) -> usize {
+        if self.expanded[node] {
+            return node;
+        }
+        self.expanded[node] = true;
+        let children = self.policy.expand(&self.tree[node].state);
+        for state in children {
+            self.tree.add_child(node, MctsNode::new(state));
+            self.expanded.push(false);
+        }
+        self.tree.children(node).first().copied().unwrap_or(node)
+    }
+
+    fn backpropagate(&mut self, node: usize, mut reward: f64) {
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let stats = &mut self.tree[index];
+            stats.visits += 1;
+            stats.total_reward += reward;
+            reward = self.policy.propagate(reward);
+            current = self.tree.parent_index_unchecked(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A two-level tree where only the subtree of the last root child yields rewards.
+    struct Fixed;
+
+    impl MctsPolicy<(u32, u32)> for Fixed {
+        fn expand(&mut self, &(depth, branch): &(u32, u32)) -> Vec<(u32, u32)> {
+            if depth == 2 {
+                return vec![];
+            }
+            (0..3)
+                .map(|b| (depth + 1, if depth == 0 { b } else { branch }))
+                .collect()
+        }
+
+        fn simulate(&mut self, &(depth, branch): &(u32, u32)) -> f64 {
+            if depth > 0 && branch == 2 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn test_mcts_prefers_rewarding_branch() {
+        let mut search = Mcts::new((0, 0), Fixed);
+        search.run(200);
+
+        let tree = search.tree();
+        assert_eq!(tree[0].visits, 200);
+        let best = search.best_child(0).unwrap();
+        assert_eq!(tree[best].state, (1, 2));
+        let child_visits: u64 = tree.children(0).iter().map(|&c| tree[c].visits).sum();
+        assert_eq!(child_visits, 200);
+        assert_eq!(tree.len(), 13);
+    }
+
+    struct Negamax;
+
+    impl MctsPolicy<u32> for Negamax {
+        fn expand(&mut self, _: &u32) -> Vec<u32> {
+            vec![]
+        }
+
+        fn simulate(&mut self, _: &u32) -> f64 {
+            1.0
+        }
+
+        fn propagate(&self, reward: f64) -> f64 {
+            -reward
+        }
+    }
+
+    #[test]
+    fn test_mcts_terminal_root_and_propagate() {
+        let mut search = Mcts::new(0, Negamax);
+        assert_eq!(search.iterate(), 0);
+        assert_eq!(search.iterate(), 0);
+        assert_eq!(search.tree()[0].visits, 2);
+        assert_eq!(search.tree()[0].mean_reward(), 1.0);
+        assert_eq!(search.best_child(0), None);
+    }
+}
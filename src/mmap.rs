@@ -0,0 +1,416 @@
+use crate::{Tree, TreeStorage};
+use std::fmt;
+use std::io::{self, Read};
+use std::mem;
+use std::ops::Index;
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+/// The first bytes of every buffer written by [`Tree::write_mmap`].
+const MAGIC: [u8; 8] = *b"EZTREE01";
+
+/// A value written in native byte order, so that readers can detect a byte order mismatch.
+const BYTE_ORDER: u64 = 0x0102_0304_0506_0708;
+
+/// The number of `u64` fields following the magic bytes.
+const HEADER_FIELDS: usize = 6;
+
+const HEADER_SIZE: usize = MAGIC.len() + HEADER_FIELDS * mem::size_of::<u64>();
+
+/// An error produced when a byte buffer cannot be viewed as an [`MmapTree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MmapError {
+    /// The buffer does not start with the expected magic bytes.
+    BadMagic,
+    /// The buffer was written on a platform with a different byte order or index width, or
+    /// for a different node data type.
+    IncompatibleLayout,
+    /// The buffer is shorter than its header says.
+    Truncated,
+    /// The buffer is not sufficiently aligned for the indices or the node data.
+    Misaligned,
+}
+
+impl fmt::Display for MmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmapError::BadMagic => write!(f, "buffer is not a serialized tree"),
+            MmapError::IncompatibleLayout => {
+                write!(f, "tree was serialized with an incompatible layout")
+            }
+            MmapError::Truncated => write!(f, "buffer is truncated"),
+            MmapError::Misaligned => write!(f, "buffer is misaligned"),
+        }
+    }
+}
+
+impl std::error::Error for MmapError {}
+
+/// A read-only tree viewing a flat byte buffer, such as a memory-mapped file, without copying
+/// or deserializing it.
+///
+/// The buffer is written with [`Tree::write_mmap`] and holds the parent of every node, the
+/// children of every node as one contiguous array, and the node data as a `[T]` array, all in
+/// native byte order. Opening it only checks the header, so it takes constant time regardless
+/// of the size of the tree; pages are loaded by the operating system as they are accessed.
+///
+/// The buffer must be aligned to both `usize` and `T`, which memory maps always are, since
+/// they start at a page boundary. A buffer that was corrupted after being written may make
+/// methods panic, but cannot cause undefined behavior.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{MmapTree, Tree};
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node(1u32);
+/// let child = tree.add_child(root, 2);
+/// tree.add_child(child, 3);
+///
+/// let mut bytes = Vec::new();
+/// tree.write_mmap(&mut bytes).unwrap();
+/// // Stands in for a memory-mapped file, which is always page-aligned.
+/// let mut aligned = vec![0u64; bytes.len().div_ceil(8)];
+/// easy_tree::zerocopy::IntoBytes::as_mut_bytes(aligned.as_mut_slice())[..bytes.len()]
+///     .copy_from_slice(&bytes);
+/// let bytes = &easy_tree::zerocopy::IntoBytes::as_bytes(aligned.as_slice())[..bytes.len()];
+///
+/// let mapped = MmapTree::<u32>::from_bytes(bytes).unwrap();
+/// assert_eq!(mapped.children(root), &[child]);
+/// let data: Vec<u32> = mapped.iter_dfs().map(|(_, data)| *data).collect();
+/// assert_eq!(data, vec![1, 2, 3]);
+/// ```
+pub struct MmapTree<'a, T> {
+    parents: &'a [usize],
+    /// The children of node `i` are `children[child_starts[i]..child_starts[i + 1]]`.
+    child_starts: &'a [usize],
+    children: &'a [usize],
+    data: &'a [T],
+}
+
+// Implemented manually so that `T` is not required to be `Clone`/`Copy`.
+impl<T> Clone for MmapTree<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MmapTree<'_, T> {}
+
+impl<'a, T: FromBytes + Immutable> MmapTree<'a, T> {
+    /// Views a buffer written by [`Tree::write_mmap`] as a tree.
+    ///
+    /// # Parameters
+    /// - `bytes`: The buffer, aligned to both `usize` and `T`. Trailing bytes are ignored.
+    ///
+    /// # Errors
+    /// Returns an [`MmapError`] if the buffer is not a tree of `T` written on a compatible
+    /// platform, is too short, or is misaligned.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, MmapError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(MmapError::Truncated);
+        }
+        if bytes[..MAGIC.len()] != MAGIC {
+            return Err(MmapError::BadMagic);
+        }
+        let (header, _) = cast::<u64>(&bytes[MAGIC.len()..], HEADER_FIELDS)?;
+        let layout = [
+            BYTE_ORDER,
+            mem::size_of::<usize>() as u64,
+            mem::size_of::<T>() as u64,
+            mem::align_of::<T>() as u64,
+        ];
+        if header[..layout.len()] != layout {
+            return Err(MmapError::IncompatibleLayout);
+        }
+        let len = usize::try_from(header[4]).map_err(|_| MmapError::Truncated)?;
+        let child_count = usize::try_from(header[5]).map_err(|_| MmapError::Truncated)?;
+
+        let (parents, rest) = cast::<usize>(&bytes[HEADER_SIZE..], len)?;
+        let (child_starts, rest) = cast::<usize>(rest, len + 1)?;
+        let (children, rest) = cast::<usize>(rest, child_count)?;
+        let offset = data_offset::<T>(len, child_count);
+        let padding = offset - (bytes.len() - rest.len());
+        let (data, _) = cast::<T>(rest.get(padding..).ok_or(MmapError::Truncated)?, len)?;
+
+        Ok(MmapTree {
+            parents,
+            child_starts,
+            children,
+            data,
+        })
+    }
+}
+
+impl<'a, T> MmapTree<'a, T> {
+    /// Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the tree contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns a reference to the data stored in a node, or `None` if the index is out of
+    /// bounds.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        self.data.get(index)
+    }
+
+    /// Returns the data of all nodes as a contiguous slice, indexed by node.
+    pub fn data(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// Returns the indices of the children of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn children(&self, index: usize) -> &'a [usize] {
+        &self.children[self.child_starts[index]..self.child_starts[index + 1]]
+    }
+
+    /// Returns the index of the parent of a node, or `None` if it has no parent.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn parent_index_unchecked(&self, index: usize) -> Option<usize> {
+        Some(self.parents[index]).filter(|&parent| parent != usize::MAX)
+    }
+
+    /// Returns the index of the root of the tree: the first node without a parent.
+    pub fn root(&self) -> Option<usize> {
+        (0..self.len()).find(|&index| self.parent_index_unchecked(index).is_none())
+    }
+
+    /// Returns an iterator over the indices and data of the nodes, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &'a T)> {
+        self.data.iter().enumerate()
+    }
+
+    /// Returns an iterator over the indices of all nodes reachable from `root`, including
+    /// `root` itself, in depth-first pre-order.
+    ///
+    /// # Panics
+    /// The iterator panics if `root` is out of bounds.
+    pub fn reachable_from(&self, root: usize) -> impl Iterator<Item = usize> + 'a {
+        let tree = *self;
+        let mut stack = vec![root];
+        std::iter::from_fn(move || {
+            let index = stack.pop()?;
+            stack.extend(tree.children(index).iter().rev());
+            Some(index)
+        })
+    }
+
+    /// Returns an iterator over the indices and data of the nodes reachable from the root, in
+    /// depth-first pre-order. See [`Tree::iter_dfs`].
+    pub fn iter_dfs(&self) -> impl Iterator<Item = (usize, &'a T)> {
+        let data = self.data;
+        let tree = *self;
+        self.root()
+            .into_iter()
+            .flat_map(move |root| tree.reachable_from(root))
+            .map(move |index| (index, &data[index]))
+    }
+
+    /// Traverses the tree from the root in a depth-first manner. See [`Tree::traverse`].
+    pub fn traverse<S>(
+        &self,
+        mut before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) {
+        let Some(root) = self.root() else {
+            return;
+        };
+
+        let mut stack = vec![(root, false)];
+        while let Some((index, children_visited)) = stack.pop() {
+            if children_visited {
+                after_processing_the_subtree(index, &self.data[index], s);
+            } else {
+                before_processing_children(index, &self.data[index], s);
+                stack.push((index, true));
+                for &child in self.children(index).iter().rev() {
+                    stack.push((child, false));
+                }
+            }
+        }
+    }
+
+    /// Copies the tree into an owned [`Tree`], keeping all indices.
+    pub fn to_tree(&self) -> Tree<T>
+    where
+        T: Clone,
+    {
+        let mut tree = Tree::new();
+        for data in self.data {
+            tree.add_node(data.clone());
+        }
+        for index in 0..self.len() {
+            for &child in self.children(index) {
+                tree.link(index, child);
+            }
+        }
+        tree
+    }
+}
+
+impl<T> Index<usize> for MmapTree<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<T: IntoBytes + Immutable, D: TreeStorage<T>> Tree<T, D> {
+    /// Writes the tree in the flat format read by [`MmapTree::from_bytes`].
+    ///
+    /// The output is meant to be written to a file and memory-mapped later. It uses the native
+    /// byte order and index width, so it can only be read on a platform that has the same
+    /// ones.
+    ///
+    /// # Parameters
+    /// - `writer`: Where to write the tree.
+    ///
+    /// # Errors
+    /// Returns any error produced by `writer`.
+    pub fn write_mmap(&self, mut writer: impl io::Write) -> io::Result<()> {
+        let len = self.links.len();
+        let child_count: usize = self.links.iter().map(|links| links.children.len()).sum();
+
+        writer.write_all(&MAGIC)?;
+        let header: [u64; HEADER_FIELDS] = [
+            BYTE_ORDER,
+            mem::size_of::<usize>() as u64,
+            mem::size_of::<T>() as u64,
+            mem::align_of::<T>() as u64,
+            len as u64,
+            child_count as u64,
+        ];
+        writer.write_all(header.as_bytes())?;
+
+        let parents: Vec<usize> = self
+            .links
+            .iter()
+            .map(|links| links.parent().unwrap_or(usize::MAX))
+            .collect();
+        writer.write_all(parents.as_bytes())?;
+        let mut start = 0;
+        let mut child_starts = Vec::with_capacity(len + 1);
+        child_starts.push(start);
+        for links in &self.links {
+            start += links.children.len();
+            child_starts.push(start);
+        }
+        writer.write_all(child_starts.as_bytes())?;
+        for links in &self.links {
+            writer.write_all(links.children.as_bytes())?;
+        }
+
+        let written = HEADER_SIZE + (2 * len + 1 + child_count) * mem::size_of::<usize>();
+        let padding = data_offset::<T>(len, child_count) - written;
+        io::copy(&mut io::repeat(0).take(padding as u64), &mut writer)?;
+        writer.write_all(self.data.as_bytes())
+    }
+}
+
+/// Returns the offset of the node data in a buffer, which follows the indices, rounded up to
+/// the alignment of `T`.
+fn data_offset<T>(len: usize, child_count: usize) -> usize {
+    let indices_end = HEADER_SIZE + (2 * len + 1 + child_count) * mem::size_of::<usize>();
+    indices_end.next_multiple_of(mem::align_of::<T>())
+}
+
+/// Views the start of `bytes` as `count` values, returning them and the remaining bytes.
+fn cast<U: FromBytes + Immutable>(bytes: &[u8], count: usize) -> Result<(&[U], &[u8]), MmapError> {
+    <[U]>::ref_from_prefix_with_elems(bytes, count).map_err(|error| match error {
+        zerocopy::ConvertError::Alignment(_) => MmapError::Misaligned,
+        zerocopy::ConvertError::Size(_) => MmapError::Truncated,
+        zerocopy::ConvertError::Validity(never) => match never {},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Copies `bytes` into a buffer aligned to 8 bytes, like a memory map would be.
+    fn aligned(bytes: &[u8]) -> Vec<u64> {
+        let mut words = vec![0u64; bytes.len().div_ceil(8)];
+        words.as_mut_bytes()[..bytes.len()].copy_from_slice(bytes);
+        words
+    }
+
+    #[test]
+    fn test_mmap_round_trip() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(10u16);
+        let a = tree.add_child(root, 11);
+        tree.add_child(a, 12);
+        tree.add_child(root, 13);
+        let detached = tree.add_node(99);
+
+        let mut bytes = Vec::new();
+        tree.write_mmap(&mut bytes).unwrap();
+        let words = aligned(&bytes);
+        let mapped = MmapTree::<u16>::from_bytes(&words.as_bytes()[..bytes.len()]).unwrap();
+
+        assert_eq!(mapped.len(), tree.len());
+        assert_eq!(mapped.root(), Some(root));
+        assert_eq!(mapped.parent_index_unchecked(detached), None);
+        assert_eq!(mapped[a], 11);
+        for index in 0..tree.len() {
+            assert_eq!(mapped.children(index), tree.children(index));
+            assert_eq!(
+                mapped.parent_index_unchecked(index),
+                tree.parent_index_unchecked(index)
+            );
+        }
+        let mut events = Vec::new();
+        mapped.traverse(
+            |index, _, events| events.push(index),
+            |index, _, events| events.push(100 + index),
+            &mut events,
+        );
+        assert_eq!(events, vec![0, 1, 2, 102, 101, 3, 103, 100]);
+
+        let copy = mapped.to_tree();
+        assert_eq!(copy.data(), tree.data());
+        assert_eq!(copy.children(root), tree.children(root));
+    }
+
+    #[test]
+    fn test_mmap_rejects_invalid_buffers() {
+        let mut tree = Tree::new();
+        tree.add_node(1u32);
+        let mut bytes = Vec::new();
+        tree.write_mmap(&mut bytes).unwrap();
+        let words = aligned(&bytes);
+        let bytes = &words.as_bytes()[..bytes.len()];
+
+        assert!(MmapTree::<u32>::from_bytes(bytes).is_ok());
+        assert_eq!(
+            MmapTree::<u64>::from_bytes(bytes).err(),
+            Some(MmapError::IncompatibleLayout)
+        );
+        assert_eq!(
+            MmapTree::<u32>::from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(MmapError::Truncated)
+        );
+        assert_eq!(
+            MmapTree::<u32>::from_bytes(&bytes[1..]).err(),
+            Some(MmapError::BadMagic)
+        );
+        let mut shifted = vec![0u8];
+        shifted.extend_from_slice(bytes);
+        let words = aligned(&shifted);
+        assert_eq!(
+            MmapTree::<u32>::from_bytes(&words.as_bytes()[1..shifted.len()]).err(),
+            Some(MmapError::Misaligned)
+        );
+    }
+}
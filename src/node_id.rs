@@ -0,0 +1,193 @@
+use crate::{Tree, TreeError, TreeStorage};
+use std::ops::{Index, IndexMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The nonce that identifies a tree in the [`NodeId`]s it hands out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct TreeNonce(u64);
+
+impl TreeNonce {
+    /// Returns a nonce that no other tree created by this process has.
+    pub(crate) fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        TreeNonce(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A node index tagged with the tree it belongs to.
+///
+/// Plain `usize` indices from one tree are valid indices in any other tree of the same size,
+/// so mixing up trees silently addresses the wrong node. A `NodeId` remembers which tree
+/// produced it, and the checked accessors, such as [`Tree::resolve`], return
+/// [`TreeError::ForeignNode`] when it is used on another tree. A clone of a tree is another
+/// tree, so it rejects the ids of the original. Indexing a tree with a `NodeId`, as in
+/// `tree[id]`, panics on a foreign id.
+///
+/// A `NodeId` does not detect that its node was removed or moved to another index, as by
+/// [`Tree::garbage_collect`].
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Tree, TreeError};
+///
+/// let mut draft = Tree::new();
+/// let title = draft.add_node("draft title");
+/// let title = draft.node_id(title);
+///
+/// let mut published = Tree::new();
+/// published.add_node("published title");
+///
+/// assert_eq!(draft.get_by_id(title), Ok(&"draft title"));
+/// assert_eq!(published.get_by_id(title), Err(TreeError::ForeignNode(0)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    tree: TreeNonce,
+    index: usize,
+}
+
+impl NodeId {
+    /// Returns the index of the node in the tree that produced the id.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns the id of a node, tagged with this tree.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn node_id(&self, index: usize) -> NodeId {
+        assert!(index < self.links.len(), "node index out of bounds");
+        NodeId {
+            tree: self.nonce,
+            index,
+        }
+    }
+
+    /// Returns `true` if `id` was produced by this tree or by the tree it was cloned from.
+    pub fn owns(&self, id: NodeId) -> bool {
+        id.tree == self.nonce
+    }
+
+    /// Checks that `id` belongs to this tree and returns the index of its node.
+    ///
+    /// # Errors
+    /// - [`TreeError::ForeignNode`] if `id` was produced by another tree.
+    /// - [`TreeError::InvalidIndex`] if the node no longer exists, because the tree shrank.
+    pub fn resolve(&self, id: NodeId) -> Result<usize, TreeError> {
+        if !self.owns(id) {
+            return Err(TreeError::ForeignNode(id.index));
+        }
+        if id.index >= self.links.len() {
+            return Err(TreeError::InvalidIndex(id.index));
+        }
+        Ok(id.index)
+    }
+
+    /// Returns the data of the node with the given id.
+    ///
+    /// # Errors
+    /// The errors of [`Tree::resolve`].
+    pub fn get_by_id(&self, id: NodeId) -> Result<&T, TreeError> {
+        let index = self.resolve(id)?;
+        Ok(&self.data[index])
+    }
+
+    /// Returns a mutable reference to the data of the node with the given id.
+    ///
+    /// # Errors
+    /// The errors of [`Tree::resolve`].
+    pub fn get_by_id_mut(&mut self, id: NodeId) -> Result<&mut T, TreeError> {
+        let index = self.resolve(id)?;
+        Ok(&mut self.data[index])
+    }
+}
+
+/// Returns a reference to the data of the node with the given id.
+///
+/// # Panics
+/// Panics if `id` belongs to another tree or its node no longer exists. Use
+/// [`Tree::get_by_id`] for a non-panicking alternative.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::Tree;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// let root = tree.node_id(root);
+/// assert_eq!(tree[root], "root");
+/// ```
+impl<T, D: TreeStorage<T>> Index<NodeId> for Tree<T, D> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &Self::Output {
+        match self.resolve(id) {
+            Ok(index) => &self.data[index],
+            Err(error) => panic!("{error}"),
+        }
+    }
+}
+
+/// Returns a mutable reference to the data of the node with the given id.
+///
+/// # Panics
+/// Panics if `id` belongs to another tree or its node no longer exists. Use
+/// [`Tree::get_by_id_mut`] for a non-panicking alternative.
+impl<T, D: TreeStorage<T>> IndexMut<NodeId> for Tree<T, D> {
+    fn index_mut(&mut self, id: NodeId) -> &mut Self::Output {
+        match self.resolve(id) {
+            Ok(index) => &mut self.data[index],
+            Err(error) => panic!("{error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_ids_are_rejected_by_other_trees() {
+        let mut a = Tree::new();
+        let root = a.add_node(1);
+        let child = a.add_child(root, 2);
+        let child = a.node_id(child);
+        let mut b = Tree::new();
+        b.add_node(10);
+        b.add_node(20);
+
+        assert_eq!(a.resolve(child), Ok(1));
+        assert_eq!(b.resolve(child), Err(TreeError::ForeignNode(1)));
+        assert!(!b.owns(child));
+
+        // A clone is another document, so the ids of the original do not resolve on it.
+        let mut copy = a.clone();
+        assert_eq!(copy.resolve(child), Err(TreeError::ForeignNode(1)));
+        let copied_child = copy.node_id(1);
+        copy[copied_child] += 1;
+        assert_eq!(copy[copied_child], 3);
+        assert!(!a.owns(copied_child));
+        a[child] *= 10;
+        assert_eq!(a.get_by_id(child), Ok(&20));
+
+        a.clear();
+        assert_eq!(a.resolve(child), Err(TreeError::InvalidIndex(1)));
+        assert_eq!(
+            TreeError::ForeignNode(1).to_string(),
+            "node id 1 belongs to another tree"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "node id 0 belongs to another tree")]
+    fn test_indexing_with_a_foreign_id_panics() {
+        let mut a = Tree::new();
+        let root = a.add_node(1);
+        let root = a.node_id(root);
+        let b = a.clone();
+        let _ = b[root];
+    }
+}
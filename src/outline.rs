@@ -0,0 +1,227 @@
+use crate::{NodeLabel, Tree, TreeStorage};
+use std::fmt::{self, Display, Write};
+
+/// An error produced when parsing a Markdown list with [`Tree::from_markdown_list`].
+///
+/// Line numbers start at `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutlineError {
+    /// A line that is neither blank nor a list item starting with `-`, `*` or `+`.
+    NotAListItem {
+        /// The number of the line.
+        line: usize,
+    },
+    /// A list item indented less than the first item of the list.
+    IndentBelowFirstItem {
+        /// The number of the line.
+        line: usize,
+    },
+}
+
+impl Display for OutlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            OutlineError::NotAListItem { line } => write!(f, "line {line} is not a list item"),
+            OutlineError::IndentBelowFirstItem { line } => {
+                write!(f, "line {line} is indented less than the first item")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OutlineError {}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Writes the tree as a nested Markdown list, as used by outliners and note-taking tools.
+    ///
+    /// Every node is a `- label` item, indented by two spaces per level of depth, in
+    /// depth-first pre-order. Nodes without a parent are top-level items, in index order. Line
+    /// breaks in labels are written as spaces, so that every node takes one line.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("Groceries");
+    /// let fruit = tree.add_child(root, "Fruit");
+    /// tree.add_child(fruit, "Apples");
+    /// tree.add_child(root, "Bread");
+    ///
+    /// assert_eq!(
+    ///     tree.to_markdown_list(),
+    ///     "- Groceries\n  - Fruit\n    - Apples\n  - Bread\n"
+    /// );
+    /// ```
+    pub fn to_markdown_list(&self) -> String
+    where
+        T: NodeLabel,
+    {
+        self.to_markdown_list_with(|data| data.label())
+    }
+
+    /// Writes the tree as a nested Markdown list like [`Tree::to_markdown_list`], producing the
+    /// label of every node with `label_fn` instead of its [`NodeLabel`] implementation.
+    ///
+    /// # Parameters
+    /// - `label_fn`: Produces the label of a node from its data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(("Release", true));
+    /// tree.add_child(root, ("Changelog", false));
+    ///
+    /// let checklist = tree.to_markdown_list_with(|(task, done)| {
+    ///     format!("[{}] {task}", if *done { 'x' } else { ' ' })
+    /// });
+    /// assert_eq!(checklist, "- [x] Release\n  - [ ] Changelog\n");
+    /// ```
+    pub fn to_markdown_list_with<'a, L: Display>(
+        &'a self,
+        mut label_fn: impl FnMut(&'a T) -> L,
+    ) -> String {
+        let mut out = String::new();
+        let mut stack = Vec::new();
+        for root in (0..self.links.len()).filter(|&index| self.links[index].parent().is_none()) {
+            stack.push((root, 0));
+            while let Some((index, depth)) = stack.pop() {
+                let label = label_fn(&self.data[index]).to_string().replace('\n', " ");
+                writeln!(out, "{:indent$}- {label}", "", indent = 2 * depth).unwrap();
+                stack.extend(
+                    self.links[index]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, depth + 1)),
+                );
+            }
+        }
+        out
+    }
+}
+
+impl Tree<String> {
+    /// Parses a nested Markdown list into a tree of item labels, the inverse of
+    /// [`Tree::to_markdown_list`].
+    ///
+    /// Items start with `-`, `*` or `+` followed by a space. An item is a child of the closest
+    /// item above it that is indented less, so any consistent indentation width works; tabs
+    /// count as four spaces. Top-level items become nodes without a parent, in order. Blank
+    /// lines are ignored.
+    ///
+    /// # Errors
+    /// - [`OutlineError::NotAListItem`] for a line that is not a list item.
+    /// - [`OutlineError::IndentBelowFirstItem`] for an item indented less than the first one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let tree = Tree::from_markdown_list("* Plan\n    * Draft\n    * Review\n* Ship\n").unwrap();
+    /// assert_eq!(tree.len(), 4);
+    /// assert_eq!(tree.children(0), &[1, 2]);
+    /// assert_eq!(tree[3], "Ship");
+    /// assert_eq!(tree.to_markdown_list(), "- Plan\n  - Draft\n  - Review\n- Ship\n");
+    /// ```
+    pub fn from_markdown_list(text: &str) -> Result<Self, OutlineError> {
+        let mut tree = Tree::new();
+        // The indentation and index of the items that can still receive children.
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut base = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line_number = number + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let content = line.trim_start();
+            let indent: usize = line[..line.len() - content.len()]
+                .chars()
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+            let label = match content.split_at_checked(1) {
+                Some(("-" | "*" | "+", rest)) if rest.is_empty() || rest.starts_with(' ') => {
+                    rest.trim()
+                }
+                _ => return Err(OutlineError::NotAListItem { line: line_number }),
+            };
+            if indent < *base.get_or_insert(indent) {
+                return Err(OutlineError::IndentBelowFirstItem { line: line_number });
+            }
+
+            while open
+                .last()
+                .is_some_and(|&(open_indent, _)| open_indent >= indent)
+            {
+                open.pop();
+            }
+            let index = match open.last() {
+                Some(&(_, parent)) => tree.add_child(parent, label.to_string()),
+                None => tree.add_node(label.to_string()),
+            };
+            open.push((indent, index));
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_list_round_trip() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("a".to_string());
+        let b = tree.add_child(root, "b".to_string());
+        tree.add_child(b, "c".to_string());
+        tree.add_child(root, String::new());
+        let orphan = tree.add_node("e".to_string());
+        tree.add_child(orphan, "f".to_string());
+
+        let text = tree.to_markdown_list();
+        assert_eq!(text, "- a\n  - b\n    - c\n  - \n- e\n  - f\n");
+        let parsed = Tree::from_markdown_list(&text).unwrap();
+        assert_eq!(parsed.to_markdown_list(), text);
+        assert_eq!(parsed.children(root), tree.children(root));
+    }
+
+    #[test]
+    fn test_markdown_list_uses_node_label() {
+        struct Task(&'static str, bool);
+
+        impl NodeLabel for Task {
+            fn label(&self) -> std::borrow::Cow<'_, str> {
+                let mark = if self.1 { 'x' } else { ' ' };
+                format!("[{mark}] {}", self.0).into()
+            }
+        }
+
+        let mut tree = Tree::new();
+        let root = tree.add_node(Task("release", false));
+        tree.add_child(root, Task("changelog", true));
+        assert_eq!(
+            tree.to_markdown_list(),
+            "- [ ] release\n  - [x] changelog\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_list_errors() {
+        assert_eq!(
+            Tree::from_markdown_list("- a\n\n  text").err(),
+            Some(OutlineError::NotAListItem { line: 3 })
+        );
+        assert_eq!(
+            Tree::from_markdown_list("  - a\n- b").err(),
+            Some(OutlineError::IndentBelowFirstItem { line: 2 })
+        );
+        assert_eq!(
+            OutlineError::NotAListItem { line: 3 }.to_string(),
+            "line 3 is not a list item"
+        );
+    }
+}
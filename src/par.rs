@@ -0,0 +1,448 @@
+use crate::trace::Operation;
+use crate::{Tree, TreeStorage};
+use rayon::prelude::*;
+
+/// The depth below which [`Tree::par_traverse_reduce`] stops spawning parallel tasks and
+/// traverses subtrees sequentially, which also bounds its recursion.
+const PARALLEL_DEPTH: usize = 32;
+
+impl<T: Send + Sync, D: TreeStorage<T> + Send + Sync> Tree<T, D> {
+    /// Traverses the tree like [`Tree::traverse`], processing sibling subtrees in parallel.
+    ///
+    /// Each parallel task starts with its own state from `identity`, and the states of the
+    /// tasks are combined with `merge` in the order of the traversal. Provided that `merge` is
+    /// associative with `identity` as its neutral element, and the callbacks only accumulate
+    /// into the state without depending on what it already holds, the result is the same as
+    /// running [`Tree::traverse`] with a single state.
+    ///
+    /// # Parameters
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `identity`: Creates an empty state.
+    /// - `merge`: Combines the states of two consecutive parts of the traversal.
+    ///
+    /// # Returns
+    /// The merged state of the whole traversal.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// for n in 2..=4 {
+    ///     let child = tree.add_child(root, n);
+    ///     tree.add_child(child, n * 10);
+    /// }
+    ///
+    /// let (count, sum) = tree.par_traverse_reduce(
+    ///     |_, value, (count, sum)| {
+    ///         *count += 1;
+    ///         *sum += value;
+    ///     },
+    ///     |_, _, _| {},
+    ///     || (0, 0),
+    ///     |a, b| (a.0 + b.0, a.1 + b.1),
+    /// );
+    /// assert_eq!((count, sum), (7, 100));
+    /// ```
+    pub fn par_traverse_reduce<S: Send>(
+        &self,
+        before_processing_children: impl Fn(usize, &T, &mut S) + Sync,
+        after_processing_the_subtree: impl Fn(usize, &T, &mut S) + Sync,
+        identity: impl Fn() -> S + Sync,
+        merge: impl Fn(S, S) -> S + Sync,
+    ) -> S {
+        if self.is_empty() {
+            return identity();
+        }
+        let operation = Operation::start("par_traverse_reduce", self.links.len());
+        let callbacks = Callbacks {
+            before_processing_children: &before_processing_children,
+            after_processing_the_subtree: &after_processing_the_subtree,
+            identity: &identity,
+            merge: &merge,
+        };
+        let (state, visited) = self.par_reduce_from(0, 0, &callbacks);
+        operation.finish(visited);
+        state
+    }
+
+    /// Traverses the tree like [`Tree::par_traverse_reduce`], but combines the states of the
+    /// subtrees in a fixed order, so that the result does not depend on the number of threads
+    /// or on how rayon schedules the work.
+    ///
+    /// The state of every node starts from `identity`, goes through
+    /// `before_processing_children`, is merged with the state of each child subtree from left
+    /// to right, and goes through `after_processing_the_subtree`:
+    /// `after(merge(merge(before(identity()), child_1), child_2))`. This holds even when `merge`
+    /// is not associative, such as a sum of floating-point numbers, which makes the output
+    /// reproducible bit for bit.
+    ///
+    /// # Parameters
+    /// - `before_processing_children`: A function to apply before merging the children.
+    /// - `after_processing_the_subtree`: A function to apply after merging the children.
+    /// - `identity`: Creates the initial state of a node.
+    /// - `merge`: Combines the state of a node with the state of one of its child subtrees.
+    ///
+    /// # Returns
+    /// The state of every tree of the forest, merged from left to right into the state of
+    /// `identity`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node('a');
+    /// let b = tree.add_child(root, 'b');
+    /// tree.add_child(b, 'c');
+    /// tree.add_child(root, 'd');
+    ///
+    /// let nested = tree.par_traverse_ordered(
+    ///     |_, name, text: &mut String| text.push(*name),
+    ///     |_, _, text| text.push('.'),
+    ///     String::new,
+    ///     |a, b| format!("{a}({b})"),
+    /// );
+    /// assert_eq!(nested, "(a(b(c.).)(d.).)");
+    /// ```
+    pub fn par_traverse_ordered<S: Send>(
+        &self,
+        before_processing_children: impl Fn(usize, &T, &mut S) + Sync,
+        after_processing_the_subtree: impl Fn(usize, &T, &mut S) + Sync,
+        identity: impl Fn() -> S + Sync,
+        merge: impl Fn(S, S) -> S + Sync,
+    ) -> S {
+        let operation = Operation::start("par_traverse_ordered", self.links.len());
+        let callbacks = Callbacks {
+            before_processing_children: &before_processing_children,
+            after_processing_the_subtree: &after_processing_the_subtree,
+            identity: &identity,
+            merge: &merge,
+        };
+        let roots: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+        let (trees, visited) = self.par_ordered(&roots, 0, &callbacks);
+        let state = trees.into_iter().fold(identity(), &merge);
+        operation.finish(visited);
+        state
+    }
+
+    /// Returns a parallel iterator over the indices and data of the nodes in the tree, which
+    /// rayon never splits into pieces of fewer than `chunk_size` nodes.
+    ///
+    /// With cheap per-node work, the overhead of scheduling many small tasks can outweigh the
+    /// gains of parallelism; larger chunks amortize it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::rayon::prelude::*;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0u64);
+    /// for n in 1..10_000 {
+    ///     tree.add_child(root, n);
+    /// }
+    ///
+    /// let sum: u64 = tree.par_iter_chunked(1024).map(|(_, value)| *value).sum();
+    /// assert_eq!(sum, 49_995_000);
+    /// ```
+    pub fn par_iter_chunked(
+        &self,
+        chunk_size: usize,
+    ) -> impl IndexedParallelIterator<Item = (usize, &T)> {
+        self.data.par_iter().enumerate().with_min_len(chunk_size)
+    }
+
+    /// Returns a mutable parallel iterator over the indices and data of the nodes in the tree,
+    /// which rayon never splits into pieces of fewer than `chunk_size` nodes.
+    ///
+    /// See [`Tree::par_iter_chunked`].
+    pub fn par_iter_mut_chunked(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl IndexedParallelIterator<Item = (usize, &mut T)> {
+        self.data
+            .par_iter_mut()
+            .enumerate()
+            .with_min_len(chunk_size)
+    }
+
+    /// Returns a parallel iterator over the indices and data of the nodes in the subtree rooted
+    /// at `index`, including `index` itself.
+    ///
+    /// Sibling subtrees are split between threads as they are reached, so no list of indices
+    /// is collected first. Sequential adaptors such as `collect` see the nodes in depth-first
+    /// order, like [`Tree::traverse`].
+    ///
+    /// # Parameters
+    /// - `index`: The index of the root of the subtree.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::rayon::prelude::*;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0);
+    /// let branch = tree.add_child(root, 1);
+    /// tree.add_child(branch, 2);
+    /// tree.add_child(branch, 3);
+    /// tree.add_child(root, 4);
+    ///
+    /// let sum: i32 = tree.par_iter_subtree(branch).map(|(_, value)| *value).sum();
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn par_iter_subtree(&self, index: usize) -> impl ParallelIterator<Item = (usize, &T)> {
+        assert!(
+            index < self.links.len(),
+            "index {index} is out of bounds for a tree of {} nodes",
+            self.links.len()
+        );
+        rayon::iter::walk_tree_prefix(index, |&index| self.links[index].children.iter().copied())
+            .map(|index| (index, &self.data[index]))
+    }
+
+    /// Returns a parallel iterator over the indices and data of the nodes without children, in
+    /// increasing index order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::rayon::prelude::*;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let branch = tree.add_child(root, "branch");
+    /// tree.add_child(branch, "a");
+    /// tree.add_child(root, "b");
+    ///
+    /// let leaves: Vec<_> = tree.par_leaves().map(|(_, name)| *name).collect();
+    /// assert_eq!(leaves, ["a", "b"]);
+    /// ```
+    pub fn par_leaves(&self) -> impl ParallelIterator<Item = (usize, &T)> {
+        self.data
+            .par_iter()
+            .zip(self.links.par_iter())
+            .enumerate()
+            .filter(|(_, (_, links))| links.children.is_empty())
+            .map(|(index, (data, _))| (index, data))
+    }
+
+    /// Returns the states of the subtrees rooted at `roots`, in order, and their number of
+    /// nodes, for [`Tree::par_traverse_ordered`].
+    fn par_ordered<S: Send>(
+        &self,
+        roots: &[usize],
+        depth: usize,
+        callbacks: &Callbacks<'_, T, S>,
+    ) -> (Vec<S>, usize) {
+        if depth >= PARALLEL_DEPTH {
+            let mut visited = 0;
+            let states = roots
+                .iter()
+                .map(|&root| {
+                    let (state, count) = self.ordered_from(root, callbacks);
+                    visited += count;
+                    state
+                })
+                .collect();
+            return (states, visited);
+        }
+        let results: Vec<(S, usize)> = roots
+            .par_iter()
+            .map(|&index| {
+                let mut state = (callbacks.identity)();
+                (callbacks.before_processing_children)(index, &self.data[index], &mut state);
+                let children = &self.links[index].children;
+                let (children, visited) = self.par_ordered(children, depth + 1, callbacks);
+                let mut state = children.into_iter().fold(state, callbacks.merge);
+                (callbacks.after_processing_the_subtree)(index, &self.data[index], &mut state);
+                (state, visited + 1)
+            })
+            .collect();
+        let visited = results.iter().map(|(_, visited)| visited).sum();
+        (
+            results.into_iter().map(|(state, _)| state).collect(),
+            visited,
+        )
+    }
+
+    /// Returns the state of the subtree rooted at `root` and its number of nodes, combined
+    /// sequentially in the same order as [`Tree::par_ordered`].
+    fn ordered_from<S>(&self, root: usize, callbacks: &Callbacks<'_, T, S>) -> (S, usize) {
+        let start = |index: usize| {
+            let mut state = (callbacks.identity)();
+            (callbacks.before_processing_children)(index, &self.data[index], &mut state);
+            (index, Some(state), 0)
+        };
+        let mut stack = vec![start(root)];
+        let mut visited = 0;
+        loop {
+            let (index, _, next) = stack.last_mut().unwrap();
+            if let Some(&child) = self.links[*index].children.get(*next) {
+                *next += 1;
+                stack.push(start(child));
+                continue;
+            }
+            let (index, state, _) = stack.pop().unwrap();
+            let mut state = state.unwrap();
+            (callbacks.after_processing_the_subtree)(index, &self.data[index], &mut state);
+            visited += 1;
+            match stack.last_mut() {
+                Some((_, parent, _)) => {
+                    *parent = Some((callbacks.merge)(parent.take().unwrap(), state));
+                }
+                None => return (state, visited),
+            }
+        }
+    }
+
+    /// Returns the state of the subtree rooted at `index` and the number of its nodes.
+    fn par_reduce_from<S: Send>(
+        &self,
+        index: usize,
+        depth: usize,
+        callbacks: &Callbacks<'_, T, S>,
+    ) -> (S, usize) {
+        let mut state = (callbacks.identity)();
+        if depth >= PARALLEL_DEPTH {
+            let visited = self.traverse_from(
+                index,
+                callbacks.before_processing_children,
+                callbacks.after_processing_the_subtree,
+                &mut state,
+            );
+            return (state, visited);
+        }
+
+        (callbacks.before_processing_children)(index, &self.data[index], &mut state);
+        let (children, visited) = self.links[index]
+            .children
+            .par_iter()
+            .map(|&child| self.par_reduce_from(child, depth + 1, callbacks))
+            .reduce(
+                || ((callbacks.identity)(), 0),
+                |a, b| ((callbacks.merge)(a.0, b.0), a.1 + b.1),
+            );
+        let mut state = (callbacks.merge)(state, children);
+        (callbacks.after_processing_the_subtree)(index, &self.data[index], &mut state);
+        (state, visited + 1)
+    }
+}
+
+/// The callbacks of [`Tree::par_traverse_reduce`] and [`Tree::par_traverse_ordered`], bundled to be passed down the recursion.
+struct Callbacks<'c, T, S> {
+    before_processing_children: &'c (dyn Fn(usize, &T, &mut S) + Sync),
+    after_processing_the_subtree: &'c (dyn Fn(usize, &T, &mut S) + Sync),
+    identity: &'c (dyn Fn() -> S + Sync),
+    merge: &'c (dyn Fn(S, S) -> S + Sync),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_traverse_reduce_matches_traverse() {
+        // A deep chain with a few branches, deeper than the parallel cut-off.
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..500 {
+            let parent = if index % 7 == 0 { index / 2 } else { index - 1 };
+            tree.add_child(parent, index);
+        }
+
+        let mut expected = vec![];
+        tree.traverse(
+            |index, _, log: &mut Vec<(bool, usize)>| log.push((true, index)),
+            |index, _, log| log.push((false, index)),
+            &mut expected,
+        );
+        let log = tree.par_traverse_reduce(
+            |index, _, log: &mut Vec<(bool, usize)>| log.push((true, index)),
+            |index, _, log| log.push((false, index)),
+            Vec::new,
+            |mut a, b| {
+                a.extend(b);
+                a
+            },
+        );
+        assert_eq!(log, expected);
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(
+            empty.par_traverse_reduce(|_, _, _| {}, |_, _, _| {}, || 7, |a, _| a),
+            7
+        );
+    }
+
+    #[test]
+    fn test_par_iter_subtree_and_leaves() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..200 {
+            tree.add_child((index - 1) / 3, index);
+        }
+
+        let mut expected = vec![];
+        tree.traverse_from(
+            1,
+            |index, _, order| order.push(index),
+            |_, _, _| {},
+            &mut expected,
+        );
+        let order: Vec<_> = tree.par_iter_subtree(1).map(|(index, _)| index).collect();
+        assert_eq!(order, expected);
+        assert_eq!(tree.par_iter_subtree(150).count(), 1);
+
+        let leaves: Vec<_> = tree.par_leaves().map(|(index, _)| index).collect();
+        let expected: Vec<_> = (0..200)
+            .filter(|&index| tree.children(index).is_empty())
+            .collect();
+        assert_eq!(leaves, expected);
+    }
+
+    #[test]
+    fn test_par_traverse_ordered_is_independent_of_threads() {
+        // Deep enough to cross the parallel cut-off, with values whose float sum depends on
+        // the order of the additions.
+        let mut tree = Tree::new();
+        tree.add_node(0.1);
+        for index in 1..2000 {
+            let parent = if index % 5 == 0 { index / 5 } else { index - 1 };
+            tree.add_child(parent, 1.0 / index as f64 + 1e8 * (index % 3) as f64);
+        }
+        tree.add_node(0.3);
+
+        let run = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                tree.par_traverse_ordered(
+                    |_, value, sum: &mut f64| *sum += value,
+                    |_, _, sum| *sum *= 0.5,
+                    || 0.0,
+                    |a, b| a + b,
+                )
+            })
+        };
+        let single = run(1);
+        assert_eq!(single.to_bits(), run(4).to_bits());
+        assert_eq!(single.to_bits(), run(7).to_bits());
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(
+            empty.par_traverse_ordered(|_, _, _| {}, |_, _, _| {}, || 7, |a, _| a),
+            7
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::Tree;
+
+impl<T> Tree<T> {
+    /// Splits the tree into two: the nodes that satisfy `predicate` and the nodes that do not.
+    ///
+    /// Every node goes to exactly one of the two trees, so a matching node can have a
+    /// non-matching parent and the other way around. In each tree, the parent of a node is its
+    /// nearest ancestor that went to the same tree; a node without such an ancestor becomes a
+    /// node without a parent there. For example, a draft page under a published page is attached
+    /// to the nearest draft above it, or becomes a root of the drafts if there is none.
+    ///
+    /// Within each tree, nodes keep their relative index order, and the children of a node are
+    /// ordered as they are met in a depth-first traversal of this tree.
+    ///
+    /// # Parameters
+    /// - `predicate`: Returns `true` for the nodes that go to the first tree, given the index
+    ///   and data of a node.
+    ///
+    /// # Returns
+    /// The tree of matching nodes and the tree of the remaining nodes.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut site = Tree::new();
+    /// let home = site.add_node(("home", true));
+    /// let blog = site.add_child(home, ("blog", false));
+    /// site.add_child(blog, ("first post", true));
+    /// site.add_child(blog, ("ideas", false));
+    /// site.add_child(home, ("about", true));
+    ///
+    /// let (published, drafts) = site.partition(|_, (_, published)| *published);
+    /// fn names(tree: &Tree<(&'static str, bool)>) -> Vec<&'static str> {
+    ///     tree.iter_dfs().map(|(_, (name, _))| *name).collect()
+    /// }
+    /// assert_eq!(names(&published), vec!["home", "first post", "about"]);
+    /// assert_eq!(published.children(0), &[1, 2]);
+    /// assert_eq!(names(&drafts), vec!["blog", "ideas"]);
+    /// assert_eq!(drafts.children(0), &[1]);
+    /// ```
+    pub fn partition(self, mut predicate: impl FnMut(usize, &T) -> bool) -> (Tree<T>, Tree<T>) {
+        let matches: Vec<bool> = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(index, data)| predicate(index, data))
+            .collect();
+
+        let mut trees = [Tree::new(), Tree::new()];
+        let mut remap = Vec::with_capacity(matches.len());
+        for (data, &matched) in self.data.into_iter().zip(&matches) {
+            remap.push(trees[usize::from(matched)].add_node(data));
+        }
+
+        // Walk every tree of the forest, tracking the nearest ancestor on each side.
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        let mut stack = Vec::new();
+        for root in roots {
+            stack.push((root, [None, None]));
+            while let Some((index, mut nearest)) = stack.pop() {
+                let side = usize::from(matches[index]);
+                if let Some(parent) = nearest[side] {
+                    trees[side].link(parent, remap[index]);
+                }
+                nearest[side] = Some(remap[index]);
+                stack.extend(
+                    self.links[index]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, nearest)),
+                );
+            }
+        }
+
+        let [rest, matching] = trees;
+        (matching, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_links_consistent, snapshot};
+
+    #[test]
+    fn test_partition_lifts_nodes_to_nearest_ancestor() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        tree.add_child(a, 2);
+        tree.add_child(a, 4);
+        let b = tree.add_child(root, 6);
+        tree.add_child(b, 7);
+        let orphan = tree.add_node(8);
+        tree.add_child(orphan, 9);
+
+        let (even, odd) = tree.partition(|_, value| value % 2 == 0);
+
+        assert_eq!(snapshot(&even), "[0] 0\n  [1] 2\n  [2] 4\n  [3] 6\n[4] 8\n");
+        assert_eq!(snapshot(&odd), "[0] 1\n[1] 7\n[2] 9\n");
+        assert_links_consistent(&even);
+        assert_links_consistent(&odd);
+    }
+}
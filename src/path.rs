@@ -0,0 +1,140 @@
+use crate::{NodeLabel, Tree, TreeStorage};
+use std::fmt::{Display, Write};
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns the human-readable address of a node: the labels of the nodes on the path from
+    /// its root down to the node, joined by `separator`.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the node.
+    /// - `separator`: The string placed between labels.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child1");
+    /// let grandchild = tree.add_child(child, "grandchild");
+    ///
+    /// assert_eq!(tree.path_string(grandchild, "/"), "root/child1/grandchild");
+    /// ```
+    pub fn path_string(&self, index: usize, separator: &str) -> String
+    where
+        T: NodeLabel,
+    {
+        self.path_string_with(index, separator, |data| data.label())
+    }
+
+    /// Returns the human-readable address of a node like [`Tree::path_string`], producing the
+    /// label of every node with `label` instead of its [`NodeLabel`] implementation.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(("root", 0));
+    /// let child = tree.add_child(root, ("child", 1));
+    ///
+    /// assert_eq!(tree.path_string_with(child, "::", |(name, _)| name), "root::child");
+    /// ```
+    pub fn path_string_with<'a, L: Display>(
+        &'a self,
+        index: usize,
+        separator: &str,
+        mut label: impl FnMut(&'a T) -> L,
+    ) -> String {
+        let mut path = vec![index];
+        while let Some(parent) = self.links[*path.last().unwrap()].parent() {
+            path.push(parent);
+        }
+
+        let mut result = String::new();
+        for (position, &node) in path.iter().rev().enumerate() {
+            if position > 0 {
+                result.push_str(separator);
+            }
+            write!(result, "{}", label(&self.data[node])).unwrap();
+        }
+        result
+    }
+
+    /// Finds a node by its human-readable address, as produced by [`Tree::path_string`].
+    ///
+    /// The first segment of `path` is matched against the nodes without a parent, in index
+    /// order, and every following segment against the children of the node found so far. At
+    /// each step the first matching node is taken.
+    ///
+    /// # Parameters
+    /// - `path`: The address of the node.
+    /// - `separator`: The string placed between segments.
+    /// - `matcher`: Returns `true` if a node's data matches a segment.
+    ///
+    /// # Returns
+    /// The index of the node, or `None` if no node has that address.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child1");
+    /// let grandchild = tree.add_child(child, "grandchild");
+    ///
+    /// let matcher = |data: &&str, segment: &str| *data == segment;
+    /// assert_eq!(tree.node_at_path_str("root/child1/grandchild", "/", matcher), Some(grandchild));
+    /// assert_eq!(tree.node_at_path_str("root/child2", "/", matcher), None);
+    /// ```
+    pub fn node_at_path_str(
+        &self,
+        path: &str,
+        separator: &str,
+        mut matcher: impl FnMut(&T, &str) -> bool,
+    ) -> Option<usize> {
+        let mut segments = path.split(separator);
+        let first = segments.next()?;
+        let mut current = (0..self.links.len()).find(|&index| {
+            self.links[index].parent().is_none() && matcher(&self.data[index], first)
+        })?;
+        for segment in segments {
+            current = *self.links[current]
+                .children
+                .iter()
+                .find(|&&child| matcher(&self.data[child], segment))?;
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_string_round_trip() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("a".to_string());
+        let b = tree.add_child(root, "b".to_string());
+        let c = tree.add_child(b, "c".to_string());
+        let other = tree.add_node("x".to_string());
+        let y = tree.add_child(other, "b".to_string());
+
+        let matcher = |data: &String, segment: &str| data == segment;
+        for node in [root, b, c, other, y] {
+            let path = tree.path_string(node, " > ");
+            assert_eq!(tree.node_at_path_str(&path, " > ", matcher), Some(node));
+        }
+        assert_eq!(tree.path_string(c, "."), "a.b.c");
+        assert_eq!(tree.node_at_path_str("b", "/", matcher), None);
+        assert_eq!(tree.node_at_path_str("a/b/c/d", "/", matcher), None);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::{Tree, TreeStorage};
+
+/// A pattern describing the shape of a subtree.
+///
+/// Nodes are matched by their *kind*, which is computed from the node data by a user-provided
+/// classifier function passed to [`Pattern::match_at`] or [`Tree::find_matches`]. Patterns can
+/// capture nodes under a name, which can then be looked up in the resulting [`Bindings`].
+///
+/// Patterns are most conveniently written with the [`pattern!`](crate::pattern!) macro, but can
+/// also be assembled with the builder methods.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Pattern, Tree};
+///
+/// #[derive(PartialEq)]
+/// enum Kind {
+///     Add,
+///     Const,
+///     Var,
+/// }
+///
+/// let mut tree = Tree::new();
+/// let add = tree.add_node("+");
+/// let one = tree.add_child(add, "1");
+/// let x = tree.add_child(add, "x");
+///
+/// let classify = |data: &&str| match *data {
+///     "+" => Kind::Add,
+///     s if s.parse::<i64>().is_ok() => Kind::Const,
+///     _ => Kind::Var,
+/// };
+///
+/// // Add(Const(..), rhs)
+/// let pattern = Pattern::node(Kind::Add, vec![Pattern::kind(Kind::Const), Pattern::capture("rhs")]);
+/// let bindings = pattern.match_at(&tree, add, classify).unwrap();
+/// assert_eq!(bindings.get("rhs"), Some(x));
+/// assert!(pattern.match_at(&tree, one, classify).is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern<K> {
+    /// Matches any subtree.
+    Any,
+    /// Matches any subtree and binds its root under the given name.
+    Capture(&'static str),
+    /// Matches a node of the given kind. If `children` is `Some`, the node must have exactly as
+    /// many children as there are patterns and each child must match the corresponding
+    /// pattern. If it is `None`, the children are not inspected.
+    Node {
+        /// The kind the node must be classified as.
+        kind: K,
+        /// The patterns the children must match, if any.
+        children: Option<Vec<Pattern<K>>>,
+    },
+    /// Matches the inner pattern and binds the root of the matched subtree under the given name.
+    Bind(&'static str, Box<Pattern<K>>),
+}
+
+/// The nodes captured by a successful [`Pattern`] match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    captures: Vec<(&'static str, usize)>,
+}
+
+impl Bindings {
+    /// Returns the index of the node captured under `name`.
+    ///
+    /// If a pattern uses the same name more than once, the first capture is returned.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.captures
+            .iter()
+            .find(|(capture, _)| *capture == name)
+            .map(|&(_, index)| index)
+    }
+
+    /// Returns an iterator over the names and node indices of all captures, in the order they
+    /// appear in the pattern.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.captures.iter().copied()
+    }
+
+    /// Returns the number of captures.
+    pub fn len(&self) -> usize {
+        self.captures.len()
+    }
+
+    /// Returns `true` if the match did not capture anything.
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+}
+
+impl<K> Pattern<K> {
+    /// Creates a pattern matching any subtree.
+    pub fn any() -> Self {
+        Pattern::Any
+    }
+
+    /// Creates a pattern matching any subtree and capturing its root under `name`.
+    pub fn capture(name: &'static str) -> Self {
+        Pattern::Capture(name)
+    }
+
+    /// Creates a pattern matching a node of the given kind, regardless of its children.
+    pub fn kind(kind: K) -> Self {
+        Pattern::Node {
+            kind,
+            children: None,
+        }
+    }
+
+    /// Creates a pattern matching a node of the given kind whose children match `children`
+    /// exactly, in order.
+    pub fn node(kind: K, children: Vec<Pattern<K>>) -> Self {
+        Pattern::Node {
+            kind,
+            children: Some(children),
+        }
+    }
+
+    /// Wraps the pattern so that the root of the matched subtree is captured under `name`.
+    pub fn bind(self, name: &'static str) -> Self {
+        Pattern::Bind(name, Box::new(self))
+    }
+}
+
+impl<K: PartialEq> Pattern<K> {
+    /// Matches the pattern against the subtree rooted at `index`.
+    ///
+    /// # Parameters
+    /// - `tree`: The tree to match against.
+    /// - `index`: The index of the root of the subtree to match.
+    /// - `classify`: Computes the kind of a node from its data.
+    ///
+    /// # Returns
+    /// The captured nodes if the pattern matches, or `None` otherwise.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn match_at<T, D: TreeStorage<T>>(
+        &self,
+        tree: &Tree<T, D>,
+        index: usize,
+        classify: impl Fn(&T) -> K,
+    ) -> Option<Bindings> {
+        let mut bindings = Bindings::default();
+        if self.match_into(tree, index, &classify, &mut bindings) {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+
+    fn match_into<T, D: TreeStorage<T>>(
+        &self,
+        tree: &Tree<T, D>,
+        index: usize,
+        classify: &impl Fn(&T) -> K,
+        bindings: &mut Bindings,
+    ) -> bool {
+        match self {
+            Pattern::Any => true,
+            Pattern::Capture(name) => {
+                bindings.captures.push((name, index));
+                true
+            }
+            Pattern::Bind(name, inner) => {
+                bindings.captures.push((name, index));
+                inner.match_into(tree, index, classify, bindings)
+            }
+            Pattern::Node { kind, children } => {
+                let node = &tree.links[index];
+                if classify(&tree.data[index]) != *kind {
+                    return false;
+                }
+                match children {
+                    None => true,
+                    Some(patterns) => {
+                        patterns.len() == node.children.len()
+                            && patterns
+                                .iter()
+                                .zip(&node.children)
+                                .all(|(pattern, &child)| {
+                                    pattern.match_into(tree, child, classify, bindings)
+                                })
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Finds all nodes reachable from the root at which `pattern` matches, in depth-first
+    /// pre-order.
+    ///
+    /// # Parameters
+    /// - `pattern`: The pattern to match.
+    /// - `classify`: Computes the kind of a node from its data.
+    ///
+    /// # Returns
+    /// The index of every matching node together with its captures.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{pattern, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node('*');
+    /// let add = tree.add_child(root, '+');
+    /// let x = tree.add_child(add, 'x');
+    /// tree.add_child(add, '0');
+    /// tree.add_child(root, 'y');
+    ///
+    /// // Matches `x + 0`, binding the left operand.
+    /// let matches = tree.find_matches(&pattern!('+'(lhs, '0'())), |data| *data);
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].0, add);
+    /// assert_eq!(matches[0].1.get("lhs"), Some(x));
+    /// ```
+    pub fn find_matches<K: PartialEq>(
+        &self,
+        pattern: &Pattern<K>,
+        classify: impl Fn(&T) -> K,
+    ) -> Vec<(usize, Bindings)> {
+        self.iter_dfs()
+            .filter_map(|(index, _)| {
+                pattern
+                    .match_at(self, index, &classify)
+                    .map(|bindings| (index, bindings))
+            })
+            .collect()
+    }
+}
+
+/// Builds a [`Pattern`] from a compact, expression-like syntax.
+///
+/// - `_` matches any subtree.
+/// - `name` matches any subtree and captures it as `name`.
+/// - `Kind(p1, p2, ...)` matches a node classified as `Kind` whose children match the given
+///   patterns exactly. `Kind()` matches a leaf.
+/// - `Kind(..)` matches a node classified as `Kind` with any children.
+/// - `name @ pattern` matches `pattern` and captures the matched node as `name`.
+///
+/// `Kind` can be any path or literal that evaluates to a value of the classifier's output
+/// type, for example `Op::Add` or `'+'`.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{pattern, Pattern};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Kind {
+///     Add,
+///     Const,
+/// }
+///
+/// let p = pattern!(sum @ Kind::Add(Kind::Const(..), x));
+/// assert_eq!(
+///     p,
+///     Pattern::node(
+///         Kind::Add,
+///         vec![Pattern::kind(Kind::Const), Pattern::capture("x")],
+///     )
+///     .bind("sum")
+/// );
+/// ```
+#[macro_export]
+macro_rules! pattern {
+    (@children [$($done:expr),*] []) => {
+        vec![$($done),*]
+    };
+    (@children [$($done:expr),*] [$($current:tt)+]) => {
+        vec![$($done,)* $crate::pattern!($($current)+)]
+    };
+    (@children [$($done:expr),*] [$($current:tt)+] , $($rest:tt)*) => {
+        $crate::pattern!(@children [$($done,)* $crate::pattern!($($current)+)] [] $($rest)*)
+    };
+    (@children [$($done:expr),*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::pattern!(@children [$($done),*] [$($current)* $next] $($rest)*)
+    };
+    (_) => {
+        $crate::Pattern::Any
+    };
+    ($name:ident @ $($pattern:tt)+) => {
+        $crate::pattern!($($pattern)+).bind(stringify!($name))
+    };
+    ($name:ident) => {
+        $crate::Pattern::Capture(stringify!($name))
+    };
+    ($kind:literal (..)) => {
+        $crate::Pattern::kind($kind)
+    };
+    ($kind:literal ($($children:tt)*)) => {
+        $crate::Pattern::node($kind, $crate::pattern!(@children [] [] $($children)*))
+    };
+    ($($kind:ident)::+ (..)) => {
+        $crate::Pattern::kind($($kind)::+)
+    };
+    ($($kind:ident)::+ ($($children:tt)*)) => {
+        $crate::Pattern::node($($kind)::+, $crate::pattern!(@children [] [] $($children)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Kind {
+        Add,
+        Mul,
+        Const,
+        Var,
+    }
+
+    fn classify(data: &&str) -> Kind {
+        match *data {
+            "+" => Kind::Add,
+            "*" => Kind::Mul,
+            s if s.parse::<i64>().is_ok() => Kind::Const,
+            _ => Kind::Var,
+        }
+    }
+
+    // (1 + x) * (y + 2)
+    fn expression() -> Tree<&'static str> {
+        let mut tree = Tree::new();
+        let mul = tree.add_node("*");
+        let left = tree.add_child(mul, "+");
+        tree.add_child(left, "1");
+        tree.add_child(left, "x");
+        let right = tree.add_child(mul, "+");
+        tree.add_child(right, "y");
+        tree.add_child(right, "2");
+        tree
+    }
+
+    #[test]
+    fn test_pattern_macro_matches() {
+        let tree = expression();
+        let matches = tree.find_matches(&pattern!(Kind::Add(Kind::Const(), x)), classify);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, 1);
+        assert_eq!(matches[0].1.get("x"), Some(3));
+
+        let matches = tree.find_matches(&pattern!(Kind::Add(_, _)), classify);
+        assert_eq!(
+            matches.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+    }
+
+    #[test]
+    fn test_pattern_nested_bindings() {
+        let tree = expression();
+        let pattern = pattern!(m @ Kind::Mul(Kind::Add(..), r @ Kind::Add(Kind::Var(), c)));
+        let bindings = pattern.match_at(&tree, 0, classify).unwrap();
+        assert_eq!(
+            bindings.iter().collect::<Vec<_>>(),
+            vec![("m", 0), ("r", 4), ("c", 6)]
+        );
+        assert_eq!(bindings.len(), 3);
+
+        assert!(pattern!(Kind::Mul(_))
+            .match_at(&tree, 0, classify)
+            .is_none());
+        assert!(pattern!(Kind::Var()).match_at(&tree, 0, classify).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{Tree, TreeStorage};
+
+/// Marks the steps of a [`TraversalPlan`] that leave a node rather than enter it.
+const LEAVE: usize = 1 << (usize::BITS - 1);
+
+/// The order of a depth-first traversal, recorded once so that it can be replayed many times.
+///
+/// Replaying a plan walks a flat array instead of following the links of the tree, so it
+/// needs no stack and no allocation. It is meant for trees whose structure does not change
+/// between walks, while their data may.
+///
+/// The plan stays valid as long as the structure of the tree it was compiled from does not
+/// change. Replaying it on a tree with a different structure gives meaningless results, or
+/// panics if an index is out of bounds.
+///
+/// Constructed with [`Tree::compile_traversal`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraversalPlan {
+    /// Node indices in the order of the traversal, with [`LEAVE`] set on the steps that
+    /// leave a node.
+    steps: Vec<usize>,
+}
+
+impl TraversalPlan {
+    /// Returns the number of nodes visited by the plan.
+    pub fn len(&self) -> usize {
+        self.steps.len() / 2
+    }
+
+    /// Returns `true` if the plan visits no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns the indices of the visited nodes in depth-first pre-order.
+    pub fn pre_order(&self) -> impl Iterator<Item = usize> + '_ {
+        self.steps.iter().copied().filter(|step| step & LEAVE == 0)
+    }
+
+    /// Traverses `tree` in the recorded order, calling the callbacks like [`Tree::traverse`].
+    ///
+    /// # Parameters
+    /// - `tree`: The tree the plan was compiled from, or one with the same structure.
+    /// - `before_processing_children`: A function to apply before visiting children.
+    /// - `after_processing_the_subtree`: A function to apply after visiting children.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Panics
+    /// This method panics if the plan refers to nodes that `tree` does not have.
+    pub fn replay<'a, T, D: TreeStorage<T>, S>(
+        &self,
+        tree: &'a Tree<T, D>,
+        mut before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) {
+        let data = tree.data();
+        for &step in &self.steps {
+            if step & LEAVE == 0 {
+                before_processing_children(step, &data[step], s);
+            } else {
+                let index = step & !LEAVE;
+                after_processing_the_subtree(index, &data[index], s);
+            }
+        }
+    }
+
+    /// Traverses `tree` in the recorded order like [`TraversalPlan::replay`], giving the
+    /// callbacks mutable access to the data of the nodes.
+    ///
+    /// # Panics
+    /// This method panics if the plan refers to nodes that `tree` does not have.
+    pub fn replay_mut<T, D: TreeStorage<T>, S>(
+        &self,
+        tree: &mut Tree<T, D>,
+        mut before_processing_children: impl FnMut(usize, &mut T, &mut S),
+        mut after_processing_the_subtree: impl FnMut(usize, &mut T, &mut S),
+        s: &mut S,
+    ) {
+        let data = tree.data_mut();
+        for &step in &self.steps {
+            if step & LEAVE == 0 {
+                before_processing_children(step, &mut data[step], s);
+            } else {
+                let index = step & !LEAVE;
+                after_processing_the_subtree(index, &mut data[index], s);
+            }
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Records the order in which [`Tree::traverse`] visits the nodes, so that the traversal
+    /// can be replayed cheaply with [`TraversalPlan::replay`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// let child = tree.add_child(root, 2);
+    /// tree.add_child(child, 3);
+    ///
+    /// let plan = tree.compile_traversal();
+    /// for round in 0..3 {
+    ///     // Sums of every subtree, computed bottom-up.
+    ///     let mut sums = vec![0; tree.len()];
+    ///     plan.replay(
+    ///         &tree,
+    ///         |_, _, _| {},
+    ///         |index, data, sums| {
+    ///             sums[index] += data;
+    ///             if let Some(parent) = tree.parent_index_unchecked(index) {
+    ///                 sums[parent] += sums[index];
+    ///             }
+    ///         },
+    ///         &mut sums,
+    ///     );
+    ///     assert_eq!(sums[root], 6 + 3 * round);
+    ///     plan.replay_mut(&mut tree, |_, data, _| *data += 1, |_, _, _| {}, &mut ());
+    /// }
+    /// ```
+    pub fn compile_traversal(&self) -> TraversalPlan {
+        let mut steps = Vec::with_capacity(2 * self.links.len());
+        if let Some(root) = self.root() {
+            let mut stack = vec![root];
+            while let Some(step) = stack.pop() {
+                steps.push(step);
+                if step & LEAVE == 0 {
+                    stack.push(step | LEAVE);
+                    stack.extend(self.links[step].children.iter().rev());
+                }
+            }
+        }
+        TraversalPlan { steps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_matches_traverse() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..50 {
+            tree.add_child((index - 1) / (1 + index % 3), index);
+        }
+        tree.add_node(50);
+
+        let mut expected = Vec::new();
+        tree.traverse(
+            |index, _, events| events.push((index, true)),
+            |index, _, events| events.push((index, false)),
+            &mut expected,
+        );
+        let plan = tree.compile_traversal();
+        let mut events = Vec::new();
+        plan.replay(
+            &tree,
+            |index, _, events| events.push((index, true)),
+            |index, _, events| events.push((index, false)),
+            &mut events,
+        );
+        assert_eq!(events, expected);
+        assert_eq!(plan.len(), 50);
+        assert!(plan.pre_order().eq(tree.iter_dfs().map(|(index, _)| index)));
+
+        assert!(Tree::<i32>::new().compile_traversal().is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{Tree, TreeStorage};
+use rayon::ThreadPool;
+use std::sync::Arc;
+
+/// A tree whose parallel operations run on a given rayon thread pool instead of the global
+/// one, created with [`Tree::with_pool`].
+///
+/// Applications with their own pools can keep the work of easy-tree away from threads that
+/// serve latency-critical tasks. Nested parallel work, such as the recursion of
+/// [`Tree::par_traverse_reduce`], stays on the same pool.
+///
+/// The pool is shared through an [`Arc`], like the pool of
+/// [`DirOptions::with_pool`](crate::DirOptions::with_pool).
+pub struct PooledTree<'a, T, D = Vec<T>> {
+    tree: &'a Tree<T, D>,
+    pool: Arc<ThreadPool>,
+}
+
+/// A mutably borrowed tree whose parallel operations run on a given rayon thread pool, created
+/// with [`Tree::with_pool_mut`].
+///
+/// See [`PooledTree`].
+pub struct PooledTreeMut<'a, T, D = Vec<T>> {
+    tree: &'a mut Tree<T, D>,
+    pool: Arc<ThreadPool>,
+}
+
+impl<T: Send + Sync, D: TreeStorage<T> + Send + Sync> Tree<T, D> {
+    /// Scopes the parallel operations on this tree to a thread pool.
+    ///
+    /// # Parameters
+    /// - `pool`: The pool to run the operations on.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::rayon::prelude::*;
+    /// use easy_tree::rayon::ThreadPoolBuilder;
+    /// use easy_tree::Tree;
+    /// use std::sync::Arc;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// tree.add_child(root, 2);
+    /// tree.add_child(root, 3);
+    ///
+    /// let pool = Arc::new(ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+    /// let sum = tree.with_pool(pool.clone()).par_traverse_reduce(
+    ///     |_, value, sum| *sum += value,
+    ///     |_, _, _| {},
+    ///     || 0,
+    ///     |a, b| a + b,
+    /// );
+    /// assert_eq!(sum, 6);
+    ///
+    /// let leaves = tree.with_pool(pool).install(|tree| tree.par_leaves().count());
+    /// assert_eq!(leaves, 2);
+    /// ```
+    pub fn with_pool(&self, pool: Arc<ThreadPool>) -> PooledTree<'_, T, D> {
+        PooledTree { tree: self, pool }
+    }
+
+    /// Scopes the parallel operations on this tree to a thread pool, with mutable access to
+    /// the tree for operations such as [`Tree::par_iter_mut`].
+    ///
+    /// # Parameters
+    /// - `pool`: The pool to run the operations on.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::rayon::prelude::*;
+    /// use easy_tree::rayon::ThreadPoolBuilder;
+    /// use easy_tree::Tree;
+    /// use std::sync::Arc;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// tree.add_child(root, 2);
+    ///
+    /// let pool = Arc::new(ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+    /// tree.with_pool_mut(pool).install_mut(|tree| {
+    ///     tree.par_iter_mut().for_each(|(_, value)| *value *= 10);
+    /// });
+    /// assert_eq!(tree.data(), &[10, 20]);
+    /// ```
+    pub fn with_pool_mut(&mut self, pool: Arc<ThreadPool>) -> PooledTreeMut<'_, T, D> {
+        PooledTreeMut { tree: self, pool }
+    }
+}
+
+impl<T: Send + Sync, D: TreeStorage<T> + Send + Sync> PooledTree<'_, T, D> {
+    /// Runs `op` on the pool, so that the parallel iterators it creates from the tree, such as
+    /// [`Tree::par_iter`] or [`Tree::par_leaves`], are driven by the threads of the pool.
+    ///
+    /// # Returns
+    /// The result of `op`.
+    pub fn install<R: Send>(&self, op: impl FnOnce(&Tree<T, D>) -> R + Send) -> R {
+        let tree = self.tree;
+        self.pool.install(|| op(tree))
+    }
+
+    /// Runs [`Tree::par_traverse_reduce`] on the pool.
+    pub fn par_traverse_reduce<S: Send>(
+        &self,
+        before_processing_children: impl Fn(usize, &T, &mut S) + Sync,
+        after_processing_the_subtree: impl Fn(usize, &T, &mut S) + Sync,
+        identity: impl Fn() -> S + Sync,
+        merge: impl Fn(S, S) -> S + Sync,
+    ) -> S {
+        self.install(|tree| {
+            tree.par_traverse_reduce(
+                &before_processing_children,
+                &after_processing_the_subtree,
+                &identity,
+                &merge,
+            )
+        })
+    }
+
+    /// Runs [`Tree::par_traverse_ordered`] on the pool.
+    pub fn par_traverse_ordered<S: Send>(
+        &self,
+        before_processing_children: impl Fn(usize, &T, &mut S) + Sync,
+        after_processing_the_subtree: impl Fn(usize, &T, &mut S) + Sync,
+        identity: impl Fn() -> S + Sync,
+        merge: impl Fn(S, S) -> S + Sync,
+    ) -> S {
+        self.install(|tree| {
+            tree.par_traverse_ordered(
+                &before_processing_children,
+                &after_processing_the_subtree,
+                &identity,
+                &merge,
+            )
+        })
+    }
+}
+
+impl<T: Send + Sync, D: TreeStorage<T> + Send + Sync> PooledTreeMut<'_, T, D> {
+    /// Runs `op` on the pool, so that the parallel iterators it creates from the tree, such as
+    /// [`Tree::par_iter`], are driven by the threads of the pool.
+    ///
+    /// # Returns
+    /// The result of `op`.
+    pub fn install<R: Send>(&self, op: impl FnOnce(&Tree<T, D>) -> R + Send) -> R {
+        let tree = &*self.tree;
+        self.pool.install(|| op(tree))
+    }
+
+    /// Runs `op` on the pool with mutable access to the tree, so that the parallel iterators
+    /// it creates, such as [`Tree::par_iter_mut`] or [`Tree::par_iter_mut_chunked`], are
+    /// driven by the threads of the pool.
+    ///
+    /// # Returns
+    /// The result of `op`.
+    pub fn install_mut<R: Send>(&mut self, op: impl FnOnce(&mut Tree<T, D>) -> R + Send) -> R {
+        let tree = &mut *self.tree;
+        self.pool.install(|| op(tree))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use rayon::ThreadPoolBuilder;
+
+    #[test]
+    fn test_with_pool_runs_on_the_pool() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        for n in 1..100 {
+            tree.add_child(root, n);
+        }
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(3)
+            .thread_name(|index| format!("tree-pool-{index}"))
+            .build()
+            .unwrap();
+        let pool = Arc::new(pool);
+
+        let pooled = tree.with_pool(pool.clone());
+        let on_pool = pooled.par_traverse_reduce(
+            |_, _, on_pool: &mut bool| {
+                let name = std::thread::current().name().map(str::to_owned);
+                *on_pool &= name.is_some_and(|name| name.starts_with("tree-pool-"));
+            },
+            |_, _, _| {},
+            || true,
+            |a, b| a && b,
+        );
+        assert!(on_pool);
+
+        let threads = pooled.install(|tree| rayon::current_num_threads() + tree.par_iter().count());
+        assert_eq!(threads, 3 + 100);
+        let sum =
+            pooled.par_traverse_ordered(|_, n, sum| *sum += n, |_, _, _| {}, || 0, |a, b| a + b);
+        assert_eq!(sum, 4950);
+
+        let on_pool = || {
+            let name = std::thread::current().name().map(str::to_owned);
+            name.is_some_and(|name| name.starts_with("tree-pool-"))
+        };
+        let mut pooled = tree.with_pool_mut(pool);
+        let all_on_pool = pooled.install_mut(|tree| {
+            tree.par_iter_mut_chunked(10)
+                .map(|(_, value)| {
+                    *value += 1;
+                    on_pool()
+                })
+                .all(|on_pool| on_pool)
+        });
+        assert!(all_on_pool);
+        assert_eq!(pooled.install(|tree| tree.par_iter().count()), 100);
+        assert_eq!(tree[0], 1);
+    }
+}
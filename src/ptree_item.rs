@@ -0,0 +1,139 @@
+use crate::{NodeLabel, Tree};
+use ptree::{Style, TreeItem};
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io;
+
+/// A view of a subtree implementing [`ptree::TreeItem`], so that it can be printed with
+/// [`ptree::print_tree`] or [`ptree::write_tree`].
+///
+/// Constructed with [`Tree::as_ptree`] or [`Tree::as_ptree_with`].
+pub struct PtreeItem<'a, T, F> {
+    tree: &'a Tree<T>,
+    index: usize,
+    display: F,
+}
+
+impl<T, F: Clone> Clone for PtreeItem<'_, T, F> {
+    fn clone(&self) -> Self {
+        PtreeItem {
+            tree: self.tree,
+            index: self.index,
+            display: self.display.clone(),
+        }
+    }
+}
+
+impl<T, F, D> TreeItem for PtreeItem<'_, T, F>
+where
+    F: Fn(usize, &T) -> D + Clone,
+    D: Display,
+{
+    type Child = Self;
+
+    fn write_self<W: io::Write>(&self, f: &mut W, style: &Style) -> io::Result<()> {
+        let data = &self.tree.data[self.index];
+        write!(f, "{}", style.paint((self.display)(self.index, data)))
+    }
+
+    fn children(&self) -> Cow<'_, [Self]> {
+        let children = self.tree.children(self.index).iter();
+        Cow::Owned(
+            children
+                .map(|&child| self.tree.as_ptree_with(child, self.display.clone()))
+                .collect(),
+        )
+    }
+}
+
+impl<T> Tree<T> {
+    /// Returns a view of the subtree rooted at `root` that prints with the `ptree` crate,
+    /// labelling every node with its [`NodeLabel`] implementation.
+    ///
+    /// # Parameters
+    /// - `root`: The index of the node to print the subtree of.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("src");
+    /// let util = tree.add_child(root, "util");
+    /// tree.add_child(util, "helpers.rs");
+    /// tree.add_child(root, "lib.rs");
+    ///
+    /// let mut output = Vec::new();
+    /// ptree::write_tree(&tree.as_ptree(root), &mut output).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "src\n├─ util\n│  └─ helpers.rs\n└─ lib.rs\n"
+    /// );
+    /// ```
+    pub fn as_ptree(&self, root: usize) -> PtreeItem<'_, T, impl Fn(usize, &T) -> String + Clone>
+    where
+        T: NodeLabel,
+    {
+        self.as_ptree_with(root, |_, data: &T| data.label().into_owned())
+    }
+
+    /// Returns a view of the subtree rooted at `root` that prints with the `ptree` crate, like
+    /// [`Tree::as_ptree`], producing the text of every node with `display` instead of its
+    /// [`NodeLabel`] implementation.
+    ///
+    /// # Parameters
+    /// - `root`: The index of the node to print the subtree of.
+    /// - `display`: Produces the text of a node from its index and data.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("src");
+    /// tree.add_child(root, "lib.rs");
+    ///
+    /// let mut output = Vec::new();
+    /// let item = tree.as_ptree_with(root, |index, name| format!("{index}: {name}"));
+    /// ptree::write_tree(&item, &mut output).unwrap();
+    /// assert_eq!(String::from_utf8(output).unwrap(), "0: src\n└─ 1: lib.rs\n");
+    /// ```
+    pub fn as_ptree_with<F, L>(&self, root: usize, display: F) -> PtreeItem<'_, T, F>
+    where
+        F: Fn(usize, &T) -> L + Clone,
+        L: Display,
+    {
+        PtreeItem {
+            tree: self,
+            index: root,
+            display,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_ptree_prints_subtree_only() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(1);
+        let child = tree.add_child(root, 2);
+        tree.add_child(child, 3);
+        tree.add_child(child, 4);
+
+        let mut output = Vec::new();
+        let item = tree.as_ptree_with(child, |index, value| format!("#{index}={value}"));
+        assert_eq!(item.children().len(), 2);
+        ptree::write_tree(&item, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "#1=2\n├─ #2=3\n└─ #3=4\n"
+        );
+
+        let mut output = Vec::new();
+        ptree::write_tree(&tree.as_ptree(child), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "2\n├─ 3\n└─ 4\n");
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{EulerStep, Tree, TreeError};
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+
+/// A tree of Python objects, exported to Python as `easy_tree.Tree` with the `python` feature.
+///
+/// It wraps a `Tree<Py<PyAny>>`, so transformations prototyped in Python run on the same tree
+/// implementation as their Rust counterparts. Methods taking a node index raise `IndexError` if
+/// it is out of bounds.
+///
+/// To build the `easy_tree` extension module, compile the crate as a dynamic library and rename
+/// it, e.g. to `easy_tree.so` on Linux:
+///
+/// ```sh
+/// cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib
+/// ```
+///
+/// ```python
+/// from easy_tree import Tree
+///
+/// tree = Tree()
+/// root = tree.add_node("root")
+/// child = tree.add_child(root, "child")
+/// assert tree.children(root) == [child]
+/// for event, index, data in tree.traverse():
+///     print(event, index, data)
+/// ```
+#[pyclass(name = "Tree", module = "easy_tree")]
+#[derive(Default)]
+pub struct PyTree {
+    tree: Tree<Py<PyAny>>,
+}
+
+#[pymethods]
+impl PyTree {
+    /// Creates an empty tree.
+    #[new]
+    pub fn new() -> PyTree {
+        PyTree { tree: Tree::new() }
+    }
+
+    /// Returns the number of nodes in the tree.
+    pub fn __len__(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns the data of a node.
+    pub fn __getitem__(&self, py: Python<'_>, index: usize) -> PyResult<Py<PyAny>> {
+        self.check_index(index)?;
+        Ok(self.tree[index].clone_ref(py))
+    }
+
+    /// Replaces the data of a node.
+    pub fn __setitem__(&mut self, index: usize, data: Py<PyAny>) -> PyResult<()> {
+        self.check_index(index)?;
+        self.tree[index] = data;
+        Ok(())
+    }
+
+    /// Adds a node without a parent and returns its index.
+    pub fn add_node(&mut self, data: Py<PyAny>) -> usize {
+        self.tree.add_node(data)
+    }
+
+    /// Adds a node as the last child of `parent` and returns its index.
+    pub fn add_child(&mut self, parent: usize, data: Py<PyAny>) -> PyResult<usize> {
+        self.tree
+            .try_add_child(parent, data)
+            .map_err(|error| PyIndexError::new_err(error.to_string()))
+    }
+
+    /// Returns a list of the indices of the children of a node, in order.
+    pub fn children(&self, index: usize) -> PyResult<Vec<usize>> {
+        self.check_index(index)?;
+        Ok(self.tree.children(index).to_vec())
+    }
+
+    /// Returns the index of the parent of a node, or `None` if it has none.
+    pub fn parent(&self, index: usize) -> PyResult<Option<usize>> {
+        self.check_index(index)?;
+        Ok(self.tree.parent_index_unchecked(index))
+    }
+
+    /// Returns a generator of `(event, index, data)` tuples walking every root's subtree
+    /// depth-first, like [`Tree::euler_tour`], where `event` is `"enter"` or `"leave"`.
+    ///
+    /// The order is fixed when the generator is created; nodes added afterwards are not
+    /// visited.
+    pub fn traverse(slf: Py<Self>, py: Python<'_>) -> Traversal {
+        let tour = slf.borrow(py).tree.euler_tour();
+        Traversal {
+            tree: slf,
+            tour: tour.into_iter(),
+        }
+    }
+}
+
+impl PyTree {
+    /// Returns the wrapped tree, for transformations written in Rust.
+    pub fn as_tree(&self) -> &Tree<Py<PyAny>> {
+        &self.tree
+    }
+
+    /// Returns the wrapped tree mutably, for transformations written in Rust.
+    pub fn as_tree_mut(&mut self) -> &mut Tree<Py<PyAny>> {
+        &mut self.tree
+    }
+
+    fn check_index(&self, index: usize) -> PyResult<()> {
+        if index < self.tree.len() {
+            Ok(())
+        } else {
+            Err(PyIndexError::new_err(
+                TreeError::InvalidIndex(index).to_string(),
+            ))
+        }
+    }
+}
+
+impl From<Tree<Py<PyAny>>> for PyTree {
+    fn from(tree: Tree<Py<PyAny>>) -> Self {
+        PyTree { tree }
+    }
+}
+
+/// The generator returned by `Tree.traverse` in Python.
+#[pyclass(module = "easy_tree")]
+pub struct Traversal {
+    tree: Py<PyTree>,
+    tour: std::vec::IntoIter<EulerStep>,
+}
+
+#[pymethods]
+impl Traversal {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> Option<(&'static str, usize, Py<PyAny>)> {
+        let (event, index) = match self.tour.next()? {
+            EulerStep::Enter { index, .. } => ("enter", index),
+            EulerStep::Leave { index, .. } => ("leave", index),
+        };
+        Some((event, index, self.tree.borrow(py).tree[index].clone_ref(py)))
+    }
+}
+
+/// The `easy_tree` Python module.
+#[pymodule]
+fn easy_tree(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyTree>()?;
+    module.add_class::<Traversal>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_python_children_and_traversal() {
+        Python::initialize();
+        Python::attach(|py| {
+            let tree = Py::new(py, PyTree::new()).unwrap();
+            pyo3::py_run!(
+                py,
+                tree,
+                r#"
+                root = tree.add_node({"name": "root"})
+                a = tree.add_child(root, "a")
+                tree.add_child(a, "a1")
+                b = tree.add_child(root, "b")
+                assert len(tree) == 4
+                assert tree.children(root) == [a, b]
+                assert tree.parent(b) == root and tree.parent(root) is None
+                tree[b] = "B"
+                events = [(event, index) for event, index, _ in tree.traverse()]
+                assert events == [("enter", 0), ("enter", 1), ("enter", 2), ("leave", 2),
+                                  ("leave", 1), ("enter", 3), ("leave", 3), ("leave", 0)]
+                assert [data for _, _, data in tree.traverse()][5] == "B"
+                "#
+            );
+            assert_eq!(tree.borrow(py).as_tree().children(0), &[1, 3]);
+        });
+    }
+
+    #[test]
+    fn test_python_invalid_index_raises_index_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let tree = Py::new(py, PyTree::new()).unwrap();
+            pyo3::py_run!(
+                py,
+                tree,
+                r#"
+                calls = [(tree.__getitem__, (0,)), (tree.add_child, (3, None)),
+                         (tree.children, (0,)), (tree.parent, (0,))]
+                for method, args in calls:
+                    try:
+                        method(*args)
+                        raise AssertionError("expected IndexError")
+                    except IndexError as error:
+                        assert "is out of bounds" in str(error)
+                assert len(tree) == 0
+                "#
+            );
+        });
+    }
+}
@@ -0,0 +1,670 @@
+//! An XPath-like query language for selecting nodes.
+//!
+//! Queries are sequences of steps separated by `/`. Each step moves from a set of context
+//! nodes along an *axis* and keeps the nodes that pass a *node test* and all *predicates*:
+//!
+//! - `name` selects children named `name`, `*` selects all children.
+//! - `//name` selects descendants named `name` instead of children.
+//! - `..` selects the parent and `.` the context node itself.
+//! - Axes can also be written explicitly: `child::name`, `descendant::name`, `parent::*` and
+//!   `self::name`.
+//! - `[key]` keeps nodes that have the attribute `key`, `[key="value"]` and `[key!="value"]`
+//!   compare its value, and `[n]` keeps only the `n`-th node (starting at `1`) selected from
+//!   each context node.
+//!
+//! A query starting with `/` is absolute: its first step starts above the root, so
+//! `/root` selects the root if it is named `root` and `//leaf` selects every node named `leaf`.
+//! Other queries are relative to a context node, which is the root for [`Tree::query`].
+//!
+//! Node names and attributes are provided by implementing [`QueryNode`] for the node data.
+//!
+//! # Example
+//! ```rust
+//! use easy_tree::query::{Query, QueryNode};
+//! use easy_tree::Tree;
+//! use std::borrow::Cow;
+//!
+//! struct Element {
+//!     tag: &'static str,
+//!     id: Option<&'static str>,
+//! }
+//!
+//! impl QueryNode for Element {
+//!     fn name(&self) -> Cow<'_, str> {
+//!         Cow::Borrowed(self.tag)
+//!     }
+//!
+//!     fn attribute(&self, key: &str) -> Option<Cow<'_, str>> {
+//!         match key {
+//!             "id" => self.id.map(Cow::Borrowed),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! let mut tree = Tree::new();
+//! let html = tree.add_node(Element { tag: "html", id: None });
+//! let body = tree.add_child(html, Element { tag: "body", id: None });
+//! let div = tree.add_child(body, Element { tag: "div", id: Some("main") });
+//! let p = tree.add_child(div, Element { tag: "p", id: None });
+//!
+//! assert_eq!(tree.query(r#"/html/body/div[id="main"]//p"#).unwrap(), vec![p]);
+//! assert_eq!(tree.query("//p/..").unwrap(), vec![div]);
+//!
+//! let query = Query::absolute().descendant("div").has_attribute("id");
+//! assert_eq!(tree.select_query(&query), vec![div]);
+//! ```
+
+use crate::{Tree, TreeStorage};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Provides the name and attributes of node data to the query engine.
+pub trait QueryNode {
+    /// Returns the name of the node, which is compared against name tests.
+    fn name(&self) -> Cow<'_, str>;
+
+    /// Returns the value of the attribute `key`, or `None` if the node does not have it.
+    fn attribute(&self, _key: &str) -> Option<Cow<'_, str>> {
+        None
+    }
+}
+
+impl QueryNode for str {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl QueryNode for String {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T: QueryNode + ?Sized> QueryNode for &T {
+    fn name(&self) -> Cow<'_, str> {
+        (**self).name()
+    }
+
+    fn attribute(&self, key: &str) -> Option<Cow<'_, str>> {
+        (**self).attribute(key)
+    }
+}
+
+impl<T: QueryNode + ?Sized> QueryNode for Box<T> {
+    fn name(&self) -> Cow<'_, str> {
+        (**self).name()
+    }
+
+    fn attribute(&self, key: &str) -> Option<Cow<'_, str>> {
+        (**self).attribute(key)
+    }
+}
+
+/// The direction a query step moves in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The children of the context node.
+    Child,
+    /// All descendants of the context node, in depth-first pre-order.
+    Descendant,
+    /// The parent of the context node.
+    Parent,
+    /// The context node itself.
+    SelfNode,
+}
+
+/// A condition on the nodes selected by a step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+    /// The node has the attribute.
+    Has(String),
+    /// The attribute exists and equals the value.
+    Eq(String, String),
+    /// The attribute does not exist or does not equal the value.
+    Ne(String, String),
+    /// The node is the `n`-th one selected from its context node, starting at `1`.
+    Position(usize),
+}
+
+/// A single step of a [`Query`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The axis the step moves along.
+    pub axis: Axis,
+    /// The name selected nodes must have, or `None` to select nodes with any name.
+    pub name: Option<String>,
+    /// Conditions the selected nodes must satisfy, applied in order.
+    pub predicates: Vec<Predicate>,
+}
+
+/// A parsed or programmatically built query.
+///
+/// Queries can be parsed from the string syntax described in the [module
+/// documentation](self) with [`Query::parse`], or assembled with the builder methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    /// Whether the query starts above the root rather than at a context node.
+    pub absolute: bool,
+    /// The steps of the query.
+    pub steps: Vec<Step>,
+}
+
+/// An error produced when parsing a [`Query`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    /// A description of the problem.
+    pub message: String,
+    /// The byte offset in the query string at which the problem was found.
+    pub position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    /// Creates an empty absolute query, whose first step starts above the root.
+    pub fn absolute() -> Self {
+        Self {
+            absolute: true,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Creates an empty query relative to the context node.
+    pub fn relative() -> Self {
+        Self {
+            absolute: false,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Parses a query from its string representation.
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        Parser {
+            input: query,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// Adds a step selecting the children named `name`, or all children if `name` is `*`.
+    pub fn child(self, name: &str) -> Self {
+        self.step(Axis::Child, name)
+    }
+
+    /// Adds a step selecting the descendants named `name`, or all descendants if `name` is `*`.
+    pub fn descendant(self, name: &str) -> Self {
+        self.step(Axis::Descendant, name)
+    }
+
+    /// Adds a step selecting the parent of each context node.
+    pub fn parent(self) -> Self {
+        self.step(Axis::Parent, "*")
+    }
+
+    /// Adds a step along `axis`, selecting nodes named `name`, or all nodes if `name` is `*`.
+    pub fn step(mut self, axis: Axis, name: &str) -> Self {
+        self.steps.push(Step {
+            axis,
+            name: (name != "*").then(|| name.to_string()),
+            predicates: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a predicate to the last step.
+    ///
+    /// # Panics
+    /// This method panics if the query has no steps.
+    pub fn with(mut self, predicate: Predicate) -> Self {
+        self.steps
+            .last_mut()
+            .expect("a predicate needs a step to apply to")
+            .predicates
+            .push(predicate);
+        self
+    }
+
+    /// Keeps the nodes selected by the last step that have the attribute `key`.
+    pub fn has_attribute(self, key: &str) -> Self {
+        self.with(Predicate::Has(key.to_string()))
+    }
+
+    /// Keeps the nodes selected by the last step whose attribute `key` equals `value`.
+    pub fn attribute_eq(self, key: &str, value: &str) -> Self {
+        self.with(Predicate::Eq(key.to_string(), value.to_string()))
+    }
+
+    /// Keeps only the `n`-th node, starting at `1`, selected by the last step from each context
+    /// node.
+    pub fn nth(self, n: usize) -> Self {
+        self.with(Predicate::Position(n))
+    }
+
+    /// Evaluates the query with the root of `tree` as the context node.
+    ///
+    /// # Returns
+    /// The indices of the selected nodes in depth-first pre-order, without duplicates.
+    pub fn evaluate<T: QueryNode, D: TreeStorage<T>>(&self, tree: &Tree<T, D>) -> Vec<usize> {
+        match tree.root() {
+            Some(root) => self.evaluate_from(tree, root),
+            None => Vec::new(),
+        }
+    }
+
+    /// Evaluates the query with `context` as the context node. Absolute queries ignore the
+    /// context node.
+    ///
+    /// # Returns
+    /// The indices of the selected nodes in depth-first pre-order, without duplicates.
+    ///
+    /// # Panics
+    /// This method panics if `context` is out of bounds.
+    pub fn evaluate_from<T: QueryNode, D: TreeStorage<T>>(
+        &self,
+        tree: &Tree<T, D>,
+        context: usize,
+    ) -> Vec<usize> {
+        assert!(
+            context < tree.len(),
+            "node index {context} is out of bounds"
+        );
+        let mut steps = self.steps.iter();
+        let mut current = if self.absolute {
+            // The first step of an absolute query starts at a virtual node above the root.
+            let Some(first) = steps.next() else {
+                return Vec::new();
+            };
+            let root = tree.root();
+            let candidates = match first.axis {
+                Axis::Child => root.into_iter().collect(),
+                Axis::Descendant => root
+                    .into_iter()
+                    .flat_map(|root| tree.reachable_from(root))
+                    .collect(),
+                Axis::Parent | Axis::SelfNode => Vec::new(),
+            };
+            first.filter(tree, candidates)
+        } else {
+            vec![context]
+        };
+
+        let mut rank = None;
+        for step in steps {
+            let mut next = Vec::new();
+            for &node in &current {
+                let candidates = match step.axis {
+                    Axis::Child => tree.children(node).to_vec(),
+                    Axis::Descendant => tree.reachable_from(node).skip(1).collect(),
+                    Axis::Parent => tree.parent_index_unchecked(node).into_iter().collect(),
+                    Axis::SelfNode => vec![node],
+                };
+                next.extend(step.filter(tree, candidates));
+            }
+
+            // Restore document order and remove nodes selected from several context nodes.
+            let rank = rank.get_or_insert_with(|| document_order(tree));
+            next.sort_unstable_by_key(|&node| (rank[node], node));
+            next.dedup();
+            current = next;
+        }
+        current
+    }
+}
+
+impl Step {
+    fn filter<T: QueryNode, D: TreeStorage<T>>(
+        &self,
+        tree: &Tree<T, D>,
+        candidates: Vec<usize>,
+    ) -> Vec<usize> {
+        let mut selected: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&node| match &self.name {
+                Some(name) => tree.data[node].name() == name.as_str(),
+                None => true,
+            })
+            .collect();
+
+        for predicate in &self.predicates {
+            selected = match predicate {
+                Predicate::Position(n) => selected
+                    .get(n.wrapping_sub(1))
+                    .copied()
+                    .into_iter()
+                    .collect(),
+                _ => selected
+                    .into_iter()
+                    .filter(|&node| predicate.test(&tree.data[node]))
+                    .collect(),
+            };
+        }
+        selected
+    }
+}
+
+impl Predicate {
+    fn test<T: QueryNode>(&self, data: &T) -> bool {
+        match self {
+            Predicate::Has(key) => data.attribute(key).is_some(),
+            Predicate::Eq(key, value) => data.attribute(key).is_some_and(|v| v == value.as_str()),
+            Predicate::Ne(key, value) => data.attribute(key).map_or(true, |v| v != value.as_str()),
+            Predicate::Position(_) => true,
+        }
+    }
+}
+
+/// Returns the position of every node in a depth-first pre-order walk from the root. Nodes that
+/// are not reachable from the root come last.
+fn document_order<T, D: TreeStorage<T>>(tree: &Tree<T, D>) -> Vec<usize> {
+    let mut rank = vec![usize::MAX; tree.len()];
+    let nodes = tree
+        .root()
+        .into_iter()
+        .flat_map(|root| tree.reachable_from(root));
+    for (position, node) in nodes.enumerate() {
+        rank[node] = position;
+    }
+    rank
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut query = Query::relative();
+        let mut axis = Axis::Child;
+        if self.eat("//") {
+            query.absolute = true;
+            axis = Axis::Descendant;
+        } else if self.eat("/") {
+            query.absolute = true;
+        }
+
+        loop {
+            query.steps.push(self.step(axis)?);
+            if self.pos == self.input.len() {
+                return Ok(query);
+            } else if self.eat("//") {
+                axis = Axis::Descendant;
+            } else if self.eat("/") {
+                axis = Axis::Child;
+            } else {
+                return Err(self.error("expected `/` or the end of the query"));
+            }
+        }
+    }
+
+    fn step(&mut self, default_axis: Axis) -> Result<Step, QueryError> {
+        for (token, axis) in [("..", Axis::Parent), (".", Axis::SelfNode)] {
+            if self.eat(token) {
+                if default_axis != Axis::Child {
+                    return Err(self.error("`.` and `..` cannot follow `//`"));
+                }
+                return Ok(Step {
+                    axis,
+                    name: None,
+                    predicates: Vec::new(),
+                });
+            }
+        }
+
+        let mut axis = default_axis;
+        let start = self.pos;
+        let mut name = self.name();
+        if self.eat("::") {
+            if default_axis != Axis::Child {
+                return Err(self.error("an explicit axis cannot follow `//`"));
+            }
+            axis = match name {
+                "child" => Axis::Child,
+                "descendant" => Axis::Descendant,
+                "parent" => Axis::Parent,
+                "self" => Axis::SelfNode,
+                _ => {
+                    return Err(QueryError {
+                        message: format!("unknown axis `{name}`"),
+                        position: start,
+                    })
+                }
+            };
+            name = self.name();
+        }
+        let name = if name.is_empty() {
+            if !self.eat("*") {
+                return Err(self.error("expected a name or `*`"));
+            }
+            None
+        } else {
+            Some(name.to_string())
+        };
+
+        let mut predicates = Vec::new();
+        while self.eat("[") {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step {
+            axis,
+            name,
+            predicates,
+        })
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, QueryError> {
+        self.skip_whitespace();
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        let predicate = if !digits.is_empty() {
+            match digits.parse() {
+                Ok(n) if n > 0 => Predicate::Position(n),
+                _ => return Err(self.error("positions start at 1")),
+            }
+        } else {
+            let key = self.name().to_string();
+            if key.is_empty() {
+                return Err(self.error("expected an attribute name or a position"));
+            }
+            self.skip_whitespace();
+            if self.eat("!=") {
+                Predicate::Ne(key, self.string()?)
+            } else if self.eat("=") {
+                Predicate::Eq(key, self.string()?)
+            } else {
+                Predicate::Has(key)
+            }
+        };
+        self.skip_whitespace();
+        if !self.eat("]") {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(predicate)
+    }
+
+    fn string(&mut self) -> Result<String, QueryError> {
+        self.skip_whitespace();
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("expected a quoted string")),
+        };
+        self.pos += 1;
+        let value = self.take_while(|c| c != quote).to_string();
+        if !self.eat(&quote.to_string()) {
+            return Err(self.error("unterminated string"));
+        }
+        Ok(value)
+    }
+
+    fn name(&mut self) -> &'a str {
+        self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !f(c))
+            .unwrap_or(self.input.len() - start);
+        self.pos += len;
+        &self.input[start..self.pos]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            message: message.to_string(),
+            position: self.pos,
+        }
+    }
+}
+
+impl<T: QueryNode, D: TreeStorage<T>> Tree<T, D> {
+    /// Parses and evaluates a query with the root as the context node.
+    ///
+    /// See the [`query`](crate::query) module for the query syntax.
+    ///
+    /// # Returns
+    /// The indices of the selected nodes in depth-first pre-order, or an error if the query
+    /// could not be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// let leaf1 = tree.add_child(a, "leaf");
+    /// let leaf2 = tree.add_child(root, "leaf");
+    ///
+    /// assert_eq!(tree.query("//leaf").unwrap(), vec![leaf1, leaf2]);
+    /// assert_eq!(tree.query("a/leaf").unwrap(), vec![leaf1]);
+    /// assert_eq!(tree.query("/root/*[2]").unwrap(), vec![leaf2]);
+    /// assert!(tree.query("a/[").is_err());
+    /// ```
+    pub fn query(&self, query: &str) -> Result<Vec<usize>, QueryError> {
+        Ok(Query::parse(query)?.evaluate(self))
+    }
+
+    /// Evaluates an already parsed or built query with the root as the context node.
+    ///
+    /// # Returns
+    /// The indices of the selected nodes in depth-first pre-order.
+    pub fn select_query(&self, query: &Query) -> Vec<usize> {
+        query.evaluate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Item {
+        name: &'static str,
+        status: Option<&'static str>,
+    }
+
+    impl QueryNode for Item {
+        fn name(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self.name)
+        }
+
+        fn attribute(&self, key: &str) -> Option<Cow<'_, str>> {
+            (key == "status").then_some(self.status?).map(Cow::Borrowed)
+        }
+    }
+
+    fn item(name: &'static str, status: Option<&'static str>) -> Item {
+        Item { name, status }
+    }
+
+    fn sample() -> Tree<Item> {
+        let mut tree = Tree::new();
+        let root = tree.add_node(item("root", None)); // 0
+        let docs = tree.add_child(root, item("docs", None)); // 1
+        tree.add_child(docs, item("page", Some("draft"))); // 2
+        tree.add_child(docs, item("page", Some("published"))); // 3
+        let archive = tree.add_child(root, item("archive", None)); // 4
+        let old = tree.add_child(archive, item("docs", None)); // 5
+        tree.add_child(old, item("page", None)); // 6
+        tree
+    }
+
+    #[test]
+    fn test_query_axes() {
+        let tree = sample();
+        assert_eq!(tree.query("/root").unwrap(), vec![0]);
+        assert_eq!(tree.query("/docs").unwrap(), Vec::<usize>::new());
+        assert_eq!(tree.query("docs/page").unwrap(), vec![2, 3]);
+        assert_eq!(tree.query("//page").unwrap(), vec![2, 3, 6]);
+        assert_eq!(tree.query("//docs/page/..").unwrap(), vec![1, 5]);
+        assert_eq!(tree.query("/root//docs/.").unwrap(), vec![1, 5]);
+        assert_eq!(
+            tree.query("descendant::page/parent::docs").unwrap(),
+            vec![1, 5]
+        );
+        assert_eq!(tree.query("archive//*").unwrap(), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_query_predicates() {
+        let tree = sample();
+        assert_eq!(tree.query(r#"//page[status="draft"]"#).unwrap(), vec![2]);
+        assert_eq!(tree.query("//page[status != 'draft']").unwrap(), vec![3, 6]);
+        assert_eq!(tree.query("//page[status]").unwrap(), vec![2, 3]);
+        assert_eq!(tree.query("//docs/page[1]").unwrap(), vec![2, 6]);
+        assert_eq!(tree.query("//page[status][2]").unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn test_query_builder_and_context() {
+        let tree = sample();
+        let query = Query::relative()
+            .child("page")
+            .attribute_eq("status", "published");
+        assert_eq!(query.evaluate_from(&tree, 1), vec![3]);
+        assert_eq!(query.evaluate_from(&tree, 5), Vec::<usize>::new());
+        assert_eq!(
+            Query::parse("docs/page[2]").unwrap(),
+            Query::relative().child("docs").child("page").nth(2)
+        );
+    }
+
+    #[test]
+    fn test_query_parse_errors() {
+        let error = Query::parse("a/b[").unwrap_err();
+        assert_eq!(error.position, 4);
+        assert!(Query::parse("a/").is_err());
+        assert!(Query::parse("sibling::a").is_err());
+        assert!(Query::parse(r#"a[k="v]"#).is_err());
+        assert!(Query::parse("a[0]").is_err());
+        assert_eq!(
+            error.to_string(),
+            "expected an attribute name or a position at position 4"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{Tree, TreeSelection, TreeStorage, VisibleRows};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::StatefulWidget;
+
+/// A `ratatui` widget showing a tree as a list of indented, collapsible rows.
+///
+/// The expansion state, the selection and the cursor live in a [`TreeWidgetState`], which is
+/// kept across frames and updated in response to input. Only the rows inside the area are
+/// visited, so rendering costs `O(height + log n)` however large the tree is.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Tree, TreeWidget, TreeWidgetState};
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::text::Line;
+/// use ratatui::widgets::StatefulWidget;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("src");
+/// tree.add_child(root, "lib.rs");
+///
+/// let mut state = TreeWidgetState::new(&tree);
+/// let area = Rect::new(0, 0, 12, 2);
+/// let mut buffer = Buffer::empty(area);
+/// TreeWidget::new(&tree, |_, name| Line::from(*name)).render(area, &mut buffer, &mut state);
+/// let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+/// assert_eq!(text, "▾ src           lib.rs  ");
+/// ```
+pub struct TreeWidget<'a, T, F, D = Vec<T>> {
+    tree: &'a Tree<T, D>,
+    label: F,
+    indent: &'a str,
+    cursor_style: Style,
+    selected_style: Style,
+}
+
+impl<'a, T, F: FnMut(usize, &'a T) -> Line<'a>, D: TreeStorage<T>> TreeWidget<'a, T, F, D> {
+    /// Creates a widget for `tree`, rendering the label of every node with `label`.
+    pub fn new(tree: &'a Tree<T, D>, label: F) -> Self {
+        TreeWidget {
+            tree,
+            label,
+            indent: "  ",
+            cursor_style: Style::new().reversed(),
+            selected_style: Style::new().bold(),
+        }
+    }
+
+    /// Sets the string repeated before a row once per level of depth. Defaults to two spaces.
+    pub fn indent(mut self, indent: &'a str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the style of the row under the cursor. Defaults to reversed colors.
+    pub fn cursor_style(mut self, style: Style) -> Self {
+        self.cursor_style = style;
+        self
+    }
+
+    /// Sets the style of selected rows. Defaults to bold.
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+}
+
+impl<'a, T, F: FnMut(usize, &'a T) -> Line<'a>, D: TreeStorage<T>> StatefulWidget
+    for TreeWidget<'a, T, F, D>
+{
+    type State = TreeWidgetState;
+
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut TreeWidgetState) {
+        let window = state.scroll_to_cursor(self.tree, area.height as usize);
+        for (y, (index, depth)) in window.into_iter().enumerate() {
+            let row_area = Rect::new(area.x, area.y + y as u16, area.width, 1);
+            let marker = if self.tree.children(index).is_empty() {
+                "  "
+            } else if state.selection.is_expanded(index) {
+                "▾ "
+            } else {
+                "▸ "
+            };
+            let mut line = (self.label)(index, self.tree.get(index).unwrap());
+            line.spans
+                .insert(0, Span::raw(self.indent.repeat(depth) + marker));
+            if state.selection.is_selected(index) {
+                line = line.patch_style(self.selected_style);
+            }
+            buf.set_line(row_area.x, row_area.y, &line, row_area.width);
+            if state.cursor == Some(index) {
+                buf.set_style(row_area, self.cursor_style);
+            }
+        }
+    }
+}
+
+/// The state of a [`TreeWidget`]: which nodes are expanded and selected, which row the cursor
+/// is on, and how far the view is scrolled.
+///
+/// The navigation methods move the cursor over the visible rows and are meant to be bound to
+/// keys, for example the arrow keys and the space bar.
+///
+/// The state keeps a [`VisibleRows`] index of the rows, which is rebuilt when the number of
+/// nodes changes or the selection is modified through [`TreeWidgetState::selection_mut`]. Call
+/// [`TreeWidgetState::refresh`] after moving nodes of the tree.
+#[derive(Debug, Clone, Default)]
+pub struct TreeWidgetState {
+    selection: TreeSelection,
+    rows: Option<VisibleRows>,
+    cursor: Option<usize>,
+    offset: usize,
+}
+
+impl TreeWidgetState {
+    /// Creates a state for `tree` with every node expanded, nothing selected and the cursor on
+    /// the first row.
+    pub fn new<T, D: TreeStorage<T>>(tree: &Tree<T, D>) -> Self {
+        let selection = TreeSelection::new(tree);
+        let rows = tree.visible_rows(selection.collapsed_mask());
+        let cursor = rows.range(0, 1).next().map(|(index, _)| index);
+        TreeWidgetState {
+            selection,
+            rows: Some(rows),
+            cursor,
+            offset: 0,
+        }
+    }
+
+    /// Returns the selection and expansion state.
+    pub fn selection(&self) -> &TreeSelection {
+        &self.selection
+    }
+
+    /// Returns the selection and expansion state for modification.
+    ///
+    /// The index of the visible rows is rebuilt on the next render or cursor movement.
+    pub fn selection_mut(&mut self) -> &mut TreeSelection {
+        self.rows = None;
+        &mut self.selection
+    }
+
+    /// Rebuilds the index of the visible rows on the next render or cursor movement. Call this
+    /// after nodes of the tree are moved.
+    pub fn refresh(&mut self) {
+        self.rows = None;
+    }
+
+    /// Returns the index of the node under the cursor.
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// Moves the cursor to a node. The node should be visible for the cursor to be shown.
+    pub fn set_cursor(&mut self, index: Option<usize>) {
+        self.cursor = index;
+    }
+
+    /// Returns the number of rows scrolled past the top of the view.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Moves the cursor to the next visible row.
+    pub fn cursor_down<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) {
+        let row = self.cursor_row(tree).map_or(0, |row| row + 1);
+        let next = self.rows(tree).range(row, 1).next();
+        if let Some((index, _)) = next {
+            self.cursor = Some(index);
+        }
+    }
+
+    /// Moves the cursor to the previous visible row.
+    pub fn cursor_up<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) {
+        let row = match self.cursor_row(tree) {
+            Some(row) => row.saturating_sub(1),
+            None => 0,
+        };
+        let next = self.rows(tree).range(row, 1).next();
+        if let Some((index, _)) = next {
+            self.cursor = Some(index);
+        }
+    }
+
+    /// Collapses the node under the cursor, or moves the cursor to its parent if it is already
+    /// collapsed or has no children.
+    pub fn cursor_left<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        if !tree.children(cursor).is_empty() && self.selection.is_expanded(cursor) {
+            self.selection.collapse(cursor);
+            self.rows(tree).collapse(cursor);
+        } else if let Some(parent) = tree.parent_index_unchecked(cursor) {
+            self.cursor = Some(parent);
+        }
+    }
+
+    /// Expands the node under the cursor, or moves the cursor to its first child if it is
+    /// already expanded.
+    pub fn cursor_right<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        if !self.selection.is_expanded(cursor) {
+            self.selection.expand(cursor);
+            self.rows(tree).expand(cursor);
+        } else if let Some(&child) = tree.children(cursor).first() {
+            self.cursor = Some(child);
+        }
+    }
+
+    /// Toggles the selection of the node under the cursor.
+    pub fn toggle_cursor_selection(&mut self) {
+        if let Some(cursor) = self.cursor {
+            self.selection.toggle(cursor);
+        }
+    }
+
+    /// Returns the index of the visible rows, rebuilding it if it is missing or was built for
+    /// a tree with a different number of nodes.
+    fn rows<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) -> &mut VisibleRows {
+        let stale = self
+            .rows
+            .as_ref()
+            .map_or(true, |rows| rows.node_count() != tree.len());
+        if stale {
+            self.rows = Some(tree.visible_rows(self.selection.collapsed_mask()));
+        }
+        self.rows.as_mut().unwrap()
+    }
+
+    /// Returns the row of the cursor, or `None` if it is not on a visible node.
+    fn cursor_row<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) -> Option<usize> {
+        let cursor = self.cursor.filter(|&cursor| cursor < tree.len())?;
+        self.rows(tree).row_of(cursor)
+    }
+
+    /// Scrolls the view so that the cursor is inside a window of `height` rows, and returns
+    /// the nodes of the window with their depths.
+    fn scroll_to_cursor<T, D: TreeStorage<T>>(
+        &mut self,
+        tree: &Tree<T, D>,
+        height: usize,
+    ) -> Vec<(usize, usize)> {
+        if let Some(cursor) = self.cursor_row(tree) {
+            if cursor < self.offset {
+                self.offset = cursor;
+            } else if cursor >= self.offset + height {
+                self.offset = cursor + 1 - height;
+            }
+        }
+        let offset = self.offset;
+        let rows = self.rows(tree);
+        let offset = offset.min(rows.row_count().saturating_sub(height));
+        let window = rows.range(offset, height).collect();
+        self.offset = offset;
+        window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_widget_navigation_and_scrolling() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        let a1 = tree.add_child(a, "a1");
+        let b = tree.add_child(root, "b");
+
+        let mut state = TreeWidgetState::new(&tree);
+        state.cursor_down(&tree);
+        assert_eq!(state.cursor(), Some(a));
+        state.cursor_left(&tree);
+        assert!(!state.selection().is_expanded(a));
+        state.cursor_down(&tree);
+        assert_eq!(state.cursor(), Some(b));
+        state.toggle_cursor_selection();
+        state.cursor_left(&tree);
+        assert_eq!(state.cursor(), Some(root));
+        state.cursor_up(&tree);
+        assert_eq!(state.cursor(), Some(root));
+
+        state.set_cursor(Some(a));
+        state.cursor_right(&tree);
+        state.cursor_right(&tree);
+        assert_eq!(state.cursor(), Some(a1));
+
+        state.set_cursor(Some(b));
+        let area = Rect::new(0, 0, 8, 2);
+        let mut buffer = Buffer::empty(area);
+        TreeWidget::new(&tree, |_, name| Line::from(*name))
+            .cursor_style(Style::new())
+            .selected_style(Style::new())
+            .render(area, &mut buffer, &mut state);
+        assert_eq!(state.offset(), 2);
+        assert_eq!(buffer, Buffer::with_lines(["      a1", "    b   "]));
+        assert_eq!(state.selection().selected().collect::<Vec<_>>(), vec![b]);
+    }
+
+    #[test]
+    fn test_tree_widget_renders_a_window_of_a_large_tree() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..100_000 {
+            tree.add_child((index - 1) / 2, index);
+        }
+
+        let mut state = TreeWidgetState::new(&tree);
+        for _ in 0..3 {
+            state.cursor_down(&tree);
+        }
+        assert_eq!(state.cursor(), Some(7));
+        state.cursor_left(&tree);
+        state.cursor_down(&tree);
+        assert_eq!(state.cursor(), Some(8));
+
+        let area = Rect::new(0, 0, 4, 3);
+        let mut buffer = Buffer::empty(area);
+        TreeWidget::new(&tree, |_, n| Line::from(n.to_string()))
+            .indent("")
+            .cursor_style(Style::new())
+            .render(area, &mut buffer, &mut state);
+        assert_eq!(state.offset(), 2);
+        assert_eq!(buffer, Buffer::with_lines(["▾ 3 ", "▸ 7 ", "▾ 8 "]));
+
+        // Adding nodes rebuilds the index of the rows.
+        tree.add_node(100_000);
+        state.set_cursor(Some(100_000));
+        TreeWidget::new(&tree, |_, n| Line::from(n.to_string())).render(
+            area,
+            &mut buffer,
+            &mut state,
+        );
+        assert_eq!(buffer.content()[2 * 4].symbol(), " ");
+        assert_eq!(state.cursor(), Some(100_000));
+    }
+}
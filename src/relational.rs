@@ -0,0 +1,455 @@
+use crate::{EulerStep, Tree, TreeStorage};
+use std::collections::HashMap;
+use std::fmt;
+
+/// An error produced when importing a tree from a relational encoding, such as
+/// [`Tree::from_materialized_paths`] or [`Tree::from_nested_set`].
+///
+/// Rows are numbered from `0` in input order, so the row of an error is also the index its
+/// node would have had.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RelationalError {
+    /// A materialized path that is empty or has an empty segment.
+    EmptySegment {
+        /// The number of the row.
+        row: usize,
+    },
+    /// A materialized path that appears in an earlier row.
+    DuplicatePath {
+        /// The number of the row.
+        row: usize,
+    },
+    /// A materialized path whose parent path appears in no row.
+    MissingParent {
+        /// The number of the row.
+        row: usize,
+    },
+    /// Nested set bounds where `lft` is not less than `rgt`.
+    InvalidBounds {
+        /// The number of the row.
+        row: usize,
+    },
+    /// Nested set bounds that share a number with, or partly overlap, the bounds of another
+    /// row.
+    OverlappingBounds {
+        /// The number of the row.
+        row: usize,
+    },
+}
+
+impl fmt::Display for RelationalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RelationalError::EmptySegment { row } => {
+                write!(f, "row {row} has an empty path segment")
+            }
+            RelationalError::DuplicatePath { row } => {
+                write!(f, "row {row} repeats the path of an earlier row")
+            }
+            RelationalError::MissingParent { row } => {
+                write!(f, "row {row} has no row for its parent path")
+            }
+            RelationalError::InvalidBounds { row } => {
+                write!(
+                    f,
+                    "row {row} has a left bound not less than its right bound"
+                )
+            }
+            RelationalError::OverlappingBounds { row } => {
+                write!(f, "row {row} has bounds overlapping another row")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RelationalError {}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Encodes the position of every node as a materialized path, for storing the tree in a
+    /// single column of a relational table.
+    ///
+    /// The path of a node is the indices of its ancestors and of itself, from its root down,
+    /// separated by `/`. Descendants of a node are then found with a prefix query, such as
+    /// `LIKE '0/4/%'`.
+    ///
+    /// # Returns
+    /// The path of every node, indexed by node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("Electronics");
+    /// let phones = tree.add_child(root, "Phones");
+    /// tree.add_child(phones, "Android");
+    /// tree.add_child(root, "Laptops");
+    ///
+    /// assert_eq!(tree.to_materialized_paths(), vec!["0", "0/1", "0/1/2", "0/3"]);
+    /// ```
+    pub fn to_materialized_paths(&self) -> Vec<String> {
+        let mut paths = vec![String::new(); self.links.len()];
+        let mut stack: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+        while let Some(index) = stack.pop() {
+            paths[index] = match self.links[index].parent() {
+                Some(parent) => format!("{}/{index}", paths[parent]),
+                None => index.to_string(),
+            };
+            stack.extend(&self.links[index].children);
+        }
+        paths
+    }
+
+    /// Encodes the tree with the nested set model, for answering subtree queries on a
+    /// relational table without recursion.
+    ///
+    /// Every node gets a left number `lft` when a depth-first traversal enters it and a right
+    /// number `rgt` when it leaves it, counting from `1`. The descendants of a node are then
+    /// exactly the nodes whose `lft` lies between its `lft` and `rgt`, and its subtree has
+    /// `(rgt - lft + 1) / 2` nodes. Nodes without a parent are numbered one after another, in
+    /// index order.
+    ///
+    /// # Returns
+    /// The `(lft, rgt)` pair of every node, indexed by node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("Electronics");
+    /// let phones = tree.add_child(root, "Phones");
+    /// tree.add_child(phones, "Android");
+    /// tree.add_child(root, "Laptops");
+    ///
+    /// assert_eq!(tree.to_nested_set(), vec![(1, 8), (2, 5), (3, 4), (6, 7)]);
+    /// ```
+    pub fn to_nested_set(&self) -> Vec<(usize, usize)> {
+        let mut bounds = vec![(0, 0); self.links.len()];
+        let mut counter = 0;
+        for root in (0..self.links.len()).filter(|&index| self.links[index].parent().is_none()) {
+            let mut stack = vec![(root, false)];
+            while let Some((index, children_visited)) = stack.pop() {
+                counter += 1;
+                if children_visited {
+                    bounds[index].1 = counter;
+                    continue;
+                }
+                bounds[index].0 = counter;
+                stack.push((index, true));
+                stack.extend(
+                    self.links[index]
+                        .children
+                        .iter()
+                        .rev()
+                        .map(|&child| (child, false)),
+                );
+            }
+        }
+        bounds
+    }
+
+    /// Encodes the tree as a closure table, which stores every ancestor-descendant pair of the
+    /// tree as a row of a relational table.
+    ///
+    /// Every node is paired with itself at depth `0`, and with each of its ancestors at the
+    /// distance between them. The rows are generated in a single pass over
+    /// [`Tree::euler_tour`], keeping the path to the current node, so the work is proportional
+    /// to the number of rows. They are ordered by descendant in depth-first pre-order, then by
+    /// ancestor from the root down to the descendant itself.
+    ///
+    /// # Returns
+    /// The `(ancestor, descendant, depth)` rows of the tree.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("Electronics");
+    /// let phones = tree.add_child(root, "Phones");
+    /// let android = tree.add_child(phones, "Android");
+    ///
+    /// assert_eq!(
+    ///     tree.to_closure_table(),
+    ///     vec![
+    ///         (root, root, 0),
+    ///         (root, phones, 1),
+    ///         (phones, phones, 0),
+    ///         (root, android, 2),
+    ///         (phones, android, 1),
+    ///         (android, android, 0),
+    ///     ]
+    /// );
+    /// ```
+    pub fn to_closure_table(&self) -> Vec<(usize, usize, usize)> {
+        let mut rows = Vec::new();
+        let mut path = Vec::new();
+        for step in self.euler_tour() {
+            match step {
+                EulerStep::Enter { index, depth } => {
+                    path.push(index);
+                    rows.extend(path.iter().enumerate().map(|(ancestor_depth, &ancestor)| {
+                        (ancestor, index, depth - ancestor_depth)
+                    }));
+                }
+                EulerStep::Leave { .. } => {
+                    path.pop();
+                }
+            }
+        }
+        rows
+    }
+}
+
+impl<T> Tree<T> {
+    /// Builds a tree from materialized paths, the inverse of [`Tree::to_materialized_paths`].
+    ///
+    /// Every row becomes a node, in order. The segments of a path are separated by `/` and
+    /// can be any non-empty strings, such as database ids. A path with a single segment is a
+    /// node without a parent; otherwise, the path without its last segment must be the path of
+    /// another row, which may come before or after it. Children are ordered as their rows.
+    ///
+    /// # Parameters
+    /// - `rows`: The path and data of every node.
+    ///
+    /// # Errors
+    /// - [`RelationalError::EmptySegment`] for an empty path or a path with an empty segment.
+    /// - [`RelationalError::DuplicatePath`] for a path that appears in an earlier row.
+    /// - [`RelationalError::MissingParent`] for a path whose parent path appears in no row.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let rows = [("7/12", "Phones"), ("7", "Electronics"), ("7/12/30", "Android")];
+    /// let tree = Tree::from_materialized_paths(rows).unwrap();
+    /// assert_eq!(tree.path_string(2, " > "), "Electronics > Phones > Android");
+    /// assert_eq!(tree.to_materialized_paths(), vec!["1/0", "1", "1/0/2"]);
+    /// assert_eq!(tree.root_data(), Some(&"Electronics"));
+    /// ```
+    pub fn from_materialized_paths<P: AsRef<str>>(
+        rows: impl IntoIterator<Item = (P, T)>,
+    ) -> Result<Self, RelationalError> {
+        let mut tree = Tree::new();
+        let mut paths = Vec::new();
+        let mut indices = HashMap::new();
+        for (row, (path, data)) in rows.into_iter().enumerate() {
+            let path = path.as_ref().to_string();
+            if path.split('/').any(str::is_empty) {
+                return Err(RelationalError::EmptySegment { row });
+            }
+            if indices.insert(path.clone(), row).is_some() {
+                return Err(RelationalError::DuplicatePath { row });
+            }
+            tree.add_node(data);
+            paths.push(path);
+        }
+
+        for (row, path) in paths.iter().enumerate() {
+            if let Some((parent, _)) = path.rsplit_once('/') {
+                let &parent = indices
+                    .get(parent)
+                    .ok_or(RelationalError::MissingParent { row })?;
+                tree.link(parent, row);
+            }
+        }
+        Ok(tree)
+    }
+
+    /// Builds a tree from nested set bounds, the inverse of [`Tree::to_nested_set`].
+    ///
+    /// Every row becomes a node, in order, whose parent is the row with the tightest bounds
+    /// around its own. Children are ordered by their `lft`. The bounds need not be numbered
+    /// without gaps, as long as every pair of rows is either nested or disjoint.
+    ///
+    /// # Parameters
+    /// - `rows`: The `lft` and `rgt` bounds and the data of every node.
+    ///
+    /// # Errors
+    /// - [`RelationalError::InvalidBounds`] for bounds where `lft` is not less than `rgt`.
+    /// - [`RelationalError::OverlappingBounds`] for bounds that share a number with, or partly
+    ///   overlap, the bounds of another row.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let rows = [(2, 5, "Phones"), (1, 8, "Electronics"), (6, 7, "Laptops"), (3, 4, "Android")];
+    /// let tree = Tree::from_nested_set(rows).unwrap();
+    /// assert_eq!(tree.children(1), &[0, 2]);
+    /// assert_eq!(tree.children(0), &[3]);
+    /// assert_eq!(tree.root_data(), Some(&"Electronics"));
+    /// ```
+    pub fn from_nested_set(
+        rows: impl IntoIterator<Item = (usize, usize, T)>,
+    ) -> Result<Self, RelationalError> {
+        let mut tree = Tree::new();
+        let mut bounds = Vec::new();
+        for (row, (lft, rgt, data)) in rows.into_iter().enumerate() {
+            if lft >= rgt {
+                return Err(RelationalError::InvalidBounds { row });
+            }
+            tree.add_node(data);
+            bounds.push((lft, rgt));
+        }
+
+        let mut order: Vec<usize> = (0..bounds.len()).collect();
+        order.sort_by_key(|&row| bounds[row].0);
+        // The rows whose bounds enclose the current one, innermost last.
+        let mut open: Vec<usize> = Vec::new();
+        for (position, &row) in order.iter().enumerate() {
+            let (lft, rgt) = bounds[row];
+            if position > 0 && bounds[order[position - 1]].0 == lft {
+                return Err(RelationalError::OverlappingBounds { row });
+            }
+            while open.last().is_some_and(|&outer| bounds[outer].1 < lft) {
+                open.pop();
+            }
+            if let Some(&parent) = open.last() {
+                if rgt >= bounds[parent].1 {
+                    return Err(RelationalError::OverlappingBounds { row });
+                }
+                tree.link(parent, row);
+            }
+            open.push(row);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::snapshot;
+
+    fn sample() -> Tree<usize> {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..20 {
+            tree.add_child((index - 1) / 3, index);
+        }
+        let orphan = tree.add_node(20);
+        tree.add_child(orphan, 21);
+        tree.add_child(4, 22);
+        tree
+    }
+
+    #[test]
+    fn test_relational_round_trips() {
+        let tree = sample();
+        let expected = snapshot(&tree);
+
+        let paths = tree.to_materialized_paths();
+        let from_paths = Tree::from_materialized_paths(paths.iter().zip(0..)).unwrap();
+        assert_eq!(snapshot(&from_paths), expected);
+        // Parents may come after their children.
+        let reversed =
+            Tree::from_materialized_paths(paths.iter().zip(0..tree.len()).rev()).unwrap();
+        for (index, value) in reversed.iter() {
+            let parent = reversed.parent_index_unchecked(index);
+            assert_eq!(
+                parent.map(|parent| reversed[parent]),
+                tree.parent_index_unchecked(*value)
+            );
+        }
+
+        let rows = tree
+            .to_nested_set()
+            .into_iter()
+            .zip(0..)
+            .map(|((lft, rgt), data)| (lft * 10, rgt * 10, data));
+        assert_eq!(snapshot(&Tree::from_nested_set(rows).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_imports_with_children_first_traverse_from_the_root() {
+        let names = |tree: &Tree<&'static str>| {
+            let mut names = vec![];
+            tree.traverse(|_, name, names| names.push(*name), |_, _, _| {}, &mut names);
+            names
+        };
+        let expected = ["Electronics", "Phones", "Android", "Laptops"];
+
+        let rows = [
+            ("7/12", "Phones"),
+            ("7", "Electronics"),
+            ("7/12/30", "Android"),
+            ("7/15", "Laptops"),
+        ];
+        let tree = Tree::from_materialized_paths(rows).unwrap();
+        assert_eq!(tree.root(), Some(1));
+        assert_eq!(names(&tree), expected);
+
+        let rows = [
+            (2, 5, "Phones"),
+            (1, 8, "Electronics"),
+            (6, 7, "Laptops"),
+            (3, 4, "Android"),
+        ];
+        let tree = Tree::from_nested_set(rows).unwrap();
+        assert_eq!(tree.root(), Some(1));
+        assert_eq!(names(&tree), expected);
+    }
+
+    #[test]
+    fn test_closure_table_matches_ancestors() {
+        let tree = sample();
+        let rows = tree.to_closure_table();
+        let mut expected = Vec::new();
+        for index in 0..tree.len() {
+            let mut ancestor = Some(index);
+            let mut depth = 0;
+            while let Some(current) = ancestor {
+                expected.push((current, index, depth));
+                ancestor = tree.parent_index_unchecked(current);
+                depth += 1;
+            }
+        }
+        let mut sorted = rows.clone();
+        sorted.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn test_relational_errors() {
+        let paths =
+            |rows: &[&str]| Tree::from_materialized_paths(rows.iter().map(|p| (p, ()))).err();
+        assert_eq!(
+            paths(&["1", "1//2"]),
+            Some(RelationalError::EmptySegment { row: 1 })
+        );
+        assert_eq!(
+            paths(&["1", "1"]),
+            Some(RelationalError::DuplicatePath { row: 1 })
+        );
+        assert_eq!(
+            paths(&["1", "2/3"]),
+            Some(RelationalError::MissingParent { row: 1 })
+        );
+
+        let nested = |rows: &[(usize, usize)]| {
+            Tree::from_nested_set(rows.iter().map(|&(lft, rgt)| (lft, rgt, ()))).err()
+        };
+        assert_eq!(
+            nested(&[(1, 4), (3, 3)]),
+            Some(RelationalError::InvalidBounds { row: 1 })
+        );
+        assert_eq!(
+            nested(&[(1, 4), (2, 6)]),
+            Some(RelationalError::OverlappingBounds { row: 1 })
+        );
+        assert_eq!(
+            nested(&[(1, 4), (4, 6)]),
+            Some(RelationalError::OverlappingBounds { row: 1 })
+        );
+        assert_eq!(
+            nested(&[(1, 4), (1, 6)]),
+            Some(RelationalError::OverlappingBounds { row: 1 })
+        );
+    }
+}
@@ -0,0 +1,444 @@
+use crate::{Bindings, Pattern, Tree};
+use std::collections::HashSet;
+
+/// The subtree that replaces a node matched by a rewrite rule.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Replacement<T> {
+    /// Reuses an existing node, together with its subtree, from the matched subtree. The node
+    /// can be the matched node itself or any of its descendants, typically one captured by the
+    /// rule's pattern. Every existing node may only be used once per replacement.
+    Existing(usize),
+    /// A new node with the given data and children.
+    Node(T, Vec<Replacement<T>>),
+}
+
+impl<T> Replacement<T> {
+    /// Creates a replacement reusing the existing node at `index`.
+    pub fn existing(index: usize) -> Self {
+        Replacement::Existing(index)
+    }
+
+    /// Creates a replacement consisting of a new leaf node.
+    pub fn leaf(data: T) -> Self {
+        Replacement::Node(data, Vec::new())
+    }
+
+    /// Creates a replacement consisting of a new node with the given children.
+    pub fn node(data: T, children: Vec<Replacement<T>>) -> Self {
+        Replacement::Node(data, children)
+    }
+}
+
+type RewriteFn<'a, T> = dyn FnMut(&Tree<T>, usize, &Bindings) -> Option<Replacement<T>> + 'a;
+
+/// A set of rewrite rules, together with the classifier used to match their patterns.
+///
+/// See [`Tree::rewrite`].
+pub struct RewriteRules<'a, T, K> {
+    classify: Box<dyn Fn(&T) -> K + 'a>,
+    rules: Vec<(Pattern<K>, Box<RewriteFn<'a, T>>)>,
+}
+
+impl<'a, T, K: PartialEq> RewriteRules<'a, T, K> {
+    /// Creates an empty rule set.
+    ///
+    /// # Parameters
+    /// - `classify`: Computes the kind of a node from its data, see [`Pattern`].
+    pub fn new(classify: impl Fn(&T) -> K + 'a) -> Self {
+        Self {
+            classify: Box::new(classify),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Adds a rule. Rules are tried in the order they were added.
+    ///
+    /// # Parameters
+    /// - `pattern`: The pattern a subtree must match for the rule to apply.
+    /// - `rewrite`: Called with the tree, the index of the matched node and the pattern's
+    ///   captures. Returns the replacement for the matched subtree, or `None` to leave it
+    ///   unchanged.
+    pub fn rule(
+        mut self,
+        pattern: Pattern<K>,
+        rewrite: impl FnMut(&Tree<T>, usize, &Bindings) -> Option<Replacement<T>> + 'a,
+    ) -> Self {
+        self.rules.push((pattern, Box::new(rewrite)));
+        self
+    }
+}
+
+/// The order in which [`Tree::rewrite`] visits nodes during a pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewriteOrder {
+    /// Parents are rewritten before their children.
+    TopDown,
+    /// Children are rewritten before their parents.
+    BottomUp,
+}
+
+/// Controls how [`Tree::rewrite`] applies rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewriteStrategy {
+    /// The order in which nodes are visited during a pass.
+    pub order: RewriteOrder,
+    /// The maximum number of rewrites to perform, or `None` for no limit.
+    pub budget: Option<usize>,
+}
+
+impl RewriteStrategy {
+    /// Rewrites parents before their children, without a budget.
+    pub fn top_down() -> Self {
+        Self {
+            order: RewriteOrder::TopDown,
+            budget: None,
+        }
+    }
+
+    /// Rewrites children before their parents, without a budget.
+    pub fn bottom_up() -> Self {
+        Self {
+            order: RewriteOrder::BottomUp,
+            budget: None,
+        }
+    }
+
+    /// Limits the number of rewrites to `budget`. This guarantees termination even if the
+    /// rules never reach a fixpoint.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+/// The outcome of [`Tree::rewrite`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewriteReport {
+    /// The number of rewrites performed.
+    pub rewrites: usize,
+    /// `true` if no rule applies anywhere in the tree anymore, `false` if the budget ran out
+    /// first.
+    pub reached_fixpoint: bool,
+}
+
+impl<T> Tree<T> {
+    /// Repeatedly replaces subtrees matching one of the `rules` until no rule applies anymore
+    /// or the budget of the `strategy` is exhausted.
+    ///
+    /// Each pass visits all nodes reachable from the root in the order given by the strategy.
+    /// At every node, rules are tried in order until none of them applies, after which the pass
+    /// moves on. Passes are repeated until one of them performs no rewrite.
+    ///
+    /// The root of a replacement always takes over the index of the node it replaces, so the
+    /// [root](Tree::root) of the tree stays the root. Nodes discarded by rewrites are removed from the
+    /// tree once rewriting is done, which shifts the indices of the nodes that come after them.
+    ///
+    /// # Parameters
+    /// - `rules`: The rewrite rules to apply.
+    /// - `strategy`: The visiting order and the rewrite budget.
+    ///
+    /// # Returns
+    /// The number of rewrites performed, and whether a fixpoint was reached.
+    ///
+    /// # Panics
+    /// This method panics if a rule returns a [`Replacement::Existing`] node that is not part
+    /// of the matched subtree, or uses the same existing node more than once.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{pattern, Replacement, RewriteRules, RewriteStrategy, Tree};
+    ///
+    /// // (x + 0) + 0
+    /// let mut tree = Tree::new();
+    /// let outer = tree.add_node("+");
+    /// let inner = tree.add_child(outer, "+");
+    /// tree.add_child(inner, "x");
+    /// tree.add_child(inner, "0");
+    /// tree.add_child(outer, "0");
+    ///
+    /// let mut rules = RewriteRules::new(|data: &&str| *data)
+    ///     .rule(pattern!("+"(x, "0"())), |_, _, bindings| {
+    ///         Some(Replacement::existing(bindings.get("x").unwrap()))
+    ///     });
+    ///
+    /// let report = tree.rewrite(&mut rules, RewriteStrategy::bottom_up());
+    /// assert_eq!(report.rewrites, 2);
+    /// assert!(report.reached_fixpoint);
+    /// assert_eq!(tree.len(), 1);
+    /// assert_eq!(tree.get(0), Some(&"x"));
+    /// ```
+    pub fn rewrite<K: PartialEq>(
+        &mut self,
+        rules: &mut RewriteRules<'_, T, K>,
+        strategy: RewriteStrategy,
+    ) -> RewriteReport {
+        let mut report = RewriteReport::default();
+        let Some(root) = self.root() else {
+            report.reached_fixpoint = true;
+            return report;
+        };
+
+        let mut budget = strategy.budget.unwrap_or(usize::MAX);
+        let mut garbage = vec![false; self.links.len()];
+        'passes: loop {
+            let order = match strategy.order {
+                RewriteOrder::TopDown => self.reachable_from(root).collect(),
+                RewriteOrder::BottomUp => self.post_order(root),
+            };
+
+            let mut changed = false;
+            for index in order {
+                while !garbage[index] {
+                    if budget == 0 {
+                        break 'passes;
+                    }
+                    if !self.rewrite_once(rules, index, &mut garbage) {
+                        break;
+                    }
+                    budget -= 1;
+                    report.rewrites += 1;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                report.reached_fixpoint = true;
+                break;
+            }
+        }
+
+        if garbage.iter().any(|&discarded| discarded) {
+            let keep: Vec<bool> = garbage.iter().map(|discarded| !discarded).collect();
+            self.compact(&keep);
+        }
+        report
+    }
+
+    /// Applies the first rule that matches at `index` and produces a replacement. Returns
+    /// `false` if no rule applied.
+    fn rewrite_once<K: PartialEq>(
+        &mut self,
+        rules: &mut RewriteRules<'_, T, K>,
+        index: usize,
+        garbage: &mut Vec<bool>,
+    ) -> bool {
+        let classify = &rules.classify;
+        let replacement = rules.rules.iter_mut().find_map(|(pattern, rewrite)| {
+            let bindings = pattern.match_at(self, index, classify)?;
+            rewrite(self, index, &bindings)
+        });
+        match replacement {
+            Some(Replacement::Existing(existing)) if existing == index => false,
+            Some(replacement) => {
+                self.replace_subtree(index, replacement, garbage);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the subtree rooted at `index` with `replacement`, marking the nodes of the old
+    /// subtree that are not reused as garbage.
+    fn replace_subtree(
+        &mut self,
+        index: usize,
+        replacement: Replacement<T>,
+        garbage: &mut Vec<bool>,
+    ) {
+        let old_subtree: HashSet<usize> = self.reachable_from(index).collect();
+        let parent = self.links[index].replace_parent(None);
+
+        let mut used = HashSet::new();
+        let top = self.build_replacement(replacement, &old_subtree, &mut used);
+        garbage.resize(self.links.len(), false);
+
+        if let Some(parent) = parent {
+            let position = self.links[parent]
+                .children
+                .iter()
+                .position(|&child| child == index)
+                .expect("a node is always among its parent's children");
+            self.links[parent].children[position] = top;
+        }
+        self.links[top].replace_parent(parent);
+        self.swap_slots(top, index);
+
+        let live: HashSet<usize> = self.reachable_from(index).collect();
+        for old in old_subtree {
+            let old = if old == index { top } else { old };
+            if !live.contains(&old) {
+                garbage[old] = true;
+            }
+        }
+    }
+
+    fn build_replacement(
+        &mut self,
+        replacement: Replacement<T>,
+        old_subtree: &HashSet<usize>,
+        used: &mut HashSet<usize>,
+    ) -> usize {
+        match replacement {
+            Replacement::Existing(existing) => {
+                assert!(
+                    old_subtree.contains(&existing),
+                    "replacement node {existing} is not part of the matched subtree"
+                );
+                assert!(
+                    used.insert(existing),
+                    "replacement node {existing} is used more than once"
+                );
+                if let Some(parent) = self.links[existing].replace_parent(None) {
+                    self.links[parent]
+                        .children
+                        .retain(|&child| child != existing);
+                }
+                existing
+            }
+            Replacement::Node(data, children) => {
+                let index = self.add_node(data);
+                for child in children {
+                    let child = self.build_replacement(child, old_subtree, used);
+                    self.link(index, child);
+                }
+                index
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Expr {
+        Add,
+        Mul,
+        Num(i64),
+        Var(&'static str),
+    }
+
+    #[derive(PartialEq)]
+    enum Kind {
+        Add,
+        Mul,
+        Num,
+        Var,
+    }
+
+    fn classify(expr: &Expr) -> Kind {
+        match expr {
+            Expr::Add => Kind::Add,
+            Expr::Mul => Kind::Mul,
+            Expr::Num(_) => Kind::Num,
+            Expr::Var(_) => Kind::Var,
+        }
+    }
+
+    fn num(tree: &Tree<Expr>, index: usize) -> i64 {
+        match tree.get(index) {
+            Some(Expr::Num(n)) => *n,
+            _ => unreachable!(),
+        }
+    }
+
+    fn constant_folding<'a>() -> RewriteRules<'a, Expr, Kind> {
+        RewriteRules::new(classify)
+            .rule(
+                pattern!(Kind::Add(a @ Kind::Num(), b @ Kind::Num())),
+                |tree, _, b| {
+                    let sum = num(tree, b.get("a")?) + num(tree, b.get("b")?);
+                    Some(Replacement::leaf(Expr::Num(sum)))
+                },
+            )
+            .rule(
+                pattern!(Kind::Mul(a @ Kind::Num(), b @ Kind::Num())),
+                |tree, _, b| {
+                    let product = num(tree, b.get("a")?) * num(tree, b.get("b")?);
+                    Some(Replacement::leaf(Expr::Num(product)))
+                },
+            )
+            .rule(pattern!(Kind::Mul(x, Kind::Num())), |tree, index, b| {
+                (num(tree, tree.children(index)[1]) == 1)
+                    .then(|| Replacement::existing(b.get("x").unwrap()))
+            })
+    }
+
+    // x * ((1 + 2) * (3 + -2))
+    fn expression() -> Tree<Expr> {
+        let mut tree = Tree::new();
+        let root = tree.add_node(Expr::Mul);
+        tree.add_child(root, Expr::Var("x"));
+        let mul = tree.add_child(root, Expr::Mul);
+        let add1 = tree.add_child(mul, Expr::Add);
+        tree.add_child(add1, Expr::Num(1));
+        tree.add_child(add1, Expr::Num(2));
+        let add2 = tree.add_child(mul, Expr::Add);
+        tree.add_child(add2, Expr::Num(3));
+        tree.add_child(add2, Expr::Num(-2));
+        tree
+    }
+
+    #[test]
+    fn test_rewrite_bottom_up_reaches_fixpoint() {
+        let mut tree = expression();
+        let report = tree.rewrite(&mut constant_folding(), RewriteStrategy::bottom_up());
+        assert!(report.reached_fixpoint);
+        assert_eq!(report.rewrites, 3);
+        let data: Vec<_> = tree.iter_dfs().map(|(_, data)| data.clone()).collect();
+        assert_eq!(data, vec![Expr::Mul, Expr::Var("x"), Expr::Num(3)]);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_rewrite_top_down_with_budget() {
+        let mut tree = expression();
+        let strategy = RewriteStrategy::top_down().with_budget(2);
+        let report = tree.rewrite(&mut constant_folding(), strategy);
+        assert_eq!(report.rewrites, 2);
+        assert!(!report.reached_fixpoint);
+
+        let report = tree.rewrite(&mut constant_folding(), RewriteStrategy::top_down());
+        assert!(report.reached_fixpoint);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_rewrite_reuses_root_and_wraps() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(Expr::Var("x"));
+        let mut wrapped = false;
+        let mut rules = RewriteRules::new(classify).rule(pattern!(Kind::Var()), |_, index, _| {
+            if wrapped {
+                return None;
+            }
+            wrapped = true;
+            Some(Replacement::node(
+                Expr::Add,
+                vec![
+                    Replacement::existing(index),
+                    Replacement::leaf(Expr::Num(0)),
+                ],
+            ))
+        });
+        let report = tree.rewrite(&mut rules, RewriteStrategy::top_down());
+        assert_eq!(report.rewrites, 1);
+        assert_eq!(tree.get(root), Some(&Expr::Add));
+        let children = tree.children(root).to_vec();
+        assert_eq!(tree.get(children[0]), Some(&Expr::Var("x")));
+        assert_eq!(tree.get(children[1]), Some(&Expr::Num(0)));
+        assert_eq!(tree.parent_index_unchecked(children[0]), Some(root));
+    }
+
+    #[test]
+    #[should_panic(expected = "not part of the matched subtree")]
+    fn test_rewrite_rejects_foreign_nodes() {
+        let mut tree = expression();
+        let mut rules = RewriteRules::new(classify).rule(pattern!(Kind::Add(..)), |_, _, _| {
+            Some(Replacement::existing(0))
+        });
+        tree.rewrite(&mut rules, RewriteStrategy::top_down());
+    }
+}
@@ -0,0 +1,390 @@
+use crate::{Tree, TreeStorage};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::{Add, ControlFlow};
+
+/// An entry of the search frontier, ordered so that the cheapest, and among equally cheap the
+/// earliest discovered, entry is popped first from a max-heap.
+struct Frontier<C> {
+    cost: C,
+    sequence: usize,
+    index: usize,
+}
+
+impl<C: PartialOrd> PartialEq for Frontier<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: PartialOrd> Eq for Frontier<C> {}
+
+impl<C: PartialOrd> PartialOrd for Frontier<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: PartialOrd> Ord for Frontier<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Searches the subtree of `start` for a goal node, always expanding the node with the
+    /// lowest accumulated cost first.
+    ///
+    /// The cost of a path is the sum of `cost_fn` over all nodes on it, including `start`. With
+    /// non-negative costs, the first goal node found is therefore one with the cheapest path.
+    /// Nodes with equal cost are expanded in the order they were discovered.
+    ///
+    /// # Parameters
+    /// - `start`: The index of the node to start the search at.
+    /// - `cost_fn`: Returns the cost of a node.
+    /// - `goal_fn`: Returns `true` for goal nodes.
+    ///
+    /// # Returns
+    /// The indices of the nodes on the path from `start` to the goal node, inclusive, together
+    /// with the cost of the path, or `None` if no goal node was found.
+    ///
+    /// # Panics
+    /// This method panics if `start` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(("root", 0));
+    /// let expensive = tree.add_child(root, ("expensive", 10));
+    /// let cheap = tree.add_child(root, ("cheap", 1));
+    /// tree.add_child(expensive, ("goal", 1));
+    /// let step = tree.add_child(cheap, ("step", 2));
+    /// let goal = tree.add_child(step, ("goal", 1));
+    ///
+    /// let (path, cost) = tree
+    ///     .search_best_first(root, |_, data| data.1, |_, data| data.0 == "goal")
+    ///     .unwrap();
+    /// assert_eq!(path, vec![root, cheap, step, goal]);
+    /// assert_eq!(cost, 4);
+    /// ```
+    pub fn search_best_first<C>(
+        &self,
+        start: usize,
+        mut cost_fn: impl FnMut(usize, &T) -> C,
+        mut goal_fn: impl FnMut(usize, &T) -> bool,
+    ) -> Option<(Vec<usize>, C)>
+    where
+        C: PartialOrd + Add<Output = C> + Copy,
+    {
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Frontier {
+            cost: cost_fn(start, &self.data[start]),
+            sequence: 0,
+            index: start,
+        });
+        let mut sequence = 1;
+
+        while let Some(Frontier { cost, index, .. }) = frontier.pop() {
+            if goal_fn(index, &self.data[index]) {
+                let mut path = vec![index];
+                let mut current = index;
+                while current != start {
+                    current = self.links[current]
+                        .parent()
+                        .expect("nodes on the frontier descend from the start node");
+                    path.push(current);
+                }
+                path.reverse();
+                return Some((path, cost));
+            }
+
+            for &child in &self.links[index].children {
+                frontier.push(Frontier {
+                    cost: cost + cost_fn(child, &self.data[child]),
+                    sequence,
+                    index: child,
+                });
+                sequence += 1;
+            }
+        }
+        None
+    }
+
+    /// Finds the first node in depth-first pre-order that satisfies `predicate`, together with
+    /// the path leading to it.
+    ///
+    /// The search visits the nodes in the same order as [`Tree::traverse`], starting at the
+    /// [root](Tree::root), and keeps the path to the current node as it goes, so no second walk up the
+    /// ancestors is needed.
+    ///
+    /// # Parameters
+    /// - `predicate`: Returns `true` for the node to find, given its index and data.
+    ///
+    /// # Returns
+    /// The index of the node and the indices of the nodes on the path from the root down to
+    /// it, inclusive, or `None` if no node matches.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("config");
+    /// let server = tree.add_child(root, "server");
+    /// tree.add_child(server, "port");
+    /// let tls = tree.add_child(server, "tls");
+    ///
+    /// let (found, path) = tree.find_with_path(|_, key| *key == "tls").unwrap();
+    /// assert_eq!(found, tls);
+    /// assert_eq!(path, vec![root, server, tls]);
+    /// assert_eq!(tree.find_with_path(|_, key| *key == "cert"), None);
+    /// ```
+    pub fn find_with_path(
+        &self,
+        mut predicate: impl FnMut(usize, &T) -> bool,
+    ) -> Option<(usize, Vec<usize>)> {
+        let root = self.root()?;
+        let mut path = Vec::new();
+        let mut stack = vec![(root, false)];
+        while let Some((index, children_visited)) = stack.pop() {
+            if children_visited {
+                path.pop();
+                continue;
+            }
+
+            path.push(index);
+            if predicate(index, &self.data[index]) {
+                return Some((index, path));
+            }
+            stack.push((index, true));
+            stack.extend(
+                self.links[index]
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, false)),
+            );
+        }
+        None
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Traverses the tree in repeated depth-first sweeps, each going deeper than the previous
+    /// one.
+    ///
+    /// The first sweep visits the nodes at depths `0..=step`, the next one `0..=2 * step` and
+    /// so on, up to `max_depth`, the root being at depth `0`. Sweeps stop early once a sweep
+    /// has visited the whole tree. Within a sweep, the callbacks are called in the same order
+    /// as by [`Tree::traverse`], and only the stack of the current path is kept in memory.
+    ///
+    /// # Parameters
+    /// - `max_depth`: The depth limit of the last sweep.
+    /// - `step`: How many levels deeper each sweep goes.
+    /// - `before_processing_children`: Called with the index, data and depth of a node before
+    ///   its children are visited. Returning [`ControlFlow::Break`] stops the traversal.
+    /// - `after_processing_the_subtree`: Called with the index, data and depth of a node after
+    ///   its children within the depth limit have been visited.
+    /// - `s`: Mutable state to share across callbacks.
+    ///
+    /// # Returns
+    /// The value passed to [`ControlFlow::Break`], or `None` if the traversal ran to the end.
+    ///
+    /// # Panics
+    /// This method panics if `step` is `0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let a = tree.add_child(root, "a");
+    /// tree.add_child(a, "target");
+    /// let b = tree.add_child(root, "b");
+    ///
+    /// let mut visits = vec![];
+    /// let found = tree.traverse_iterative_deepening(
+    ///     10,
+    ///     1,
+    ///     |idx, data, _depth, visits: &mut Vec<usize>| {
+    ///         visits.push(idx);
+    ///         if *data == "target" {
+    ///             ControlFlow::Break(idx)
+    ///         } else {
+    ///             ControlFlow::Continue(())
+    ///         }
+    ///     },
+    ///     |_, _, _, _| {},
+    ///     &mut visits,
+    /// );
+    ///
+    /// assert_eq!(found, Some(2));
+    /// // The first sweep only reaches depth 1, the second one finds the target.
+    /// assert_eq!(visits, vec![root, a, b, root, a, 2]);
+    /// ```
+    pub fn traverse_iterative_deepening<'a, S, B>(
+        &'a self,
+        max_depth: usize,
+        step: usize,
+        mut before_processing_children: impl FnMut(usize, &'a T, usize, &mut S) -> ControlFlow<B>,
+        mut after_processing_the_subtree: impl FnMut(usize, &'a T, usize, &mut S),
+        s: &mut S,
+    ) -> Option<B> {
+        assert!(step > 0, "the depth step must be positive");
+        let root = self.root()?;
+        let mut limit = step.min(max_depth);
+        loop {
+            let mut truncated = false;
+            let mut stack = vec![(root, 0, false)];
+            while let Some((index, depth, children_visited)) = stack.pop() {
+                let node = &self.links[index];
+                if children_visited {
+                    after_processing_the_subtree(index, &self.data[index], depth, s);
+                    continue;
+                }
+
+                if let ControlFlow::Break(value) =
+                    before_processing_children(index, &self.data[index], depth, s)
+                {
+                    return Some(value);
+                }
+                stack.push((index, depth, true));
+                if depth == limit {
+                    truncated |= !node.children.is_empty();
+                    continue;
+                }
+                for &child in node.children.iter().rev() {
+                    stack.push((child, depth + 1, false));
+                }
+            }
+
+            if !truncated || limit == max_depth {
+                return None;
+            }
+            limit = limit.saturating_add(step).min(max_depth);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_with_path_matches_ancestors() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..40 {
+            tree.add_child((index - 1) / 3, index);
+        }
+        let detached = tree.add_node(40);
+
+        for target in 0..40 {
+            let (found, path) = tree.find_with_path(|_, &data| data == target).unwrap();
+            let mut expected = vec![found];
+            while let Some(parent) = tree.parent_index_unchecked(*expected.last().unwrap()) {
+                expected.push(parent);
+            }
+            expected.reverse();
+            assert_eq!(path, expected);
+        }
+        // Only the subtree of the root is searched.
+        assert_eq!(tree.find_with_path(|index, _| index == detached), None);
+        // The first match in pre-order wins.
+        assert_eq!(
+            tree.find_with_path(|_, &data| data % 2 == 1),
+            Some((1, vec![0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_search_best_first_float_costs() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0.0f64);
+        let a = tree.add_child(root, 0.5);
+        let b = tree.add_child(root, 0.25);
+        let a_goal = tree.add_child(a, 0.1);
+        let b_goal = tree.add_child(b, 0.5);
+
+        let goals = [a_goal, b_goal];
+        let (path, cost) = tree
+            .search_best_first(root, |_, &c| c, |idx, _| goals.contains(&idx))
+            .unwrap();
+        assert_eq!(path, vec![root, a, a_goal]);
+        assert!((cost - 0.6).abs() < 1e-9);
+
+        // The search stays within the subtree of the start node.
+        let (path, _) = tree
+            .search_best_first(b, |_, &c| c, |idx, _| goals.contains(&idx))
+            .unwrap();
+        assert_eq!(path, vec![b, b_goal]);
+        assert!(tree
+            .search_best_first(a_goal, |_, &c| c, |idx, _| idx == b_goal)
+            .is_none());
+    }
+
+    #[test]
+    fn test_traverse_iterative_deepening_sweeps() {
+        // A chain of five nodes.
+        let mut tree = Tree::new();
+        let mut last = tree.add_node(0);
+        for data in 1..5 {
+            last = tree.add_child(last, data);
+        }
+
+        let mut entered = vec![];
+        let mut left = vec![];
+        let result: Option<()> = tree.traverse_iterative_deepening(
+            3,
+            2,
+            |idx, _, depth, entered: &mut Vec<(usize, usize)>| {
+                assert_eq!(idx, depth);
+                entered.push((idx, depth));
+                ControlFlow::Continue(())
+            },
+            |idx, _, _, _| left.push(idx),
+            &mut entered,
+        );
+        assert_eq!(result, None);
+        // Limits 2 and then 3, never reaching node 4.
+        let indices: Vec<usize> = entered.iter().map(|&(idx, _)| idx).collect();
+        assert_eq!(indices, vec![0, 1, 2, 0, 1, 2, 3]);
+        assert_eq!(left, vec![2, 1, 0, 3, 2, 1, 0]);
+
+        // The sweeps stop once the whole tree has been seen.
+        let mut count = 0;
+        tree.traverse_iterative_deepening(
+            100,
+            10,
+            |_, _, _, count: &mut usize| {
+                *count += 1;
+                ControlFlow::<()>::Continue(())
+            },
+            |_, _, _, _| {},
+            &mut count,
+        );
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn test_search_best_first_ties_in_discovery_order() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(());
+        let first = tree.add_child(root, ());
+        tree.add_child(root, ());
+
+        let (path, cost) = tree
+            .search_best_first(root, |_, _| 1u32, |idx, _| idx != root)
+            .unwrap();
+        assert_eq!(path, vec![root, first]);
+        assert_eq!(cost, 2);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{NodeMask, Tree, TreeStorage};
+
+/// A chainable, type-safe builder for selecting nodes of a [`Tree`].
+///
+/// A selector starts with every node of the tree and each method narrows the selection down.
+/// Nodes are kept in index order, except after [`children_of`](Selector::children_of), which
+/// keeps the order of the children.
+///
+/// Constructed with [`Tree::select`].
+///
+/// # Example
+/// ```rust
+/// use easy_tree::Tree;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node(0);
+/// let a = tree.add_child(root, 1);
+/// let b = tree.add_child(root, 2);
+/// let c = tree.add_child(a, 3);
+/// let d = tree.add_child(b, 4);
+///
+/// let even: Vec<usize> = tree.select().at_depth(2).filter(|data| data % 2 == 0).collect();
+/// assert_eq!(even, vec![d]);
+///
+/// let leaves = tree.select().descendants_of(a).leaves().collect();
+/// assert_eq!(leaves, vec![c]);
+/// ```
+pub struct Selector<'a, T, D = Vec<T>> {
+    tree: &'a Tree<T, D>,
+    // `None` stands for all nodes of the tree, so it does not have to be materialized.
+    nodes: Option<Vec<usize>>,
+}
+
+impl<'a, T, D: TreeStorage<T>> Selector<'a, T, D> {
+    /// Keeps only the children of `parent`.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    pub fn children_of(mut self, parent: usize) -> Self {
+        let children = self.tree.children(parent);
+        self.nodes = Some(match self.nodes {
+            None => children.to_vec(),
+            Some(nodes) => {
+                let mut selected = NodeMask::for_tree(self.tree);
+                for node in nodes {
+                    selected.set(node);
+                }
+                children
+                    .iter()
+                    .copied()
+                    .filter(|&child| selected.contains(child))
+                    .collect()
+            }
+        });
+        self
+    }
+
+    /// Keeps only the descendants of `ancestor`, not including `ancestor` itself.
+    ///
+    /// # Panics
+    /// This method panics if `ancestor` is out of bounds.
+    pub fn descendants_of(self, ancestor: usize) -> Self {
+        let mut descendants = NodeMask::for_tree(self.tree);
+        for index in self.tree.reachable_from(ancestor).skip(1) {
+            descendants.set(index);
+        }
+        self.filter_indexed(|index, _| descendants.contains(index))
+    }
+
+    /// Keeps only the nodes at `depth`, where nodes without a parent are at depth `0`.
+    pub fn at_depth(self, depth: usize) -> Self {
+        let depths = self.tree.depths();
+        self.filter_indexed(|index, _| depths[index] == depth)
+    }
+
+    /// Keeps only the nodes without children.
+    pub fn leaves(self) -> Self {
+        let tree = self.tree;
+        self.filter_indexed(|index, _| tree.links[index].children.is_empty())
+    }
+
+    /// Keeps only the nodes without a parent.
+    pub fn roots(self) -> Self {
+        let tree = self.tree;
+        self.filter_indexed(|index, _| tree.links[index].parent().is_none())
+    }
+
+    /// Keeps only the nodes whose data satisfies `predicate`.
+    pub fn filter(self, mut predicate: impl FnMut(&T) -> bool) -> Self {
+        self.filter_indexed(|_, data| predicate(data))
+    }
+
+    /// Keeps only the nodes for which `predicate`, called with the index and the data of the
+    /// node, returns `true`.
+    pub fn filter_indexed(mut self, mut predicate: impl FnMut(usize, &T) -> bool) -> Self {
+        let tree = self.tree;
+        let nodes = match self.nodes.take() {
+            None => (0..tree.len()).collect(),
+            Some(nodes) => nodes,
+        };
+        self.nodes = Some(
+            nodes
+                .into_iter()
+                .filter(|&index| predicate(index, &tree.data[index]))
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns an iterator over the indices and data of the selected nodes.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &'a T)> + '_ {
+        let tree = self.tree;
+        let indices: Box<dyn Iterator<Item = usize>> = match &self.nodes {
+            None => Box::new(0..tree.len()),
+            Some(nodes) => Box::new(nodes.iter().copied()),
+        };
+        indices.map(move |index| (index, &tree.data[index]))
+    }
+
+    /// Returns the index of the first selected node.
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next().map(|(index, _)| index)
+    }
+
+    /// Returns the number of selected nodes.
+    pub fn count(&self) -> usize {
+        match &self.nodes {
+            None => self.tree.len(),
+            Some(nodes) => nodes.len(),
+        }
+    }
+
+    /// Returns the indices of the selected nodes.
+    pub fn collect(self) -> Vec<usize> {
+        match self.nodes {
+            None => (0..self.tree.len()).collect(),
+            Some(nodes) => nodes,
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Starts a selection of nodes. See [`Selector`].
+    pub fn select(&self) -> Selector<'_, T, D> {
+        Selector {
+            tree: self,
+            nodes: None,
+        }
+    }
+
+    /// Returns the number of ancestors of every node, computed in a single walk from the
+    /// nodes without a parent.
+    fn depths(&self) -> Vec<usize> {
+        let mut depths = vec![usize::MAX; self.links.len()];
+        let roots = (0..self.links.len()).filter(|&index| self.links[index].parent().is_none());
+        let mut stack: Vec<(usize, usize)> = roots.map(|root| (root, 0)).collect();
+        while let Some((index, depth)) = stack.pop() {
+            depths[index] = depth;
+            stack.extend(
+                self.links[index]
+                    .children
+                    .iter()
+                    .map(|&child| (child, depth + 1)),
+            );
+        }
+        depths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_chaining() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        let b = tree.add_child(root, "b");
+        let a1 = tree.add_child(a, "a1");
+        let a2 = tree.add_child(a, "a2");
+        let detached = tree.add_node("detached");
+
+        assert_eq!(tree.select().count(), 6);
+        assert_eq!(tree.select().roots().collect(), vec![root, detached]);
+        assert_eq!(tree.select().children_of(root).collect(), vec![a, b]);
+        assert_eq!(
+            tree.select().leaves().children_of(a).collect(),
+            vec![a1, a2]
+        );
+        assert_eq!(
+            tree.select()
+                .descendants_of(root)
+                .filter(|data| data.starts_with('a'))
+                .at_depth(1)
+                .first(),
+            Some(a)
+        );
+        let selected: Vec<_> = tree.select().at_depth(2).iter().collect();
+        assert_eq!(selected, vec![(a1, &"a1"), (a2, &"a2")]);
+        assert_eq!(tree.select().filter_indexed(|idx, _| idx > b).count(), 3);
+    }
+
+    #[test]
+    fn test_depth_and_descendants_with_children_before_parents() {
+        // A deep chain built bottom-up, so that every node comes before its parent.
+        let mut tree = Tree::new();
+        for depth in (0..10_000).rev() {
+            tree.add_node(depth);
+        }
+        for child in 0..9_999 {
+            tree.set_parent(child, child + 1).unwrap();
+        }
+
+        assert_eq!(tree.select().at_depth(9_998).collect(), vec![1]);
+        assert_eq!(tree.select().descendants_of(5_000).count(), 5_000);
+        assert_eq!(tree.select().descendants_of(5_000).first(), Some(0));
+    }
+}
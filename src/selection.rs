@@ -0,0 +1,200 @@
+use crate::{NodeMask, Tree, TreeStorage};
+
+/// Selection and expansion state of the rows of a tree widget.
+///
+/// Every node is either selected or not, and either expanded or collapsed. A node is visible
+/// when all of its ancestors are expanded. New nodes start unselected and expanded.
+///
+/// The state is kept apart from the tree, indexed by node, and grows on demand as nodes are
+/// added to the tree.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Tree, TreeSelection};
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("src");
+/// let lib = tree.add_child(root, "lib.rs");
+/// let util = tree.add_child(root, "util");
+/// let helpers = tree.add_child(util, "helpers.rs");
+///
+/// let mut selection = TreeSelection::new(&tree);
+/// selection.collapse(util);
+/// let rows: Vec<_> = selection.expanded_visible_nodes(&tree).collect();
+/// assert_eq!(rows, vec![(root, 0), (lib, 1), (util, 1)]);
+///
+/// selection.select_subtree(&tree, util);
+/// assert_eq!(selection.selected().collect::<Vec<_>>(), vec![util, helpers]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TreeSelection {
+    selected: NodeMask,
+    collapsed: NodeMask,
+}
+
+impl TreeSelection {
+    /// Creates a selection state for `tree` with no node selected and every node expanded.
+    pub fn new<T, D: TreeStorage<T>>(tree: &Tree<T, D>) -> Self {
+        TreeSelection {
+            selected: NodeMask::for_tree(tree),
+            collapsed: NodeMask::for_tree(tree),
+        }
+    }
+
+    /// Returns `true` if the node is selected.
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(index)
+    }
+
+    /// Selects a node.
+    pub fn select(&mut self, index: usize) {
+        self.selected.grow(index + 1);
+        self.selected.set(index);
+    }
+
+    /// Deselects a node.
+    pub fn deselect(&mut self, index: usize) {
+        if index < self.selected.len() {
+            self.selected.clear(index);
+        }
+    }
+
+    /// Flips the selection of a node.
+    ///
+    /// # Returns
+    /// `true` if the node is selected afterwards.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        self.selected.grow(index + 1);
+        self.selected.toggle(index)
+    }
+
+    /// Selects a node and all of its descendants.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds for `tree`.
+    pub fn select_subtree<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>, index: usize) {
+        self.selected.grow(tree.len());
+        for node in tree.reachable_from(index) {
+            self.selected.set(node);
+        }
+    }
+
+    /// Deselects a node and all of its descendants.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds for `tree`.
+    pub fn deselect_subtree<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>, index: usize) {
+        for node in tree.reachable_from(index) {
+            self.deselect(node);
+        }
+    }
+
+    /// Deselects every node.
+    pub fn clear_selection(&mut self) {
+        self.selected.clear_all();
+    }
+
+    /// Returns an iterator over the selected nodes, in increasing index order.
+    pub fn selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter()
+    }
+
+    /// Returns the selected nodes as a mask.
+    pub fn selection_mask(&self) -> &NodeMask {
+        &self.selected
+    }
+
+    /// Returns `true` if the children of the node are shown.
+    pub fn is_expanded(&self, index: usize) -> bool {
+        !self.collapsed.contains(index)
+    }
+
+    /// Shows the children of a node.
+    pub fn expand(&mut self, index: usize) {
+        if index < self.collapsed.len() {
+            self.collapsed.clear(index);
+        }
+    }
+
+    /// Hides the children of a node.
+    pub fn collapse(&mut self, index: usize) {
+        self.collapsed.grow(index + 1);
+        self.collapsed.set(index);
+    }
+
+    /// Expands a collapsed node, or collapses an expanded one.
+    ///
+    /// # Returns
+    /// `true` if the node is expanded afterwards.
+    pub fn toggle_expanded(&mut self, index: usize) -> bool {
+        self.collapsed.grow(index + 1);
+        !self.collapsed.toggle(index)
+    }
+
+    /// Expands every node.
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear_all();
+    }
+
+    /// Collapses every node of `tree`.
+    pub fn collapse_all<T, D: TreeStorage<T>>(&mut self, tree: &Tree<T, D>) {
+        self.collapsed.grow(tree.len());
+        for index in 0..tree.len() {
+            self.collapsed.set(index);
+        }
+    }
+
+    /// Returns the collapsed nodes as a mask.
+    pub fn collapsed_mask(&self) -> &NodeMask {
+        &self.collapsed
+    }
+
+    /// Returns an iterator over the visible nodes of `tree` with their depths, in the order
+    /// they appear as rows: depth-first, descending only into expanded nodes.
+    ///
+    /// Every node without a parent starts a tree of its own and is always visible; they are
+    /// listed in index order.
+    pub fn expanded_visible_nodes<'a, T, D: TreeStorage<T>>(
+        &'a self,
+        tree: &'a Tree<T, D>,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        tree.iter_visible(&self.collapsed)
+            .map(|(index, depth, _)| (index, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_expansion_and_growth() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        let a1 = tree.add_child(a, 2);
+        let mut selection = TreeSelection::new(&tree);
+
+        selection.collapse_all(&tree);
+        let rows: Vec<_> = selection.expanded_visible_nodes(&tree).collect();
+        assert_eq!(rows, vec![(root, 0)]);
+        assert!(selection.toggle_expanded(root));
+        assert!(!selection.is_expanded(a));
+
+        // Nodes added after the selection was created start expanded and unselected.
+        let b = tree.add_child(root, 3);
+        let other = tree.add_node(4);
+        assert!(selection.is_expanded(b));
+        assert!(!selection.is_selected(b));
+        let rows: Vec<_> = selection.expanded_visible_nodes(&tree).collect();
+        assert_eq!(rows, vec![(root, 0), (a, 1), (b, 1), (other, 0)]);
+
+        assert!(selection.toggle(other));
+        selection.select_subtree(&tree, a);
+        assert_eq!(selection.selected().collect::<Vec<_>>(), vec![a, a1, other]);
+        selection.deselect_subtree(&tree, root);
+        assert_eq!(selection.selected().collect::<Vec<_>>(), vec![other]);
+        selection.clear_selection();
+        assert_eq!(selection.selected().count(), 0);
+    }
+}
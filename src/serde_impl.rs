@@ -0,0 +1,85 @@
+use crate::{Tree, TreeStorage};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The schema of a serialized tree, borrowing the tree being serialized.
+#[derive(Serialize)]
+#[serde(rename = "Tree")]
+pub(crate) struct SchemaRef<'a, T> {
+    pub(crate) data: &'a [T],
+    pub(crate) children: Vec<&'a [usize]>,
+}
+
+/// The schema of a serialized tree, as it is deserialized.
+#[derive(Deserialize)]
+#[serde(rename = "Tree")]
+struct Schema<T> {
+    data: Vec<T>,
+    children: Vec<Vec<usize>>,
+}
+
+impl<T> Schema<T> {
+    /// Checks that the children lists describe a forest over the nodes, and builds it.
+    fn into_tree(self) -> Result<Tree<T>, String> {
+        let len = self.data.len();
+        if self.children.len() != len {
+            return Err(format!(
+                "{} children lists for {len} nodes",
+                self.children.len()
+            ));
+        }
+        let mut has_parent = vec![false; len];
+        for (parent, children) in self.children.iter().enumerate() {
+            for &child in children {
+                if child >= len {
+                    return Err(format!("child {child} of node {parent} is out of bounds"));
+                }
+                if std::mem::replace(&mut has_parent[child], true) {
+                    return Err(format!("node {child} has more than one parent"));
+                }
+            }
+        }
+        // Nodes on a cycle all have a parent, so they cannot be reached from the roots.
+        let mut stack: Vec<usize> = (0..len).filter(|&index| !has_parent[index]).collect();
+        let mut reached = 0;
+        while let Some(index) = stack.pop() {
+            reached += 1;
+            stack.extend(&self.children[index]);
+        }
+        if reached != len {
+            return Err("the children lists contain a cycle".to_string());
+        }
+
+        let mut tree = Tree::new();
+        for data in self.data {
+            tree.add_node(data);
+        }
+        for (parent, children) in self.children.into_iter().enumerate() {
+            for child in children {
+                tree.link(parent, child);
+            }
+        }
+        Ok(tree)
+    }
+}
+
+/// Trees are serialized as a structure with two fields: `data`, the data of every node in
+/// index order, and `children`, the indices of the children of every node in index order.
+impl<T: Serialize, D: TreeStorage<T>> Serialize for Tree<T, D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SchemaRef {
+            data: &self.data[..],
+            children: self.links.iter().map(|links| &links.children[..]).collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializing checks that every node has at most one parent and that the tree has no
+/// cycle, so that a corrupted input fails instead of producing a tree that loops forever.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Schema::deserialize(deserializer)?
+            .into_tree()
+            .map_err(de::Error::custom)
+    }
+}
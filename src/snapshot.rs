@@ -0,0 +1,672 @@
+use crate::{Tree, TreeStorage};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use xxhash_rust::xxh64::xxh64;
+
+/// The version of the header and schema written by [`Tree::write_snapshot`] and the
+/// `to_bytes_*` functions, such as [`Tree::to_bytes_cbor`].
+///
+/// Every buffer starts with a header of seven bytes: this version, the tag of the [`Format`]
+/// of the payload (`b'B'` for bincode, `b'C'` for CBOR and `b'M'` for MessagePack), a byte of
+/// flags, and the version of the node data chosen by the application, as a little-endian
+/// `u32`. If the flags have the bit [`CHECKSUM_FLAG`] set, the header is followed by the
+/// XXH64 checksum of the payload, with seed `0`, as a little-endian `u64`. The rest of the
+/// buffer is the payload, the encoded tree.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// The bit of the flags byte of a snapshot header telling that the payload is preceded by
+/// its checksum, as described in [`FORMAT_VERSION`].
+pub const CHECKSUM_FLAG: u8 = 0b1;
+
+/// The encodings that trees can be serialized to, each enabled by the feature of the same
+/// name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// [bincode](https://docs.rs/bincode), a compact encoding for Rust programs.
+    #[cfg(feature = "bincode")]
+    Bincode,
+    /// [CBOR](https://cbor.io), readable from most languages.
+    #[cfg(feature = "cbor")]
+    Cbor,
+    /// [MessagePack](https://msgpack.org), readable from most languages.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl Format {
+    /// Returns the format identified by a tag byte, if it is enabled.
+    pub(crate) fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            #[cfg(feature = "bincode")]
+            b'B' => Some(Format::Bincode),
+            #[cfg(feature = "cbor")]
+            b'C' => Some(Format::Cbor),
+            #[cfg(feature = "msgpack")]
+            b'M' => Some(Format::MessagePack),
+            _ => None,
+        }
+    }
+
+    /// Returns the byte identifying the format in the header of a buffer.
+    fn tag(self) -> u8 {
+        match self {
+            #[cfg(feature = "bincode")]
+            Format::Bincode => b'B',
+            #[cfg(feature = "cbor")]
+            Format::Cbor => b'C',
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => b'M',
+        }
+    }
+
+    /// Appends `value` to `out` in this format.
+    fn encode<U: Serialize>(self, value: &U, out: &mut Vec<u8>) -> Result<(), SnapshotError> {
+        let result: Result<(), String> = match self {
+            #[cfg(feature = "bincode")]
+            Format::Bincode => bincode::serialize_into(out, value).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::ser::into_writer(value, out).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                rmp_serde::encode::write_named(out, value).map_err(|e| e.to_string())
+            }
+        };
+        result.map_err(SnapshotError::Encode)
+    }
+
+    /// Decodes a value from `bytes` in this format.
+    ///
+    /// This is meant for the hooks of [`Migrations`], which decode the payload of a snapshot
+    /// written with an older type, such as a `Tree<OldType>`, and convert it.
+    ///
+    /// # Errors
+    /// [`SnapshotError::Decode`] if `bytes` does not hold a valid value.
+    pub fn decode<U: DeserializeOwned>(self, bytes: &[u8]) -> Result<U, SnapshotError> {
+        let result: Result<U, String> = match self {
+            #[cfg(feature = "bincode")]
+            Format::Bincode => bincode::deserialize(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::de::from_reader(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        };
+        result.map_err(SnapshotError::Decode)
+    }
+}
+
+/// An error produced when writing or reading a serialized tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// The buffer is too short to hold a header.
+    MissingHeader,
+    /// The buffer was written with a schema version this crate cannot read.
+    UnsupportedVersion(u8),
+    /// The buffer was written in another format than the one it is read in, identified by its
+    /// tag byte.
+    FormatMismatch(u8),
+    /// The buffer was written in a format that is unknown or not enabled, identified by its tag
+    /// byte.
+    UnknownFormat(u8),
+    /// The node data was written with a data version that has no migration.
+    UnsupportedDataVersion(u32),
+    /// The header has flags this crate does not know.
+    UnknownFlags(u8),
+    /// The checksum of the payload does not match the one stored in the header, because the
+    /// buffer was truncated or corrupted.
+    ChecksumMismatch {
+        /// The checksum stored in the header.
+        expected: u64,
+        /// The checksum of the payload.
+        actual: u64,
+    },
+    /// The tree changed since the last save of a [`SnapshotLog`](crate::SnapshotLog) in a way
+    /// that appending new nodes cannot record.
+    NotAppendOnly,
+    /// A snapshot log ends in the middle of a frame, usually because an append was
+    /// interrupted.
+    TruncatedLog,
+    /// Reading or writing the snapshot failed.
+    Io(String),
+    /// The tree or its data could not be encoded.
+    Encode(String),
+    /// The buffer could not be decoded, or does not hold a valid tree.
+    Decode(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::MissingHeader => write!(f, "buffer is too short to hold a header"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            SnapshotError::FormatMismatch(tag) => {
+                write!(f, "buffer was written in another format (tag {tag:#04x})")
+            }
+            SnapshotError::UnknownFormat(tag) => {
+                write!(
+                    f,
+                    "buffer was written in an unknown format (tag {tag:#04x})"
+                )
+            }
+            SnapshotError::UnsupportedDataVersion(version) => {
+                write!(f, "no migration from data version {version}")
+            }
+            SnapshotError::UnknownFlags(flags) => write!(f, "unknown header flags {flags:#010b}"),
+            SnapshotError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {expected:#018x}, got {actual:#018x}"
+            ),
+            SnapshotError::NotAppendOnly => {
+                write!(f, "tree changed in a way that cannot be appended")
+            }
+            SnapshotError::TruncatedLog => write!(f, "snapshot log ends in the middle of a frame"),
+            SnapshotError::Io(message) => write!(f, "I/O error: {message}"),
+            SnapshotError::Encode(message) => write!(f, "cannot encode tree: {message}"),
+            SnapshotError::Decode(message) => write!(f, "cannot decode tree: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error.to_string())
+    }
+}
+
+/// Controls how [`Tree::write_snapshot`] writes a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// The encoding of the tree.
+    pub format: Format,
+    /// The version of the node data, checked by [`Tree::deserialize_with_migration`].
+    pub data_version: u32,
+    /// Whether to store a checksum of the payload, verified when the snapshot is read.
+    pub checksum: bool,
+}
+
+impl SnapshotOptions {
+    /// Writes the tree in `format`, with data version `0`.
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            data_version: 0,
+            checksum: false,
+        }
+    }
+
+    /// Sets the version of the node data. Bump it whenever the serialized form of the node
+    /// data changes, and register a hook for the previous version in [`Migrations`].
+    pub fn with_data_version(mut self, data_version: u32) -> Self {
+        self.data_version = data_version;
+        self
+    }
+
+    /// Stores a checksum of the payload, so that reading a truncated or corrupted snapshot
+    /// fails with [`SnapshotError::ChecksumMismatch`] instead of decoding garbage. Computing
+    /// it takes a single fast pass over the payload.
+    pub fn with_checksum(mut self) -> Self {
+        self.checksum = true;
+        self
+    }
+}
+
+/// A hook of [`Migrations`], decoding the payload of a snapshot written with an older data
+/// version.
+type MigrationHook<T> = Box<dyn Fn(Format, &[u8]) -> Result<Tree<T>, SnapshotError>>;
+
+/// The migrations that let [`Tree::deserialize_with_migration`] load snapshots written with
+/// older data versions.
+pub struct Migrations<T> {
+    current: u32,
+    hooks: HashMap<u32, MigrationHook<T>>,
+}
+
+impl<T> Migrations<T> {
+    /// Creates migrations with no hook, for node data currently at version `current`.
+    pub fn new(current: u32) -> Self {
+        Self {
+            current,
+            hooks: HashMap::new(),
+        }
+    }
+
+    /// Registers the hook loading snapshots of data version `data_version`.
+    ///
+    /// The hook receives the format and the payload of the snapshot, the bytes after the
+    /// header, and returns the tree converted to the current data version. It usually decodes
+    /// the payload with [`Format::decode`] into a tree of the old type, and maps its data.
+    pub fn with_hook(
+        mut self,
+        data_version: u32,
+        hook: impl Fn(Format, &[u8]) -> Result<Tree<T>, SnapshotError> + 'static,
+    ) -> Self {
+        self.hooks.insert(data_version, Box::new(hook));
+        self
+    }
+}
+
+/// Encodes `value` as the payload of a snapshot, preceded by its header.
+pub(crate) fn encode_snapshot<U: Serialize>(
+    value: &U,
+    options: &SnapshotOptions,
+) -> Result<Vec<u8>, SnapshotError> {
+    let flags = if options.checksum { CHECKSUM_FLAG } else { 0 };
+    let mut header = vec![FORMAT_VERSION, options.format.tag(), flags];
+    header.extend_from_slice(&options.data_version.to_le_bytes());
+    let mut payload = Vec::new();
+    options.format.encode(value, &mut payload)?;
+    if options.checksum {
+        header.extend_from_slice(&xxh64(&payload, 0).to_le_bytes());
+    }
+    header.extend_from_slice(&payload);
+    Ok(header)
+}
+
+/// Splits a buffer into its format tag, its data version and its payload, verifying the
+/// checksum of the payload if it has one.
+pub(crate) fn read_header(bytes: &[u8]) -> Result<(u8, u32, &[u8]), SnapshotError> {
+    let [version, tag, rest @ ..] = bytes else {
+        return Err(SnapshotError::MissingHeader);
+    };
+    if *version != FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(*version));
+    }
+    let (flags, rest) = rest.split_first().ok_or(SnapshotError::MissingHeader)?;
+    if flags & !CHECKSUM_FLAG != 0 {
+        return Err(SnapshotError::UnknownFlags(*flags));
+    }
+    let (data_version, mut payload) = rest
+        .split_first_chunk()
+        .ok_or(SnapshotError::MissingHeader)?;
+    if flags & CHECKSUM_FLAG != 0 {
+        let (expected, rest) = payload
+            .split_first_chunk()
+            .ok_or(SnapshotError::MissingHeader)?;
+        let expected = u64::from_le_bytes(*expected);
+        let actual = xxh64(rest, 0);
+        if actual != expected {
+            return Err(SnapshotError::ChecksumMismatch { expected, actual });
+        }
+        payload = rest;
+    }
+    Ok((*tag, u32::from_le_bytes(*data_version), payload))
+}
+
+impl<T: Serialize, D: TreeStorage<T>> Tree<T, D> {
+    /// Writes the tree as a snapshot, with a header recording the format and the data
+    /// version, as described in [`FORMAT_VERSION`].
+    ///
+    /// # Parameters
+    /// - `writer`: Where to write the snapshot.
+    /// - `options`: The format, the data version and whether to store a checksum.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    /// - [`SnapshotError::Io`] if `writer` fails.
+    pub fn write_snapshot(
+        &self,
+        mut writer: impl io::Write,
+        options: &SnapshotOptions,
+    ) -> Result<(), SnapshotError> {
+        writer.write_all(&encode_snapshot(self, options)?)?;
+        Ok(())
+    }
+
+    /// Writes the snapshot of the tree in `format`, with data version `0`.
+    fn to_bytes(&self, format: Format) -> Result<Vec<u8>, SnapshotError> {
+        let mut out = Vec::new();
+        self.write_snapshot(&mut out, &SnapshotOptions::new(format))?;
+        Ok(out)
+    }
+
+    /// Serializes the tree with [bincode](https://docs.rs/bincode).
+    ///
+    /// The buffer starts with the header described in [`FORMAT_VERSION`], with data version
+    /// `0`. The rest is the tree encoded as described in its [`Serialize`] implementation.
+    ///
+    /// # Errors
+    /// [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root".to_string());
+    /// tree.add_child(root, "child".to_string());
+    ///
+    /// let bytes = tree.to_bytes_bincode().unwrap();
+    /// let copy: Tree<String> = Tree::from_bytes_bincode(&bytes).unwrap();
+    /// assert_eq!(copy.children(root), &[1]);
+    /// assert_eq!(copy[1], "child");
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn to_bytes_bincode(&self) -> Result<Vec<u8>, SnapshotError> {
+        self.to_bytes(Format::Bincode)
+    }
+
+    /// Serializes the tree with [CBOR](https://cbor.io), for exchanging it with programs in
+    /// other languages.
+    ///
+    /// The buffer starts with the seven-byte header described in [`FORMAT_VERSION`], with data
+    /// version `0` and no checksum. The rest is a CBOR map with two keys: `data`, the data of every node in
+    /// index order, and `children`, the indices of the children of every node in index order.
+    /// In Python, for example, `cbor2.loads(buffer[7:])` returns that map.
+    ///
+    /// # Errors
+    /// [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1u32);
+    /// tree.add_child(root, 2);
+    ///
+    /// let bytes = tree.to_bytes_cbor().unwrap();
+    /// assert_eq!(&bytes[..7], &[easy_tree::FORMAT_VERSION, b'C', 0, 0, 0, 0, 0]);
+    /// let copy: Tree<u32> = Tree::from_bytes_cbor(&bytes).unwrap();
+    /// assert_eq!(copy.data(), tree.data());
+    /// ```
+    #[cfg(feature = "cbor")]
+    pub fn to_bytes_cbor(&self) -> Result<Vec<u8>, SnapshotError> {
+        self.to_bytes(Format::Cbor)
+    }
+
+    /// Serializes the tree with [MessagePack](https://msgpack.org), for exchanging it with
+    /// programs in other languages.
+    ///
+    /// The buffer starts with the header described in [`FORMAT_VERSION`], with data version
+    /// `0`. The rest is a MessagePack map with the same `data` and `children` keys as
+    /// [`Tree::to_bytes_cbor`].
+    ///
+    /// # Errors
+    /// [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    #[cfg(feature = "msgpack")]
+    pub fn to_bytes_msgpack(&self) -> Result<Vec<u8>, SnapshotError> {
+        self.to_bytes(Format::MessagePack)
+    }
+}
+
+impl<T: DeserializeOwned> Tree<T> {
+    /// Checks the header and decodes the tree.
+    fn from_bytes(bytes: &[u8], format: Format) -> Result<Self, SnapshotError> {
+        let (tag, data_version, payload) = read_header(bytes)?;
+        if tag != format.tag() {
+            return Err(SnapshotError::FormatMismatch(tag));
+        }
+        if data_version != 0 {
+            return Err(SnapshotError::UnsupportedDataVersion(data_version));
+        }
+        format.decode(payload)
+    }
+
+    /// Reads a snapshot written by [`Tree::write_snapshot`], in any enabled format, migrating
+    /// it if it was written with an older data version.
+    ///
+    /// A snapshot of the current data version of `migrations` is decoded directly; one of
+    /// another data version is passed to the hook registered for that version. Snapshots
+    /// written by older versions of this crate are read as well.
+    ///
+    /// # Parameters
+    /// - `reader`: The snapshot, read to its end.
+    /// - `migrations`: The current data version and the hooks for older ones.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::Io`] if `reader` fails.
+    /// - [`SnapshotError::MissingHeader`] if the snapshot is shorter than its header.
+    /// - [`SnapshotError::UnsupportedVersion`] if it was written by a newer version of this
+    ///   crate.
+    /// - [`SnapshotError::UnknownFormat`] if it was written in a format that is not enabled.
+    /// - [`SnapshotError::UnsupportedDataVersion`] if its data version has no hook.
+    /// - [`SnapshotError::Decode`] if it does not hold a valid tree, or any error of the hook.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Format, Migrations, SnapshotOptions, Tree};
+    ///
+    /// # #[cfg(feature = "cbor")]
+    /// # {
+    /// // Version 1 of the application stored sizes in kilobytes.
+    /// let mut old = Tree::new();
+    /// old.add_node(4u32);
+    /// let mut cache = Vec::new();
+    /// let options = SnapshotOptions::new(Format::Cbor).with_data_version(1);
+    /// old.write_snapshot(&mut cache, &options).unwrap();
+    ///
+    /// // Version 2 stores sizes in bytes, and migrates old caches.
+    /// let migrations = Migrations::new(2).with_hook(1, |format, payload| {
+    ///     let old: Tree<u32> = format.decode(payload)?;
+    ///     Ok(old.map_ref(|kilobytes| u64::from(*kilobytes) * 1024))
+    /// });
+    /// let tree = Tree::deserialize_with_migration(&cache[..], &migrations).unwrap();
+    /// assert_eq!(tree[0], 4096);
+    /// # }
+    /// ```
+    pub fn deserialize_with_migration<R: io::Read>(
+        mut reader: R,
+        migrations: &Migrations<T>,
+    ) -> Result<Self, SnapshotError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let (tag, data_version, payload) = read_header(&bytes)?;
+        let format = Format::from_tag(tag).ok_or(SnapshotError::UnknownFormat(tag))?;
+        if data_version == migrations.current {
+            return format.decode(payload);
+        }
+        match migrations.hooks.get(&data_version) {
+            Some(hook) => hook(format, payload),
+            None => Err(SnapshotError::UnsupportedDataVersion(data_version)),
+        }
+    }
+
+    /// Deserializes a tree written by [`Tree::to_bytes_bincode`].
+    ///
+    /// # Errors
+    /// - [`SnapshotError::MissingHeader`] if `bytes` is shorter than the header.
+    /// - [`SnapshotError::UnsupportedVersion`] if it was written with another schema version.
+    /// - [`SnapshotError::FormatMismatch`] if it was written in another format.
+    /// - [`SnapshotError::Decode`] if it does not hold a valid tree.
+    #[cfg(feature = "bincode")]
+    pub fn from_bytes_bincode(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_bytes(bytes, Format::Bincode)
+    }
+
+    /// Deserializes a tree written by [`Tree::to_bytes_cbor`], or by another program following
+    /// the same schema.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::MissingHeader`] if `bytes` is shorter than the header.
+    /// - [`SnapshotError::UnsupportedVersion`] if it was written with another schema version.
+    /// - [`SnapshotError::FormatMismatch`] if it was written in another format.
+    /// - [`SnapshotError::Decode`] if it does not hold a valid tree.
+    #[cfg(feature = "cbor")]
+    pub fn from_bytes_cbor(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_bytes(bytes, Format::Cbor)
+    }
+
+    /// Deserializes a tree written by [`Tree::to_bytes_msgpack`], or by another program
+    /// following the same schema.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::MissingHeader`] if `bytes` is shorter than the header.
+    /// - [`SnapshotError::UnsupportedVersion`] if it was written with another schema version.
+    /// - [`SnapshotError::FormatMismatch`] if it was written in another format.
+    /// - [`SnapshotError::Decode`] if it does not hold a valid tree.
+    #[cfg(feature = "msgpack")]
+    pub fn from_bytes_msgpack(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_bytes(bytes, Format::MessagePack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "cbor")]
+    use crate::serde_impl::SchemaRef;
+    use crate::testing::snapshot;
+
+    fn sample() -> Tree<String> {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root".to_string());
+        let a = tree.add_child(root, "a".to_string());
+        let b = tree.add_node("b".to_string());
+        tree.add_child(a, "c".to_string());
+        tree.set_parent(b, root).unwrap();
+        tree.add_node("detached".to_string());
+        tree
+    }
+
+    #[test]
+    fn test_round_trips_in_every_format() {
+        let tree = sample();
+        let formats: Vec<Format> = vec![
+            #[cfg(feature = "bincode")]
+            Format::Bincode,
+            #[cfg(feature = "cbor")]
+            Format::Cbor,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack,
+        ];
+        for &format in &formats {
+            let bytes = tree.to_bytes(format).unwrap();
+            let copy = Tree::<String>::from_bytes(&bytes, format).unwrap();
+            assert_eq!(snapshot(&copy), snapshot(&tree));
+            assert_eq!(copy.children(0), tree.children(0));
+
+            assert_eq!(
+                Tree::<String>::from_bytes(&bytes[..1], format).err(),
+                Some(SnapshotError::MissingHeader)
+            );
+            let mut future = bytes.clone();
+            future[0] = FORMAT_VERSION + 1;
+            assert_eq!(
+                Tree::<String>::from_bytes(&future, format).err(),
+                Some(SnapshotError::UnsupportedVersion(FORMAT_VERSION + 1))
+            );
+            for &other in formats.iter().filter(|&&other| other != format) {
+                assert_eq!(
+                    Tree::<String>::from_bytes(&bytes, other).err(),
+                    Some(SnapshotError::FormatMismatch(format.tag()))
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_deserialize_rejects_invalid_structure() {
+        let decode = |children: Vec<Vec<usize>>| {
+            let mut bytes = vec![FORMAT_VERSION, b'C', 0, 0, 0, 0, 0];
+            let schema = SchemaRef {
+                data: &vec![0; children.len()],
+                children: children.iter().map(Vec::as_slice).collect(),
+            };
+            ciborium::ser::into_writer(&schema, &mut bytes).unwrap();
+            Tree::<i32>::from_bytes_cbor(&bytes)
+                .err()
+                .map(|e| e.to_string())
+        };
+        assert_eq!(decode(vec![vec![1], vec![2], vec![]]), None);
+        assert!(decode(vec![vec![3], vec![], vec![]])
+            .unwrap()
+            .contains("out of bounds"));
+        assert!(decode(vec![vec![2], vec![2], vec![]])
+            .unwrap()
+            .contains("more than one parent"));
+        assert!(decode(vec![vec![], vec![2], vec![1]])
+            .unwrap()
+            .contains("cycle"));
+    }
+
+    #[test]
+    fn test_deserialize_with_migration() {
+        let format = [
+            #[cfg(feature = "bincode")]
+            Format::Bincode,
+            #[cfg(feature = "cbor")]
+            Format::Cbor,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack,
+        ][0];
+        let mut old = Tree::new();
+        let root = old.add_node(1u8);
+        old.add_child(root, 2);
+        let mut v1 = Vec::new();
+        let options = SnapshotOptions::new(format).with_data_version(1);
+        old.write_snapshot(&mut v1, &options).unwrap();
+
+        let migrations = Migrations::new(2).with_hook(1, |format, payload| {
+            let old: Tree<u8> = format.decode(payload)?;
+            Ok(old.map_ref(|value| value.to_string()))
+        });
+        let migrated = Tree::deserialize_with_migration(&v1[..], &migrations).unwrap();
+        assert_eq!(migrated.data(), &["1", "2"]);
+        assert_eq!(migrated.children(root), &[1]);
+
+        let current = sample();
+        let mut v2 = Vec::new();
+        let options = options.with_data_version(2);
+        current.write_snapshot(&mut v2, &options).unwrap();
+        let loaded = Tree::deserialize_with_migration(&v2[..], &migrations).unwrap();
+        assert_eq!(snapshot(&loaded), snapshot(&current));
+        assert_eq!(
+            Tree::<String>::from_bytes(&v2, format).err(),
+            Some(SnapshotError::UnsupportedDataVersion(2))
+        );
+        assert_eq!(
+            Tree::deserialize_with_migration(&v2[..], &Migrations::<String>::new(3)).err(),
+            Some(SnapshotError::UnsupportedDataVersion(2))
+        );
+
+        let mut unknown = v2.clone();
+        unknown[1] = b'?';
+        assert_eq!(
+            Tree::deserialize_with_migration(&unknown[..], &Migrations::<String>::new(2)).err(),
+            Some(SnapshotError::UnknownFormat(b'?'))
+        );
+    }
+
+    #[test]
+    fn test_checksum_detects_truncation() {
+        let format = [
+            #[cfg(feature = "bincode")]
+            Format::Bincode,
+            #[cfg(feature = "cbor")]
+            Format::Cbor,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack,
+        ][0];
+        let tree = sample();
+        let mut bytes = Vec::new();
+        let options = SnapshotOptions::new(format).with_checksum();
+        tree.write_snapshot(&mut bytes, &options).unwrap();
+        assert_eq!(bytes[2], CHECKSUM_FLAG);
+        let copy = Tree::<String>::from_bytes(&bytes, format).unwrap();
+        assert_eq!(snapshot(&copy), snapshot(&tree));
+
+        let read = |bytes: &[u8]| Tree::<String>::from_bytes(bytes, format).err();
+        assert!(matches!(
+            read(&bytes[..bytes.len() - 3]),
+            Some(SnapshotError::ChecksumMismatch { .. })
+        ));
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            read(&corrupted),
+            Some(SnapshotError::ChecksumMismatch { .. })
+        ));
+        assert_eq!(read(&bytes[..10]), Some(SnapshotError::MissingHeader));
+        corrupted[2] = 0b10;
+        assert_eq!(read(&corrupted), Some(SnapshotError::UnknownFlags(0b10)));
+    }
+}
@@ -0,0 +1,449 @@
+use crate::snapshot::{encode_snapshot, read_header};
+use crate::{Format, SnapshotError, SnapshotOptions, Tree, TreeStorage};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use xxhash_rust::xxh64::Xxh64;
+
+/// A frame of a snapshot log, borrowing the tree being saved.
+#[derive(Serialize)]
+#[serde(rename = "Frame", bound = "T: Serialize, D: TreeStorage<T>")]
+enum FrameRef<'a, T, D> {
+    /// The whole tree, replacing everything before it.
+    Base(&'a Tree<T, D>),
+    /// The nodes from index `start` on, with their parents.
+    Append {
+        start: usize,
+        parents: Vec<Option<usize>>,
+        data: &'a [T],
+    },
+}
+
+/// A frame of a snapshot log, as it is deserialized.
+#[derive(Deserialize)]
+#[serde(rename = "Frame")]
+enum Frame<T> {
+    Base(Tree<T>),
+    Append {
+        start: usize,
+        parents: Vec<Option<usize>>,
+        data: Vec<T>,
+    },
+}
+
+/// Saves an append-mostly tree incrementally, writing only the nodes added since the last
+/// save instead of a full snapshot.
+///
+/// A log is a sequence of frames, each holding either the whole tree or the nodes appended
+/// after the previous frame. Every frame is written as its length, a little-endian `u64`,
+/// followed by a snapshot buffer as described in [`FORMAT_VERSION`](crate::FORMAT_VERSION),
+/// so frames can carry checksums. The log is read back with [`Tree::read_snapshot_log`].
+///
+/// Appending only records new nodes. New nodes must come after their parent, and be the last
+/// children of their parent in index order, as [`Tree::add_child`] places them; other changes
+/// need a [`SnapshotLog::compact`]. The log keeps a checksum of the links of the saved nodes,
+/// and when they changed, for example because a saved node was moved with
+/// [`Tree::set_parent`] or its children were reordered, [`SnapshotLog::append`] writes the
+/// whole tree instead. Changes to the data of saved nodes, such as through [`Tree::get_mut`],
+/// are not detected and not persisted: save them with [`SnapshotLog::compact`].
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Format, SnapshotLog, SnapshotOptions, Tree};
+///
+/// # #[cfg(feature = "cbor")]
+/// # {
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root".to_string());
+/// let mut log = SnapshotLog::new(SnapshotOptions::new(Format::Cbor).with_checksum());
+/// let mut file = Vec::new();
+/// log.append(&tree, &mut file).unwrap();
+///
+/// tree.add_child(root, "child".to_string());
+/// assert_eq!(log.append(&tree, &mut file).unwrap(), 1);
+///
+/// let loaded: Tree<String> = Tree::read_snapshot_log(&file[..], 0).unwrap();
+/// assert_eq!(loaded.children(root), &[1]);
+///
+/// // Continue the same file after loading it.
+/// let mut log = SnapshotLog::resume(SnapshotOptions::new(Format::Cbor), &loaded);
+/// assert_eq!(log.saved_len(), 2);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotLog {
+    options: SnapshotOptions,
+    saved_len: usize,
+    /// The checksum of the links of the saved nodes, from [`links_checksum`].
+    saved_links: u64,
+}
+
+impl SnapshotLog {
+    /// Creates a log for a new, empty file.
+    pub fn new(options: SnapshotOptions) -> Self {
+        Self {
+            options,
+            saved_len: 0,
+            saved_links: links_checksum::<(), Vec<()>>(&Tree::new(), 0),
+        }
+    }
+
+    /// Creates a log continuing a file that holds `saved`, such as the tree returned by
+    /// [`Tree::read_snapshot_log`].
+    pub fn resume<T, D: TreeStorage<T>>(options: SnapshotOptions, saved: &Tree<T, D>) -> Self {
+        Self {
+            options,
+            saved_len: saved.links.len(),
+            saved_links: links_checksum(saved, saved.links.len()),
+        }
+    }
+
+    /// Returns the number of nodes saved so far.
+    pub fn saved_len(&self) -> usize {
+        self.saved_len
+    }
+
+    /// Appends the nodes added to `tree` since the last save to the log.
+    ///
+    /// Nothing is written if there is no new node. Only the new nodes are encoded and written,
+    /// but checking that the saved links did not change reads the links of the whole tree. If
+    /// they changed, the whole tree is written as with [`SnapshotLog::compact`], which
+    /// replaces the earlier frames when the log is read.
+    ///
+    /// # Parameters
+    /// - `tree`: The tree being saved.
+    /// - `writer`: The end of the log.
+    ///
+    /// # Returns
+    /// The number of nodes written: the new nodes, or all of them if the whole tree was
+    /// written.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::NotAppendOnly`] if the tree changed in a way an append cannot
+    ///   record, as described in [`SnapshotLog`].
+    /// - [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    /// - [`SnapshotError::Io`] if `writer` fails.
+    pub fn append<T: Serialize, D: TreeStorage<T>>(
+        &mut self,
+        tree: &Tree<T, D>,
+        writer: impl io::Write,
+    ) -> Result<usize, SnapshotError> {
+        let start = self.saved_len;
+        if start <= tree.links.len() && links_checksum(tree, start) != self.saved_links {
+            self.compact(tree, writer)?;
+            return Ok(tree.links.len());
+        }
+        if !is_appendable(tree, start) {
+            return Err(SnapshotError::NotAppendOnly);
+        }
+        let len = tree.links.len();
+        if len == start {
+            return Ok(0);
+        }
+        let frame: FrameRef<T, D> = FrameRef::Append {
+            start,
+            parents: tree.links[start..]
+                .iter()
+                .map(|links| links.parent())
+                .collect(),
+            data: &tree.data[start..],
+        };
+        write_frame(&frame, &self.options, writer)?;
+        self.saved_len = len;
+        self.saved_links = links_checksum(tree, len);
+        Ok(len - start)
+    }
+
+    /// Writes the whole tree as a single frame, which replaces everything before it when the
+    /// log is read.
+    ///
+    /// This is meant to start a new, compact file once a log has grown many frames, or after
+    /// changes that cannot be appended. Write it to a temporary file and move it over the old
+    /// log, so that the log stays intact if writing is interrupted.
+    ///
+    /// # Parameters
+    /// - `tree`: The tree being saved.
+    /// - `writer`: The start of the new log.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::Encode`] if the data of a node cannot be encoded.
+    /// - [`SnapshotError::Io`] if `writer` fails.
+    pub fn compact<T: Serialize, D: TreeStorage<T>>(
+        &mut self,
+        tree: &Tree<T, D>,
+        writer: impl io::Write,
+    ) -> Result<(), SnapshotError> {
+        write_frame(&FrameRef::Base(tree), &self.options, writer)?;
+        self.saved_len = tree.links.len();
+        self.saved_links = links_checksum(tree, self.saved_len);
+        Ok(())
+    }
+}
+
+/// Writes a frame preceded by its length.
+fn write_frame<T: Serialize, D: TreeStorage<T>>(
+    frame: &FrameRef<'_, T, D>,
+    options: &SnapshotOptions,
+    mut writer: impl io::Write,
+) -> Result<(), SnapshotError> {
+    let bytes = encode_snapshot(frame, options)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Returns a checksum of the links among the first `len` nodes: the parent of each node and
+/// the order of its children below `len`.
+fn links_checksum<T, D: TreeStorage<T>>(tree: &Tree<T, D>, len: usize) -> u64 {
+    let mut hasher = Xxh64::new(0);
+    for links in &tree.links[..len] {
+        let parent = links.parent().map_or(u64::MAX, |parent| parent as u64);
+        hasher.update(&parent.to_le_bytes());
+        for &child in links.children.iter().filter(|&&child| child < len) {
+            hasher.update(&(child as u64).to_le_bytes());
+        }
+        // Ends the children, so that moving a child to the next node changes the checksum.
+        hasher.update(&u64::MAX.to_le_bytes());
+    }
+    hasher.digest()
+}
+
+/// Returns `true` if replaying the nodes from index `start` on, linking each to its parent in
+/// index order, rebuilds `tree` from its first `start` nodes.
+fn is_appendable<T, D: TreeStorage<T>>(tree: &Tree<T, D>, start: usize) -> bool {
+    if tree.links.len() < start {
+        return false;
+    }
+    let mut parents = HashSet::new();
+    for index in start..tree.links.len() {
+        let links = &tree.links[index];
+        // Children of new nodes with lower indices are old nodes that changed parent.
+        if links.children.iter().any(|&child| child < index) {
+            return false;
+        }
+        if let Some(parent) = links.parent() {
+            if parent >= index {
+                return false;
+            }
+            parents.insert(parent);
+        }
+    }
+    parents.into_iter().all(|parent| {
+        let children = &tree.links[parent].children;
+        let old = children.iter().take_while(|&&child| child < start).count();
+        let new_children = &children[old..];
+        new_children.windows(2).all(|pair| pair[0] <= pair[1])
+            && new_children.iter().all(|&child| child >= start)
+    })
+}
+
+impl<T: DeserializeOwned> Tree<T> {
+    /// Reads a log written by a [`SnapshotLog`], replaying its frames in order.
+    ///
+    /// # Parameters
+    /// - `reader`: The log, read to its end.
+    /// - `data_version`: The data version every frame must have.
+    ///
+    /// # Errors
+    /// - [`SnapshotError::Io`] if `reader` fails.
+    /// - [`SnapshotError::TruncatedLog`] if the log ends in the middle of a frame.
+    /// - [`SnapshotError::ChecksumMismatch`] if a frame with a checksum is corrupted.
+    /// - [`SnapshotError::UnknownFormat`] if a frame was written in a format that is not
+    ///   enabled.
+    /// - [`SnapshotError::UnsupportedDataVersion`] if a frame has another data version.
+    /// - [`SnapshotError::Decode`] if a frame does not hold valid nodes.
+    pub fn read_snapshot_log<R: io::Read>(
+        mut reader: R,
+        data_version: u32,
+    ) -> Result<Self, SnapshotError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut tree = Tree::new();
+        let mut rest = &bytes[..];
+        while !rest.is_empty() {
+            let (len, after) = rest
+                .split_first_chunk()
+                .ok_or(SnapshotError::TruncatedLog)?;
+            let len = usize::try_from(u64::from_le_bytes(*len))
+                .ok()
+                .filter(|&len| len <= after.len())
+                .ok_or(SnapshotError::TruncatedLog)?;
+            let (frame, after) = after.split_at(len);
+            rest = after;
+
+            let (tag, version, payload) = read_header(frame)?;
+            let format = Format::from_tag(tag).ok_or(SnapshotError::UnknownFormat(tag))?;
+            if version != data_version {
+                return Err(SnapshotError::UnsupportedDataVersion(version));
+            }
+            match format.decode(payload)? {
+                Frame::Base(base) => tree = base,
+                Frame::Append {
+                    start,
+                    parents,
+                    data,
+                } => {
+                    if start != tree.len() || parents.len() != data.len() {
+                        return Err(SnapshotError::Decode(format!(
+                            "frame of {} nodes from node {start} does not follow {} nodes",
+                            data.len(),
+                            tree.len()
+                        )));
+                    }
+                    for data in data {
+                        tree.add_node(data);
+                    }
+                    for (index, parent) in (start..).zip(parents) {
+                        match parent {
+                            Some(parent) if parent < index => tree.link(parent, index),
+                            Some(parent) => {
+                                return Err(SnapshotError::Decode(format!(
+                                    "parent {parent} of node {index} does not precede it"
+                                )))
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::snapshot;
+
+    fn options() -> SnapshotOptions {
+        let format = [
+            #[cfg(feature = "bincode")]
+            Format::Bincode,
+            #[cfg(feature = "cbor")]
+            Format::Cbor,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack,
+        ][0];
+        SnapshotOptions::new(format).with_checksum()
+    }
+
+    #[test]
+    fn test_snapshot_log_appends_and_compacts() {
+        let mut tree = Tree::new();
+        let mut log = SnapshotLog::new(options());
+        let mut file = Vec::new();
+        let mut frame_ends = Vec::new();
+        for round in 0..5 {
+            for index in 0..7 {
+                let value = tree.len();
+                if round + index == 0 || index == 3 {
+                    tree.add_node(value);
+                } else {
+                    tree.add_child(value * 7 % tree.len(), value);
+                }
+            }
+            assert_eq!(log.append(&tree, &mut file).unwrap(), 7);
+            frame_ends.push(file.len());
+        }
+        assert_eq!(log.append(&tree, &mut file).unwrap(), 0);
+        assert_eq!(file.len(), *frame_ends.last().unwrap());
+
+        let loaded = Tree::<usize>::read_snapshot_log(&file[..], 0).unwrap();
+        assert_eq!(snapshot(&loaded), snapshot(&tree));
+        // A log cut inside a frame is reported, and one cut between frames holds earlier saves.
+        assert_eq!(
+            Tree::<usize>::read_snapshot_log(&file[..file.len() - 1], 0).err(),
+            Some(SnapshotError::TruncatedLog)
+        );
+        let partial = Tree::<usize>::read_snapshot_log(&file[..frame_ends[2]], 0).unwrap();
+        assert_eq!(partial.len(), 21);
+
+        // Attaching a new node below a later one needs a compaction.
+        let detached = tree.add_node(99);
+        let later = tree.add_child(0, 100);
+        tree.set_parent(detached, later).unwrap();
+        assert_eq!(
+            log.append(&tree, &mut file).err(),
+            Some(SnapshotError::NotAppendOnly)
+        );
+        let mut compacted = Vec::new();
+        log.compact(&tree, &mut compacted).unwrap();
+        tree.add_child(4, 101);
+        let mut log = SnapshotLog::resume(
+            options(),
+            &Tree::<usize>::read_snapshot_log(&compacted[..], 0).unwrap(),
+        );
+        log.append(&tree, &mut compacted).unwrap();
+        let loaded = Tree::<usize>::read_snapshot_log(&compacted[..], 0).unwrap();
+        assert_eq!(snapshot(&loaded), snapshot(&tree));
+        assert_eq!(
+            Tree::<usize>::read_snapshot_log(&compacted[..], 1).err(),
+            Some(SnapshotError::UnsupportedDataVersion(0))
+        );
+    }
+
+    #[test]
+    fn test_changed_links_of_saved_nodes_rewrite_the_tree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0_usize);
+        let a = tree.add_child(root, 1);
+        let b = tree.add_child(root, 2);
+        let a1 = tree.add_child(a, 3);
+        let mut log = SnapshotLog::new(options());
+        let mut file = Vec::new();
+        log.append(&tree, &mut file).unwrap();
+
+        // Reparent a saved node under another saved node, then add a node.
+        tree.detach(a1);
+        tree.set_parent(a1, b).unwrap();
+        tree.add_child(a, 4);
+        assert_eq!(log.append(&tree, &mut file).unwrap(), 5);
+        let loaded = Tree::<usize>::read_snapshot_log(&file[..], 0).unwrap();
+        assert_eq!(snapshot(&loaded), snapshot(&tree));
+
+        // Reorder the children of a saved node, with nothing new to append.
+        tree.sort_children_by_key(root, |value| std::cmp::Reverse(*value));
+        assert_eq!(log.append(&tree, &mut file).unwrap(), 5);
+        tree.detach(a);
+        let mut log = SnapshotLog::resume(
+            options(),
+            &Tree::<usize>::read_snapshot_log(&file[..], 0).unwrap(),
+        );
+        log.append(&tree, &mut file).unwrap();
+        assert_eq!(log.append(&tree, &mut file).unwrap(), 0);
+        let loaded = Tree::<usize>::read_snapshot_log(&file[..], 0).unwrap();
+        assert_eq!(snapshot(&loaded), snapshot(&tree));
+    }
+
+    #[test]
+    fn test_is_appendable() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let a = tree.add_child(root, 1);
+        assert!(is_appendable(&tree, 0));
+        let b = tree.add_child(root, 2);
+        let c = tree.add_node(3);
+        assert!(is_appendable(&tree, 2));
+        tree.set_parent(c, a).unwrap();
+        assert!(is_appendable(&tree, 2));
+        assert_eq!(tree.children(root), &[a, b]);
+
+        // A new node attached to a later node.
+        let mut later = Tree::new();
+        later.add_node(0);
+        let x = later.add_node(1);
+        let y = later.add_node(2);
+        later.set_parent(x, y).unwrap();
+        assert!(!is_appendable(&later, 1));
+        // A new child placed before an older new sibling.
+        let mut reordered = Tree::new();
+        let root = reordered.add_node(0);
+        let first = reordered.add_node(1);
+        reordered.add_child(root, 2);
+        reordered.set_parent(first, root).unwrap();
+        assert!(!is_appendable(&reordered, 1));
+        assert!(!is_appendable(&reordered, 5));
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{Links, Tree, TreeStorage};
+use std::cmp::Ordering;
+use std::mem;
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Sorts the children of a node by a key, so that [`Tree::add_child_sorted`] and
+    /// [`Tree::child_by_key`] can be used on it. The sort is stable.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the node whose children to sort.
+    /// - `key`: Extracts the sort key from a child's data.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds.
+    pub fn sort_children_by_key<K: Ord>(&mut self, parent: usize, mut key: impl FnMut(&T) -> K) {
+        let mut children = std::mem::take(&mut self.links[parent].children);
+        children.sort_by_key(|&child| key(&self.data[child]));
+        self.links[parent].children = children;
+    }
+
+    /// Adds a child to a node whose children are sorted by `key`, inserting it at its sorted
+    /// position with a binary search. The child is placed after any children with an equal
+    /// key, so the order stays stable.
+    ///
+    /// The children of `parent` must already be sorted by the same key, for example because
+    /// they were all added with this method or sorted with [`Tree::sort_children_by_key`].
+    /// The tree does not remember the key, so children attached in other ways, such as with
+    /// [`Tree::add_child`] or [`Tree::set_parent`], are appended and can break the order; sort
+    /// the children again afterwards.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the parent node.
+    /// - `data`: The data to associate with the new child node.
+    /// - `key`: Extracts the sort key from a child's data.
+    ///
+    /// # Returns
+    /// The index of the newly added child node.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds. In debug builds, it also panics if the
+    /// children of `parent` are not sorted by `key`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut words = Tree::new();
+    /// let root = words.add_node("");
+    /// for word in ["cat", "ape", "bee"] {
+    ///     words.add_child_sorted(root, word, |word| *word);
+    /// }
+    ///
+    /// let children: Vec<_> = words.children(root).iter().map(|&child| words[child]).collect();
+    /// assert_eq!(children, vec!["ape", "bee", "cat"]);
+    /// ```
+    pub fn add_child_sorted<K: Ord>(
+        &mut self,
+        parent: usize,
+        data: T,
+        mut key: impl FnMut(&T) -> K,
+    ) -> usize {
+        self.debug_assert_children_sorted(parent, &mut key);
+        let new_key = key(&data);
+        let position = self.links[parent]
+            .children
+            .partition_point(|&child| key(&self.data[child]) <= new_key);
+        let index = self.add_node(data);
+        self.links[parent].children.insert(position, index);
+        self.links[index].set_parent(parent);
+        index
+    }
+
+    /// Finds a child of a node whose children are sorted by `key`, using a binary search.
+    ///
+    /// The children of `parent` must be sorted by the same key, as with
+    /// [`Tree::add_child_sorted`]. Otherwise the search can miss a child that exists.
+    ///
+    /// # Parameters
+    /// - `parent`: The index of the parent node.
+    /// - `wanted`: The key to look for.
+    /// - `key`: Extracts the sort key from a child's data.
+    ///
+    /// # Returns
+    /// The index of a child with the wanted key, or `None` if there is none. If several
+    /// children have the key, the first of them is returned.
+    ///
+    /// # Panics
+    /// This method panics if `parent` is out of bounds. In debug builds, it also panics if the
+    /// children of `parent` are not sorted by `key`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut words = Tree::new();
+    /// let root = words.add_node("");
+    /// let bee = words.add_child_sorted(root, "bee", |word| *word);
+    /// words.add_child_sorted(root, "ape", |word| *word);
+    ///
+    /// assert_eq!(words.child_by_key(root, &"bee", |word| *word), Some(bee));
+    /// assert_eq!(words.child_by_key(root, &"cat", |word| *word), None);
+    /// ```
+    pub fn child_by_key<K: Ord>(
+        &self,
+        parent: usize,
+        wanted: &K,
+        mut key: impl FnMut(&T) -> K,
+    ) -> Option<usize> {
+        self.debug_assert_children_sorted(parent, &mut key);
+        let children = &self.links[parent].children;
+        let position = children.partition_point(|&child| key(&self.data[child]) < *wanted);
+        children
+            .get(position)
+            .copied()
+            .filter(|&child| key(&self.data[child]) == *wanted)
+    }
+
+    /// Panics in debug builds if the children of `parent` are not sorted by `key`, since the
+    /// binary searches of the sorted methods silently go wrong on them.
+    fn debug_assert_children_sorted<K: Ord>(&self, parent: usize, key: &mut impl FnMut(&T) -> K) {
+        if cfg!(debug_assertions) {
+            let children = &self.links[parent].children;
+            let sorted = children
+                .windows(2)
+                .all(|pair| key(&self.data[pair[0]]) <= key(&self.data[pair[1]]));
+            assert!(
+                sorted,
+                "the children of node {parent} are not sorted by the key; sort them with \
+                 sort_children_by_key after attaching children in other ways"
+            );
+        }
+    }
+
+    /// Puts the tree in a canonical form: sorts the children of every node and the nodes
+    /// without a parent with `compare`, then renumbers the nodes in depth-first order.
+    ///
+    /// The [root](Tree::root) stays first, at index `0`, so that traversals still start from
+    /// it. Only the other nodes without a parent are sorted, and follow its subtree.
+    ///
+    /// Siblings that `compare` considers equal are ordered by their subtrees, comparing their
+    /// nodes in depth-first order, so that two trees with the same nodes and links, whatever
+    /// the order in which they were built, become identical, index for index. Their
+    /// serializations, hashes and diffs then match too.
+    ///
+    /// # Parameters
+    /// - `compare`: Orders the data of two nodes.
+    ///
+    /// # Returns
+    /// A mapping from old node indices to new node indices.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut first = Tree::new();
+    /// let root = first.add_node("config");
+    /// let b = first.add_child(root, "b");
+    /// first.add_child(b, "b1");
+    /// first.add_child(root, "a");
+    ///
+    /// let mut second = Tree::new();
+    /// let a = second.add_node("a");
+    /// let root = second.add_node("config");
+    /// let b = second.add_node("b");
+    /// second.add_child(b, "b1");
+    /// second.set_parent(b, root).unwrap();
+    /// second.set_parent(a, root).unwrap();
+    ///
+    /// let remap = first.canonicalize_by(|x, y| x.cmp(y));
+    /// second.canonicalize_by(|x, y| x.cmp(y));
+    /// assert_eq!(remap, [0, 2, 3, 1]);
+    /// assert_eq!(first.data(), &["config", "a", "b", "b1"]);
+    /// assert_eq!(first.data(), second.data());
+    /// assert_eq!(first.children(0), second.children(0));
+    /// ```
+    pub fn canonicalize_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) -> Vec<usize> {
+        let mut roots: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+
+        // Sort the children of every node after those of its descendants, so that the
+        // subtrees compared when breaking ties are already canonical.
+        let mut order = Vec::with_capacity(self.links.len());
+        let mut stack: Vec<usize> = roots.clone();
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            stack.extend(&self.links[index].children);
+        }
+        for &index in order.iter().rev() {
+            let mut children = mem::take(&mut self.links[index].children);
+            children
+                .sort_by(|&a, &b| compare_subtrees(&self.data, &self.links, a, b, &mut compare));
+            self.links[index].children = children;
+        }
+        if let Some((_, others)) = roots.split_first_mut() {
+            others.sort_by(|&a, &b| compare_subtrees(&self.data, &self.links, a, b, &mut compare));
+        }
+
+        // Renumber the nodes in depth-first order.
+        let mut remap = vec![0; self.links.len()];
+        let mut next = 0;
+        let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+        while let Some(index) = stack.pop() {
+            remap[index] = next;
+            next += 1;
+            stack.extend(self.links[index].children.iter().rev());
+        }
+        self.renumber(&remap);
+        remap
+    }
+
+    /// Moves the node at every index `index` to `remap[index]`, rewriting all links so that
+    /// the structure of the tree is unchanged. `remap` must be a permutation of the indices.
+    pub(crate) fn renumber(&mut self, remap: &[usize]) {
+        if let Some(first_moved) = (0..remap.len()).find(|&index| remap[index] != index) {
+            self.history.changed_from(first_moved);
+        }
+        // Move every value to its new index in place, one cycle of the permutation at a time.
+        let mut target = remap.to_vec();
+        for index in 0..target.len() {
+            while target[index] != index {
+                let other = target[index];
+                self.data.swap(index, other);
+                self.ext.swap(index, other);
+                target.swap(index, other);
+            }
+        }
+
+        let mut links = vec![Links::default(); self.links.len()];
+        for (index, mut old) in mem::take(&mut self.links).into_iter().enumerate() {
+            old.replace_parent(old.parent().map(|parent| remap[parent]));
+            for child in &mut old.children {
+                *child = remap[*child];
+            }
+            links[remap[index]] = old;
+        }
+        self.links = links;
+    }
+
+    /// Renumbers the tree so that `nodes` take the last indices, in the given order, and the
+    /// other nodes keep their relative order before them. `nodes` must not hold duplicates.
+    ///
+    /// Returns the new index of the first of `nodes`.
+    pub(crate) fn move_to_end(&mut self, nodes: &[usize]) -> usize {
+        let kept = self.links.len() - nodes.len();
+        let mut remap = vec![usize::MAX; self.links.len()];
+        for (position, &node) in nodes.iter().enumerate() {
+            remap[node] = kept + position;
+        }
+        let slots = remap.iter_mut().filter(|slot| **slot == usize::MAX);
+        for (next, slot) in slots.enumerate() {
+            *slot = next;
+        }
+        self.renumber(&remap);
+        kept
+    }
+
+    /// Puts the tree in a canonical form, ordering nodes by their data, like
+    /// [`Tree::canonicalize_by`].
+    ///
+    /// # Returns
+    /// A mapping from old node indices to new node indices.
+    pub fn canonicalize(&mut self) -> Vec<usize>
+    where
+        T: Ord,
+    {
+        self.canonicalize_by(T::cmp)
+    }
+}
+
+/// Compares the subtrees of `a` and `b` node by node in depth-first order, looking at the data
+/// and the number of children of every node.
+fn compare_subtrees<T>(
+    data: &[T],
+    links: &[Links],
+    a: usize,
+    b: usize,
+    compare: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Ordering {
+    let mut stack = vec![(a, b)];
+    while let Some((a, b)) = stack.pop() {
+        let (a_children, b_children) = (&links[a].children, &links[b].children);
+        let ordering = compare(&data[a], &data[b]).then(a_children.len().cmp(&b_children.len()));
+        if ordering.is_ne() {
+            return ordering;
+        }
+        stack.extend(
+            a_children
+                .iter()
+                .copied()
+                .zip(b_children.iter().copied())
+                .rev(),
+        );
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_children_stay_sorted_and_stable() {
+        let mut tree = Tree::new();
+        let root = tree.add_node((0, 'r'));
+        tree.add_child(root, (5, 'a'));
+        tree.add_child(root, (1, 'b'));
+        tree.sort_children_by_key(root, |&(key, _)| key);
+
+        for (key, tag) in [(3, 'c'), (1, 'd'), (9, 'e'), (3, 'f'), (0, 'g')] {
+            tree.add_child_sorted(root, (key, tag), |&(key, _)| key);
+        }
+        let children: Vec<_> = tree
+            .children(root)
+            .iter()
+            .map(|&child| tree[child])
+            .collect();
+        assert_eq!(
+            children,
+            vec![
+                (0, 'g'),
+                (1, 'b'),
+                (1, 'd'),
+                (3, 'c'),
+                (3, 'f'),
+                (5, 'a'),
+                (9, 'e')
+            ]
+        );
+
+        let found = tree.child_by_key(root, &3, |&(key, _)| key).unwrap();
+        assert_eq!(tree[found], (3, 'c'));
+        assert_eq!(tree.child_by_key(root, &4, |&(key, _)| key), None);
+        assert_eq!(tree.child_by_key(root, &10, |&(key, _)| key), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "the children of node 0 are not sorted by the key")]
+    fn test_sorted_lookup_on_unsorted_children_panics_in_debug_builds() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        tree.add_child_sorted(root, 2, |&key| key);
+        tree.add_child(root, 1);
+        tree.child_by_key(root, &1, |&key| key);
+    }
+
+    #[test]
+    fn test_canonicalize_breaks_ties_by_subtree() {
+        let mut first = Tree::new();
+        let root = first.add_node("root");
+        let x = first.add_child(root, "x");
+        first.add_child(x, "2");
+        let x = first.add_child(root, "x");
+        first.add_child(x, "1");
+        first.add_node("other");
+
+        let mut second = Tree::new();
+        let root = second.add_node("root");
+        second.add_node("other");
+        let x = second.add_child(root, "x");
+        second.add_child(x, "1");
+        let x = second.add_child(root, "x");
+        second.add_child(x, "2");
+
+        assert_eq!(first.canonicalize(), [0, 3, 4, 1, 2, 5]);
+        second.canonicalize();
+        assert_eq!(first.data(), &["root", "x", "1", "x", "2", "other"]);
+        assert_eq!(first.root(), Some(0));
+        assert_eq!(first.data(), second.data());
+        for index in 0..first.len() {
+            assert_eq!(first.children(index), second.children(index));
+            assert_eq!(
+                first.parent_index_unchecked(index),
+                second.parent_index_unchecked(index)
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{Links, Tree, TreeStorage};
+
+/// A read-only view of the structure of a [`Tree`]: the parent and children of every node,
+/// without access to their data.
+///
+/// Obtained from [`Tree::split`] together with mutable access to the data, or from
+/// [`Tree::structure`].
+#[derive(Clone, Copy)]
+pub struct TreeStructure<'a> {
+    links: &'a [Links],
+}
+
+impl<'a> TreeStructure<'a> {
+    /// Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns `true` if the tree contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Returns the indices of the children of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn children(&self, index: usize) -> &'a [usize] {
+        &self.links[index].children
+    }
+
+    /// Returns the index of the parent of a node, or `None` if it has no parent.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        self.links[index].parent()
+    }
+
+    /// Returns an iterator over the indices of all nodes reachable from `root`, including
+    /// `root` itself, in depth-first pre-order.
+    ///
+    /// # Panics
+    /// The iterator panics if `root` is out of bounds.
+    pub fn reachable_from(&self, root: usize) -> impl Iterator<Item = usize> + 'a {
+        let links = self.links;
+        let mut stack = vec![root];
+        std::iter::from_fn(move || {
+            let index = stack.pop()?;
+            stack.extend(links[index].children.iter().rev());
+            Some(index)
+        })
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Splits the tree into its structure and its data, so that the structure can be queried
+    /// while the data is being modified.
+    ///
+    /// # Returns
+    /// A read-only [`TreeStructure`] and a mutable slice of the data of every node, by index.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(1);
+    /// let child = tree.add_child(root, 10);
+    /// tree.add_child(child, 100);
+    ///
+    /// // Add the parent's value to every node, top-down.
+    /// let (structure, data) = tree.split();
+    /// for index in structure.reachable_from(root) {
+    ///     for &child in structure.children(index) {
+    ///         data[child] += data[index];
+    ///     }
+    /// }
+    /// assert_eq!(tree.iter().map(|(_, value)| *value).collect::<Vec<_>>(), vec![1, 11, 111]);
+    /// ```
+    pub fn split(&mut self) -> (TreeStructure<'_>, &mut [T]) {
+        (TreeStructure { links: &self.links }, &mut self.data)
+    }
+
+    /// Returns a read-only view of the structure of the tree.
+    pub fn structure(&self) -> TreeStructure<'_> {
+        TreeStructure { links: &self.links }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_structure_matches_tree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(String::from("a"));
+        let b = tree.add_child(root, String::from("b"));
+        tree.add_child(b, String::from("c"));
+        tree.add_node(String::from("orphan"));
+
+        let structure = tree.structure();
+        assert_eq!(structure.len(), 4);
+        assert_eq!(structure.parent(b), Some(root));
+        assert_eq!(structure.reachable_from(root).count(), 3);
+
+        let (structure, data) = tree.split();
+        for index in structure.reachable_from(root).collect::<Vec<_>>() {
+            if let Some(parent) = structure.parent(index) {
+                data[index] = format!("{}/{}", data[parent], data[index]);
+            }
+        }
+        assert_eq!(tree.path_string(2, "|"), "a|a/b|a/b/c");
+    }
+}
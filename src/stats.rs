@@ -0,0 +1,249 @@
+use crate::{Links, Tree, TreeStorage};
+use std::collections::VecDeque;
+use std::mem;
+
+/// Summary statistics describing the shape of a [`Tree`].
+///
+/// Obtained via [`Tree::stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreeStats {
+    /// Total number of nodes in the tree.
+    pub node_count: usize,
+    /// Number of nodes without children.
+    pub leaf_count: usize,
+    /// Number of levels in the tree. `0` for an empty tree, `1` for a tree with only a root.
+    pub height: usize,
+    /// The largest number of children any single node has.
+    pub max_branching_factor: usize,
+    /// The average number of children of the nodes that have at least one child.
+    /// `0.0` if no node has children.
+    pub avg_branching_factor: f64,
+    /// Number of nodes on each level, starting with the roots at index `0`.
+    pub width_per_level: Vec<usize>,
+    /// Estimated number of bytes used by the tree structure itself, including the node arena
+    /// and child index vectors. Heap memory owned by the payloads is not included.
+    pub memory_footprint: usize,
+}
+
+/// A breakdown of the memory used by a [`Tree`].
+///
+/// Obtained via [`Tree::memory_usage`] or [`Tree::memory_usage_with`]. All values are in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Bytes used by the node payloads: `size_of::<T>()` per node, plus whatever the size
+    /// callback reported for heap memory owned by the payloads.
+    pub payload_bytes: usize,
+    /// Bytes used by the per-node bookkeeping (parent link and child vector header).
+    pub node_overhead_bytes: usize,
+    /// Bytes used by the child index vectors, counting only their occupied length.
+    pub child_vector_bytes: usize,
+    /// Bytes allocated but not used, both in the node arena and in the child index vectors.
+    pub slack_bytes: usize,
+}
+
+impl MemoryReport {
+    /// Returns the sum of all the categories in the report.
+    pub fn total(&self) -> usize {
+        self.payload_bytes + self.node_overhead_bytes + self.child_vector_bytes + self.slack_bytes
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Computes statistics about the shape of the tree.
+    ///
+    /// All nodes are taken into account, including nodes added with [`Tree::add_node`] that
+    /// are not connected to the first root. Every parentless node starts a new level `0`.
+    ///
+    /// # Returns
+    /// A [`TreeStats`] report.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child1 = tree.add_child(root, "child1");
+    /// tree.add_child(root, "child2");
+    /// tree.add_child(child1, "grandchild");
+    ///
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.node_count, 4);
+    /// assert_eq!(stats.leaf_count, 2);
+    /// assert_eq!(stats.height, 3);
+    /// assert_eq!(stats.max_branching_factor, 2);
+    /// assert_eq!(stats.width_per_level, vec![1, 2, 1]);
+    /// ```
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats {
+            node_count: self.links.len(),
+            memory_footprint: mem::size_of::<Self>()
+                + self.data.capacity() * mem::size_of::<T>()
+                + self.links.capacity() * mem::size_of::<Links>(),
+            ..TreeStats::default()
+        };
+
+        let mut internal_nodes = 0;
+        let mut edges = 0;
+        for links in &self.links {
+            let children = links.children.len();
+            if children == 0 {
+                stats.leaf_count += 1;
+            } else {
+                internal_nodes += 1;
+                edges += children;
+            }
+            stats.max_branching_factor = stats.max_branching_factor.max(children);
+            stats.memory_footprint += links.children.capacity() * mem::size_of::<usize>();
+        }
+        if internal_nodes > 0 {
+            stats.avg_branching_factor = edges as f64 / internal_nodes as f64;
+        }
+
+        let mut queue: VecDeque<(usize, usize)> = self
+            .links
+            .iter()
+            .enumerate()
+            .filter(|(_, links)| links.parent().is_none())
+            .map(|(index, _)| (index, 0))
+            .collect();
+        while let Some((index, level)) = queue.pop_front() {
+            if stats.width_per_level.len() <= level {
+                stats.width_per_level.push(0);
+            }
+            stats.width_per_level[level] += 1;
+            for &child in &self.links[index].children {
+                queue.push_back((child, level + 1));
+            }
+        }
+        stats.height = stats.width_per_level.len();
+
+        stats
+    }
+
+    /// Reports how much memory the tree uses, counting payloads with `size_of::<T>()` only.
+    ///
+    /// Use [`Tree::memory_usage_with`] if the payloads own heap memory that should be
+    /// accounted for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0u64);
+    /// tree.add_child(root, 1);
+    ///
+    /// let report = tree.memory_usage();
+    /// assert_eq!(report.payload_bytes, 2 * std::mem::size_of::<u64>());
+    /// assert_eq!(report.child_vector_bytes, std::mem::size_of::<usize>());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryReport {
+        self.memory_usage_with(|_| 0)
+    }
+
+    /// Reports how much memory the tree uses.
+    ///
+    /// # Parameters
+    /// - `heap_size`: Returns the number of heap bytes owned by a payload, in addition to
+    ///   `size_of::<T>()` which is always counted.
+    ///
+    /// # Returns
+    /// A [`MemoryReport`] with the memory usage broken down by category.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(String::from("root"));
+    /// tree.add_child(root, String::from("child"));
+    ///
+    /// let report = tree.memory_usage_with(|data| data.capacity());
+    /// assert_eq!(
+    ///     report.payload_bytes,
+    ///     2 * std::mem::size_of::<String>() + "root".len() + "child".len()
+    /// );
+    /// ```
+    pub fn memory_usage_with(&self, mut heap_size: impl FnMut(&T) -> usize) -> MemoryReport {
+        let payload_size = mem::size_of::<T>();
+        let links_size = mem::size_of::<Links>();
+        let index_size = mem::size_of::<usize>();
+
+        let len = self.links.len();
+        let mut report = MemoryReport {
+            payload_bytes: len * payload_size,
+            node_overhead_bytes: len * links_size,
+            slack_bytes: (self.data.capacity() - len) * payload_size
+                + (self.links.capacity() - len) * links_size,
+            ..MemoryReport::default()
+        };
+        for (data, links) in self.data.iter().zip(&self.links) {
+            report.payload_bytes += heap_size(data);
+            report.child_vector_bytes += links.children.len() * index_size;
+            report.slack_bytes += (links.children.capacity() - links.children.len()) * index_size;
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_empty_tree() {
+        let tree: Tree<i32> = Tree::new();
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 0);
+        assert_eq!(stats.leaf_count, 0);
+        assert_eq!(stats.height, 0);
+        assert_eq!(stats.avg_branching_factor, 0.0);
+        assert!(stats.width_per_level.is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child1 = tree.add_child(root, 1);
+        let child2 = tree.add_child(root, 2);
+        tree.add_child(child1, 3);
+        tree.add_child(child1, 4);
+        tree.add_child(child1, 5);
+        tree.add_child(child2, 6);
+
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 7);
+        assert_eq!(stats.leaf_count, 4);
+        assert_eq!(stats.height, 3);
+        assert_eq!(stats.max_branching_factor, 3);
+        assert_eq!(stats.avg_branching_factor, 2.0);
+        assert_eq!(stats.width_per_level, vec![1, 2, 4]);
+        assert!(stats.memory_footprint >= 7 * std::mem::size_of::<crate::Node<i32>>());
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(vec![1u8; 10]);
+        tree.add_child(root, vec![2u8; 20]);
+
+        let report = tree.memory_usage_with(|data| data.capacity());
+        let vec_size = std::mem::size_of::<Vec<u8>>();
+        assert_eq!(report.payload_bytes, 2 * vec_size + 30);
+        assert_eq!(report.child_vector_bytes, std::mem::size_of::<usize>());
+        assert_eq!(
+            report.node_overhead_bytes,
+            2 * std::mem::size_of::<crate::Links>()
+        );
+        assert_eq!(
+            report.total(),
+            report.payload_bytes
+                + report.node_overhead_bytes
+                + report.child_vector_bytes
+                + report.slack_bytes
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::ops::{Deref, DerefMut};
+
+/// Storage for the data of the nodes of a [`Tree`](crate::Tree).
+///
+/// A tree keeps the data of its nodes in one contiguous column, indexed by node, and its links
+/// in a separate `Vec`. This trait abstracts the data column, so that node data can live in
+/// a slab, a bump arena, a memory-mapped file, or any other container that hands out a
+/// contiguous slice, while all of the tree's algorithms keep working unchanged.
+///
+/// `Vec<T>` is the default storage. To use another one, create the tree with
+/// [`Tree::with_storage`](crate::Tree::with_storage).
+/// The constructors that build a whole tree at once, [`Tree::rewrite`](crate::Tree::rewrite)
+/// and the `ptree` integration are only available for the default storage.
+///
+/// # Example
+/// A storage backed by a `Vec` that only keeps track of how many nodes were ever added:
+/// ```rust
+/// use easy_tree::{Tree, TreeStorage};
+/// use std::ops::{Deref, DerefMut};
+///
+/// #[derive(Default)]
+/// struct Counting<T> {
+///     items: Vec<T>,
+///     pushed: usize,
+/// }
+///
+/// impl<T> Deref for Counting<T> {
+///     type Target = [T];
+///
+///     fn deref(&self) -> &[T] {
+///         &self.items
+///     }
+/// }
+///
+/// impl<T> DerefMut for Counting<T> {
+///     fn deref_mut(&mut self) -> &mut [T] {
+///         &mut self.items
+///     }
+/// }
+///
+/// impl<T> TreeStorage<T> for Counting<T> {
+///     fn push(&mut self, value: T) {
+///         self.pushed += 1;
+///         self.items.push(value);
+///     }
+///
+///     fn pop(&mut self) -> Option<T> {
+///         self.items.pop()
+///     }
+/// }
+///
+/// let mut tree = Tree::with_storage(Counting::default());
+/// let root = tree.add_node("root");
+/// tree.add_child(root, "child");
+/// assert_eq!(tree.storage().pushed, 2);
+/// assert_eq!(tree.iter_dfs().map(|(_, data)| *data).collect::<Vec<_>>(), ["root", "child"]);
+/// ```
+pub trait TreeStorage<T>: Deref<Target = [T]> + DerefMut {
+    /// Appends a value at the end of the storage.
+    fn push(&mut self, value: T);
+
+    /// Removes the last value from the storage, if there is one.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Shortens the storage to its first `len` values, dropping the rest. Does nothing if the
+    /// storage is not longer than `len`.
+    fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Reserves room for at least `additional` more values. Storages that cannot grow
+    /// ahead of time can ignore this, which is the default.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Returns how many values the storage can hold without growing. Defaults to the current
+    /// length.
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> TreeStorage<T> for Vec<T> {
+    fn push(&mut self, value: T) {
+        Vec::push(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tree;
+
+    /// A storage that refuses to grow beyond a fixed capacity, like an arena with a single
+    /// pre-allocated block.
+    struct Fixed<T> {
+        items: Vec<T>,
+        limit: usize,
+    }
+
+    impl<T> Deref for Fixed<T> {
+        type Target = [T];
+
+        fn deref(&self) -> &[T] {
+            &self.items
+        }
+    }
+
+    impl<T> DerefMut for Fixed<T> {
+        fn deref_mut(&mut self) -> &mut [T] {
+            &mut self.items
+        }
+    }
+
+    impl<T> TreeStorage<T> for Fixed<T> {
+        fn push(&mut self, value: T) {
+            assert!(self.items.len() < self.limit, "arena is full");
+            self.items.push(value);
+        }
+
+        fn pop(&mut self) -> Option<T> {
+            self.items.pop()
+        }
+    }
+
+    #[test]
+    fn test_custom_storage_runs_tree_algorithms() {
+        let mut tree = Tree::with_storage(Fixed {
+            items: Vec::new(),
+            limit: 8,
+        });
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        let b = tree.add_child(root, "b");
+        tree.add_child(a, "a1");
+        tree.add_child(b, "b1");
+        tree.detach(a);
+
+        assert_eq!(tree.garbage_collect([root]).len(), 5);
+        assert_eq!(tree.storage().len(), 3);
+        let paths: Vec<_> = tree
+            .iter_dfs()
+            .map(|(index, _)| tree.path_string(index, "/"))
+            .collect();
+        assert_eq!(paths, vec!["root", "root/b", "root/b/b1"]);
+        assert_eq!(tree.stats().height, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "tree storage must be empty")]
+    fn test_with_storage_rejects_non_empty_storage() {
+        Tree::with_storage(vec![1]);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{Tree, TreeStorage};
+
+/// A read-only view of the subtree rooted at a node, borrowed from a [`Tree`].
+///
+/// Nodes are addressed with the same indices as in the underlying tree, so indices obtained
+/// from the view can be used with the tree directly and vice versa. Nodes outside of the
+/// subtree are not visible through the view.
+///
+/// Constructed with [`Tree::subtree`] or [`Tree::as_subtree`]. Functions that only need to
+/// read a tree can accept a `SubtreeRef` and work both on whole trees and on branches.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{SubtreeRef, Tree};
+///
+/// fn count_nodes<T>(tree: SubtreeRef<'_, T>) -> usize {
+///     tree.iter_dfs().count()
+/// }
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// let child = tree.add_child(root, "child");
+/// tree.add_child(child, "grandchild");
+///
+/// assert_eq!(count_nodes(tree.as_subtree()), 3);
+/// assert_eq!(count_nodes(tree.subtree(child)), 2);
+/// ```
+pub struct SubtreeRef<'a, T, D = Vec<T>> {
+    tree: &'a Tree<T, D>,
+    root: usize,
+}
+
+// Implemented manually so that `T` is not required to be `Clone`/`Copy`.
+impl<T, D> Clone for SubtreeRef<'_, T, D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, D> Copy for SubtreeRef<'_, T, D> {}
+
+impl<'a, T, D: TreeStorage<T>> SubtreeRef<'a, T, D> {
+    /// Returns the index of the root of the subtree.
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Returns the underlying tree.
+    pub fn tree(&self) -> &'a Tree<T, D> {
+        self.tree
+    }
+
+    /// Returns `true` if the node at `index` belongs to the subtree.
+    ///
+    /// This walks up the ancestors of the node, so it takes time proportional to its depth.
+    pub fn contains(&self, index: usize) -> bool {
+        if index >= self.tree.len() {
+            return false;
+        }
+        let mut current = Some(index);
+        while let Some(node) = current {
+            if node == self.root {
+                return true;
+            }
+            current = self.tree.links[node].parent();
+        }
+        false
+    }
+
+    /// Retrieves a reference to the data stored in a node of the subtree.
+    ///
+    /// # Returns
+    /// `Some(&T)` if the node belongs to the subtree, or `None` otherwise.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        if self.contains(index) {
+            self.tree.get(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a slice of the indices of the children of a node of the subtree.
+    ///
+    /// # Returns
+    /// The indices of the node's children, or an empty slice if the node does not belong to
+    /// the subtree.
+    pub fn children(&self, index: usize) -> &'a [usize] {
+        if self.contains(index) {
+            self.tree.children(index)
+        } else {
+            &[]
+        }
+    }
+
+    /// Returns the parent index of a node of the subtree.
+    ///
+    /// # Returns
+    /// `Some(parent_index)` if the node belongs to the subtree and is not its root, or `None`
+    /// otherwise.
+    pub fn parent_index(&self, index: usize) -> Option<usize> {
+        if index != self.root && self.contains(index) {
+            self.tree.links[index].parent()
+        } else {
+            None
+        }
+    }
+
+    /// Traverses the subtree in a depth-first manner, starting at its root.
+    ///
+    /// See [`Tree::traverse`] for the order in which the callbacks are called.
+    pub fn traverse<S>(
+        &self,
+        before_processing_children: impl FnMut(usize, &'a T, &mut S),
+        after_processing_the_subtree: impl FnMut(usize, &'a T, &mut S),
+        s: &mut S,
+    ) {
+        self.tree.traverse_from(
+            self.root,
+            before_processing_children,
+            after_processing_the_subtree,
+            s,
+        );
+    }
+
+    /// Returns an iterator over the indices and data of the nodes of the subtree, in
+    /// depth-first pre-order.
+    pub fn iter_dfs(&self) -> impl Iterator<Item = (usize, &'a T)> {
+        let tree = self.tree;
+        tree.reachable_from(self.root)
+            .map(move |index| (index, &tree.data[index]))
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns a read-only view of the subtree rooted at `root`.
+    ///
+    /// # Panics
+    /// This method panics if `root` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("root");
+    /// let child = tree.add_child(root, "child");
+    /// let grandchild = tree.add_child(child, "grandchild");
+    ///
+    /// let subtree = tree.subtree(child);
+    /// assert_eq!(subtree.get(grandchild), Some(&"grandchild"));
+    /// assert_eq!(subtree.get(root), None);
+    /// assert_eq!(subtree.parent_index(child), None);
+    /// ```
+    pub fn subtree(&self, root: usize) -> SubtreeRef<'_, T, D> {
+        assert!(
+            root < self.links.len(),
+            "node index {root} is out of bounds for a tree of {} nodes",
+            self.links.len()
+        );
+        SubtreeRef { tree: self, root }
+    }
+
+    /// Returns a read-only view of the whole tree, as visited by [`Tree::traverse`].
+    ///
+    /// # Panics
+    /// This method panics if the tree is empty.
+    pub fn as_subtree(&self) -> SubtreeRef<'_, T, D> {
+        self.subtree(self.root().expect("the tree is empty"))
+    }
+
+    /// Extracts the given nodes along with the minimal set of nodes needed to connect them:
+    /// the nodes on the paths between them, up to their lowest common ancestor.
+    ///
+    /// Nodes in different trees of a forest cannot be connected, so each tree contributes its
+    /// own connected part, rooted at the lowest common ancestor of the given nodes in it. To
+    /// show the nodes with their whole path from the root instead, include the root in
+    /// `nodes`.
+    ///
+    /// The kept nodes are numbered in depth-first pre-order, one tree after the other in the
+    /// index order of their roots, and keep the order of their children, so node `0` of the
+    /// result is a root.
+    ///
+    /// # Parameters
+    /// - `nodes`: The indices of the nodes to keep. Duplicates are ignored.
+    ///
+    /// # Panics
+    /// This method panics if any of `nodes` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let mut docs = Tree::new();
+    /// let root = docs.add_node("docs");
+    /// let guide = docs.add_child(root, "guide");
+    /// let install = docs.add_child(guide, "install");
+    /// docs.add_child(guide, "faq");
+    /// let usage = docs.add_child(guide, "usage");
+    /// let config = docs.add_child(usage, "config");
+    /// docs.add_child(root, "api");
+    ///
+    /// let results = docs.induced_subtree(&[config, install]);
+    /// assert_eq!(results.data(), &["guide", "install", "usage", "config"]);
+    /// assert_eq!(results.children(0), &[1, 2]);
+    /// assert_eq!(results.children(2), &[3]);
+    /// ```
+    pub fn induced_subtree(&self, nodes: &[usize]) -> Tree<T>
+    where
+        T: Clone,
+    {
+        let len = self.links.len();
+        let mut given = vec![false; len];
+        for &node in nodes {
+            given[node] = true;
+        }
+        // The given nodes and their ancestors, each walk stopping at the first marked node.
+        let marked = self.with_ancestors(nodes.iter().copied());
+        let marked_children = |index: usize| {
+            let children = self.links[index].children.iter().copied();
+            children.filter(|&child| marked.contains(child))
+        };
+
+        // List the marked nodes in depth-first pre-order, from the roots in index order.
+        let mut order = Vec::with_capacity(marked.count());
+        let mut stack: Vec<usize> = marked
+            .iter()
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+        stack.reverse();
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            let first = stack.len();
+            stack.extend(marked_children(index));
+            stack[first..].reverse();
+        }
+
+        // Count the given nodes in the subtree of every marked node, children first.
+        let mut counts = vec![0; len];
+        for &index in order.iter().rev() {
+            counts[index] += usize::from(given[index]);
+            if let Some(parent) = self.links[index].parent() {
+                counts[parent] += counts[index];
+            }
+        }
+
+        // Every marked node is kept, except those above the lowest common ancestor, which
+        // have all the given nodes of their tree below a single child. Kept nodes are
+        // attached to their closest kept ancestor.
+        let mut induced = Tree::new();
+        let mut root_of = vec![0; len];
+        let mut attach_to: Vec<Option<usize>> = vec![None; len];
+        for &index in &order {
+            let parent = self.links[index].parent();
+            root_of[index] = parent.map_or(index, |parent| root_of[parent]);
+            let above = parent.and_then(|parent| attach_to[parent]);
+            let branches = marked_children(index).count();
+            let keep = given[index] || branches > 1 || counts[index] < counts[root_of[index]];
+            attach_to[index] = if keep {
+                let data = self.data[index].clone();
+                Some(match above {
+                    Some(parent) => induced.add_child(parent, data),
+                    None => induced.add_node(data),
+                })
+            } else {
+                above
+            };
+        }
+        induced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subtree_ref() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child1 = tree.add_child(root, 1);
+        let child2 = tree.add_child(root, 2);
+        let grandchild = tree.add_child(child1, 3);
+
+        let subtree = tree.subtree(child1);
+        assert_eq!(subtree.root(), child1);
+        assert!(subtree.contains(grandchild));
+        assert!(!subtree.contains(child2));
+        assert!(!subtree.contains(100));
+        assert_eq!(subtree.children(child1), &[grandchild]);
+        assert!(subtree.children(root).is_empty());
+        assert_eq!(subtree.parent_index(grandchild), Some(child1));
+
+        let data: Vec<_> = subtree.iter_dfs().collect();
+        assert_eq!(data, vec![(child1, &1), (grandchild, &3)]);
+
+        let mut log = vec![];
+        subtree.traverse(
+            |idx, _, log| log.push(format!("enter {idx}")),
+            |idx, _, log| log.push(format!("leave {idx}")),
+            &mut log,
+        );
+        assert_eq!(log, vec!["enter 1", "enter 3", "leave 3", "leave 1"]);
+    }
+
+    #[test]
+    fn test_as_subtree_matches_traverse() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        tree.add_child(root, "a");
+        tree.add_node("detached");
+
+        let from_view: Vec<_> = tree.as_subtree().iter_dfs().collect();
+        let from_tree: Vec<_> = tree.iter_dfs().collect();
+        assert_eq!(from_view, from_tree);
+        assert_eq!(from_tree, vec![(0, &"root"), (1, &"a")]);
+    }
+
+    #[test]
+    fn test_induced_subtree_connects_nodes_per_tree() {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        let a1 = tree.add_child(a, "a1");
+        let b = tree.add_child(root, "b");
+        let b1 = tree.add_child(b, "b1");
+        let other = tree.add_node("other");
+        let o1 = tree.add_child(other, "o1");
+
+        let induced = tree.induced_subtree(&[a1, b1, o1, a1]);
+        assert_eq!(induced.data(), &["root", "a", "a1", "b", "b1", "o1"]);
+        assert_eq!(induced.children(0), &[1, 3]);
+        assert_eq!(induced.parent_index_unchecked(5), None);
+
+        let single = tree.induced_subtree(&[b, b1]);
+        assert_eq!(single.data(), &["b", "b1"]);
+        assert!(tree.induced_subtree(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_induced_subtree_starts_with_a_root() {
+        // The root is added after its children and attached with `set_parent`.
+        let mut tree = Tree::new();
+        let a = tree.add_node("a");
+        let a1 = tree.add_child(a, "a1");
+        let b = tree.add_node("b");
+        let root = tree.add_node("root");
+        tree.set_parent(a, root).unwrap();
+        tree.set_parent(b, root).unwrap();
+
+        let induced = tree.induced_subtree(&[a1, b]);
+        assert_eq!(induced.data(), &["root", "a", "a1", "b"]);
+        assert_eq!(induced.parent_index_unchecked(0), None);
+        let visited: Vec<_> = induced.iter_dfs().map(|(_, name)| *name).collect();
+        assert_eq!(visited, ["root", "a", "a1", "b"]);
+    }
+}
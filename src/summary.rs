@@ -0,0 +1,245 @@
+use crate::{Tree, TreeStorage};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+/// A node of a tree produced by [`Tree::summarize`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Summary<T> {
+    /// A node that was kept.
+    Node {
+        /// The index of the node in the summarized tree.
+        index: usize,
+        /// The data of the node.
+        data: T,
+    },
+    /// A placeholder for the nodes that were left out below a kept node.
+    Collapsed {
+        /// The number of nodes left out.
+        count: usize,
+    },
+}
+
+impl<T: fmt::Display> fmt::Display for Summary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Summary::Node { data, .. } => data.fmt(f),
+            Summary::Collapsed { count } => write!(f, "… ({count} more)"),
+        }
+    }
+}
+
+impl<T> Summary<T> {
+    /// Maps the data of a kept node, leaving placeholders unchanged.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Summary<U> {
+        match self {
+            Summary::Node { index, data } => Summary::Node {
+                index,
+                data: f(data),
+            },
+            Summary::Collapsed { count } => Summary::Collapsed { count },
+        }
+    }
+}
+
+/// A candidate node of [`Tree::summarize`], ordered so that the most important, and among
+/// equally important the earliest discovered, candidate is popped first from a max-heap.
+struct Candidate<C> {
+    importance: C,
+    sequence: usize,
+    index: usize,
+}
+
+impl<C: PartialOrd> PartialEq for Candidate<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: PartialOrd> Eq for Candidate<C> {}
+
+impl<C: PartialOrd> PartialOrd for Candidate<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: PartialOrd> Ord for Candidate<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.importance
+            .partial_cmp(&other.importance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Reduces the subtree of the [root](Tree::root) to at most `max_nodes` of its most important nodes,
+    /// replacing the rest with placeholders.
+    ///
+    /// Nodes are kept greedily by decreasing importance, and a node can only be kept once its
+    /// parent is, so the kept nodes always form a connected tree around the root. Nodes with
+    /// equal importance are kept in the order they were discovered. Below every kept node whose subtree
+    /// was not kept entirely, a [`Summary::Collapsed`] placeholder is added after its kept
+    /// children, counting the nodes that were left out there.
+    ///
+    /// # Parameters
+    /// - `max_nodes`: The maximum number of nodes to keep, not counting placeholders.
+    /// - `importance_fn`: Returns the importance of a node, given its index and data.
+    ///
+    /// # Returns
+    /// A tree of the kept nodes and placeholders, borrowing the data of this tree. It is empty
+    /// if this tree is, and consists of a single placeholder if `max_nodes` is `0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::testing::snapshot;
+    /// use easy_tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(("/", 100));
+    /// let home = tree.add_child(root, ("home", 80));
+    /// tree.add_child(home, ("photos", 70));
+    /// tree.add_child(home, ("notes", 5));
+    /// let tmp = tree.add_child(root, ("tmp", 10));
+    /// tree.add_child(tmp, ("cache", 9));
+    ///
+    /// let summary = tree.summarize(3, |_, (_, size)| *size);
+    /// let names = summary.map_ref(|node| node.map(|(name, _)| *name).to_string());
+    /// assert_eq!(
+    ///     snapshot(&names),
+    ///     "[0] /\n  [1] home\n    [2] photos\n    [3] … (1 more)\n  [4] … (2 more)\n"
+    /// );
+    /// ```
+    pub fn summarize<C: PartialOrd>(
+        &self,
+        max_nodes: usize,
+        mut importance_fn: impl FnMut(usize, &T) -> C,
+    ) -> Tree<Summary<&T>> {
+        let mut summary = Tree::new();
+        let Some(root) = self.root() else {
+            return summary;
+        };
+
+        let mut sizes = vec![0; self.links.len()];
+        for index in self.post_order(root) {
+            sizes[index] = 1 + self.links[index]
+                .children
+                .iter()
+                .map(|&child| sizes[child])
+                .sum::<usize>();
+        }
+
+        let mut keep = vec![false; self.links.len()];
+        let mut candidates = BinaryHeap::new();
+        candidates.push(Candidate {
+            importance: importance_fn(root, &self.data[root]),
+            sequence: 0,
+            index: root,
+        });
+        let mut sequence = 1;
+        for _ in 0..max_nodes {
+            let Some(Candidate { index, .. }) = candidates.pop() else {
+                break;
+            };
+            keep[index] = true;
+            for &child in &self.links[index].children {
+                candidates.push(Candidate {
+                    importance: importance_fn(child, &self.data[child]),
+                    sequence,
+                    index: child,
+                });
+                sequence += 1;
+            }
+        }
+
+        if !keep[root] {
+            summary.add_node(Summary::Collapsed { count: sizes[root] });
+            return summary;
+        }
+
+        // Copy the kept nodes in pre-order, adding the placeholder of a node when leaving it.
+        let mut stack = vec![(root, None, false)];
+        while let Some((index, parent, children_visited)) = stack.pop() {
+            if children_visited {
+                let node = parent.unwrap();
+                let count: usize = self.links[index]
+                    .children
+                    .iter()
+                    .filter(|&&child| !keep[child])
+                    .map(|&child| sizes[child])
+                    .sum();
+                if count > 0 {
+                    summary.add_child(node, Summary::Collapsed { count });
+                }
+                continue;
+            }
+
+            let data = Summary::Node {
+                index,
+                data: &self.data[index],
+            };
+            let node = match parent {
+                Some(parent) => summary.add_child(parent, data),
+                None => summary.add_node(data),
+            };
+            stack.push((index, Some(node), true));
+            stack.extend(
+                self.links[index]
+                    .children
+                    .iter()
+                    .rev()
+                    .filter(|&&child| keep[child])
+                    .map(|&child| (child, Some(node), false)),
+            );
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_counts_every_node_once() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..100 {
+            tree.add_child((index - 1) / 3, index);
+        }
+
+        for max_nodes in [0, 1, 5, 40, 100, 200] {
+            let summary = tree.summarize(max_nodes, |index, _| index % 7);
+            let kept = summary
+                .iter()
+                .filter(|(_, node)| matches!(node, Summary::Node { .. }))
+                .count();
+            let collapsed: usize = summary
+                .iter()
+                .map(|(_, node)| match node {
+                    Summary::Collapsed { count } => *count,
+                    Summary::Node { .. } => 0,
+                })
+                .sum();
+            assert_eq!(kept, max_nodes.min(100));
+            assert_eq!(kept + collapsed, 100);
+
+            // Kept nodes keep their parent.
+            for (index, node) in summary.iter() {
+                if let Summary::Node {
+                    index: original, ..
+                } = node
+                {
+                    let parent = summary
+                        .parent_index_unchecked(index)
+                        .map(|parent| match summary[parent] {
+                            Summary::Node { index, .. } => index,
+                            Summary::Collapsed { .. } => unreachable!(),
+                        });
+                    assert_eq!(parent, tree.parent_index_unchecked(*original));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::Tree;
+use std::ops::Range;
+
+/// The data of a node imported from a tree-sitter syntax tree with
+/// [`Tree::from_syntax_tree`] or [`Tree::from_syntax_node`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxInfo {
+    /// The kind of the node, such as `"function_item"` or `"{"`.
+    pub kind: &'static str,
+    /// The numeric id of the kind, as used by the grammar.
+    pub kind_id: u16,
+    /// Whether the node is named in the grammar, as opposed to anonymous punctuation.
+    pub is_named: bool,
+    /// The name of the field of its parent that holds the node, if any.
+    pub field_name: Option<&'static str>,
+    /// The bytes of the source covered by the node.
+    pub byte_range: Range<usize>,
+    /// The source text of the node, if it is a leaf and the source was given. Invalid UTF-8 is
+    /// replaced with `U+FFFD`.
+    pub text: Option<String>,
+}
+
+impl Tree<SyntaxInfo> {
+    /// Imports a tree-sitter syntax tree, keeping every node, named or anonymous.
+    ///
+    /// This is [`Tree::from_syntax_node`] applied to the root node of `syntax_tree`.
+    ///
+    /// # Parameters
+    /// - `syntax_tree`: The parsed syntax tree.
+    /// - `source`: The parsed source, to record the text of leaves, or `None` to skip it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::Tree;
+    ///
+    /// let source = r#"{"a": [1, 2]}"#;
+    /// let mut parser = tree_sitter::Parser::new();
+    /// parser.set_language(&tree_sitter_json::LANGUAGE.into()).unwrap();
+    /// let syntax_tree = parser.parse(source, None).unwrap();
+    ///
+    /// let tree = Tree::from_syntax_tree(&syntax_tree, Some(source.as_bytes()));
+    /// let numbers: Vec<_> = tree
+    ///     .iter()
+    ///     .filter(|(_, info)| info.kind == "number")
+    ///     .map(|(_, info)| info.text.as_deref().unwrap())
+    ///     .collect();
+    /// assert_eq!(numbers, ["1", "2"]);
+    /// assert_eq!(tree[0].kind, "document");
+    /// ```
+    pub fn from_syntax_tree(syntax_tree: &tree_sitter::Tree, source: Option<&[u8]>) -> Self {
+        Tree::from_syntax_node(syntax_tree.root_node(), source)
+    }
+
+    /// Imports the subtree of a tree-sitter node, walking it with a tree cursor.
+    ///
+    /// `node` becomes node `0` and its descendants follow in depth-first order, with children
+    /// in source order. Anonymous nodes, such as punctuation, are kept and marked with
+    /// [`SyntaxInfo::is_named`].
+    ///
+    /// # Parameters
+    /// - `node`: The root of the imported subtree.
+    /// - `source`: The parsed source, to record the text of leaves, or `None` to skip it.
+    pub fn from_syntax_node(node: tree_sitter::Node<'_>, source: Option<&[u8]>) -> Self {
+        let mut tree = Tree::new();
+        let mut cursor = node.walk();
+        // The indices of the ancestors of the node under the cursor.
+        let mut parents = Vec::new();
+        loop {
+            let node = cursor.node();
+            let text = source
+                .filter(|_| node.child_count() == 0)
+                .map(|source| String::from_utf8_lossy(&source[node.byte_range()]).into_owned());
+            let info = SyntaxInfo {
+                kind: node.kind(),
+                kind_id: node.kind_id(),
+                is_named: node.is_named(),
+                field_name: cursor.field_name(),
+                byte_range: node.byte_range(),
+                text,
+            };
+            let index = match parents.last() {
+                Some(&parent) => tree.add_child(parent, info),
+                None => tree.add_node(info),
+            };
+            if cursor.goto_first_child() {
+                parents.push(index);
+                continue;
+            }
+            loop {
+                if parents.is_empty() {
+                    return tree;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                cursor.goto_parent();
+                parents.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> tree_sitter::Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_json::LANGUAGE.into())
+            .unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    #[test]
+    fn test_from_syntax_tree_keeps_structure_and_fields() {
+        let source = r#"{"key": true}"#;
+        let tree = Tree::from_syntax_tree(&parse(source), Some(source.as_bytes()));
+        let kinds: Vec<_> = tree.iter().map(|(_, info)| info.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "document",
+                "object",
+                "{",
+                "pair",
+                "string",
+                "\"",
+                "string_content",
+                "\"",
+                ":",
+                "true",
+                "}"
+            ]
+        );
+        assert_eq!(tree.children(3), &[4, 8, 9]);
+        assert_eq!(tree[4].field_name, Some("key"));
+        assert_eq!(tree[9].field_name, Some("value"));
+        assert_eq!(tree[6].text.as_deref(), Some("key"));
+        assert_eq!(tree[3].text, None);
+        assert_eq!(tree[3].byte_range, 1..12);
+        assert!(!tree[2].is_named && tree[3].is_named);
+    }
+
+    #[test]
+    fn test_from_syntax_node_stops_at_the_subtree() {
+        let syntax_tree = parse("[1, [2, 3], 4]");
+        let inner = syntax_tree
+            .root_node()
+            .child(0)
+            .unwrap()
+            .named_child(1)
+            .unwrap();
+        let tree = Tree::from_syntax_node(inner, None);
+        let kinds: Vec<_> = tree.iter().map(|(_, info)| info.kind).collect();
+        assert_eq!(kinds, ["array", "[", "number", ",", "number", "]"]);
+        assert!(tree.iter().all(|(_, info)| info.text.is_none()));
+        assert_eq!(tree[0].field_name, None);
+    }
+}
@@ -0,0 +1,782 @@
+//! Helpers for testing code that builds or transforms trees.
+//!
+//! [`snapshot`] renders a tree into a canonical text form that is stable across runs, which
+//! makes it suitable for golden files and snapshot testing tools such as
+//! [insta](https://docs.rs/insta). [`assert_tree_eq!`](crate::assert_tree_eq) compares two
+//! trees through that form and prints both when they differ.
+//!
+//! [`check_tree_invariants`] applies a sequence of [`TreeOp`]s to a tree and to a simple
+//! reference model, and checks after every step that both agree and that the links of the tree
+//! are consistent. [`check_model_invariants`] also applies them to a structure of your own
+//! that implements [`TreeModel`], such as a wrapper around a `Tree`, and compares it to the
+//! reference. With the `proptest` feature, `TreeOp` implements `Arbitrary`, so random
+//! sequences can be generated and shrunk by [proptest](https://docs.rs/proptest).
+
+use crate::{Checkpoint, NodeLabel, Tree, TreeError, TreeStorage};
+use std::fmt::Write;
+
+/// Renders a tree into a canonical, deterministic text form.
+///
+/// Every node is written on its own line as `[index] label`, indented by two spaces per level
+/// of depth, in depth-first pre-order. The [attributes](NodeLabel::attrs) of a node, if any,
+/// follow the label as `{key: value, ...}`. Nodes without a parent start a new top-level entry,
+/// in index order, so orphans are included. Line breaks in labels and attributes are escaped as
+/// `\n` so that every node takes exactly one line. The output ends with a newline unless the
+/// tree is empty.
+///
+/// Two trees have the same snapshot if and only if they have the same indices, links, labels
+/// and attributes.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::testing::snapshot;
+/// use easy_tree::Tree;
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// let child = tree.add_child(root, "child");
+/// tree.add_child(child, "grandchild");
+/// tree.add_child(root, "second\nline");
+/// tree.add_node("orphan");
+///
+/// assert_eq!(
+///     snapshot(&tree),
+///     "[0] root\n  [1] child\n    [2] grandchild\n  [3] second\\nline\n[4] orphan\n"
+/// );
+/// ```
+pub fn snapshot<T: NodeLabel, D: TreeStorage<T>>(tree: &Tree<T, D>) -> String {
+    let mut out = String::new();
+    let mut stack = Vec::new();
+    for root in (0..tree.len()).filter(|&index| tree.links[index].parent().is_none()) {
+        stack.push((root, 0));
+        while let Some((index, depth)) = stack.pop() {
+            let data = &tree.data[index];
+            let mut line = data.label().into_owned();
+            let attrs = data.attrs();
+            for (position, (key, value)) in attrs.iter().enumerate() {
+                let separator = if position == 0 { " {" } else { ", " };
+                write!(line, "{separator}{key}: {value}").unwrap();
+            }
+            if !attrs.is_empty() {
+                line.push('}');
+            }
+            let line = line.replace('\n', "\\n");
+            writeln!(out, "{:indent$}[{index}] {line}", "", indent = 2 * depth).unwrap();
+            stack.extend(
+                tree.links[index]
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, depth + 1)),
+            );
+        }
+    }
+    out
+}
+
+/// Asserts that two trees are equal, comparing their [`snapshot`]s.
+///
+/// The trees may have different data types, as long as their labels are the same. On
+/// failure, the panic message contains both snapshots. Like [`assert_eq!`], an optional
+/// message with format arguments can be given after the trees.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{assert_tree_eq, Tree};
+///
+/// let mut expected = Tree::new();
+/// let root = expected.add_node("a");
+/// expected.add_child(root, "b");
+///
+/// let mut built = Tree::new();
+/// let root = built.add_node(String::from("a"));
+/// built.add_child(root, String::from("b"));
+///
+/// assert_tree_eq!(built, expected, "building {} nodes", built.len());
+/// ```
+#[macro_export]
+macro_rules! assert_tree_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_tree_eq!($left, $right, "trees differ")
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (
+            $crate::testing::snapshot(&$left),
+            $crate::testing::snapshot(&$right),
+        ) {
+            (left, right) => {
+                if left != right {
+                    panic!(
+                        "assertion `left == right` failed: {}\n--- left\n{}--- right\n{}",
+                        format_args!($($arg)+),
+                        left,
+                        right
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// An operation applied by [`check_tree_invariants`].
+///
+/// Node indices are reduced modulo the number of nodes when the operation is applied, so that
+/// any sequence of operations is meaningful. Operations that refer to nodes are skipped while
+/// the tree is empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TreeOp {
+    /// [`Tree::add_node`].
+    AddNode,
+    /// [`Tree::add_child`].
+    AddChild {
+        /// The parent of the new node.
+        parent: usize,
+    },
+    /// [`Tree::set_parent`], which may fail.
+    SetParent {
+        /// The node to attach.
+        child: usize,
+        /// Its new parent.
+        parent: usize,
+    },
+    /// [`Tree::remove_child`], which may fail.
+    RemoveChild {
+        /// The expected parent.
+        parent: usize,
+        /// The node to unlink.
+        child: usize,
+    },
+    /// [`Tree::detach`].
+    Detach {
+        /// The node to detach.
+        child: usize,
+    },
+    /// [`Tree::checkpoint`], pushing the checkpoint on a stack.
+    Checkpoint,
+    /// [`Tree::rollback_to`] the most recent checkpoint on the stack, popping it. Does nothing
+    /// if the stack is empty.
+    Rollback,
+    /// [`Tree::garbage_collect`] with a single root. Clears the stack of checkpoints, since
+    /// compaction invalidates them.
+    GarbageCollect {
+        /// The root of the subtree to keep.
+        root: usize,
+    },
+}
+
+/// A tree-like structure that [`check_model_invariants`] drives alongside its reference tree.
+///
+/// Implement it for a type that wraps a [`Tree`], or that maintains a hierarchy in some other
+/// way, to check that it keeps the same structure as a plain tree under any sequence of
+/// operations.
+pub trait TreeModel {
+    /// Applies an operation to the structure.
+    ///
+    /// The node indices of `op` are in bounds. [`TreeOp::AddNode`] and [`TreeOp::AddChild`]
+    /// must add a node at the next index, and [`TreeOp::Checkpoint`] and [`TreeOp::Rollback`]
+    /// work on a stack of checkpoints that [`TreeOp::GarbageCollect`] clears. A rollback is
+    /// only applied when a checkpoint is on the stack.
+    fn apply(&mut self, op: TreeOp);
+
+    /// Returns the number of nodes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the structure has no nodes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the parent of a node.
+    fn parent(&self, index: usize) -> Option<usize>;
+
+    /// Returns the children of a node, in order.
+    fn children(&self, index: usize) -> Vec<usize>;
+}
+
+/// Applies `ops` to an empty tree, panicking as soon as the tree breaks an invariant.
+///
+/// The data of every node is the number of nodes added before it, which makes it unique. After
+/// each operation, the tree is checked with [`assert_links_consistent`] and compared to a
+/// straightforward reference model: the results of the operations, the data, the parent of
+/// every node and the order of the children must all match. The panic message names the
+/// failing step and operation.
+///
+/// Because it panics, it can be called directly from property tests, which then shrink the
+/// sequence of operations to a minimal failing one.
+///
+/// # Returns
+/// The tree after all operations.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::testing::{check_tree_invariants, TreeOp};
+///
+/// let tree = check_tree_invariants(&[
+///     TreeOp::AddNode,
+///     TreeOp::AddChild { parent: 0 },
+///     TreeOp::Checkpoint,
+///     TreeOp::AddChild { parent: 1 },
+///     TreeOp::SetParent { child: 0, parent: 2 },
+///     TreeOp::Rollback,
+/// ]);
+/// assert_eq!(tree.len(), 2);
+/// ```
+pub fn check_tree_invariants(ops: &[TreeOp]) -> Tree<usize> {
+    run_ops(ops, None)
+}
+
+/// Applies `ops` to `model` and to an empty reference tree, panicking as soon as they differ
+/// or the reference tree breaks an invariant.
+///
+/// The reference tree is checked as by [`check_tree_invariants`]. After each operation, the
+/// number of nodes, the parent of every node and the order of its children must be the same
+/// in `model` as in the reference tree. The panic message names the failing step and
+/// operation.
+///
+/// # Returns
+/// The reference tree after all operations.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::testing::{check_model_invariants, TreeModel, TreeOp};
+/// use easy_tree::{Checkpoint, Tree};
+///
+/// /// An outline whose headings are numbered by the order they were added in.
+/// #[derive(Default)]
+/// struct Outline {
+///     headings: Tree<String>,
+///     checkpoints: Vec<Checkpoint>,
+/// }
+///
+/// impl TreeModel for Outline {
+///     fn apply(&mut self, op: TreeOp) {
+///         let heading = format!("heading {}", self.headings.len());
+///         match op {
+///             TreeOp::AddNode => {
+///                 self.headings.add_node(heading);
+///             }
+///             TreeOp::AddChild { parent } => {
+///                 self.headings.add_child(parent, heading);
+///             }
+///             TreeOp::SetParent { child, parent } => {
+///                 let _ = self.headings.set_parent(child, parent);
+///             }
+///             TreeOp::RemoveChild { parent, child } => {
+///                 let _ = self.headings.remove_child(parent, child);
+///             }
+///             TreeOp::Detach { child } => {
+///                 self.headings.detach(child);
+///             }
+///             TreeOp::Checkpoint => self.checkpoints.push(self.headings.checkpoint()),
+///             TreeOp::Rollback => {
+///                 let checkpoint = self.checkpoints.pop().unwrap();
+///                 self.headings.rollback_to(checkpoint);
+///             }
+///             TreeOp::GarbageCollect { root } => {
+///                 self.headings.garbage_collect([root]);
+///                 self.checkpoints.clear();
+///             }
+///         }
+///     }
+///
+///     fn len(&self) -> usize {
+///         self.headings.len()
+///     }
+///
+///     fn parent(&self, index: usize) -> Option<usize> {
+///         self.headings.parent_index_unchecked(index)
+///     }
+///
+///     fn children(&self, index: usize) -> Vec<usize> {
+///         self.headings.children(index).to_vec()
+///     }
+/// }
+///
+/// let mut outline = Outline::default();
+/// check_model_invariants(
+///     &mut outline,
+///     &[
+///         TreeOp::AddNode,
+///         TreeOp::AddChild { parent: 0 },
+///         TreeOp::Checkpoint,
+///         TreeOp::AddChild { parent: 1 },
+///         TreeOp::Rollback,
+///         TreeOp::Detach { child: 1 },
+///     ],
+/// );
+/// assert_eq!(outline.len(), 2);
+/// ```
+pub fn check_model_invariants<M: TreeModel>(model: &mut M, ops: &[TreeOp]) -> Tree<usize> {
+    run_ops(ops, Some(model))
+}
+
+/// Applies `ops` to a reference tree and model, and to `model` if there is one.
+fn run_ops(ops: &[TreeOp], mut model: Option<&mut dyn TreeModel>) -> Tree<usize> {
+    let mut tree = Tree::new();
+    let mut reference = Model::default();
+    let mut checkpoints: Vec<(Checkpoint, usize)> = Vec::new();
+    let mut added = 0;
+
+    for (step, &op) in ops.iter().enumerate() {
+        let len = reference.parents.len();
+        let pick = |index: usize| index % len;
+        // The operation with its indices in bounds, if it was applied.
+        let mut applied = Some(op);
+        match op {
+            TreeOp::AddNode => {
+                let index = tree.add_node(added);
+                assert_eq!(index, reference.push(None, added), "step {step} ({op:?})");
+                added += 1;
+            }
+            TreeOp::AddChild { .. }
+            | TreeOp::SetParent { .. }
+            | TreeOp::RemoveChild { .. }
+            | TreeOp::Detach { .. }
+            | TreeOp::GarbageCollect { .. }
+                if len == 0 =>
+            {
+                applied = None;
+            }
+            TreeOp::AddChild { parent } => {
+                let parent = pick(parent);
+                applied = Some(TreeOp::AddChild { parent });
+                let index = tree.add_child(parent, added);
+                assert_eq!(
+                    index,
+                    reference.push(Some(parent), added),
+                    "step {step} ({op:?})"
+                );
+                added += 1;
+            }
+            TreeOp::SetParent { child, parent } => {
+                let (child, parent) = (pick(child), pick(parent));
+                applied = Some(TreeOp::SetParent { child, parent });
+                let result = tree.set_parent(child, parent);
+                assert_eq!(
+                    result,
+                    reference.set_parent(child, parent),
+                    "step {step} ({op:?})"
+                );
+            }
+            TreeOp::RemoveChild { parent, child } => {
+                let (parent, child) = (pick(parent), pick(child));
+                applied = Some(TreeOp::RemoveChild { parent, child });
+                let result = tree.remove_child(parent, child);
+                assert_eq!(
+                    result,
+                    reference.remove_child(parent, child),
+                    "step {step} ({op:?})"
+                );
+            }
+            TreeOp::Detach { child } => {
+                let child = pick(child);
+                applied = Some(TreeOp::Detach { child });
+                let result = tree.detach(child);
+                assert_eq!(result, reference.detach(child), "step {step} ({op:?})");
+            }
+            TreeOp::Checkpoint => checkpoints.push((tree.checkpoint(), len)),
+            TreeOp::Rollback => match checkpoints.pop() {
+                Some((checkpoint, len)) => {
+                    tree.rollback_to(checkpoint);
+                    reference.truncate(len);
+                }
+                None => applied = None,
+            },
+            TreeOp::GarbageCollect { root } => {
+                let root = pick(root);
+                applied = Some(TreeOp::GarbageCollect { root });
+                let remap = tree.garbage_collect([root]);
+                assert_eq!(
+                    remap,
+                    reference.garbage_collect(root),
+                    "step {step} ({op:?})"
+                );
+                checkpoints.clear();
+            }
+        }
+        assert_links_consistent(&tree);
+        reference.assert_matches(&tree, step, op);
+        if let Some(model) = model.as_deref_mut() {
+            if let Some(applied) = applied {
+                model.apply(applied);
+            }
+            assert_model_matches(&*model, &tree, step, op);
+        }
+    }
+    tree
+}
+
+/// Asserts that `model` has the same structure as the reference tree.
+fn assert_model_matches(model: &dyn TreeModel, tree: &Tree<usize>, step: usize, op: TreeOp) {
+    assert_eq!(
+        model.len(),
+        tree.len(),
+        "number of nodes of the model after step {step} ({op:?})"
+    );
+    for index in 0..tree.len() {
+        assert_eq!(
+            model.parent(index),
+            tree.parent_index_unchecked(index),
+            "parent of node {index} in the model after step {step} ({op:?})"
+        );
+        assert_eq!(
+            model.children(index),
+            tree.children(index),
+            "children of node {index} in the model after step {step} ({op:?})"
+        );
+    }
+}
+
+/// Asserts that the links of a tree are consistent with each other.
+///
+/// Checks that every child lists the node as its parent, that every node with a parent is
+/// listed exactly once among the children of that parent, that no index is out of bounds and
+/// that following parents never loops.
+///
+/// # Panics
+/// This function panics with a message naming the offending nodes if a check fails.
+pub fn assert_links_consistent<T, D: TreeStorage<T>>(tree: &Tree<T, D>) {
+    let len = tree.links.len();
+    assert_eq!(
+        tree.data.len(),
+        len,
+        "the tree has {len} links but not as much data"
+    );
+    for (index, links) in tree.links.iter().enumerate() {
+        for &child in &links.children {
+            assert!(
+                child < len,
+                "node {index} has an out of bounds child {child}"
+            );
+            assert_eq!(
+                tree.links[child].parent(),
+                Some(index),
+                "node {child} is a child of node {index}, but not linked back to it"
+            );
+        }
+        if let Some(parent) = links.parent() {
+            assert!(
+                parent < len,
+                "node {index} has an out of bounds parent {parent}"
+            );
+            let listed = tree.links[parent]
+                .children
+                .iter()
+                .filter(|&&child| child == index)
+                .count();
+            assert_eq!(
+                listed, 1,
+                "node {index} is listed {listed} times among the children of its parent {parent}"
+            );
+        }
+    }
+
+    // Following parents from any node must reach a root within `len` steps.
+    for index in 0..len {
+        let mut ancestor = tree.links[index].parent();
+        for _ in 0..len {
+            match ancestor {
+                Some(parent) => ancestor = tree.links[parent].parent(),
+                None => break,
+            }
+        }
+        assert!(
+            ancestor.is_none(),
+            "node {index} is part of a cycle of parents"
+        );
+    }
+}
+
+/// The reference model of [`check_tree_invariants`], which implements every operation in the
+/// most direct way.
+#[derive(Default)]
+struct Model {
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    data: Vec<usize>,
+}
+
+impl Model {
+    fn push(&mut self, parent: Option<usize>, data: usize) -> usize {
+        let index = self.parents.len();
+        self.parents.push(parent);
+        self.children.push(Vec::new());
+        self.data.push(data);
+        if let Some(parent) = parent {
+            self.children[parent].push(index);
+        }
+        index
+    }
+
+    fn is_ancestor(&self, ancestor: usize, mut index: usize) -> bool {
+        loop {
+            if index == ancestor {
+                return true;
+            }
+            match self.parents[index] {
+                Some(parent) => index = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn set_parent(&mut self, child: usize, parent: usize) -> Result<(), TreeError> {
+        if let Some(current) = self.parents[child] {
+            return Err(TreeError::AlreadyHasParent {
+                child,
+                parent: current,
+            });
+        }
+        if self.is_ancestor(child, parent) {
+            return Err(TreeError::WouldCreateCycle { child, parent });
+        }
+        self.parents[child] = Some(parent);
+        self.children[parent].push(child);
+        Ok(())
+    }
+
+    fn remove_child(&mut self, parent: usize, child: usize) -> Result<usize, TreeError> {
+        if self.parents[child] != Some(parent) {
+            return Err(TreeError::NotAChild { parent, child });
+        }
+        let position = self.children[parent]
+            .iter()
+            .position(|&index| index == child)
+            .unwrap();
+        self.children[parent].remove(position);
+        self.parents[child] = None;
+        Ok(position)
+    }
+
+    fn detach(&mut self, child: usize) -> Option<usize> {
+        let parent = self.parents[child]?;
+        self.remove_child(parent, child).ok()?;
+        Some(parent)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.parents.truncate(len);
+        self.children.truncate(len);
+        self.data.truncate(len);
+        for parent in &mut self.parents {
+            *parent = parent.filter(|&parent| parent < len);
+        }
+        for children in &mut self.children {
+            children.retain(|&child| child < len);
+        }
+    }
+
+    fn garbage_collect(&mut self, root: usize) -> Vec<Option<usize>> {
+        let keep: Vec<bool> = (0..self.parents.len())
+            .map(|index| self.is_ancestor(root, index))
+            .collect();
+        let mut remap = Vec::new();
+        let mut next = 0;
+        for &kept in &keep {
+            remap.push(kept.then_some(next));
+            next += usize::from(kept);
+        }
+
+        let old = std::mem::take(self);
+        for (index, &kept) in keep.iter().enumerate() {
+            if kept {
+                let parent = old.parents[index].and_then(|parent| remap[parent]);
+                self.parents.push(parent);
+                self.children.push(
+                    old.children[index]
+                        .iter()
+                        .filter_map(|&child| remap[child])
+                        .collect(),
+                );
+                self.data.push(old.data[index]);
+            }
+        }
+        remap
+    }
+
+    fn assert_matches(&self, tree: &Tree<usize>, step: usize, op: TreeOp) {
+        assert_eq!(tree.data(), self.data, "data after step {step} ({op:?})");
+        for (index, (&parent, children)) in self.parents.iter().zip(&self.children).enumerate() {
+            assert_eq!(
+                tree.parent_index_unchecked(index),
+                parent,
+                "parent of node {index} after step {step} ({op:?})"
+            );
+            assert_eq!(
+                tree.children(index),
+                children,
+                "children of node {index} after step {step} ({op:?})"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for TreeOp {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<TreeOp>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        let index = || any::<usize>();
+        prop_oneof![
+            2 => Just(TreeOp::AddNode),
+            6 => index().prop_map(|parent| TreeOp::AddChild { parent }),
+            2 => (index(), index()).prop_map(|(child, parent)| TreeOp::SetParent { child, parent }),
+            2 => (index(), index()).prop_map(|(parent, child)| TreeOp::RemoveChild { parent, child }),
+            2 => index().prop_map(|child| TreeOp::Detach { child }),
+            1 => Just(TreeOp::Checkpoint),
+            1 => Just(TreeOp::Rollback),
+            1 => index().prop_map(|root| TreeOp::GarbageCollect { root }),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_random_operations_keep_invariants() {
+        // Random sequences from a xorshift generator, biased towards growing the tree.
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..50 {
+            let ops: Vec<TreeOp> = (0..200)
+                .map(|_| match next() % 16 {
+                    0..=1 => TreeOp::AddNode,
+                    2..=7 => TreeOp::AddChild { parent: next() },
+                    8..=9 => TreeOp::SetParent {
+                        child: next(),
+                        parent: next(),
+                    },
+                    10 => TreeOp::RemoveChild {
+                        parent: next(),
+                        child: next(),
+                    },
+                    11..=12 => TreeOp::Detach { child: next() },
+                    13 => TreeOp::Checkpoint,
+                    14 => TreeOp::Rollback,
+                    _ => TreeOp::GarbageCollect { root: next() },
+                })
+                .collect();
+            check_tree_invariants(&ops);
+        }
+    }
+
+    /// A wrapper that forgets to detach nodes.
+    #[derive(Default)]
+    struct Forgetful(Tree<()>);
+
+    impl TreeModel for Forgetful {
+        fn apply(&mut self, op: TreeOp) {
+            match op {
+                TreeOp::AddNode => {
+                    self.0.add_node(());
+                }
+                TreeOp::AddChild { parent } => {
+                    self.0.add_child(parent, ());
+                }
+                _ => {}
+            }
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn parent(&self, index: usize) -> Option<usize> {
+            self.0.parent_index_unchecked(index)
+        }
+
+        fn children(&self, index: usize) -> Vec<usize> {
+            self.0.children(index).to_vec()
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "children of node 0 in the model after step 2 (Detach { child: 5 })")]
+    fn test_check_model_invariants_catches_a_diverging_model() {
+        let ops = [
+            TreeOp::AddNode,
+            TreeOp::AddChild { parent: 0 },
+            TreeOp::Detach { child: 5 },
+        ];
+        let tree = check_model_invariants(&mut Forgetful::default(), &ops[..2]);
+        assert_eq!(tree.len(), 2);
+        check_model_invariants(&mut Forgetful::default(), &ops);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 1 is a child of node 0, but not linked back to it")]
+    fn test_assert_links_consistent_detects_broken_links() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+        tree.links[child].replace_parent(None);
+        assert_links_consistent(&tree);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn test_arbitrary_operations_keep_invariants(
+            ops in proptest::collection::vec(proptest::prelude::any::<TreeOp>(), 0..100)
+        ) {
+            check_tree_invariants(&ops);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_includes_attributes() {
+        struct Node(&'static str, u32);
+
+        impl NodeLabel for Node {
+            fn label(&self) -> Cow<'_, str> {
+                Cow::Borrowed(self.0)
+            }
+
+            fn attrs(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+                vec![
+                    ("weight".into(), self.1.to_string().into()),
+                    ("note".into(), "a\nb".into()),
+                ]
+            }
+        }
+
+        let mut tree = Tree::new();
+        let root = tree.add_node(Node("root", 1));
+        tree.add_child(root, Node("leaf", 2));
+        assert_eq!(
+            snapshot(&tree),
+            "[0] root {weight: 1, note: a\\nb}\n  [1] leaf {weight: 2, note: a\\nb}\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_of_empty_tree() {
+        assert_eq!(snapshot(&Tree::<i32>::new()), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "trees differ\n--- left\n[0] 1\n  [1] 2\n--- right\n[0] 1\n[1] 2\n")]
+    fn test_assert_tree_eq_shows_both_snapshots() {
+        let mut left = Tree::new();
+        let root = left.add_node(1);
+        left.add_child(root, 2);
+
+        let mut right = Tree::new();
+        right.add_node(1);
+        right.add_node(2);
+
+        assert_tree_eq!(left, right);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    AncestorIndex, CowTree, FrozenTree, NodeMask, SubtreeRef, TraversalPlan, Tree, TreeStorage,
+    TreeStructure,
+};
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+// The functions below are only called from `const _` items. Older compilers, including the
+// MSRV, do not count those calls as uses and report the functions as dead code.
+
+/// Fails to compile if `S` cannot be sent to or shared with another thread.
+#[allow(dead_code)]
+const fn assert_send_sync<S: Send + Sync>() {}
+
+/// Fails to compile if `S` cannot be used across `std::panic::catch_unwind`.
+#[allow(dead_code)]
+const fn assert_unwind_safe<S: UnwindSafe + RefUnwindSafe>() {}
+
+/// The guarantees documented in the "Thread Safety" section of the crate documentation,
+/// checked at compile time for every `T` and `D` that are `Send + Sync`. Removing one of them
+/// is a breaking change.
+#[allow(dead_code)]
+const fn guarantees<'a, T: Send + Sync + 'a, D: TreeStorage<T> + Send + Sync + 'a>() {
+    assert_send_sync::<Tree<T, D>>();
+    assert_send_sync::<FrozenTree<T, D>>();
+    assert_send_sync::<SubtreeRef<'a, T, D>>();
+    assert_send_sync::<CowTree<'a, T, D>>();
+    assert_send_sync::<TreeStructure<'a>>();
+    assert_send_sync::<NodeMask>();
+    assert_send_sync::<AncestorIndex>();
+    assert_send_sync::<TraversalPlan>();
+}
+
+const _: () = guarantees::<(), Vec<()>>();
+
+/// The unwind safety of the trees, checked at compile time for every `T` and `D` that are
+/// unwind safe. Removing one of them is a breaking change.
+#[allow(dead_code)]
+const fn unwind_guarantees<
+    T: UnwindSafe + RefUnwindSafe,
+    D: TreeStorage<T> + UnwindSafe + RefUnwindSafe,
+>() {
+    assert_unwind_safe::<Tree<T, D>>();
+    assert_unwind_safe::<FrozenTree<T, D>>();
+}
+
+const _: () = unwind_guarantees::<(), Vec<()>>();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn wide_tree() -> Tree<usize> {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..1000 {
+            tree.add_child((index - 1) / 4, index);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_concurrent_reads_of_a_shared_tree() {
+        let tree = wide_tree();
+        let mut expected = vec![];
+        tree.traverse(
+            |index, _, order| order.push(index),
+            |_, _, _| {},
+            &mut expected,
+        );
+
+        // Every reader borrows the same tree and must see the same structure and data.
+        thread::scope(|scope| {
+            let readers: Vec<_> = (0..8)
+                .map(|reader| {
+                    let tree = &tree;
+                    scope.spawn(move || {
+                        let mut order = vec![];
+                        tree.traverse(
+                            |index, _, order| order.push(index),
+                            |_, _, _| {},
+                            &mut order,
+                        );
+                        let subtree = tree.subtree(reader + 1).iter_dfs().count();
+                        let sum: usize = tree.iter().map(|(_, value)| value).sum();
+                        (order, subtree, sum)
+                    })
+                })
+                .collect();
+            for (reader, handle) in readers.into_iter().enumerate() {
+                let (order, subtree, sum) = handle.join().unwrap();
+                assert_eq!(order, expected);
+                assert_eq!(subtree, tree.subtree(reader + 1).iter_dfs().count());
+                assert_eq!(sum, 999 * 1000 / 2);
+            }
+        });
+    }
+
+    #[test]
+    fn test_frozen_tree_shared_through_arc() {
+        let frozen = Arc::new(wide_tree().freeze());
+        let handles: Vec<_> = (0..4)
+            .map(|reader| {
+                let frozen = Arc::clone(&frozen);
+                thread::spawn(move || {
+                    (reader * 250..(reader + 1) * 250)
+                        .map(|index| frozen.depth(index))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let depths: Vec<usize> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        let expected: Vec<_> = (0..1000).map(|index| frozen.depth(index)).collect();
+        assert_eq!(depths, expected);
+        assert!(frozen.is_ancestor(0, 999));
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// An expensive operation on a tree, reported through [`tracing`] when the `tracing` feature is
+/// enabled.
+///
+/// Starting an operation enters a `DEBUG` span named `easy_tree`, recording the name of the
+/// operation and the number of nodes in the tree. Finishing it emits an event inside that span
+/// with the number of nodes the operation processed and the time it took in microseconds.
+/// Without the feature, both do nothing.
+///
+/// On `wasm32-unknown-unknown`, which has no clock, the time is not recorded.
+pub(crate) struct Operation {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    start: Option<Instant>,
+}
+
+impl Operation {
+    #[inline]
+    pub(crate) fn start(name: &'static str, tree_len: usize) -> Self {
+        #[cfg(feature = "tracing")]
+        {
+            Operation {
+                span: tracing::debug_span!("easy_tree", operation = name, tree_len).entered(),
+                start: (!cfg!(all(target_arch = "wasm32", target_os = "unknown")))
+                    .then(Instant::now),
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            let _ = (name, tree_len);
+            Operation {}
+        }
+    }
+
+    #[inline]
+    pub(crate) fn finish(self, nodes: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_us = self.start.map(|start| start.elapsed().as_micros() as u64);
+            tracing::debug!(nodes, elapsed_us, "finished");
+            drop(self.span);
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = nodes;
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::Tree;
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of every span and event as `name=value` strings.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        lines: Arc<Mutex<Vec<String>>>,
+        next_id: Arc<AtomicU64>,
+    }
+
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(vec![]);
+            span.record(&mut fields);
+            self.lines.lock().unwrap().push(fields.0.join(" "));
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(vec![]);
+            event.record(&mut fields);
+            // The timing differs between runs.
+            fields.0.retain(|field| !field.starts_with("elapsed_us="));
+            self.lines.lock().unwrap().push(fields.0.join(" "));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_operations_are_traced() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(0);
+        let child = tree.add_child(root, 1);
+        tree.add_child(child, 2);
+        tree.add_node(3);
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            tree.traverse(|_, _, _| {}, |_, _, _| {}, &mut ());
+            tree.garbage_collect([root]);
+        });
+
+        assert_eq!(
+            *recorder.lines.lock().unwrap(),
+            vec![
+                "operation=\"traverse\" tree_len=4",
+                "message=finished nodes=3",
+                "operation=\"compact\" tree_len=4",
+                "message=finished nodes=3",
+            ]
+        );
+    }
+}
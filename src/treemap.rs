@@ -0,0 +1,223 @@
+use crate::{Tree, TreeStorage};
+
+/// An axis-aligned rectangle of a treemap, computed by [`Tree::treemap`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TreemapRect {
+    /// The left edge.
+    pub x: f64,
+    /// The top edge.
+    pub y: f64,
+    /// The width.
+    pub width: f64,
+    /// The height.
+    pub height: f64,
+}
+
+impl TreemapRect {
+    /// Creates a rectangle from its top-left corner and its size.
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        TreemapRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the area of the rectangle.
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Computes a squarified treemap layout of the tree inside `bounds`.
+    ///
+    /// The size of a subtree is the sum of the sizes of its nodes, as computed by
+    /// [`Tree::rollup`], and the area of the rectangle of a node is proportional to the size
+    /// of its subtree. The rectangles of the children of a node are nested inside the
+    /// rectangle of the node, and the part left over represents the node's own size. Nodes
+    /// without a parent share `bounds` in the same way.
+    ///
+    /// Siblings are placed with the squarified algorithm of Bruls, Huizing and van Wijk, which
+    /// keeps the rectangles close to squares: the largest ones are placed first, in rows along
+    /// the shorter side of the remaining space. Nodes whose subtree has a size of `0` get an
+    /// empty rectangle.
+    ///
+    /// # Parameters
+    /// - `bounds`: The rectangle to fill.
+    /// - `size`: Returns the own size of a node, which must not be negative.
+    ///
+    /// # Returns
+    /// The rectangle of every node, indexed by node.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{Tree, TreemapRect};
+    ///
+    /// let mut disk = Tree::new();
+    /// let root = disk.add_node(0.0);
+    /// let videos = disk.add_child(root, 6.0);
+    /// let music = disk.add_child(root, 3.0);
+    /// let docs = disk.add_child(root, 1.0);
+    ///
+    /// let rects = disk.treemap(TreemapRect::new(0.0, 0.0, 10.0, 6.0), |size| *size);
+    /// assert_eq!(rects[root], TreemapRect::new(0.0, 0.0, 10.0, 6.0));
+    /// assert_eq!(rects[videos], TreemapRect::new(0.0, 0.0, 6.0, 6.0));
+    /// assert_eq!(rects[music], TreemapRect::new(6.0, 0.0, 4.0, 4.5));
+    /// assert_eq!(rects[docs], TreemapRect::new(6.0, 4.5, 4.0, 1.5));
+    /// ```
+    pub fn treemap(&self, bounds: TreemapRect, size: impl Fn(&T) -> f64) -> Vec<TreemapRect> {
+        let totals = self.rollup(&size, |a, b| a + b);
+        let mut rects = vec![TreemapRect::default(); self.links.len()];
+
+        let roots: Vec<usize> = (0..self.links.len())
+            .filter(|&index| self.links[index].parent().is_none())
+            .collect();
+        let items = roots.iter().map(|&root| (Some(root), totals[root]));
+        squarify(items.collect(), bounds, &mut rects);
+
+        let mut stack = roots;
+        while let Some(index) = stack.pop() {
+            let children = &self.links[index].children;
+            let own = (None, size(&self.data[index]));
+            let items = children.iter().map(|&child| (Some(child), totals[child]));
+            squarify(items.chain([own]).collect(), rects[index], &mut rects);
+            stack.extend(children);
+        }
+        rects
+    }
+}
+
+/// Lays out `items`, pairs of an optional node index and a size, inside `bounds` with the
+/// squarified algorithm, storing the rectangles of the nodes in `rects`.
+fn squarify(
+    mut items: Vec<(Option<usize>, f64)>,
+    mut bounds: TreemapRect,
+    rects: &mut [TreemapRect],
+) {
+    // Empty items get an empty rectangle in the corner of the bounds.
+    let empty = TreemapRect::new(bounds.x, bounds.y, 0.0, 0.0);
+    let total: f64 = items.iter().map(|&(_, size)| size).sum();
+    let scale = if total > 0.0 {
+        bounds.area() / total
+    } else {
+        0.0
+    };
+    items.retain(|&(index, size)| {
+        if size * scale > 0.0 {
+            return true;
+        }
+        if let Some(index) = index {
+            rects[index] = empty;
+        }
+        false
+    });
+
+    // Convert sizes to areas, largest first. The sort is stable, so equal items keep their order.
+    for (_, size) in &mut items {
+        *size *= scale;
+    }
+    items.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut row_start = 0;
+    for end in 1..=items.len() {
+        let side = bounds.width.min(bounds.height);
+        let row = &items[row_start..end];
+        let is_last = end == items.len();
+        // Close the row before the next item if adding it would make the row less square.
+        if is_last || worst_ratio(&items[row_start..=end], side) > worst_ratio(row, side) {
+            bounds = layout_row(row, bounds, rects);
+            row_start = end;
+        }
+    }
+}
+
+/// Returns the worst aspect ratio of the rectangles of a row of `areas` laid out along a side
+/// of length `side`.
+fn worst_ratio(row: &[(Option<usize>, f64)], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|&(_, area)| area).sum();
+    let (min, max) = row
+        .iter()
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), &(_, area)| {
+            (min.min(area), max.max(area))
+        });
+    let side = side * side;
+    (side * max / (sum * sum)).max(sum * sum / (side * min))
+}
+
+/// Places a row of items along the shorter side of `bounds`, and returns the space left.
+fn layout_row(
+    row: &[(Option<usize>, f64)],
+    bounds: TreemapRect,
+    rects: &mut [TreemapRect],
+) -> TreemapRect {
+    let sum: f64 = row.iter().map(|&(_, area)| area).sum();
+    let mut offset = 0.0;
+    if bounds.width >= bounds.height {
+        // A column on the left.
+        let width = sum / bounds.height;
+        for &(index, area) in row {
+            let height = area / width;
+            if let Some(index) = index {
+                rects[index] = TreemapRect::new(bounds.x, bounds.y + offset, width, height);
+            }
+            offset += height;
+        }
+        TreemapRect::new(
+            bounds.x + width,
+            bounds.y,
+            bounds.width - width,
+            bounds.height,
+        )
+    } else {
+        // A row at the top.
+        let height = sum / bounds.width;
+        for &(index, area) in row {
+            let width = area / height;
+            if let Some(index) = index {
+                rects[index] = TreemapRect::new(bounds.x + offset, bounds.y, width, height);
+            }
+            offset += width;
+        }
+        TreemapRect::new(
+            bounds.x,
+            bounds.y + height,
+            bounds.width,
+            bounds.height - height,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_treemap_areas_and_nesting() {
+        let mut tree = Tree::new();
+        let root = tree.add_node(2.0);
+        for index in 1..40 {
+            tree.add_child((index - 1) / 4, (index % 5) as f64);
+        }
+        let bounds = TreemapRect::new(1.0, 2.0, 30.0, 20.0);
+        let rects = tree.treemap(bounds, |size| *size);
+        let totals = tree.rollup(|size| *size, |a, b| a + b);
+
+        assert_eq!(rects[root], bounds);
+        for index in 0..tree.len() {
+            let rect = rects[index];
+            let expected = bounds.area() * totals[index] / totals[root];
+            assert!(
+                (rect.area() - expected).abs() < 1e-9,
+                "area of node {index}"
+            );
+            if let Some(parent) = tree.parent_index_unchecked(index) {
+                let outer = rects[parent];
+                assert!(rect.x >= outer.x - 1e-9 && rect.y >= outer.y - 1e-9);
+                assert!(rect.x + rect.width <= outer.x + outer.width + 1e-9);
+                assert!(rect.y + rect.height <= outer.y + outer.height + 1e-9);
+            }
+        }
+    }
+}
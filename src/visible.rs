@@ -0,0 +1,369 @@
+use crate::{EulerStep, NodeMask, Tree, TreeStorage};
+
+/// An index over the rows of a tree widget, locating any window of visible rows in
+/// `O(rows + log n)`, as needed for virtual scrolling.
+///
+/// The index owns the collapsed state. Nodes are laid out in depth-first pre-order, where the
+/// visible rows appear in order, and a Fenwick tree counts the visible nodes before any
+/// position. Collapsing or expanding a node costs `O(k log n)`, where `k` is the number of
+/// rows it hides or reveals.
+///
+/// The index reflects the structure of the tree at the time it was built; it has to be rebuilt
+/// after nodes are added or moved.
+///
+/// Constructed with [`Tree::visible_rows`].
+#[derive(Debug, Clone)]
+pub struct VisibleRows {
+    collapsed: NodeMask,
+    visible: NodeMask,
+    /// The node at every pre-order position.
+    order: Vec<usize>,
+    position: Vec<usize>,
+    /// The position one past the last node of every node's subtree.
+    end: Vec<usize>,
+    depth: Vec<usize>,
+    /// A Fenwick tree over the positions, holding `1` for every visible node.
+    counts: Vec<usize>,
+}
+
+impl VisibleRows {
+    /// Returns the number of visible rows.
+    pub fn row_count(&self) -> usize {
+        self.rows_before(self.order.len())
+    }
+
+    /// Returns `true` if all ancestors of the node are expanded.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn is_visible(&self, index: usize) -> bool {
+        self.visible.contains(index)
+    }
+
+    /// Returns `true` if the children of the node are hidden.
+    pub fn is_collapsed(&self, index: usize) -> bool {
+        self.collapsed.contains(index)
+    }
+
+    /// Returns the collapsed nodes as a mask.
+    pub fn collapsed_mask(&self) -> &NodeMask {
+        &self.collapsed
+    }
+
+    /// Returns the number of nodes the tree had when the index was built, which tells whether
+    /// the index has to be rebuilt after nodes were added or removed.
+    pub fn node_count(&self) -> usize {
+        self.position.len()
+    }
+
+    /// Returns the row a node occupies, or `None` if it is hidden.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn row_of(&self, index: usize) -> Option<usize> {
+        self.is_visible(index)
+            .then(|| self.rows_before(self.position[index]))
+    }
+
+    /// Hides the children of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn collapse(&mut self, index: usize) {
+        if self.collapsed.contains(index) {
+            return;
+        }
+        if self.is_visible(index) {
+            for hidden in self.visible_descendants(index).collect::<Vec<_>>() {
+                self.visible.clear(hidden);
+                self.add(self.position[hidden], false);
+            }
+        }
+        self.collapsed.set(index);
+    }
+
+    /// Shows the children of a node.
+    ///
+    /// # Panics
+    /// This method panics if `index` is out of bounds.
+    pub fn expand(&mut self, index: usize) {
+        if !self.collapsed.contains(index) {
+            return;
+        }
+        self.collapsed.clear(index);
+        if self.is_visible(index) {
+            for shown in self.visible_descendants(index).collect::<Vec<_>>() {
+                self.visible.set(shown);
+                self.add(self.position[shown], true);
+            }
+        }
+    }
+
+    /// Returns an iterator over the nodes occupying the rows `start_row..start_row + count`,
+    /// with their depths. The iterator stops early at the last row.
+    pub fn range(
+        &self,
+        start_row: usize,
+        count: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut next = (start_row < self.row_count()).then(|| self.position_of_row(start_row));
+        std::iter::from_fn(move || {
+            let position = next?;
+            let index = self.order[position];
+            next = Some(self.skip_hidden(position)).filter(|&position| position < self.order.len());
+            Some((index, self.depth[index]))
+        })
+        .take(count)
+    }
+
+    /// Returns the nodes below `index` that would be visible if `index` were visible and
+    /// expanded, according to the collapsed state of the other nodes.
+    fn visible_descendants(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let end = self.end[index];
+        let mut position = self.position[index] + 1;
+        std::iter::from_fn(move || {
+            if position >= end {
+                return None;
+            }
+            let node = self.order[position];
+            position = self.skip_hidden(position);
+            Some(node)
+        })
+    }
+
+    /// Returns the position following `position` in the layout, skipping the subtree of the
+    /// node at `position` if it is collapsed.
+    fn skip_hidden(&self, position: usize) -> usize {
+        let index = self.order[position];
+        if self.collapsed.contains(index) {
+            self.end[index]
+        } else {
+            position + 1
+        }
+    }
+
+    /// Returns the number of visible nodes at positions before `position`.
+    fn rows_before(&self, mut position: usize) -> usize {
+        let mut rows = 0;
+        while position > 0 {
+            rows += self.counts[position];
+            position &= position - 1;
+        }
+        rows
+    }
+
+    /// Returns the position of the visible node at `row`, which must be less than the number of
+    /// rows.
+    fn position_of_row(&self, mut row: usize) -> usize {
+        let mut position = 0;
+        let mut step = self.counts.len().next_power_of_two();
+        while step > 0 {
+            if position + step < self.counts.len() && self.counts[position + step] <= row {
+                position += step;
+                row -= self.counts[position];
+            }
+            step /= 2;
+        }
+        position
+    }
+
+    fn add(&mut self, position: usize, visible: bool) {
+        let mut slot = position + 1;
+        while slot < self.counts.len() {
+            if visible {
+                self.counts[slot] += 1;
+            } else {
+                self.counts[slot] -= 1;
+            }
+            slot += slot & slot.wrapping_neg();
+        }
+    }
+}
+
+impl<T, D: TreeStorage<T>> Tree<T, D> {
+    /// Returns an iterator over the visible nodes of the tree, in the order they appear as rows
+    /// of a tree widget.
+    ///
+    /// The iteration is depth-first and does not descend into the nodes in `collapsed`; the
+    /// collapsed nodes themselves are still yielded. Every node without a parent starts a tree
+    /// of its own and is always visible; they are listed in index order.
+    ///
+    /// # Parameters
+    /// - `collapsed`: The nodes whose children are hidden.
+    ///
+    /// # Returns
+    /// An iterator over `(index, depth, &data)`, where the depth of nodes without a parent is
+    /// `0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{NodeMask, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node("src");
+    /// let util = tree.add_child(root, "util");
+    /// tree.add_child(util, "helpers.rs");
+    /// let lib = tree.add_child(root, "lib.rs");
+    ///
+    /// let mut collapsed = NodeMask::for_tree(&tree);
+    /// collapsed.set(util);
+    ///
+    /// let rows: Vec<_> = tree.iter_visible(&collapsed).collect();
+    /// assert_eq!(rows, vec![(root, 0, &"src"), (util, 1, &"util"), (lib, 1, &"lib.rs")]);
+    /// ```
+    pub fn iter_visible<'a>(
+        &'a self,
+        collapsed: &'a NodeMask,
+    ) -> impl Iterator<Item = (usize, usize, &'a T)> + 'a {
+        let mut stack: Vec<_> = (0..self.links.len())
+            .rev()
+            .filter(|&index| self.links[index].parent().is_none())
+            .map(|index| (index, 0))
+            .collect();
+        std::iter::from_fn(move || {
+            let (index, depth) = stack.pop()?;
+            let node = &self.links[index];
+            if !collapsed.contains(index) {
+                let children = node.children.iter().rev();
+                stack.extend(children.map(|&child| (child, depth + 1)));
+            }
+            Some((index, depth, &self.data[index]))
+        })
+    }
+
+    /// Builds a [`VisibleRows`] index for virtual scrolling over the rows of the tree.
+    ///
+    /// The rows are the same, and in the same order, as those of [`Tree::iter_visible`].
+    ///
+    /// # Parameters
+    /// - `collapsed`: The nodes whose children are initially hidden.
+    pub fn visible_rows(&self, collapsed: &NodeMask) -> VisibleRows {
+        let len = self.links.len();
+        let mut rows = VisibleRows {
+            collapsed: NodeMask::new(len),
+            visible: NodeMask::new(len),
+            order: Vec::with_capacity(len),
+            position: vec![0; len],
+            end: vec![0; len],
+            depth: vec![0; len],
+            counts: vec![0; len + 1],
+        };
+        for index in collapsed.iter().take_while(|&index| index < len) {
+            rows.collapsed.set(index);
+        }
+        for step in self.euler_tour() {
+            match step {
+                EulerStep::Enter { index, depth } => {
+                    rows.position[index] = rows.order.len();
+                    rows.depth[index] = depth;
+                    rows.order.push(index);
+                }
+                EulerStep::Leave { index, .. } => rows.end[index] = rows.order.len(),
+            }
+        }
+        for (index, _, _) in self.iter_visible(collapsed) {
+            rows.visible.set(index);
+            rows.add(rows.position[index], true);
+        }
+        rows
+    }
+
+    /// Returns the nodes occupying a window of visible rows, in `O(count + log n)`.
+    ///
+    /// # Parameters
+    /// - `rows`: The index of the visible rows, built with [`Tree::visible_rows`].
+    /// - `start_row`: The first row of the window.
+    /// - `count`: The number of rows in the window.
+    ///
+    /// # Returns
+    /// An iterator over `(index, depth, &data)` for the rows of the window. It stops early at
+    /// the last row.
+    ///
+    /// # Example
+    /// ```rust
+    /// use easy_tree::{NodeMask, Tree};
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.add_node(0);
+    /// for n in 1..1000 {
+    ///     tree.add_child(root, n);
+    /// }
+    ///
+    /// let mut rows = tree.visible_rows(&NodeMask::for_tree(&tree));
+    /// let window: Vec<_> = tree.visible_range(&rows, 500, 3).map(|(_, _, n)| *n).collect();
+    /// assert_eq!(window, vec![500, 501, 502]);
+    ///
+    /// rows.collapse(root);
+    /// assert_eq!(rows.row_count(), 1);
+    /// assert_eq!(tree.visible_range(&rows, 500, 3).count(), 0);
+    /// ```
+    pub fn visible_range<'a>(
+        &'a self,
+        rows: &'a VisibleRows,
+        start_row: usize,
+        count: usize,
+    ) -> impl Iterator<Item = (usize, usize, &'a T)> + 'a {
+        rows.range(start_row, count)
+            .map(|(index, depth)| (index, depth, &self.data[index]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_visible_forest() {
+        let mut tree = Tree::new();
+        let a = tree.add_node('a');
+        let b = tree.add_child(a, 'b');
+        tree.add_child(b, 'c');
+        let d = tree.add_child(a, 'd');
+        let e = tree.add_child(d, 'e');
+        let f = tree.add_node('f');
+        tree.add_child(f, 'g');
+
+        let mut collapsed = NodeMask::for_tree(&tree);
+        collapsed.set(b);
+        collapsed.set(f);
+        let rows: Vec<_> = tree
+            .iter_visible(&collapsed)
+            .map(|(index, depth, _)| (index, depth))
+            .collect();
+        assert_eq!(rows, vec![(a, 0), (b, 1), (d, 1), (e, 2), (f, 0)]);
+
+        let everything = NodeMask::new(0);
+        assert_eq!(tree.iter_visible(&everything).count(), tree.len());
+    }
+
+    #[test]
+    fn test_visible_rows_match_iter_visible() {
+        let mut tree = Tree::new();
+        tree.add_node(0);
+        for index in 1..200 {
+            let parent = (index - 1) / (1 + index % 3);
+            tree.add_child(parent, index);
+        }
+        tree.add_node(200);
+
+        let mut collapsed = NodeMask::for_tree(&tree);
+        let mut rows = tree.visible_rows(&collapsed);
+        for toggled in [17, 3, 40, 0, 3, 90, 0, 17] {
+            if collapsed.toggle(toggled) {
+                rows.collapse(toggled);
+            } else {
+                rows.expand(toggled);
+            }
+            let expected: Vec<_> = tree.iter_visible(&collapsed).collect();
+            assert_eq!(rows.row_count(), expected.len());
+            for start in [0, 1, expected.len() / 2, expected.len() - 1, expected.len()] {
+                let window: Vec<_> = tree.visible_range(&rows, start, 5).collect();
+                let end = (start + 5).min(expected.len());
+                assert_eq!(window, expected[start..end]);
+            }
+            for (row, &(index, _, _)) in expected.iter().enumerate() {
+                assert_eq!(rows.row_of(index), Some(row));
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{Tree, TreeError};
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+/// A tree of JavaScript values, exported to JavaScript as `JsTree` with the `wasm-bindgen`
+/// feature.
+///
+/// It wraps a `Tree<JsValue>`, so the same tree logic can run in a browser as on a server.
+/// Methods taking a node index throw an `Error` if it is out of bounds.
+///
+/// ```js
+/// const tree = new JsTree();
+/// const root = tree.addNode({ name: "root" });
+/// tree.addChild(root, { name: "child" });
+/// tree.traverse((index, data) => console.log("enter", index, data.name));
+/// ```
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct JsTree {
+    tree: Tree<JsValue>,
+}
+
+#[wasm_bindgen]
+impl JsTree {
+    /// Creates an empty tree.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsTree {
+        JsTree { tree: Tree::new() }
+    }
+
+    /// Returns the number of nodes in the tree.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Adds a node without a parent and returns its index.
+    #[wasm_bindgen(js_name = addNode)]
+    pub fn add_node(&mut self, data: JsValue) -> usize {
+        self.tree.add_node(data)
+    }
+
+    /// Adds a node as the last child of `parent` and returns its index.
+    #[wasm_bindgen(js_name = addChild)]
+    pub fn add_child(&mut self, parent: usize, data: JsValue) -> Result<usize, JsError> {
+        self.tree
+            .try_add_child(parent, data)
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Returns the data of a node, or `undefined` if there is no such node.
+    pub fn get(&self, index: usize) -> JsValue {
+        self.tree.get(index).cloned().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Replaces the data of a node.
+    pub fn set(&mut self, index: usize, data: JsValue) -> Result<(), JsError> {
+        self.check_index(index)?;
+        self.tree[index] = data;
+        Ok(())
+    }
+
+    /// Returns the indices of the children of a node, in order.
+    pub fn children(&self, index: usize) -> Result<Vec<usize>, JsError> {
+        self.check_index(index)?;
+        Ok(self.tree.children(index).to_vec())
+    }
+
+    /// Returns the index of the parent of a node, or `undefined` if it has none.
+    pub fn parent(&self, index: usize) -> Result<Option<usize>, JsError> {
+        self.check_index(index)?;
+        Ok(self.tree.parent_index_unchecked(index))
+    }
+
+    /// Traverses the subtree of the root depth-first, like [`Tree::traverse`], calling
+    /// `before(index, data)` when entering a node and `after(index, data)`, if given, when
+    /// leaving it.
+    ///
+    /// If a callback throws, no further callback is called and the exception is rethrown.
+    pub fn traverse(&self, before: &Function, after: Option<Function>) -> Result<(), JsValue> {
+        let call = |callback: &Function, index: usize, data: &JsValue, error: &mut Option<_>| {
+            if error.is_none() {
+                if let Err(thrown) = callback.call2(&JsValue::NULL, &JsValue::from(index), data) {
+                    *error = Some(thrown);
+                }
+            }
+        };
+        let mut error = None;
+        self.tree.traverse(
+            |index, data, error| call(before, index, data, error),
+            |index, data, error| {
+                if let Some(after) = &after {
+                    call(after, index, data, error);
+                }
+            },
+            &mut error,
+        );
+        error.map_or(Ok(()), Err)
+    }
+}
+
+impl JsTree {
+    fn check_index(&self, index: usize) -> Result<(), JsError> {
+        if index < self.tree.len() {
+            Ok(())
+        } else {
+            Err(JsError::new(&TreeError::InvalidIndex(index).to_string()))
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use crate::Tree;
+use std::rc::Rc;
+
+struct ZipperNode<T> {
+    data: T,
+    children: Vec<Rc<ZipperNode<T>>>,
+}
+
+/// The path from the focused node back to the root: the parent node as it was when the focus
+/// moved down, the position of the focus among its children, and the path above the parent.
+struct Crumb<T> {
+    parent: Rc<ZipperNode<T>>,
+    index: usize,
+    up: Option<Rc<Crumb<T>>>,
+}
+
+/// A persistent, functional cursor over an immutable tree.
+///
+/// A zipper focuses on a single node and can move [`down`](TreeZipper::down) to a child,
+/// [`up`](TreeZipper::up) to the parent and [`left`](TreeZipper::left) or
+/// [`right`](TreeZipper::right) to a sibling. Edits such as [`set`](TreeZipper::set) return a
+/// new zipper and never modify the original one: only the nodes on the path from the edited
+/// node to the root are copied, all other subtrees are shared between the old and the new
+/// version. Every operation takes `&self`, so earlier versions stay usable.
+///
+/// Use [`TreeZipper::new`] to create a zipper from a [`Tree`] and [`TreeZipper::to_tree`] to
+/// turn the result back into one.
+///
+/// # Example
+/// ```rust
+/// use easy_tree::{Tree, TreeZipper};
+///
+/// let mut tree = Tree::new();
+/// let root = tree.add_node("root");
+/// tree.add_child(root, "a");
+/// tree.add_child(root, "b");
+///
+/// let zipper = TreeZipper::new(&tree).unwrap();
+/// let edited = zipper.down(1).unwrap().set("B").up().unwrap();
+///
+/// assert_eq!(edited.down(1).unwrap().data(), &"B");
+/// assert_eq!(zipper.down(1).unwrap().data(), &"b");
+///
+/// let result = edited.to_tree();
+/// assert_eq!(result.get(2), Some(&"B"));
+/// ```
+pub struct TreeZipper<T> {
+    focus: Rc<ZipperNode<T>>,
+    path: Option<Rc<Crumb<T>>>,
+}
+
+// Implemented manually so that `T` is not required to be `Clone`.
+impl<T> Clone for TreeZipper<T> {
+    fn clone(&self) -> Self {
+        Self {
+            focus: self.focus.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
+
+impl<T: Clone> TreeZipper<T> {
+    /// Creates a zipper focused on the root of `tree`.
+    ///
+    /// # Returns
+    /// `None` if the tree is empty.
+    pub fn new(tree: &Tree<T>) -> Option<Self> {
+        tree.root().map(|root| Self::from_subtree(tree, root))
+    }
+
+    /// Creates a zipper over a copy of the subtree of `tree` rooted at `root`, focused on its
+    /// root.
+    ///
+    /// # Panics
+    /// This method panics if `root` is out of bounds.
+    pub fn from_subtree(tree: &Tree<T>, root: usize) -> Self {
+        let mut built: Vec<Option<Rc<ZipperNode<T>>>> = vec![None; tree.len()];
+        for index in tree.post_order(root) {
+            let children = tree.links[index]
+                .children
+                .iter()
+                .map(|&child| built[child].take().expect("children are built first"))
+                .collect();
+            built[index] = Some(Rc::new(ZipperNode {
+                data: tree.data[index].clone(),
+                children,
+            }));
+        }
+
+        Self {
+            focus: built[root].take().expect("the root is built last"),
+            path: None,
+        }
+    }
+
+    /// Returns the data of the focused node.
+    pub fn data(&self) -> &T {
+        &self.focus.data
+    }
+
+    /// Returns the number of children of the focused node.
+    pub fn num_children(&self) -> usize {
+        self.focus.children.len()
+    }
+
+    /// Returns `true` if the focus is on the root.
+    pub fn is_root(&self) -> bool {
+        self.path.is_none()
+    }
+
+    /// Moves the focus to the `n`-th child of the focused node.
+    ///
+    /// # Returns
+    /// `None` if the focused node has no `n`-th child.
+    pub fn down(&self, n: usize) -> Option<Self> {
+        let child = self.focus.children.get(n)?.clone();
+        Some(Self {
+            focus: child,
+            path: Some(Rc::new(Crumb {
+                parent: self.focus.clone(),
+                index: n,
+                up: self.path.clone(),
+            })),
+        })
+    }
+
+    /// Moves the focus to the parent of the focused node, carrying any edits along.
+    ///
+    /// # Returns
+    /// `None` if the focus is on the root.
+    pub fn up(&self) -> Option<Self> {
+        let crumb = self.path.as_ref()?;
+        Some(Self {
+            focus: self.rebuild_parent(crumb),
+            path: crumb.up.clone(),
+        })
+    }
+
+    /// Moves the focus to the previous sibling of the focused node.
+    ///
+    /// # Returns
+    /// `None` if the focused node is the root or the first child of its parent.
+    pub fn left(&self) -> Option<Self> {
+        let index = self.path.as_ref()?.index.checked_sub(1)?;
+        self.sibling(index)
+    }
+
+    /// Moves the focus to the next sibling of the focused node.
+    ///
+    /// # Returns
+    /// `None` if the focused node is the root or the last child of its parent.
+    pub fn right(&self) -> Option<Self> {
+        let index = self.path.as_ref()?.index + 1;
+        self.sibling(index)
+    }
+
+    /// Moves the focus all the way up to the root, carrying any edits along.
+    pub fn root(&self) -> Self {
+        let mut zipper = self.clone();
+        while let Some(parent) = zipper.up() {
+            zipper = parent;
+        }
+        zipper
+    }
+
+    /// Replaces the data of the focused node.
+    pub fn set(&self, data: T) -> Self {
+        self.with_focus(ZipperNode {
+            data,
+            children: self.focus.children.clone(),
+        })
+    }
+
+    /// Replaces the data of the focused node with the result of applying `f` to it.
+    pub fn modify(&self, f: impl FnOnce(&T) -> T) -> Self {
+        self.set(f(&self.focus.data))
+    }
+
+    /// Inserts a new leaf with `data` as the `n`-th child of the focused node, keeping the
+    /// focus where it is.
+    ///
+    /// # Panics
+    /// This method panics if `n` is greater than the number of children.
+    pub fn insert_child(&self, n: usize, data: T) -> Self {
+        let mut children = self.focus.children.clone();
+        children.insert(
+            n,
+            Rc::new(ZipperNode {
+                data,
+                children: Vec::new(),
+            }),
+        );
+        self.with_focus(ZipperNode {
+            data: self.focus.data.clone(),
+            children,
+        })
+    }
+
+    /// Appends a new leaf with `data` as the last child of the focused node, keeping the focus
+    /// where it is.
+    pub fn push_child(&self, data: T) -> Self {
+        self.insert_child(self.focus.children.len(), data)
+    }
+
+    /// Removes the focused node together with its subtree and moves the focus to its parent.
+    ///
+    /// # Returns
+    /// `None` if the focus is on the root.
+    pub fn remove(&self) -> Option<Self> {
+        let crumb = self.path.as_ref()?;
+        let mut children = crumb.parent.children.clone();
+        children.remove(crumb.index);
+        Some(Self {
+            focus: Rc::new(ZipperNode {
+                data: crumb.parent.data.clone(),
+                children,
+            }),
+            path: crumb.up.clone(),
+        })
+    }
+
+    /// Builds a [`Tree`] from the whole zipped tree, including all edits.
+    ///
+    /// Nodes are numbered in depth-first pre-order, with the root at index `0`.
+    pub fn to_tree(&self) -> Tree<T> {
+        let root = self.root().focus;
+        let mut tree = Tree::new();
+        let mut stack = vec![(None, &root)];
+        while let Some((parent, node)) = stack.pop() {
+            let index = match parent {
+                Some(parent) => tree.add_child(parent, node.data.clone()),
+                None => tree.add_node(node.data.clone()),
+            };
+            for child in node.children.iter().rev() {
+                stack.push((Some(index), child));
+            }
+        }
+        tree
+    }
+
+    fn with_focus(&self, node: ZipperNode<T>) -> Self {
+        Self {
+            focus: Rc::new(node),
+            path: self.path.clone(),
+        }
+    }
+
+    fn sibling(&self, index: usize) -> Option<Self> {
+        let crumb = self.path.as_ref()?;
+        let parent = self.rebuild_parent(crumb);
+        let focus = parent.children.get(index)?.clone();
+        Some(Self {
+            focus,
+            path: Some(Rc::new(Crumb {
+                parent,
+                index,
+                up: crumb.up.clone(),
+            })),
+        })
+    }
+
+    /// Returns the parent from `crumb` with the focused node put in place of the original
+    /// child. The parent is only copied if the focused node was edited.
+    fn rebuild_parent(&self, crumb: &Crumb<T>) -> Rc<ZipperNode<T>> {
+        if Rc::ptr_eq(&crumb.parent.children[crumb.index], &self.focus) {
+            return crumb.parent.clone();
+        }
+        let mut children = crumb.parent.children.clone();
+        children[crumb.index] = self.focus.clone();
+        Rc::new(ZipperNode {
+            data: crumb.parent.data.clone(),
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tree<&'static str> {
+        let mut tree = Tree::new();
+        let root = tree.add_node("root");
+        let a = tree.add_child(root, "a");
+        tree.add_child(a, "a1");
+        tree.add_child(root, "b");
+        tree.add_child(root, "c");
+        tree
+    }
+
+    #[test]
+    fn test_zipper_navigation() {
+        let zipper = TreeZipper::new(&sample()).unwrap();
+        assert!(zipper.is_root());
+        assert_eq!(zipper.num_children(), 3);
+        assert!(zipper.up().is_none());
+        assert!(zipper.left().is_none());
+
+        let a = zipper.down(0).unwrap();
+        assert_eq!(a.data(), &"a");
+        assert!(a.left().is_none());
+        assert_eq!(a.down(0).unwrap().data(), &"a1");
+        let c = a.right().unwrap().right().unwrap();
+        assert_eq!(c.data(), &"c");
+        assert!(c.right().is_none());
+        assert_eq!(c.left().unwrap().data(), &"b");
+        assert_eq!(c.up().unwrap().data(), &"root");
+        assert!(zipper.down(3).is_none());
+    }
+
+    #[test]
+    fn test_zipper_edits_share_structure() {
+        let zipper = TreeZipper::new(&sample()).unwrap();
+        let edited = zipper
+            .down(2)
+            .unwrap()
+            .set("C")
+            .push_child("c1")
+            .left()
+            .unwrap()
+            .remove()
+            .unwrap();
+
+        // The untouched subtree of "a" is shared between both versions.
+        assert!(Rc::ptr_eq(
+            &zipper.focus.children[0],
+            &edited.focus.children[0]
+        ));
+        assert_eq!(zipper.to_tree().len(), 5);
+
+        let tree = edited.to_tree();
+        let data: Vec<_> = tree.iter_dfs().map(|(_, data)| *data).collect();
+        assert_eq!(data, vec!["root", "a", "a1", "C", "c1"]);
+        assert_eq!(tree.children(0), &[1, 3]);
+    }
+
+    #[test]
+    fn test_zipper_from_subtree_and_modify() {
+        let tree = sample();
+        let zipper = TreeZipper::from_subtree(&tree, 1);
+        assert!(zipper.is_root());
+        let modified = zipper.modify(|data| if *data == "a" { "A" } else { data });
+        let result = modified.insert_child(0, "a0").to_tree();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.get(0), Some(&"A"));
+        assert_eq!(result.get(1), Some(&"a0"));
+        assert_eq!(result.get(2), Some(&"a1"));
+    }
+}